@@ -0,0 +1,662 @@
+//! Finding stale data by age (`--older-than`, `--newer-than`, `--age-histogram`).
+//!
+//! Every entry's timestamps are recorded during traversal (see
+//! [`Timestamps`]). This module compares them against the current time to
+//! answer "what hasn't been modified in 180 days, and how big is it?".
+//! `--time=WORD` selects which timestamp is compared: `mtime` (default),
+//! `atime` or `ctime`.
+//!
+//! # Filters
+//!
+//! `--older-than AGE` keeps only files whose timestamp is before the cutoff,
+//! `--newer-than AGE` only those at or after it. `AGE` is either a duration
+//! counted back from now (`180d`, `12h`, `2y`) or a date (`2024-01-31`,
+//! local midnight); see [`AgeLimit`].
+//!
+//! - Files, symlinks and other non-directories are kept if they match
+//! - Directories are kept if anything below them is, so every path that
+//!   leads to matching data is still listed
+//! - Directory totals then only count what was kept: the text output reads
+//!   as "stale bytes below each directory". A directory's own inode only
+//!   counts if the directory itself matches
+//! - The root of each path is always kept
+//!
+//! Entries without timestamps (skipped mount points, unreadable entries,
+//! non-Unix platforms) never match.
+//!
+//! # Histogram
+//!
+//! `--age-histogram` replaces the tree with a table of regular files by age
+//! bucket (see [`AGE_BUCKETS`]):
+//!
+//! ```text
+//! 12.0K     3    0.0%                        < 1 day
+//!  1.2G   210   24.0%  ████▊                 1 week - 1 month
+//!  3.8G  4021   76.0%  ███████████████▏      > 2 years
+//! ```
+//!
+//! Empty buckets are left out. Like `--by-extension`, every output format is
+//! supported: `json`, `ndjson` and `csv` report one record per bucket.
+//!
+//! # Examples
+//!
+//! ```
+//! use disk_usage_clone::age::{AgeFilter, AgeLimit, filter_by_age};
+//! use disk_usage_clone::entry::{DiskEntry, EntryType, TimeKind, Timestamps};
+//! use std::path::PathBuf;
+//!
+//! const DAY: i64 = 86_400;
+//! let now = 1000 * DAY;
+//!
+//! let mut dir = DiskEntry::new(PathBuf::from("/data"), 0, EntryType::Directory, 0);
+//! for (name, age_days) in [("old.csv", 400), ("new.csv", 3)] {
+//!     let mut file = DiskEntry::new(PathBuf::from(format!("/data/{name}")), 10, EntryType::File, 1);
+//!     let time = now - age_days * DAY;
+//!     file.times = Some(Timestamps { modified: time, accessed: time, changed: time });
+//!     dir.children.push(file);
+//! }
+//!
+//! let filter = AgeFilter::new(AgeLimit::parse("180d"), None, TimeKind::Modified, now);
+//! filter_by_age(&mut dir, &filter);
+//! assert_eq!(dir.children.len(), 1);
+//! assert_eq!(dir.children[0].path, PathBuf::from("/data/old.csv"));
+//! ```
+
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use chrono::{Local, NaiveDate, TimeZone};
+use colored::Colorize;
+use serde_json::{Value, json};
+
+use crate::entry::{DiskEntry, EntryType, SizeMode, TimeKind, Timestamps};
+use crate::formatter::format_size;
+use crate::output::{colorize_size, csv_field};
+use crate::tree_view::{bar_width, render_bar};
+
+/// Seconds in a minute.
+const MINUTE: i64 = 60;
+
+/// Seconds in an hour.
+const HOUR: i64 = 60 * MINUTE;
+
+/// Seconds in a day.
+const DAY: i64 = 24 * HOUR;
+
+/// Seconds in a week.
+const WEEK: i64 = 7 * DAY;
+
+/// Seconds in a (365-day) year.
+const YEAR: i64 = 365 * DAY;
+
+/// Header row of the CSV output, in column order.
+const CSV_HEADER: &str = "bucket,max_age_seconds,files,size_bytes,size_human";
+
+/// Histogram buckets: label and exclusive upper bound of the age in seconds.
+///
+/// The last bucket has no upper bound. Files with a timestamp in the future
+/// count as less than a day old.
+pub const AGE_BUCKETS: &[(&str, Option<i64>)] = &[
+    ("< 1 day", Some(DAY)),
+    ("1 day - 1 week", Some(WEEK)),
+    ("1 week - 1 month", Some(30 * DAY)),
+    ("1 - 3 months", Some(91 * DAY)),
+    ("3 - 6 months", Some(182 * DAY)),
+    ("6 - 12 months", Some(YEAR)),
+    ("1 - 2 years", Some(2 * YEAR)),
+    ("> 2 years", None),
+];
+
+/// Age given to `--older-than` or `--newer-than`.
+///
+/// # Variants
+///
+/// - `Ago` - A duration in seconds, counted back from now (`180d`)
+/// - `Since` - A fixed point in time, seconds since the epoch (`2024-01-31`)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AgeLimit {
+    Ago(i64),
+    Since(i64),
+}
+
+impl AgeLimit {
+    /// Parses a duration or a date.
+    ///
+    /// Durations are a whole number with an optional unit: `s`econds,
+    /// `m`inutes, `h`ours, `d`ays (the default), `w`eeks or `y`ears of 365
+    /// days. Dates are `YYYY-MM-DD` and mean midnight, local time.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use disk_usage_clone::age::AgeLimit;
+    ///
+    /// assert_eq!(AgeLimit::parse("180d"), Some(AgeLimit::Ago(180 * 86_400)));
+    /// assert_eq!(AgeLimit::parse("90"), Some(AgeLimit::Ago(90 * 86_400)));
+    /// assert_eq!(AgeLimit::parse("12h"), Some(AgeLimit::Ago(12 * 3600)));
+    /// assert!(matches!(AgeLimit::parse("2024-01-31"), Some(AgeLimit::Since(_))));
+    /// assert_eq!(AgeLimit::parse("soon"), None);
+    /// ```
+    pub fn parse(s: &str) -> Option<AgeLimit> {
+        if let Ok(date) = NaiveDate::parse_from_str(s, "%Y-%m-%d") {
+            let midnight = Local
+                .from_local_datetime(&date.and_hms_opt(0, 0, 0)?)
+                .earliest()?;
+            return Some(AgeLimit::Since(midnight.timestamp()));
+        }
+
+        let split = s.find(|c: char| !c.is_ascii_digit()).unwrap_or(s.len());
+        let (number, unit) = s.split_at(split);
+        let unit = match unit.to_ascii_lowercase().as_str() {
+            "s" => 1,
+            "m" => MINUTE,
+            "h" => HOUR,
+            "" | "d" => DAY,
+            "w" => WEEK,
+            "y" => YEAR,
+            _ => return None,
+        };
+        let number: i64 = number.parse().ok()?;
+        number.checked_mul(unit).map(AgeLimit::Ago)
+    }
+
+    /// Returns the cutoff as seconds since the epoch, given the current time.
+    pub fn cutoff(&self, now: i64) -> i64 {
+        match *self {
+            AgeLimit::Ago(secs) => now.saturating_sub(secs),
+            AgeLimit::Since(time) => time,
+        }
+    }
+}
+
+/// Resolved `--older-than` / `--newer-than` cutoffs.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AgeFilter {
+    /// Keep entries with a timestamp strictly before this time.
+    pub before: Option<i64>,
+    /// Keep entries with a timestamp at or after this time.
+    pub since: Option<i64>,
+    /// Which timestamp is compared.
+    pub kind: TimeKind,
+}
+
+impl AgeFilter {
+    /// Resolves the limits against the current time.
+    ///
+    /// # Arguments
+    ///
+    /// * `older_than` - Limit given to `--older-than`, if any
+    /// * `newer_than` - Limit given to `--newer-than`, if any
+    /// * `kind` - Timestamp to compare
+    /// * `now` - Current time, seconds since the epoch (see [`now`])
+    pub fn new(
+        older_than: Option<AgeLimit>,
+        newer_than: Option<AgeLimit>,
+        kind: TimeKind,
+        now: i64,
+    ) -> AgeFilter {
+        AgeFilter {
+            before: older_than.map(|limit| limit.cutoff(now)),
+            since: newer_than.map(|limit| limit.cutoff(now)),
+            kind,
+        }
+    }
+
+    /// Returns whether an entry with these timestamps matches.
+    pub fn matches(&self, times: Option<Timestamps>) -> bool {
+        let Some(times) = times else {
+            return false;
+        };
+        let time = times.get(self.kind);
+        self.before.is_none_or(|before| time < before)
+            && self.since.is_none_or(|since| time >= since)
+    }
+}
+
+/// Returns the current time in seconds since the epoch.
+pub fn now() -> i64 {
+    match SystemTime::now().duration_since(UNIX_EPOCH) {
+        Ok(elapsed) => i64::try_from(elapsed.as_secs()).unwrap_or(i64::MAX),
+        // A clock set before 1970
+        Err(err) => -i64::try_from(err.duration().as_secs()).unwrap_or(i64::MAX),
+    }
+}
+
+/// Removes every entry that neither matches nor leads to a match.
+///
+/// Run it on uncollapsed trees: a file folded into its directory by
+/// `--max-depth` can no longer be told apart from its siblings.
+///
+/// # Arguments
+///
+/// * `tree` - Tree to filter; its root is always kept
+/// * `filter` - Cutoffs and the timestamp to compare
+pub fn filter_by_age(tree: &mut DiskEntry, filter: &AgeFilter) {
+    tree.children.retain_mut(|child| keep(child, filter));
+    discount_unless_matching(tree, filter);
+}
+
+/// Filters `entry`'s subtree and returns whether `entry` itself stays.
+fn keep(entry: &mut DiskEntry, filter: &AgeFilter) -> bool {
+    match entry.entry_type {
+        EntryType::Directory => {
+            entry.children.retain_mut(|child| keep(child, filter));
+            discount_unless_matching(entry, filter);
+            !entry.children.is_empty()
+        }
+        _ => filter.matches(entry.times),
+    }
+}
+
+/// Zeroes a kept directory's own size when only its contents match, so a
+/// freshly modified directory inode isn't counted as stale.
+fn discount_unless_matching(entry: &mut DiskEntry, filter: &AgeFilter) {
+    if !filter.matches(entry.times) {
+        entry.size_bytes = 0;
+        entry.allocated_bytes = 0;
+    }
+}
+
+/// Totals of one histogram bucket.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AgeBucket {
+    /// Human-readable range, e.g. `1 - 3 months`.
+    pub label: &'static str,
+    /// Exclusive upper bound of the age in seconds; `None` for the oldest.
+    pub max_age: Option<i64>,
+    /// Number of files in the bucket.
+    pub files: u64,
+    /// Sum of the files' apparent sizes.
+    pub apparent_bytes: u64,
+    /// Sum of the files' allocated sizes.
+    pub allocated_bytes: u64,
+}
+
+impl AgeBucket {
+    /// Returns the bucket's total in the given mode.
+    pub fn size(&self, size_mode: SizeMode) -> u64 {
+        match size_mode {
+            SizeMode::Apparent => self.apparent_bytes,
+            SizeMode::Allocated => self.allocated_bytes,
+        }
+    }
+}
+
+/// Sorts every regular file of the trees into age buckets.
+///
+/// Files without timestamps are left out. Buckets are returned youngest
+/// first, without the empty ones.
+///
+/// # Arguments
+///
+/// * `trees` - Scanned trees (uncollapsed, so every file is present)
+/// * `kind` - Timestamp that determines a file's age
+/// * `now` - Current time, seconds since the epoch
+pub fn age_histogram(trees: &[DiskEntry], kind: TimeKind, now: i64) -> Vec<AgeBucket> {
+    let mut buckets: Vec<AgeBucket> = AGE_BUCKETS
+        .iter()
+        .map(|&(label, max_age)| AgeBucket {
+            label,
+            max_age,
+            files: 0,
+            apparent_bytes: 0,
+            allocated_bytes: 0,
+        })
+        .collect();
+    // Explicit stack: trees can be deeper than the call stack allows
+    let mut pending: Vec<&DiskEntry> = trees.iter().collect();
+
+    while let Some(entry) = pending.pop() {
+        pending.extend(&entry.children);
+        let (EntryType::File, Some(times)) = (&entry.entry_type, entry.times) else {
+            continue;
+        };
+
+        let age = now.saturating_sub(times.get(kind));
+        let index = buckets
+            .iter()
+            .position(|bucket| bucket.max_age.is_none_or(|max| age < max))
+            .unwrap_or(buckets.len() - 1);
+        let bucket = &mut buckets[index];
+        bucket.files += 1;
+        bucket.apparent_bytes += entry.size_bytes;
+        bucket.allocated_bytes += entry.allocated_bytes;
+    }
+
+    buckets.retain(|bucket| bucket.files > 0);
+    buckets
+}
+
+/// Renders the histogram with a share of the total and a bar per bucket.
+///
+/// Columns are the size, the file count, the percentage of all bytes, a bar
+/// sized from `terminal_width` (as in `--tree`), and the bucket's label.
+pub fn render_histogram(
+    buckets: &[AgeBucket],
+    human_readable: bool,
+    use_color: bool,
+    size_mode: SizeMode,
+    terminal_width: usize,
+) -> String {
+    let total: u64 = buckets.iter().map(|b| b.size(size_mode)).sum();
+    let sizes: Vec<String> = buckets
+        .iter()
+        .map(|b| format_size(b.size(size_mode), human_readable))
+        .collect();
+    let size_width = sizes.iter().map(String::len).max().unwrap_or(0);
+    let files_width = buckets
+        .iter()
+        .map(|b| b.files.to_string().len())
+        .max()
+        .unwrap_or(0);
+    let bar_width = bar_width(terminal_width);
+
+    buckets
+        .iter()
+        .zip(&sizes)
+        .map(|(bucket, size_str)| {
+            let size = bucket.size(size_mode);
+            let fraction = if total == 0 {
+                0.0
+            } else {
+                size as f64 / total as f64
+            };
+            let padded = format!("{size_str:>size_width$}");
+            let files = format!("{:>files_width$}", bucket.files);
+            let percent = format!("{:>5.1}%", fraction * 100.0);
+            let bar = render_bar(fraction, bar_width);
+
+            if use_color {
+                format!(
+                    "{}  {files}  {percent}  {bar}  {}",
+                    colorize_size(&padded, size),
+                    bucket.label.bold()
+                )
+            } else {
+                format!("{padded}  {files}  {percent}  {bar}  {}", bucket.label)
+            }
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Builds the JSON object describing one bucket.
+fn bucket_object(bucket: &AgeBucket, size_mode: SizeMode) -> Value {
+    json!({
+        "bucket": bucket.label,
+        "max_age_seconds": bucket.max_age,
+        "files": bucket.files,
+        "size": bucket.size(size_mode),
+        "apparent_size": bucket.apparent_bytes,
+        "allocated_size": bucket.allocated_bytes,
+    })
+}
+
+/// Renders the histogram as one pretty-printed JSON array (`--format json`).
+pub fn render_histogram_json(buckets: &[AgeBucket], size_mode: SizeMode) -> String {
+    let values: Vec<Value> = buckets
+        .iter()
+        .map(|b| bucket_object(b, size_mode))
+        .collect();
+    // Serializing a Value cannot fail
+    serde_json::to_string_pretty(&Value::Array(values)).unwrap_or_default()
+}
+
+/// Renders the histogram as one JSON object per line (`--format ndjson`).
+pub fn render_histogram_ndjson(buckets: &[AgeBucket], size_mode: SizeMode) -> String {
+    buckets
+        .iter()
+        .map(|b| bucket_object(b, size_mode).to_string())
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Renders the histogram as CSV with a header row (`--format csv`).
+///
+/// `max_age_seconds` is empty for the oldest bucket.
+pub fn render_histogram_csv(buckets: &[AgeBucket], size_mode: SizeMode) -> String {
+    let mut rows = vec![CSV_HEADER.to_string()];
+    for bucket in buckets {
+        let size = bucket.size(size_mode);
+        rows.push(format!(
+            "{},{},{},{size},{}",
+            csv_field(bucket.label),
+            bucket
+                .max_age
+                .map(|max| max.to_string())
+                .unwrap_or_default(),
+            bucket.files,
+            format_size(size, true)
+        ));
+    }
+    rows.join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+
+    const NOW: i64 = 1000 * DAY;
+
+    fn file(path: &str, size: u64, age: i64) -> DiskEntry {
+        let mut entry = DiskEntry::new(PathBuf::from(path), size, EntryType::File, 2);
+        entry.allocated_bytes = 4096;
+        entry.times = Some(Timestamps {
+            modified: NOW - age,
+            accessed: NOW,
+            changed: NOW - age,
+        });
+        entry
+    }
+
+    fn dir(path: &str, depth: usize, children: Vec<DiskEntry>) -> DiskEntry {
+        let mut entry = DiskEntry::new(PathBuf::from(path), 100, EntryType::Directory, depth);
+        entry.children = children;
+        entry
+    }
+
+    fn sample() -> DiskEntry {
+        dir(
+            "/d",
+            0,
+            vec![
+                dir(
+                    "/d/logs",
+                    1,
+                    vec![
+                        file("/d/logs/a.log", 10, 400 * DAY),
+                        file("/d/logs/b.log", 20, 2 * HOUR),
+                    ],
+                ),
+                dir("/d/cache", 1, vec![file("/d/cache/c", 40, 3 * DAY)]),
+                dir("/d/empty", 1, vec![]),
+                file("/d/old.tar", 80, 800 * DAY),
+            ],
+        )
+    }
+
+    fn paths(entry: &DiskEntry) -> Vec<String> {
+        let mut out = vec![entry.path.display().to_string()];
+        for child in &entry.children {
+            out.extend(paths(child));
+        }
+        out
+    }
+
+    #[test]
+    fn test_parse_age_units() {
+        assert_eq!(AgeLimit::parse("30s"), Some(AgeLimit::Ago(30)));
+        assert_eq!(AgeLimit::parse("5m"), Some(AgeLimit::Ago(5 * MINUTE)));
+        assert_eq!(AgeLimit::parse("2W"), Some(AgeLimit::Ago(2 * WEEK)));
+        assert_eq!(AgeLimit::parse("1y"), Some(AgeLimit::Ago(YEAR)));
+        assert_eq!(AgeLimit::parse(""), None);
+        assert_eq!(AgeLimit::parse("d"), None);
+        assert_eq!(AgeLimit::parse("-5d"), None);
+        assert_eq!(AgeLimit::parse("1.5d"), None);
+        assert_eq!(AgeLimit::parse("2024-13-01"), None);
+        assert_eq!(AgeLimit::parse("99999999999999999y"), None);
+    }
+
+    #[test]
+    fn test_parse_age_date_is_local_midnight() {
+        let Some(AgeLimit::Since(time)) = AgeLimit::parse("2024-01-31") else {
+            panic!("expected a date");
+        };
+        let midnight = chrono::DateTime::from_timestamp(time, 0)
+            .unwrap()
+            .with_timezone(&Local);
+        assert_eq!(
+            midnight.format("%Y-%m-%d %H:%M").to_string(),
+            "2024-01-31 00:00"
+        );
+    }
+
+    #[test]
+    fn test_cutoff() {
+        assert_eq!(AgeLimit::Ago(DAY).cutoff(NOW), NOW - DAY);
+        assert_eq!(AgeLimit::Since(42).cutoff(NOW), 42);
+    }
+
+    #[test]
+    fn test_older_than_keeps_paths_to_matches() {
+        let mut tree = sample();
+        let filter = AgeFilter::new(
+            Some(AgeLimit::Ago(180 * DAY)),
+            None,
+            TimeKind::Modified,
+            NOW,
+        );
+        filter_by_age(&mut tree, &filter);
+
+        assert_eq!(
+            paths(&tree),
+            vec!["/d", "/d/logs", "/d/logs/a.log", "/d/old.tar"]
+        );
+        // The directories themselves carry no timestamps, so only the files count
+        assert_eq!(tree.total_size_for(SizeMode::Apparent), 10 + 80);
+        assert_eq!(tree.children[0].total_size_for(SizeMode::Apparent), 10);
+    }
+
+    #[test]
+    fn test_kept_directories_count_only_if_they_match() {
+        let mut tree = sample();
+        // `logs` is as old as its oldest file, the root was just modified
+        tree.children[0].times = Some(Timestamps {
+            modified: NOW - 400 * DAY,
+            accessed: NOW,
+            changed: NOW - 400 * DAY,
+        });
+        tree.children[0].allocated_bytes = 4096;
+        tree.times = Some(Timestamps {
+            modified: NOW,
+            accessed: NOW,
+            changed: NOW,
+        });
+        tree.allocated_bytes = 4096;
+        let filter = AgeFilter::new(
+            Some(AgeLimit::Ago(180 * DAY)),
+            None,
+            TimeKind::Modified,
+            NOW,
+        );
+        filter_by_age(&mut tree, &filter);
+
+        assert_eq!(tree.size_bytes, 0);
+        assert_eq!(tree.allocated_bytes, 0);
+        assert_eq!(
+            tree.children[0].total_size_for(SizeMode::Apparent),
+            100 + 10
+        );
+        assert_eq!(tree.total_size_for(SizeMode::Apparent), 100 + 10 + 80);
+        assert_eq!(tree.total_size_for(SizeMode::Allocated), 3 * 4096);
+    }
+
+    #[test]
+    fn test_newer_than_and_range() {
+        let mut tree = sample();
+        let filter = AgeFilter::new(None, Some(AgeLimit::Ago(DAY)), TimeKind::Modified, NOW);
+        filter_by_age(&mut tree, &filter);
+        assert_eq!(paths(&tree), vec!["/d", "/d/logs", "/d/logs/b.log"]);
+
+        // Between one and 500 days old
+        let mut tree = sample();
+        let filter = AgeFilter::new(
+            Some(AgeLimit::Ago(DAY)),
+            Some(AgeLimit::Ago(500 * DAY)),
+            TimeKind::Modified,
+            NOW,
+        );
+        filter_by_age(&mut tree, &filter);
+        assert_eq!(
+            paths(&tree),
+            vec!["/d", "/d/logs", "/d/logs/a.log", "/d/cache", "/d/cache/c"]
+        );
+    }
+
+    #[test]
+    fn test_filter_uses_selected_timestamp() {
+        // Every file was accessed just now
+        let mut tree = sample();
+        let filter = AgeFilter::new(Some(AgeLimit::Ago(DAY)), None, TimeKind::Accessed, NOW);
+        filter_by_age(&mut tree, &filter);
+        assert_eq!(paths(&tree), vec!["/d"]);
+    }
+
+    #[test]
+    fn test_entries_without_times_never_match() {
+        let filter = AgeFilter::new(None, None, TimeKind::Modified, NOW);
+        assert!(!filter.matches(None));
+        assert!(filter.matches(sample().children[3].times));
+    }
+
+    #[test]
+    fn test_histogram_buckets() {
+        let buckets = age_histogram(&[sample()], TimeKind::Modified, NOW);
+        let summary: Vec<(&str, u64, u64)> = buckets
+            .iter()
+            .map(|b| (b.label, b.files, b.apparent_bytes))
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                ("< 1 day", 1, 20),
+                ("1 day - 1 week", 1, 40),
+                ("1 - 2 years", 1, 10),
+                ("> 2 years", 1, 80),
+            ]
+        );
+        assert_eq!(buckets[0].allocated_bytes, 4096);
+    }
+
+    #[test]
+    fn test_histogram_future_times_are_youngest() {
+        let tree = dir("/d", 0, vec![file("/d/f", 1, -YEAR)]);
+        let buckets = age_histogram(&[tree], TimeKind::Modified, NOW);
+        assert_eq!(buckets[0].label, "< 1 day");
+    }
+
+    #[test]
+    fn test_render_histogram_formats() {
+        let buckets = age_histogram(&[sample()], TimeKind::Modified, NOW);
+
+        let text = render_histogram(&buckets, false, false, SizeMode::Apparent, 40);
+        let lines: Vec<&str> = text.lines().collect();
+        assert_eq!(lines.len(), 4);
+        assert_eq!(lines[0], "20  1   13.3%  █▍          < 1 day");
+        assert_eq!(lines[3], "80  1   53.3%  █████▍      > 2 years");
+
+        let csv = render_histogram_csv(&buckets, SizeMode::Apparent);
+        assert_eq!(csv.lines().next(), Some(CSV_HEADER));
+        assert_eq!(csv.lines().nth(1), Some("< 1 day,86400,1,20,20B"));
+        assert_eq!(csv.lines().nth(4), Some("> 2 years,,1,80,80B"));
+
+        let parsed: Value =
+            serde_json::from_str(&render_histogram_json(&buckets, SizeMode::Apparent)).unwrap();
+        assert_eq!(parsed[3]["bucket"], "> 2 years");
+        assert_eq!(parsed[3]["max_age_seconds"], Value::Null);
+        assert_eq!(parsed[0]["allocated_size"], 4096);
+
+        let ndjson = render_histogram_ndjson(&buckets, SizeMode::Apparent);
+        assert_eq!(ndjson.lines().count(), 4);
+    }
+}
@@ -0,0 +1,1146 @@
+//! Interactive full-screen tree browser (`--interactive`).
+//!
+//! Presents the [`DiskEntry`] tree of a finished scan as a navigable list,
+//! similar to `ncdu`. One directory is shown at a time; each row has the
+//! entry's size, its share of the current directory and a percent bar.
+//!
+//! Navigation only walks the in-memory tree, so moving around never touches
+//! the disk again. The only disk access after the scan is an explicit
+//! delete or trash action.
+//!
+//! # Key Bindings
+//!
+//! | Key | Action |
+//! |-----|--------|
+//! | `↑` / `k`, `↓` / `j` | Move the selection |
+//! | `PgUp`, `PgDn` | Move by one screen |
+//! | `Home` / `g`, `End` / `G` | Jump to first / last entry |
+//! | `Enter` / `→` / `l` | Descend into the selected directory |
+//! | `Backspace` / `←` / `h` | Go up to the parent directory |
+//! | `s` | Sort by size (press again to reverse) |
+//! | `n` | Sort by name (press again to reverse) |
+//! | `d` | Delete the selected entry (asks for confirmation) |
+//! | `t` | Move the selected entry to the trash (asks for confirmation) |
+//! | `q` / `Esc` / `Ctrl+C` | Quit |
+//!
+//! # Removing Entries
+//!
+//! `d` and `t` ask `[y/N]` first; only `y` goes ahead, any other key
+//! cancels. Removal goes through [`remove_entry`], which also prunes the
+//! entry from the tree, so all totals on screen shrink right away. With
+//! `--dry-run` the browser only reports what would be freed.
+//!
+//! # Architecture
+//!
+//! - [`map_event`] turns terminal events into [`Action`]s
+//! - [`BrowserState`] holds the position in the tree and applies actions
+//! - [`render`] draws a state onto any [`Terminal`]
+//! - [`run_browser`] ties them together in an event loop
+//!
+//! All drawing goes through the [`Terminal`] trait, so the view logic is
+//! tested against a mock terminal.
+
+use std::cmp::Reverse;
+
+use crossterm::event::{Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers};
+
+use crate::entry::{DiskEntry, EntryType, SizeMode, SortOrder};
+use crate::error::DuskError;
+use crate::formatter::format_size;
+use crate::output::link_suffix;
+use crate::remove::{RemoveMode, remove_entry};
+use crate::terminal::Terminal;
+
+/// Width of the percent bar, in cells (without brackets).
+const BAR_WIDTH: usize = 10;
+
+/// Sort order of the rows in the browser.
+///
+/// Unlike [`SortOrder`], every order can be reversed with a second key press.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BrowseSort {
+    SizeDescending,
+    SizeAscending,
+    NameAscending,
+    NameDescending,
+}
+
+impl BrowseSort {
+    /// Picks the initial browser order matching a `--sort` value.
+    ///
+    /// `none` has no meaning in a sorted list and starts with largest first.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use disk_usage_clone::browser::BrowseSort;
+    /// use disk_usage_clone::entry::SortOrder;
+    ///
+    /// assert_eq!(BrowseSort::from_sort_order(&SortOrder::Name), BrowseSort::NameAscending);
+    /// assert_eq!(BrowseSort::from_sort_order(&SortOrder::None), BrowseSort::SizeDescending);
+    /// ```
+    pub fn from_sort_order(order: &SortOrder) -> BrowseSort {
+        match order {
+            SortOrder::SizeAscending => BrowseSort::SizeAscending,
+            SortOrder::Name => BrowseSort::NameAscending,
+            SortOrder::None | SortOrder::SizeDescending => BrowseSort::SizeDescending,
+        }
+    }
+
+    /// Short description for the status line.
+    pub fn label(&self) -> &'static str {
+        match self {
+            BrowseSort::SizeDescending => "size desc",
+            BrowseSort::SizeAscending => "size asc",
+            BrowseSort::NameAscending => "name asc",
+            BrowseSort::NameDescending => "name desc",
+        }
+    }
+}
+
+/// All browser commands.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Action {
+    /// Move the selection up by one row.
+    Up,
+    /// Move the selection down by one row.
+    Down,
+    /// Move the selection up by one screen.
+    PageUp,
+    /// Move the selection down by one screen.
+    PageDown,
+    /// Select the first row.
+    Top,
+    /// Select the last row.
+    Bottom,
+    /// Descend into the selected directory.
+    Open,
+    /// Go up to the parent directory.
+    Back,
+    /// Sort by size, or reverse if already sorted by size.
+    SortBySize,
+    /// Sort by name, or reverse if already sorted by name.
+    SortByName,
+    /// Ask to delete the selected entry.
+    Delete,
+    /// Ask to move the selected entry to the trash.
+    Trash,
+    /// Answer "yes" to a pending confirmation.
+    Confirm,
+    /// Leave the browser.
+    Quit,
+    /// Terminal was resized.
+    Resize(u16, u16),
+    /// No action (unrecognized key or event).
+    None,
+}
+
+/// Maps a crossterm [`Event`] to an [`Action`].
+///
+/// Key releases are ignored, so each key press acts once on platforms that
+/// report both.
+pub fn map_event(event: &Event) -> Action {
+    match event {
+        Event::Key(KeyEvent {
+            code,
+            modifiers,
+            kind,
+            ..
+        }) if *kind != KeyEventKind::Release => map_key(*code, *modifiers),
+        Event::Resize(cols, rows) => Action::Resize(*cols, *rows),
+        _ => Action::None,
+    }
+}
+
+fn map_key(code: KeyCode, modifiers: KeyModifiers) -> Action {
+    // Ctrl+C always quits
+    if modifiers.contains(KeyModifiers::CONTROL) && code == KeyCode::Char('c') {
+        return Action::Quit;
+    }
+
+    match code {
+        KeyCode::Char('q') | KeyCode::Esc => Action::Quit,
+        KeyCode::Char('k') | KeyCode::Up => Action::Up,
+        KeyCode::Char('j') | KeyCode::Down => Action::Down,
+        KeyCode::PageUp => Action::PageUp,
+        KeyCode::PageDown => Action::PageDown,
+        KeyCode::Char('g') | KeyCode::Home => Action::Top,
+        KeyCode::Char('G') | KeyCode::End => Action::Bottom,
+        KeyCode::Enter | KeyCode::Right | KeyCode::Char('l') => Action::Open,
+        KeyCode::Backspace | KeyCode::Left | KeyCode::Char('h') => Action::Back,
+        KeyCode::Char('s') => Action::SortBySize,
+        KeyCode::Char('n') => Action::SortByName,
+        KeyCode::Char('d') => Action::Delete,
+        KeyCode::Char('t') => Action::Trash,
+        KeyCode::Char('y') | KeyCode::Char('Y') => Action::Confirm,
+        _ => Action::None,
+    }
+}
+
+/// One listed child of the current directory.
+#[derive(Debug, Clone, Copy)]
+struct Row {
+    /// Index into the current directory's `children`.
+    index: usize,
+    /// Total size of the child, computed once per listing.
+    size: u64,
+}
+
+/// A directory entered on the way down from the root.
+#[derive(Debug, Clone, Copy)]
+struct Level {
+    /// Index of the entered directory in its parent's `children`.
+    index: usize,
+    /// Scroll offset of the parent listing, restored when going back.
+    top: usize,
+}
+
+/// Position and view settings of the browser.
+///
+/// Sorting only reorders the rows of the current listing, never the tree
+/// itself. The tree is borrowed mutably so removals can prune it.
+pub struct BrowserState<'a> {
+    root: &'a mut DiskEntry,
+    size_mode: SizeMode,
+    /// Current sort order of the listing.
+    pub sort: BrowseSort,
+    /// Path from the root to the current directory.
+    stack: Vec<Level>,
+    /// Children of the current directory, in display order.
+    rows: Vec<Row>,
+    /// Selected row (index into the listing).
+    pub selected: usize,
+    /// First visible row (index into the listing).
+    pub top: usize,
+    /// Whether the browser should quit.
+    pub quit: bool,
+    /// Only report what delete and trash would do.
+    pub dry_run: bool,
+    /// Removal waiting for a `y`, if any.
+    pub confirm: Option<RemoveMode>,
+    /// One-off message for the status line (result of the last removal).
+    pub message: Option<String>,
+    /// Everything that failed to be removed during this session.
+    pub failures: Vec<DuskError>,
+    /// Terminal width.
+    pub cols: u16,
+    /// Terminal height.
+    pub height: u16,
+}
+
+impl<'a> BrowserState<'a> {
+    /// Creates a browser positioned at the root of `tree`.
+    ///
+    /// # Arguments
+    ///
+    /// * `tree` - Scanned tree to browse
+    /// * `size_mode` - Show apparent or allocated sizes?
+    /// * `sort` - Initial sort order
+    /// * `cols`, `height` - Terminal size
+    pub fn new(
+        tree: &'a mut DiskEntry,
+        size_mode: SizeMode,
+        sort: BrowseSort,
+        cols: u16,
+        height: u16,
+    ) -> Self {
+        let mut state = BrowserState {
+            root: tree,
+            size_mode,
+            sort,
+            stack: Vec::new(),
+            rows: Vec::new(),
+            selected: 0,
+            top: 0,
+            quit: false,
+            dry_run: false,
+            confirm: None,
+            message: None,
+            failures: Vec::new(),
+            cols,
+            height,
+        };
+        state.refresh_rows();
+        state
+    }
+
+    /// Returns the directory currently shown.
+    pub fn current(&self) -> &DiskEntry {
+        let mut entry: &DiskEntry = self.root;
+        for level in &self.stack {
+            entry = &entry.children[level.index];
+        }
+        entry
+    }
+
+    /// Returns the children of the current directory in display order.
+    pub fn listing(&self) -> Vec<&DiskEntry> {
+        let current = self.current();
+        self.rows
+            .iter()
+            .map(|row| &current.children[row.index])
+            .collect()
+    }
+
+    /// Returns the selected entry, or `None` in an empty directory.
+    pub fn selected_entry(&self) -> Option<&DiskEntry> {
+        let row = self.rows.get(self.selected)?;
+        Some(&self.current().children[row.index])
+    }
+
+    /// Number of rows available for the listing (minus header and status).
+    pub fn list_rows(&self) -> usize {
+        (self.height as usize).saturating_sub(2).max(1)
+    }
+
+    /// Applies an action to update the state.
+    ///
+    /// While a removal waits for confirmation, `Confirm` carries it out and
+    /// any other key except quit and resize cancels it.
+    pub fn apply_action(&mut self, action: Action) {
+        // Messages are shown once, until the next key
+        self.message = None;
+
+        if let Some(mode) = self.confirm {
+            match action {
+                Action::Confirm => {
+                    self.confirm = None;
+                    self.remove_selected(mode);
+                }
+                Action::Resize(..) | Action::Quit => {}
+                _ => {
+                    self.confirm = None;
+                    self.message = Some("Cancelled".to_string());
+                    return;
+                }
+            }
+        }
+
+        let last = self.rows.len().saturating_sub(1);
+        match action {
+            Action::Quit => self.quit = true,
+            Action::Up => self.selected = self.selected.saturating_sub(1),
+            Action::Down => self.selected = (self.selected + 1).min(last),
+            Action::PageUp => self.selected = self.selected.saturating_sub(self.list_rows()),
+            Action::PageDown => self.selected = (self.selected + self.list_rows()).min(last),
+            Action::Top => self.selected = 0,
+            Action::Bottom => self.selected = last,
+            Action::Open => self.descend(),
+            Action::Back => self.ascend(),
+            Action::SortBySize => {
+                self.resort(if self.sort == BrowseSort::SizeDescending {
+                    BrowseSort::SizeAscending
+                } else {
+                    BrowseSort::SizeDescending
+                });
+            }
+            Action::SortByName => {
+                self.resort(if self.sort == BrowseSort::NameAscending {
+                    BrowseSort::NameDescending
+                } else {
+                    BrowseSort::NameAscending
+                });
+            }
+            Action::Delete | Action::Trash => match self.selected_entry() {
+                // Shown as 0 bytes, but holds a filesystem that wasn't scanned
+                Some(entry) if entry.entry_type == EntryType::MountPoint => {
+                    self.message = Some(format!(
+                        "Cannot remove {}: not scanned",
+                        display_name(entry)
+                    ));
+                }
+                Some(_) => {
+                    self.confirm = Some(if action == Action::Delete {
+                        RemoveMode::Delete
+                    } else {
+                        RemoveMode::Trash
+                    });
+                }
+                None => {}
+            },
+            Action::Resize(cols, height) => {
+                self.cols = cols;
+                self.height = height;
+            }
+            Action::Confirm | Action::None => {}
+        }
+        self.scroll_to_selection();
+    }
+
+    /// Removes the selected entry and reports the outcome in `message`.
+    fn remove_selected(&mut self, mode: RemoveMode) {
+        let Some(entry) = self.selected_entry() else {
+            return;
+        };
+        let path = entry.path.clone();
+        let name = display_name(entry);
+
+        let result = remove_entry(self.root, &path, mode, self.dry_run, self.size_mode);
+        self.message = Some(match result {
+            Ok(report) if report.dry_run => format!(
+                "Dry run: would {} {name}, freeing {}",
+                mode.verb(),
+                format_size(report.freed_bytes, true)
+            ),
+            Ok(mut report) => {
+                let freed = format_size(report.freed_bytes, true);
+                let text = match report.failures.first() {
+                    None => format!("Removed {name}, freed {freed}"),
+                    Some(first) => format!(
+                        "Removed {name} partially, freed {freed}; {} error(s), first: {first}",
+                        report.failures.len()
+                    ),
+                };
+                self.failures.append(&mut report.failures);
+                text
+            }
+            Err(err) => format!("Error: {err}"),
+        });
+
+        self.refresh_rows();
+        self.selected = self.selected.min(self.rows.len().saturating_sub(1));
+    }
+
+    /// Enters the selected entry if it is a directory.
+    fn descend(&mut self) {
+        let Some(row) = self.rows.get(self.selected).copied() else {
+            return;
+        };
+        if self.current().children[row.index].entry_type != EntryType::Directory {
+            return;
+        }
+
+        self.stack.push(Level {
+            index: row.index,
+            top: self.top,
+        });
+        self.refresh_rows();
+        self.selected = 0;
+        self.top = 0;
+    }
+
+    /// Returns to the parent directory, selecting the directory we came from.
+    fn ascend(&mut self) {
+        let Some(level) = self.stack.pop() else {
+            return;
+        };
+        self.refresh_rows();
+        self.selected = self.position_of(level.index);
+        self.top = level.top;
+    }
+
+    /// Changes the sort order, keeping the selected entry selected.
+    fn resort(&mut self, sort: BrowseSort) {
+        let selected_index = self.rows.get(self.selected).map(|row| row.index);
+        self.sort = sort;
+        self.refresh_rows();
+        if let Some(index) = selected_index {
+            self.selected = self.position_of(index);
+        }
+    }
+
+    /// Returns the listing position of a child index (0 if not listed).
+    fn position_of(&self, index: usize) -> usize {
+        self.rows
+            .iter()
+            .position(|row| row.index == index)
+            .unwrap_or(0)
+    }
+
+    /// Rebuilds the listing of the current directory in the current order.
+    fn refresh_rows(&mut self) {
+        let current = self.current();
+        let mut rows: Vec<Row> = current
+            .children
+            .iter()
+            .enumerate()
+            .map(|(index, child)| Row {
+                index,
+                size: child.total_size_for(self.size_mode),
+            })
+            .collect();
+
+        let name = |row: &Row| &current.children[row.index].path;
+        match self.sort {
+            BrowseSort::SizeDescending => rows.sort_by_key(|row| Reverse(row.size)),
+            BrowseSort::SizeAscending => rows.sort_by_key(|row| row.size),
+            BrowseSort::NameAscending => rows.sort_by(|a, b| name(a).cmp(name(b))),
+            BrowseSort::NameDescending => rows.sort_by(|a, b| name(b).cmp(name(a))),
+        }
+
+        self.rows = rows;
+    }
+
+    /// Adjusts `top` so the selected row is visible.
+    fn scroll_to_selection(&mut self) {
+        let visible = self.list_rows();
+        if self.selected < self.top {
+            self.top = self.selected;
+        } else if self.selected >= self.top + visible {
+            self.top = self.selected + 1 - visible;
+        }
+    }
+}
+
+/// Returns the share of `size` in `total`, in percent.
+fn percent(size: u64, total: u64) -> f64 {
+    if total == 0 {
+        0.0
+    } else {
+        size as f64 * 100.0 / total as f64
+    }
+}
+
+/// Draws a percent bar of [`BAR_WIDTH`] cells, e.g. `[#####     ]`.
+fn percent_bar(percent: f64) -> String {
+    let filled = ((percent / 100.0 * BAR_WIDTH as f64).round() as usize).min(BAR_WIDTH);
+    format!("[{}{}]", "#".repeat(filled), " ".repeat(BAR_WIDTH - filled))
+}
+
+/// Returns the name shown for an entry: its file name plus a type suffix.
+///
+/// Symlinks also show their target, as `name@ -> target`.
+fn display_name(entry: &DiskEntry) -> String {
+    let name = entry
+        .path
+        .file_name()
+        .map(|n| n.to_string_lossy().into_owned())
+        .unwrap_or_else(|| entry.path.display().to_string());
+
+    let name = match entry.entry_type {
+        EntryType::Directory => format!("{name}/"),
+        EntryType::Symlink => format!("{name}@{}", link_suffix(entry)),
+        EntryType::MountPoint => format!("{name}/ [mount point]"),
+        EntryType::File | EntryType::Other | EntryType::Aggregate => name,
+    };
+
+    // Directories a stopped scan didn't finish reading (--timeout)
+    if entry.incomplete {
+        format!("{name} [incomplete]")
+    } else {
+        name
+    }
+}
+
+/// Uppercases the first letter of a prompt verb.
+fn capitalize(text: &str) -> String {
+    let mut chars = text.chars();
+    match chars.next() {
+        Some(first) => first.to_uppercase().chain(chars).collect(),
+        None => String::new(),
+    }
+}
+
+/// Truncates or pads a line to exactly `width` characters.
+fn fit(text: &str, width: usize) -> String {
+    let truncated: String = text.chars().take(width).collect();
+    format!("{truncated:<width$}")
+}
+
+/// Draws one frame: header, visible rows of the listing and status line.
+///
+/// # Layout
+///
+/// ```text
+/// dusk  /var/log                                       (inverted)
+///     1.2G  80.0% [########  ] journal/                (inverted if selected)
+///   300.0M  20.0% [##        ] syslog
+/// Total: 1.5G  Items: 2  Sort: size desc  ...          (inverted)
+/// ```
+///
+/// # Errors
+///
+/// Propagates terminal errors.
+pub fn render(term: &mut dyn Terminal, state: &BrowserState) -> Result<(), DuskError> {
+    let width = state.cols as usize;
+    let current = state.current();
+    let total = current.total_size_for(state.size_mode);
+    let listing = state.listing();
+
+    term.clear()?;
+
+    // Header: where we are, and whether the scan got to the bottom of it
+    term.move_to(0, 0)?;
+    let incomplete = if current.incomplete {
+        " [incomplete]"
+    } else {
+        ""
+    };
+    term.print_inverted(&fit(
+        &format!(" dusk  {}{incomplete}", current.path.display()),
+        width,
+    ))?;
+
+    if listing.is_empty() {
+        term.move_to(0, 1)?;
+        term.print(&fit("  (empty directory)", width))?;
+    }
+
+    // Rows of the listing
+    let visible = listing
+        .iter()
+        .zip(&state.rows)
+        .enumerate()
+        .skip(state.top)
+        .take(state.list_rows());
+    for (screen_row, (position, (entry, row))) in visible.enumerate() {
+        let share = percent(row.size, total);
+        let line = fit(
+            &format!(
+                "{:>9} {:>5.1}% {} {}",
+                format_size(row.size, true),
+                share,
+                percent_bar(share),
+                display_name(entry)
+            ),
+            width,
+        );
+
+        term.move_to(0, (screen_row + 1) as u16)?;
+        if position == state.selected {
+            term.print_inverted(&line)?;
+        } else {
+            term.print(&line)?;
+        }
+    }
+
+    // Status line: pending confirmation, last message or summary
+    let status = if let (Some(mode), Some(entry)) = (state.confirm, state.selected_entry()) {
+        format!(
+            " {} {} ({})?{} [y/N]",
+            capitalize(mode.verb()),
+            entry.path.display(),
+            format_size(entry.total_size_for(state.size_mode), true),
+            if state.dry_run { " (dry run)" } else { "" }
+        )
+    } else if let Some(message) = &state.message {
+        format!(" {message}")
+    } else {
+        format!(
+            " Total: {}  Items: {}  Sort: {}  ⏎ open  ⌫ back  s/n sort  d/t remove  q quit",
+            format_size(total, true),
+            listing.len(),
+            state.sort.label()
+        )
+    };
+    term.move_to(0, state.height.saturating_sub(1))?;
+    term.print_inverted(&fit(&status, width))?;
+
+    term.flush()
+}
+
+/// Runs the interactive browser until the user quits.
+///
+/// Enters the alternate screen, then draws and handles events in a loop.
+/// The terminal is always restored, even if drawing or reading fails.
+///
+/// # Arguments
+///
+/// * `term` - Terminal to draw on (crossterm in production, mock in tests)
+/// * `tree` - Scanned tree; never rescanned, only pruned by removals
+/// * `size_mode` - Show apparent or allocated sizes?
+/// * `sort` - Initial sort order
+/// * `dry_run` - Only report what delete and trash would do
+///
+/// # Returns
+///
+/// Every removal failure of the session, so the caller can report them
+/// once the terminal is restored.
+///
+/// # Errors
+///
+/// Returns `TerminalError` if terminal I/O fails. The first error wins.
+pub fn run_browser(
+    term: &mut dyn Terminal,
+    tree: &mut DiskEntry,
+    size_mode: SizeMode,
+    sort: BrowseSort,
+    dry_run: bool,
+) -> Result<Vec<DuskError>, DuskError> {
+    term.enter()?;
+
+    let result = run_browser_inner(term, tree, size_mode, sort, dry_run);
+
+    // Always restore terminal
+    let leave_result = term.leave();
+
+    // Return the first error
+    let failures = result?;
+    leave_result?;
+    Ok(failures)
+}
+
+fn run_browser_inner(
+    term: &mut dyn Terminal,
+    tree: &mut DiskEntry,
+    size_mode: SizeMode,
+    sort: BrowseSort,
+    dry_run: bool,
+) -> Result<Vec<DuskError>, DuskError> {
+    let (cols, height) = term.size()?;
+    let mut state = BrowserState::new(tree, size_mode, sort, cols, height);
+    state.dry_run = dry_run;
+
+    while !state.quit {
+        render(term, &state)?;
+        let event = term.read_event()?;
+        state.apply_action(map_event(&event));
+    }
+
+    Ok(state.failures)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::terminal::MockTerminal;
+    use std::path::PathBuf;
+
+    fn key(code: KeyCode) -> Event {
+        Event::Key(KeyEvent::new(code, KeyModifiers::NONE))
+    }
+
+    fn entry(path: &str, size: u64, entry_type: EntryType, depth: usize) -> DiskEntry {
+        DiskEntry::new(PathBuf::from(path), size, entry_type, depth)
+    }
+
+    /// /r (0) with big/ (600 + 300 in big/inner.bin), small.txt (100) and
+    /// empty/ (0).
+    fn sample_tree() -> DiskEntry {
+        let mut root = entry("/r", 0, EntryType::Directory, 0);
+        let mut big = entry("/r/big", 600, EntryType::Directory, 1);
+        big.children
+            .push(entry("/r/big/inner.bin", 300, EntryType::File, 2));
+        root.children
+            .push(entry("/r/small.txt", 100, EntryType::File, 1));
+        root.children.push(big);
+        root.children
+            .push(entry("/r/empty", 0, EntryType::Directory, 1));
+        root
+    }
+
+    fn names(state: &BrowserState) -> Vec<String> {
+        state.listing().iter().map(|e| display_name(e)).collect()
+    }
+
+    #[test]
+    fn test_map_event_keys() {
+        assert_eq!(map_event(&key(KeyCode::Enter)), Action::Open);
+        assert_eq!(map_event(&key(KeyCode::Backspace)), Action::Back);
+        assert_eq!(map_event(&key(KeyCode::Down)), Action::Down);
+        assert_eq!(map_event(&key(KeyCode::Char('k'))), Action::Up);
+        assert_eq!(map_event(&key(KeyCode::Char('s'))), Action::SortBySize);
+        assert_eq!(map_event(&key(KeyCode::Char('n'))), Action::SortByName);
+        assert_eq!(map_event(&key(KeyCode::Char('q'))), Action::Quit);
+        assert_eq!(map_event(&key(KeyCode::Char('x'))), Action::None);
+        assert_eq!(map_event(&Event::Resize(100, 30)), Action::Resize(100, 30));
+    }
+
+    #[test]
+    fn test_map_event_ctrl_c_and_release() {
+        let ctrl_c = Event::Key(KeyEvent::new(KeyCode::Char('c'), KeyModifiers::CONTROL));
+        assert_eq!(map_event(&ctrl_c), Action::Quit);
+
+        let mut release = KeyEvent::new(KeyCode::Char('q'), KeyModifiers::NONE);
+        release.kind = KeyEventKind::Release;
+        assert_eq!(map_event(&Event::Key(release)), Action::None);
+    }
+
+    #[test]
+    fn test_initial_listing_sorted_by_size() {
+        let mut tree = sample_tree();
+        let state = BrowserState::new(
+            &mut tree,
+            SizeMode::Apparent,
+            BrowseSort::SizeDescending,
+            80,
+            10,
+        );
+        assert_eq!(names(&state), vec!["big/", "small.txt", "empty/"]);
+        assert_eq!(
+            state.selected_entry().unwrap().path,
+            PathBuf::from("/r/big")
+        );
+    }
+
+    #[test]
+    fn test_descend_and_back_restores_selection() {
+        let mut tree = sample_tree();
+        let mut state = BrowserState::new(
+            &mut tree,
+            SizeMode::Apparent,
+            BrowseSort::NameAscending,
+            80,
+            10,
+        );
+        // big/, empty/, small.txt
+        assert_eq!(state.selected, 0);
+
+        state.apply_action(Action::Open);
+        assert_eq!(state.current().path, PathBuf::from("/r/big"));
+        assert_eq!(names(&state), vec!["inner.bin"]);
+
+        state.apply_action(Action::Back);
+        assert_eq!(state.current().path, PathBuf::from("/r"));
+        assert_eq!(
+            state.selected_entry().unwrap().path,
+            PathBuf::from("/r/big")
+        );
+
+        // Back at the root does nothing
+        state.apply_action(Action::Back);
+        assert_eq!(state.current().path, PathBuf::from("/r"));
+    }
+
+    #[test]
+    fn test_open_ignores_files() {
+        let mut tree = sample_tree();
+        let mut state = BrowserState::new(
+            &mut tree,
+            SizeMode::Apparent,
+            BrowseSort::SizeDescending,
+            80,
+            10,
+        );
+        state.apply_action(Action::Down);
+        assert_eq!(names(&state)[state.selected], "small.txt");
+        state.apply_action(Action::Open);
+        assert_eq!(state.current().path, PathBuf::from("/r"));
+    }
+
+    #[test]
+    fn test_sort_toggles_keep_selection() {
+        let mut tree = sample_tree();
+        let mut state = BrowserState::new(
+            &mut tree,
+            SizeMode::Apparent,
+            BrowseSort::SizeDescending,
+            80,
+            10,
+        );
+        state.apply_action(Action::SortBySize);
+        assert_eq!(state.sort, BrowseSort::SizeAscending);
+        assert_eq!(names(&state), vec!["empty/", "small.txt", "big/"]);
+        // big/ stays selected after reordering
+        assert_eq!(state.selected, 2);
+
+        state.apply_action(Action::SortByName);
+        assert_eq!(state.sort, BrowseSort::NameAscending);
+        assert_eq!(names(&state), vec!["big/", "empty/", "small.txt"]);
+        state.apply_action(Action::SortByName);
+        assert_eq!(state.sort, BrowseSort::NameDescending);
+        assert_eq!(names(&state), vec!["small.txt", "empty/", "big/"]);
+    }
+
+    #[test]
+    fn test_selection_clamped_and_scrolled() {
+        let mut root = entry("/r", 0, EntryType::Directory, 0);
+        for i in 0..20 {
+            root.children
+                .push(entry(&format!("/r/f{i:02}"), i, EntryType::File, 1));
+        }
+        // 5 rows: header, 3 listing rows, status
+        let mut state = BrowserState::new(
+            &mut root,
+            SizeMode::Apparent,
+            BrowseSort::NameAscending,
+            80,
+            5,
+        );
+        state.apply_action(Action::Bottom);
+        assert_eq!(state.selected, 19);
+        assert_eq!(state.top, 17);
+        state.apply_action(Action::Down);
+        assert_eq!(state.selected, 19);
+        state.apply_action(Action::PageUp);
+        assert_eq!(state.selected, 16);
+        assert_eq!(state.top, 16);
+        state.apply_action(Action::Top);
+        assert_eq!((state.selected, state.top), (0, 0));
+    }
+
+    #[test]
+    fn test_percent_bar() {
+        assert_eq!(percent(50, 200), 25.0);
+        assert_eq!(percent(5, 0), 0.0);
+        assert_eq!(percent_bar(0.0), "[          ]");
+        assert_eq!(percent_bar(50.0), "[#####     ]");
+        assert_eq!(percent_bar(100.0), "[##########]");
+    }
+
+    #[test]
+    fn test_render_rows_relative_to_parent() {
+        let mut tree = sample_tree();
+        let state = BrowserState::new(
+            &mut tree,
+            SizeMode::Apparent,
+            BrowseSort::SizeDescending,
+            80,
+            10,
+        );
+        let mut term = MockTerminal::new(80, 10, vec![]);
+        render(&mut term, &state).unwrap();
+
+        let frame = term.last_frame().join("");
+        // Header shows the current directory
+        assert!(frame.contains("dusk  /r"));
+        // big/ is 900 of 1000 bytes, selected and therefore inverted
+        assert!(frame.contains("[INV]     900B  90.0% [######### ] big/"));
+        assert!(frame.contains("     100B  10.0% [#         ] small.txt"));
+        assert!(frame.contains("Total: 1000B  Items: 3  Sort: size desc"));
+    }
+
+    #[test]
+    fn test_render_marks_incomplete_directories() {
+        let mut tree = sample_tree();
+        tree.incomplete = true;
+        tree.children[1].incomplete = true;
+        let state = BrowserState::new(
+            &mut tree,
+            SizeMode::Apparent,
+            BrowseSort::SizeDescending,
+            80,
+            10,
+        );
+        let mut term = MockTerminal::new(80, 10, vec![]);
+        render(&mut term, &state).unwrap();
+
+        let frame = term.last_frame().join("");
+        assert!(frame.contains("dusk  /r [incomplete]"));
+        assert!(frame.contains("big/ [incomplete]"));
+        assert!(!frame.contains("small.txt [incomplete]"));
+    }
+
+    #[test]
+    fn test_render_empty_directory() {
+        let mut tree = sample_tree();
+        let mut state = BrowserState::new(
+            &mut tree,
+            SizeMode::Apparent,
+            BrowseSort::SizeDescending,
+            40,
+            6,
+        );
+        state.apply_action(Action::Bottom);
+        state.apply_action(Action::Open);
+
+        let mut term = MockTerminal::new(40, 6, vec![]);
+        render(&mut term, &state).unwrap();
+        assert!(
+            term.last_frame()
+                .iter()
+                .any(|s| s.contains("(empty directory)"))
+        );
+    }
+
+    #[test]
+    fn test_render_truncates_to_width() {
+        let mut tree = sample_tree();
+        let state = BrowserState::new(
+            &mut tree,
+            SizeMode::Apparent,
+            BrowseSort::SizeDescending,
+            20,
+            6,
+        );
+        let mut term = MockTerminal::new(20, 6, vec![]);
+        render(&mut term, &state).unwrap();
+        for text in term.last_frame() {
+            let plain = text.replace("[INV]", "").replace("[/INV]", "");
+            if !plain.starts_with("[MOVE") {
+                assert_eq!(plain.chars().count(), 20, "{plain:?}");
+            }
+        }
+    }
+
+    #[test]
+    fn test_run_browser_navigates_without_rescan() {
+        let mut tree = sample_tree();
+        let events = vec![
+            key(KeyCode::Enter),
+            key(KeyCode::Backspace),
+            key(KeyCode::Char('n')),
+            key(KeyCode::Char('q')),
+        ];
+        let mut term = MockTerminal::new(80, 10, events);
+        run_browser(
+            &mut term,
+            &mut tree,
+            SizeMode::Apparent,
+            BrowseSort::SizeDescending,
+            false,
+        )
+        .unwrap();
+
+        assert!(term.entered);
+        assert!(term.left);
+        // 4 frames: start, inside big/, back at /r, sorted by name
+        let frames = term.output.iter().filter(|s| *s == "[CLEAR]").count();
+        assert_eq!(frames, 4);
+        assert!(term.last_frame().join("").contains("Sort: name asc"));
+    }
+
+    /// Scans a temp dir holding `keep.txt` (5 bytes) and `cache/` with
+    /// `blob.bin` (1000 bytes).
+    fn scanned_temp_tree() -> (tempfile::TempDir, DiskEntry) {
+        let tmp = tempfile::TempDir::new().unwrap();
+        std::fs::write(tmp.path().join("keep.txt"), "hello").unwrap();
+        std::fs::create_dir(tmp.path().join("cache")).unwrap();
+        std::fs::write(tmp.path().join("cache/blob.bin"), vec![0u8; 1000]).unwrap();
+        let tree = crate::traversal::traverse(tmp.path(), None).unwrap();
+        (tmp, tree)
+    }
+
+    #[test]
+    fn test_delete_asks_and_updates_totals() {
+        let (tmp, mut tree) = scanned_temp_tree();
+        let total_before = tree.total_size_for(SizeMode::Apparent);
+        let mut state = BrowserState::new(
+            &mut tree,
+            SizeMode::Apparent,
+            BrowseSort::SizeDescending,
+            80,
+            10,
+        );
+        assert_eq!(names(&state)[0], "cache/");
+
+        state.apply_action(Action::Delete);
+        assert_eq!(state.confirm, Some(RemoveMode::Delete));
+        // Nothing happens before the answer
+        assert!(tmp.path().join("cache").exists());
+
+        state.apply_action(Action::Confirm);
+        assert!(state.confirm.is_none());
+        assert!(!tmp.path().join("cache").exists());
+        assert_eq!(names(&state), vec!["keep.txt"]);
+        assert!(
+            state
+                .message
+                .as_deref()
+                .unwrap()
+                .starts_with("Removed cache/")
+        );
+
+        let freed = total_before - state.current().total_size_for(SizeMode::Apparent);
+        assert!(freed >= 1000);
+        assert!(state.failures.is_empty());
+    }
+
+    #[test]
+    fn test_delete_refuses_mount_points() {
+        let (tmp, mut tree) = scanned_temp_tree();
+        let cache = tree
+            .children
+            .iter_mut()
+            .find(|child| child.path.ends_with("cache"))
+            .unwrap();
+        cache.entry_type = EntryType::MountPoint;
+        cache.children.clear();
+        let mut state = BrowserState::new(
+            &mut tree,
+            SizeMode::Apparent,
+            BrowseSort::NameAscending,
+            80,
+            10,
+        );
+        assert_eq!(names(&state)[0], "cache/ [mount point]");
+
+        for action in [Action::Delete, Action::Trash] {
+            state.apply_action(action);
+            assert!(state.confirm.is_none());
+            assert_eq!(
+                state.message.as_deref(),
+                Some("Cannot remove cache/ [mount point]: not scanned")
+            );
+        }
+        assert!(tmp.path().join("cache/blob.bin").exists());
+    }
+
+    #[test]
+    fn test_delete_cancelled_by_other_key() {
+        let (tmp, mut tree) = scanned_temp_tree();
+        let mut state = BrowserState::new(
+            &mut tree,
+            SizeMode::Apparent,
+            BrowseSort::SizeDescending,
+            80,
+            10,
+        );
+        state.apply_action(Action::Delete);
+        state.apply_action(Action::Down);
+
+        assert!(state.confirm.is_none());
+        assert_eq!(state.message.as_deref(), Some("Cancelled"));
+        // The key only cancelled, it did not move the selection
+        assert_eq!(state.selected, 0);
+        assert!(tmp.path().join("cache/blob.bin").exists());
+    }
+
+    #[test]
+    fn test_dry_run_keeps_files_and_tree() {
+        let (tmp, mut tree) = scanned_temp_tree();
+        let mut state = BrowserState::new(
+            &mut tree,
+            SizeMode::Apparent,
+            BrowseSort::SizeDescending,
+            80,
+            10,
+        );
+        state.dry_run = true;
+        state.apply_action(Action::Delete);
+
+        let mut term = MockTerminal::new(80, 10, vec![]);
+        render(&mut term, &state).unwrap();
+        assert!(term.last_frame().join("").contains("(dry run) [y/N]"));
+
+        state.apply_action(Action::Confirm);
+        assert!(tmp.path().join("cache/blob.bin").exists());
+        assert_eq!(names(&state), vec!["cache/", "keep.txt"]);
+        assert!(
+            state
+                .message
+                .as_deref()
+                .unwrap()
+                .starts_with("Dry run: would delete cache/")
+        );
+    }
+
+    #[test]
+    fn test_run_browser_delete_flow() {
+        let (tmp, mut tree) = scanned_temp_tree();
+        let events = vec![
+            key(KeyCode::Char('d')),
+            key(KeyCode::Char('y')),
+            key(KeyCode::Char('q')),
+        ];
+        let mut term = MockTerminal::new(80, 10, events);
+        let failures = run_browser(
+            &mut term,
+            &mut tree,
+            SizeMode::Apparent,
+            BrowseSort::SizeDescending,
+            false,
+        )
+        .unwrap();
+
+        assert!(failures.is_empty());
+        assert!(!tmp.path().join("cache").exists());
+        assert!(tree.children.iter().all(|c| !c.path.ends_with("cache")));
+    }
+
+    #[test]
+    fn test_run_browser_restores_terminal_on_error() {
+        let mut tree = sample_tree();
+        // Events run out without a quit key
+        let mut term = MockTerminal::new(80, 10, vec![key(KeyCode::Down)]);
+        let result = run_browser(
+            &mut term,
+            &mut tree,
+            SizeMode::Apparent,
+            BrowseSort::SizeDescending,
+            false,
+        );
+        assert!(matches!(result, Err(DuskError::TerminalError(_))));
+        assert!(term.left);
+    }
+}
@@ -0,0 +1,603 @@
+//! Persistent scan cache (`--cache`).
+//!
+//! Re-scanning a large tree is dominated by `readdir` and `lstat` calls on
+//! directories that haven't changed since the last run. With `--cache`,
+//! the parallel traversal remembers every directory's listing together
+//! with the sizes, owners and timestamps of its non-directory entries,
+//! keyed by the directory's path, inode and modification time. On the next
+//! run a directory whose key still matches is not read again: its cached
+//! entries are reused and only its subdirectories are checked, each with a
+//! single `lstat`.
+//!
+//! # Invalidation
+//!
+//! A directory's mtime changes whenever an entry is created, removed or
+//! renamed in it, so such directories are re-read and their listing is
+//! replaced. Replacing a directory (new inode) also misses. Two cases need
+//! extra care:
+//!
+//! - **Racy mtimes**: a directory changed within the same second as it was
+//!   read could keep the same mtime. Directories modified at or after the
+//!   start of the scan are therefore never cached.
+//! - **Files rewritten in place**: appending to or truncating a file
+//!   changes the file's metadata but not its directory's mtime, so the old
+//!   size is reused until something else touches the directory. This is
+//!   the price of skipping per-file `lstat` calls; `--no-cache` always
+//!   gives fresh results.
+//!
+//! Only the default parallel traversal uses the cache; `--stream` always
+//! reads the disk.
+//!
+//! # File Format
+//!
+//! The cache is a gzip-compressed file of JSON lines. The first line
+//! identifies the format, every other line describes one directory:
+//!
+//! ```json
+//! {"format": "dusk-cache", "version": 1}
+//! {"path": "/srv/www", "inode": 1234, "mtime": 1767225600, "mtime_nsec": 0,
+//!  "entries": [["index.html", "file", [512, 4096, [33, 33], [1767225600, 1767225600, 1767225600], null]],
+//!              ["static", "directory", null]]}
+//! ```
+//!
+//! - An entry is `[name, type, stats]`, with `stats` holding the apparent
+//!   and allocated sizes, `[uid, gid]`, `[mtime, atime, ctime]` and the
+//!   `[device, inode]` pair of multiply-linked files (hard links are
+//!   deduplicated again on every run)
+//! - Subdirectories, and entries excluded or unreadable when the cache was
+//!   written, have no stats; the latter make the directory a miss, so a
+//!   different `--exclude` never hides anything
+//! - Directories with names that aren't valid UTF-8 are not cached
+//!
+//! An unreadable, damaged or newer-version cache is treated as empty.
+//!
+//! # Examples
+//!
+//! ```no_run
+//! use disk_usage_clone::cache::ScanCache;
+//! use disk_usage_clone::traversal::{TraversalOptions, traverse_parallel_with_options};
+//! use std::path::Path;
+//! use std::sync::Arc;
+//!
+//! let file = ScanCache::default_path().unwrap();
+//! let cache = Arc::new(ScanCache::load(&file));
+//! let options = TraversalOptions {
+//!     cache: Some(Arc::clone(&cache)),
+//!     ..TraversalOptions::default()
+//! };
+//! let tree = traverse_parallel_with_options(Path::new("/srv"), &options).unwrap();
+//! cache.save(&file).unwrap();
+//! println!("{} bytes, {} directories reused", tree.total_size(), cache.hits());
+//! ```
+
+use std::collections::HashMap;
+use std::env;
+use std::fs::{self, File};
+use std::io::{self, BufRead, BufReader, BufWriter, Write};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Mutex, PoisonError};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use flate2::Compression;
+use flate2::read::GzDecoder;
+use flate2::write::GzEncoder;
+use serde_json::{Value, json};
+
+use crate::entry::{EntryType, Owner, Timestamps};
+use crate::error::DuskError;
+use crate::traversal::EntryStats;
+
+/// Value of the `format` field, identifying dusk scan caches.
+const CACHE_FORMAT: &str = "dusk-cache";
+
+/// Current cache format version.
+pub const CACHE_VERSION: u64 = 1;
+
+/// Identity and state of a directory when its listing was cached.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct DirKey {
+    inode: u64,
+    mtime: i64,
+    mtime_nsec: i64,
+}
+
+impl DirKey {
+    /// Returns the key of a directory from its metadata.
+    #[cfg(unix)]
+    pub(crate) fn from_metadata(metadata: &fs::Metadata) -> Option<DirKey> {
+        use std::os::unix::fs::MetadataExt;
+
+        Some(DirKey {
+            inode: metadata.ino(),
+            mtime: metadata.mtime(),
+            mtime_nsec: metadata.mtime_nsec(),
+        })
+    }
+
+    /// Returns the key of a directory from its metadata.
+    ///
+    /// Non-Unix fallback: without inode numbers a replaced directory can't
+    /// be told apart, so nothing is cached.
+    #[cfg(not(unix))]
+    pub(crate) fn from_metadata(_metadata: &fs::Metadata) -> Option<DirKey> {
+        None
+    }
+}
+
+/// One entry of a cached directory listing.
+#[derive(Debug, Clone)]
+pub(crate) struct CachedEntry {
+    /// File name within the directory.
+    pub(crate) name: String,
+    /// Type as read by `readdir`; never a mount point or aggregate.
+    pub(crate) entry_type: EntryType,
+    /// Stats before hard-link deduplication, or `None` for directories and
+    /// entries that weren't stat'ed.
+    pub(crate) stats: Option<EntryStats>,
+}
+
+/// A directory listing with the key it is valid for.
+#[derive(Debug, Clone)]
+pub(crate) struct CachedDir {
+    key: DirKey,
+    pub(crate) entries: Vec<CachedEntry>,
+}
+
+/// Directory listings kept between runs.
+///
+/// Loaded once before scanning and shared by all traversal workers, which
+/// look up directories and report fresh listings concurrently. Listings
+/// reported during the scan only take effect in [`ScanCache::save`], so
+/// lookups always see the cache as it was loaded.
+#[derive(Debug)]
+pub struct ScanCache {
+    /// Listings loaded from disk.
+    dirs: HashMap<PathBuf, CachedDir>,
+    /// Start of this run in seconds since the epoch (see racy mtimes above).
+    started: i64,
+    /// Directories seen by this run: a fresh listing, or `None` when the
+    /// loaded one was reused.
+    seen: Mutex<Vec<(PathBuf, Option<CachedDir>)>>,
+    /// Roots scanned by this run, whose old listings are replaced on save.
+    roots: Mutex<Vec<PathBuf>>,
+    hits: AtomicUsize,
+    misses: AtomicUsize,
+}
+
+impl Default for ScanCache {
+    fn default() -> Self {
+        ScanCache::new()
+    }
+}
+
+impl ScanCache {
+    /// Creates an empty cache.
+    pub fn new() -> ScanCache {
+        let started = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |d| d.as_secs() as i64);
+
+        ScanCache {
+            dirs: HashMap::new(),
+            started,
+            seen: Mutex::new(Vec::new()),
+            roots: Mutex::new(Vec::new()),
+            hits: AtomicUsize::new(0),
+            misses: AtomicUsize::new(0),
+        }
+    }
+
+    /// Loads the cache stored in `path`.
+    ///
+    /// A missing, unreadable, damaged or newer-version file gives an empty
+    /// cache: the scan then reads everything, as without `--cache`.
+    pub fn load(path: &Path) -> ScanCache {
+        let mut cache = ScanCache::new();
+        if let Some(dirs) = read_dirs(path) {
+            cache.dirs = dirs;
+        }
+        cache
+    }
+
+    /// Returns the default cache file.
+    ///
+    /// `$XDG_CACHE_HOME/dusk/scan-cache.gz`, or `~/.cache/dusk/scan-cache.gz`
+    /// when `XDG_CACHE_HOME` is unset. `None` if neither variable is set.
+    pub fn default_path() -> Option<PathBuf> {
+        let base = env::var_os("XDG_CACHE_HOME")
+            .filter(|dir| !dir.is_empty())
+            .map(PathBuf::from)
+            .or_else(|| env::var_os("HOME").map(|home| PathBuf::from(home).join(".cache")))?;
+        Some(base.join("dusk").join("scan-cache.gz"))
+    }
+
+    /// Number of directories reused from the cache so far.
+    pub fn hits(&self) -> usize {
+        self.hits.load(Ordering::Relaxed)
+    }
+
+    /// Number of directories read from disk so far (with a usable key).
+    pub fn misses(&self) -> usize {
+        self.misses.load(Ordering::Relaxed)
+    }
+
+    /// Returns the cached listing of `dir` if it is still valid for `key`.
+    ///
+    /// Counts a hit or a miss; the caller reports a hit that it can't use
+    /// (see [`ScanCache::reject`]) so the directory is read after all.
+    pub(crate) fn lookup(&self, dir: &Path, key: DirKey) -> Option<&CachedDir> {
+        match self.dirs.get(dir).filter(|cached| cached.key == key) {
+            Some(cached) => {
+                self.hits.fetch_add(1, Ordering::Relaxed);
+                Some(cached)
+            }
+            None => {
+                self.misses.fetch_add(1, Ordering::Relaxed);
+                None
+            }
+        }
+    }
+
+    /// Turns the last successful lookup of a directory into a miss.
+    pub(crate) fn reject(&self) {
+        self.hits.fetch_sub(1, Ordering::Relaxed);
+        self.misses.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Keeps the loaded listing of `dir`, which this run reused.
+    pub(crate) fn reuse(&self, dir: &Path) {
+        self.seen_dirs().push((dir.to_path_buf(), None));
+    }
+
+    /// Stores a fresh listing of `dir`, read while it had `key`.
+    ///
+    /// Ignored if the directory was modified at or after the start of the
+    /// run, since a change within the same second could go unnoticed.
+    pub(crate) fn store(&self, dir: &Path, key: DirKey, entries: Vec<CachedEntry>) {
+        if key.mtime < self.started {
+            self.seen_dirs()
+                .push((dir.to_path_buf(), Some(CachedDir { key, entries })));
+        }
+    }
+
+    /// Registers a scanned root: on save, every old listing under it that
+    /// this run didn't see is dropped.
+    pub(crate) fn add_root(&self, root: &Path) {
+        self.roots
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .push(root.to_path_buf());
+    }
+
+    /// Writes the cache to `path`, creating its directory if needed.
+    ///
+    /// Listings under the roots scanned by this run are replaced by what
+    /// the run saw; listings of other trees are kept. The file is written
+    /// next to `path` and renamed into place, so an interrupted save never
+    /// leaves a truncated cache behind.
+    ///
+    /// # Errors
+    ///
+    /// Returns `IoError` if the directory or file can't be created or written.
+    pub fn save(&self, path: &Path) -> Result<(), DuskError> {
+        let roots = self.roots.lock().unwrap_or_else(PoisonError::into_inner);
+        let mut dirs: HashMap<&Path, &CachedDir> = self
+            .dirs
+            .iter()
+            .filter(|(dir, _)| !roots.iter().any(|root| dir.starts_with(root)))
+            .map(|(dir, cached)| (dir.as_path(), cached))
+            .collect();
+
+        let seen = self.seen_dirs();
+        for (dir, fresh) in seen.iter() {
+            let listing = fresh.as_ref().or_else(|| self.dirs.get(dir));
+            if let Some(listing) = listing {
+                dirs.insert(dir, listing);
+            }
+        }
+
+        if let Some(parent) = path.parent().filter(|p| !p.as_os_str().is_empty()) {
+            fs::create_dir_all(parent)?;
+        }
+        let mut temp = path.as_os_str().to_owned();
+        temp.push(format!(".{}.tmp", std::process::id()));
+        let temp = PathBuf::from(temp);
+
+        let written = write_dirs(&temp, &dirs).and_then(|()| fs::rename(&temp, path));
+        if written.is_err() {
+            let _ = fs::remove_file(&temp);
+        }
+        Ok(written?)
+    }
+
+    /// Locks the list of directories seen by this run.
+    fn seen_dirs(&self) -> std::sync::MutexGuard<'_, Vec<(PathBuf, Option<CachedDir>)>> {
+        // A panicking worker can't leave the list half-updated
+        self.seen.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+/// Writes the header line and one line per directory.
+fn write_dirs(path: &Path, dirs: &HashMap<&Path, &CachedDir>) -> io::Result<()> {
+    let file = BufWriter::new(File::create(path)?);
+    let mut encoder = GzEncoder::new(file, Compression::fast());
+
+    serde_json::to_writer(
+        &mut encoder,
+        &json!({"format": CACHE_FORMAT, "version": CACHE_VERSION}),
+    )?;
+    encoder.write_all(b"\n")?;
+
+    for (dir, cached) in dirs {
+        if let Some(line) = encode_dir(dir, cached) {
+            serde_json::to_writer(&mut encoder, &line)?;
+            encoder.write_all(b"\n")?;
+        }
+    }
+
+    encoder.finish()?.flush()
+}
+
+/// Reads every directory of a cache file, or `None` if it can't be used.
+fn read_dirs(path: &Path) -> Option<HashMap<PathBuf, CachedDir>> {
+    let file = File::open(path).ok()?;
+    let mut lines = BufReader::new(GzDecoder::new(BufReader::new(file))).lines();
+
+    let header: Value = serde_json::from_str(&lines.next()?.ok()?).ok()?;
+    if header["format"] != CACHE_FORMAT || header["version"].as_u64()? > CACHE_VERSION {
+        return None;
+    }
+
+    let mut dirs = HashMap::new();
+    for line in lines {
+        let value: Value = serde_json::from_str(&line.ok()?).ok()?;
+        let (dir, cached) = decode_dir(&value)?;
+        dirs.insert(dir, cached);
+    }
+    Some(dirs)
+}
+
+/// Encodes one directory, or `None` if its path isn't valid UTF-8.
+fn encode_dir(dir: &Path, cached: &CachedDir) -> Option<Value> {
+    let entries: Vec<Value> = cached
+        .entries
+        .iter()
+        .map(|entry| {
+            json!([
+                entry.name,
+                entry.entry_type.as_str(),
+                entry.stats.as_ref().map(encode_stats)
+            ])
+        })
+        .collect();
+
+    Some(json!({
+        "path": dir.to_str()?,
+        "inode": cached.key.inode,
+        "mtime": cached.key.mtime,
+        "mtime_nsec": cached.key.mtime_nsec,
+        "entries": entries,
+    }))
+}
+
+/// Encodes entry stats as `[size, allocated, owner, times, link]`.
+fn encode_stats(stats: &EntryStats) -> Value {
+    json!([
+        stats.size,
+        stats.allocated,
+        stats.owner.map(|o| [o.uid, o.gid]),
+        stats.times.map(|t| [t.modified, t.accessed, t.changed]),
+        stats.link.map(|(dev, ino)| [dev, ino]),
+    ])
+}
+
+/// Decodes one directory line, or `None` if a field is missing or invalid.
+fn decode_dir(value: &Value) -> Option<(PathBuf, CachedDir)> {
+    let key = DirKey {
+        inode: value["inode"].as_u64()?,
+        mtime: value["mtime"].as_i64()?,
+        mtime_nsec: value["mtime_nsec"].as_i64()?,
+    };
+    let entries = value["entries"]
+        .as_array()?
+        .iter()
+        .map(decode_entry)
+        .collect::<Option<Vec<_>>>()?;
+
+    Some((
+        PathBuf::from(value["path"].as_str()?),
+        CachedDir { key, entries },
+    ))
+}
+
+/// Decodes one `[name, type, stats]` entry.
+fn decode_entry(value: &Value) -> Option<CachedEntry> {
+    let stats = match &value[2] {
+        Value::Null => None,
+        stats => Some(decode_stats(stats)?),
+    };
+    Some(CachedEntry {
+        name: value[0].as_str()?.to_string(),
+        entry_type: EntryType::parse(value[1].as_str()?)?,
+        stats,
+    })
+}
+
+/// Decodes `[size, allocated, owner, times, link]`.
+fn decode_stats(value: &Value) -> Option<EntryStats> {
+    let owner = match &value[2] {
+        Value::Null => None,
+        owner => Some(Owner {
+            uid: u32::try_from(owner[0].as_u64()?).ok()?,
+            gid: u32::try_from(owner[1].as_u64()?).ok()?,
+        }),
+    };
+    let times = match &value[3] {
+        Value::Null => None,
+        times => Some(Timestamps {
+            modified: times[0].as_i64()?,
+            accessed: times[1].as_i64()?,
+            changed: times[2].as_i64()?,
+        }),
+    };
+    let link = match &value[4] {
+        Value::Null => None,
+        link => Some((link[0].as_u64()?, link[1].as_u64()?)),
+    };
+
+    Some(EntryStats {
+        size: value[0].as_u64()?,
+        allocated: value[1].as_u64()?,
+        owner,
+        times,
+        link,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn key(mtime: i64) -> DirKey {
+        DirKey {
+            inode: 7,
+            mtime,
+            mtime_nsec: 5,
+        }
+    }
+
+    fn listing() -> Vec<CachedEntry> {
+        vec![
+            CachedEntry {
+                name: "a.txt".to_string(),
+                entry_type: EntryType::File,
+                stats: Some(EntryStats {
+                    size: 10,
+                    allocated: 4096,
+                    owner: Some(Owner { uid: 1, gid: 2 }),
+                    times: Some(Timestamps {
+                        modified: 3,
+                        accessed: 4,
+                        changed: -5,
+                    }),
+                    link: Some((8, 9)),
+                }),
+            },
+            CachedEntry {
+                name: "sub".to_string(),
+                entry_type: EntryType::Directory,
+                stats: None,
+            },
+        ]
+    }
+
+    #[test]
+    fn test_save_and_load_round_trip() {
+        let tmp = TempDir::new().unwrap();
+        let file = tmp.path().join("nested/cache.gz");
+
+        let cache = ScanCache::new();
+        cache.add_root(Path::new("/r"));
+        cache.store(Path::new("/r"), key(100), listing());
+        cache.save(&file).unwrap();
+
+        let loaded = ScanCache::load(&file);
+        let cached = loaded.lookup(Path::new("/r"), key(100)).unwrap();
+        assert_eq!(cached.entries.len(), 2);
+        assert_eq!(cached.entries[0].name, "a.txt");
+        let stats = cached.entries[0].stats.unwrap();
+        assert_eq!(
+            (stats.size, stats.allocated, stats.link),
+            (10, 4096, Some((8, 9)))
+        );
+        assert_eq!(stats.times.unwrap().changed, -5);
+        assert_eq!(cached.entries[1].entry_type, EntryType::Directory);
+        assert!(cached.entries[1].stats.is_none());
+        assert_eq!(loaded.hits(), 1);
+    }
+
+    #[test]
+    fn test_lookup_misses_on_changed_key() {
+        let tmp = TempDir::new().unwrap();
+        let file = tmp.path().join("cache.gz");
+        let cache = ScanCache::new();
+        cache.store(Path::new("/r"), key(100), listing());
+        cache.save(&file).unwrap();
+
+        let loaded = ScanCache::load(&file);
+        assert!(loaded.lookup(Path::new("/r"), key(101)).is_none());
+        let mut other_inode = key(100);
+        other_inode.inode = 8;
+        assert!(loaded.lookup(Path::new("/r"), other_inode).is_none());
+        assert!(loaded.lookup(Path::new("/elsewhere"), key(100)).is_none());
+        assert_eq!((loaded.hits(), loaded.misses()), (0, 3));
+    }
+
+    #[test]
+    fn test_recently_modified_directories_not_stored() {
+        let tmp = TempDir::new().unwrap();
+        let file = tmp.path().join("cache.gz");
+        let cache = ScanCache::new();
+        cache.store(Path::new("/r"), key(cache.started), listing());
+        cache.save(&file).unwrap();
+
+        assert!(ScanCache::load(&file).dirs.is_empty());
+    }
+
+    #[test]
+    fn test_save_replaces_listings_under_scanned_roots() {
+        let tmp = TempDir::new().unwrap();
+        let file = tmp.path().join("cache.gz");
+        let first = ScanCache::new();
+        for dir in ["/r", "/r/gone", "/r/kept", "/other"] {
+            first.store(Path::new(dir), key(100), listing());
+        }
+        first.save(&file).unwrap();
+
+        // A second scan of /r still sees /r/kept, but /r/gone was removed
+        let second = ScanCache::load(&file);
+        second.add_root(Path::new("/r"));
+        second.store(Path::new("/r"), key(200), listing());
+        second.reuse(Path::new("/r/kept"));
+        second.save(&file).unwrap();
+
+        let third = ScanCache::load(&file);
+        let mut dirs: Vec<_> = third.dirs.keys().cloned().collect();
+        dirs.sort();
+        assert_eq!(
+            dirs,
+            ["/other", "/r", "/r/kept"].map(PathBuf::from).to_vec()
+        );
+        assert_eq!(third.dirs[Path::new("/r")].key, key(200));
+    }
+
+    #[test]
+    fn test_unusable_files_load_empty() {
+        let tmp = TempDir::new().unwrap();
+        assert!(ScanCache::load(&tmp.path().join("missing")).dirs.is_empty());
+
+        let garbage = tmp.path().join("garbage");
+        fs::write(&garbage, "not gzip").unwrap();
+        assert!(ScanCache::load(&garbage).dirs.is_empty());
+
+        let newer = tmp.path().join("newer");
+        let mut encoder = GzEncoder::new(File::create(&newer).unwrap(), Compression::fast());
+        writeln!(
+            encoder,
+            "{}",
+            json!({"format": CACHE_FORMAT, "version": CACHE_VERSION + 1})
+        )
+        .unwrap();
+        encoder.finish().unwrap();
+        assert!(ScanCache::load(&newer).dirs.is_empty());
+    }
+
+    #[test]
+    fn test_default_path_under_cache_dir() {
+        if let Some(path) = ScanCache::default_path() {
+            assert!(path.ends_with("dusk/scan-cache.gz"));
+        }
+    }
+}
@@ -0,0 +1,156 @@
+//! Stopping scans early.
+//!
+//! A [`CancelToken`] in
+//! [`TraversalOptions::cancel`](crate::traversal::TraversalOptions::cancel)
+//! lets another thread, or a deadline, stop a running traversal. The
+//! traversal then stops reading directories and stat'ing entries, and
+//! returns the tree of what it read so far instead of an error.
+//!
+//! Directories whose contents were not completely read are flagged with
+//! [`DiskEntry::incomplete`](crate::entry::DiskEntry::incomplete), and so
+//! are all their ancestors: a partial tree has `incomplete` set on its
+//! root. Every renderer shows the flag (an `[incomplete]` marker in text
+//! output, an `incomplete` field in JSON and CSV).
+//!
+//! The command line uses a token with a deadline for `--timeout`.
+//!
+//! # Examples
+//!
+//! ```no_run
+//! use disk_usage_clone::cancel::CancelToken;
+//! use disk_usage_clone::traversal::{TraversalOptions, traverse_parallel_with_options};
+//! use std::path::Path;
+//! use std::time::Duration;
+//!
+//! // Give up after 30 seconds, or earlier if the request is aborted
+//! let token = CancelToken::with_timeout(Duration::from_secs(30));
+//! let options = TraversalOptions {
+//!     cancel: Some(token.clone()),
+//!     ..TraversalOptions::default()
+//! };
+//! // ... hand `token` to whatever may abort the scan, then call token.cancel()
+//!
+//! let tree = traverse_parallel_with_options(Path::new("/mnt/archive"), &options).unwrap();
+//! if tree.incomplete {
+//!     eprintln!("partial result");
+//! }
+//! ```
+
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::{Duration, Instant};
+
+/// Requests that running scans stop early.
+///
+/// Clones share the same state: cancelling one cancels all, so a clone
+/// can be kept to cancel a scan running with the original. A token can
+/// also carry a deadline, after which it counts as cancelled.
+///
+/// # Examples
+///
+/// ```
+/// use disk_usage_clone::cancel::CancelToken;
+///
+/// let token = CancelToken::new();
+/// let handle = token.clone();
+/// assert!(!token.is_cancelled());
+///
+/// handle.cancel();
+/// assert!(token.is_cancelled());
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct CancelToken {
+    cancelled: Arc<AtomicBool>,
+    deadline: Option<Instant>,
+}
+
+impl CancelToken {
+    /// Creates a token that is cancelled only by [`CancelToken::cancel`].
+    pub fn new() -> CancelToken {
+        CancelToken::default()
+    }
+
+    /// Creates a token that is also cancelled once `timeout` has elapsed.
+    ///
+    /// The time counts from this call, not from the start of a scan, so
+    /// one token bounds several scans run one after the other.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use disk_usage_clone::cancel::CancelToken;
+    /// use std::time::Duration;
+    ///
+    /// assert!(CancelToken::with_timeout(Duration::ZERO).is_cancelled());
+    /// assert!(!CancelToken::with_timeout(Duration::from_secs(60)).is_cancelled());
+    /// ```
+    pub fn with_timeout(timeout: Duration) -> CancelToken {
+        CancelToken {
+            cancelled: Arc::default(),
+            deadline: Instant::now().checked_add(timeout),
+        }
+    }
+
+    /// Cancels the token and all its clones.
+    pub fn cancel(&self) {
+        self.cancelled.store(true, Ordering::Relaxed);
+    }
+
+    /// Returns `true` once the token was cancelled or its deadline passed.
+    pub fn is_cancelled(&self) -> bool {
+        if self.cancelled.load(Ordering::Relaxed) {
+            return true;
+        }
+        // Remember the deadline so later checks skip the clock
+        if self
+            .deadline
+            .is_some_and(|deadline| Instant::now() >= deadline)
+        {
+            self.cancel();
+            return true;
+        }
+        false
+    }
+}
+
+/// Returns `true` if `token` is set and cancelled.
+///
+/// Shorthand for the optional token of the traversal options.
+pub(crate) fn is_cancelled(token: Option<&CancelToken>) -> bool {
+    token.is_some_and(CancelToken::is_cancelled)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_clones_share_cancellation() {
+        let token = CancelToken::with_timeout(Duration::from_secs(60));
+        let clone = token.clone();
+        clone.cancel();
+        assert!(token.is_cancelled());
+    }
+
+    #[test]
+    fn test_deadline_cancels() {
+        let token = CancelToken::with_timeout(Duration::from_millis(20));
+        assert!(!token.is_cancelled());
+        std::thread::sleep(Duration::from_millis(30));
+        assert!(token.is_cancelled());
+    }
+
+    #[test]
+    fn test_optional_token() {
+        assert!(!is_cancelled(None));
+        assert!(!is_cancelled(Some(&CancelToken::new())));
+        assert!(is_cancelled(Some(&CancelToken::with_timeout(
+            Duration::ZERO
+        ))));
+    }
+
+    #[test]
+    fn test_huge_timeout_never_expires() {
+        assert!(!CancelToken::with_timeout(Duration::MAX).is_cancelled());
+    }
+}
@@ -18,9 +18,20 @@
 //! assert_eq!(args.paths, vec!["/tmp"]);
 //! ```
 
-use clap::Parser;
+use std::path::PathBuf;
 
-use crate::entry::SortOrder;
+use clap::{Args, Parser, Subcommand};
+
+use crate::age::AgeLimit;
+use crate::cache::ScanCache;
+use crate::diff::DiffSort;
+use crate::entry::{SizeMode, SortOrder, TimeKind};
+use crate::formatter::parse_size;
+use crate::group::GroupBy;
+use crate::output::OutputFormat;
+use crate::owner::OwnerBy;
+use crate::prune::Threshold;
+use crate::traversal::Dereference;
 
 /// Command-line arguments for the disk usage analyzer.
 ///
@@ -37,11 +48,43 @@ use crate::entry::SortOrder;
 ///
 /// - `-H, --human-readable`: Human-readable sizes (1.5K vs 1536)
 /// - `-s, --summarize`: Show only totals (like `du -s`)
-/// - `-a, --all`: Show files, not just directories
 /// - `-d, --max-depth <N>`: Limit traversal depth
-/// - `-j, --threads <N>`: Control parallelization
+/// - `-a, --all`: Show files, not just directories
 /// - `--sort <ORDER>`: Sort by size/name
+/// - `-t, --threshold <SIZE>`: Hide entries smaller (or, negative, larger)
+/// - `--top <N>`: Keep the N largest entries per directory
+/// - `--tree`: Draw the result as an indented tree
+/// - `--by-extension`, `--by-type`: Table of usage per extension or type
+/// - `--by-user`, `--by-group`: Table of usage per owning user or group
+/// - `--time[=WORD]`: Show the time of the last modification, or of WORD
+/// - `--older-than <AGE>`, `--newer-than <AGE>`: Only count files of some ages
+/// - `--age-histogram`: Table of usage per file age
+/// - `-c, --total`: Print a grand total of all paths
+/// - `--compare`: Compare the given paths side by side
+/// - `-j, --threads <N>`: Control parallelization
 /// - `--no-color`: Disable color output
+/// - `--apparent-size`: Report file lengths instead of disk usage
+/// - `-l, --count-links`: Count hard-linked files every time they appear
+/// - `-x, --one-file-system`: Skip directories on other filesystems
+/// - `-L, --dereference`, `-D, --dereference-args`: Follow symlinks
+/// - `--exclude <GLOB>`, `--exclude-from <FILE>`: Skip matching paths
+/// - `--respect-gitignore`: Skip paths ignored by `.gitignore` files
+/// - `--stream`: Aggregate sizes during the walk to keep memory bounded
+/// - `--cache`, `--cache-file <FILE>`, `--no-cache`: Reuse earlier scans
+/// - `--no-progress`: Don't show the progress line
+/// - `--format <FORMAT>`: Output as text, json, ndjson or csv
+/// - `--interactive`: Browse the result in a full-screen view
+/// - `--dry-run`: Only simulate deletions made in the browser
+/// - `--save <FILE>`: Save the scan to a snapshot file
+/// - `--watch`, `--interval <SECONDS>`: Rescan whenever something changes
+/// - `--timeout <SECONDS>`: Stop scanning and show what was read so far
+///
+/// `dusk --help` describes each of them in full.
+///
+/// # Subcommands
+///
+/// - `diff OLD NEW`: Compare two snapshots (see [`DiffArgs`])
+/// - `dupes PATH...`: Find duplicate files (see [`DupesArgs`])
 ///
 /// # Examples
 ///
@@ -74,8 +117,20 @@ use crate::entry::SortOrder;
 /// assert_eq!(args.max_depth, Some(2));
 /// ```
 #[derive(Parser, Debug)]
-#[command(name = "dusk", about = "Disk usage analysis tool", version)]
+#[command(
+    name = "dusk",
+    about = "Disk usage analysis tool",
+    version,
+    args_conflicts_with_subcommands = true
+)]
 pub struct CliArgs {
+    /// Subcommand to run instead of a scan
+    ///
+    /// A directory literally named like a subcommand can still be scanned
+    /// with `dusk ./diff`.
+    #[command(subcommand)]
+    pub command: Option<Command>,
+
     /// Paths to analyze (defaults to current directory)
     ///
     /// Multiple paths can be specified. Each is analyzed independently.
@@ -161,6 +216,182 @@ pub struct CliArgs {
     #[arg(long, default_value = "none")]
     pub sort: String,
 
+    /// Hide entries smaller than SIZE, or larger than SIZE if negative
+    ///
+    /// Accepts byte counts or K/M/G/T suffixes (`512`, `10M`, `1.5G`).
+    /// Hidden sizes still count towards their parent's total, and the root
+    /// of each path is always shown. With a negative size, entries below a
+    /// hidden directory are shown if they are small enough.
+    ///
+    /// # Example
+    ///
+    /// ```bash
+    /// dusk -a -t 100M ~        # Only entries of 100M or more
+    /// dusk -a -t -4K /etc      # Only entries of 4K or less
+    /// ```
+    #[arg(short = 't', long, value_name = "SIZE", allow_hyphen_values = true, value_parser = parse_threshold, conflicts_with = "interactive")]
+    pub threshold: Option<Threshold>,
+
+    /// Show only the N largest entries of each directory
+    ///
+    /// The remaining entries are combined into one `<K other entries>` line,
+    /// so each directory's total still adds up. Only listed entries count:
+    /// files compete only with `--all`.
+    ///
+    /// # Example
+    ///
+    /// ```bash
+    /// dusk --top 5 -d 2 -H /var
+    /// ```
+    #[arg(long, value_name = "N", value_parser = clap::builder::RangedU64ValueParser::<usize>::new().range(1..), conflicts_with = "interactive")]
+    pub top: Option<usize>,
+
+    /// Draw the output as a tree with percentages and bars
+    ///
+    /// Lists each path top-down with `├──`/`└──` connectors, showing every
+    /// entry's share of its parent as a percentage and a bar scaled to the
+    /// terminal width. Children are sorted by size unless `--sort` is given.
+    ///
+    /// # Example
+    ///
+    /// ```bash
+    /// dusk --tree -H -d 2 ~/projects
+    /// ```
+    #[arg(long, conflicts_with_all = ["format", "interactive"])]
+    pub tree: bool,
+
+    /// Show a table of usage per file extension instead of a tree
+    ///
+    /// Every regular file below the paths is counted, whatever
+    /// `--max-depth`; several paths are combined into one table. Each row
+    /// has the total size, the file count and the extension (`.png`, or
+    /// `(none)`). Rows are sorted by size, or as given by `--sort`, and
+    /// `--format` selects text, JSON, NDJSON or CSV.
+    ///
+    /// # Example
+    ///
+    /// ```bash
+    /// dusk --by-extension -H ~/projects/site
+    /// ```
+    #[arg(long, conflicts_with_all = ["by_type", "by_user", "by_group", "age_histogram", "tree", "interactive", "summarize", "max_depth", "threshold", "top"])]
+    pub by_extension: bool,
+
+    /// Show a table of usage per file type instead of a tree
+    ///
+    /// Like `--by-extension`, with extensions mapped to built-in categories:
+    /// images, video, archives, source, binaries, and other.
+    #[arg(long, conflicts_with_all = ["by_user", "by_group", "age_histogram", "tree", "interactive", "summarize", "max_depth", "threshold", "top"])]
+    pub by_type: bool,
+
+    /// Show a table of usage per owning user instead of a tree
+    ///
+    /// Every entry below the paths is charged to the user owning it. Each
+    /// user's total is followed by a breakdown per top-level directory
+    /// (the subdirectories directly inside each path), plus a row for each
+    /// path holding the files directly in it. User ids are resolved through
+    /// `/etc/passwd`; unknown ids are shown as numbers. Rows are sorted by
+    /// size, or as given by `--sort`, and `--format` selects text, JSON,
+    /// NDJSON or CSV.
+    ///
+    /// # Example
+    ///
+    /// ```bash
+    /// dusk --by-user -H /srv/build
+    /// ```
+    #[arg(long, conflicts_with_all = ["by_group", "age_histogram", "tree", "interactive", "summarize", "max_depth", "threshold", "top"])]
+    pub by_user: bool,
+
+    /// Show a table of usage per owning group instead of a tree
+    ///
+    /// Like `--by-user`, with group ids resolved through `/etc/group`.
+    #[arg(long, conflicts_with_all = ["age_histogram", "tree", "interactive", "summarize", "max_depth", "threshold", "top"])]
+    pub by_group: bool,
+
+    /// Show the time of the last modification, or of WORD
+    ///
+    /// Adds a column between size and path with the latest timestamp in
+    /// each entry's subtree, in local time (`YYYY-MM-DD HH:MM`), like
+    /// `du --time`. WORD selects the timestamp: `mtime` (default), `atime`
+    /// or `ctime`. It is also the timestamp compared by `--older-than`,
+    /// `--newer-than` and `--age-histogram`; the column itself is only part
+    /// of the text output.
+    ///
+    /// # Example
+    ///
+    /// ```bash
+    /// dusk --time -d 1 /srv        # Last modification below each directory
+    /// dusk --time=atime -a ~/Downloads
+    /// ```
+    #[arg(long, value_name = "WORD", num_args = 0..=1, require_equals = true, default_missing_value = "mtime", value_parser = clap::builder::PossibleValuesParser::new(TimeKind::NAMES), conflicts_with_all = ["tree", "interactive"])]
+    pub time: Option<String>,
+
+    /// Only count files last modified before AGE
+    ///
+    /// AGE is a duration back from now (`180d`; units `s`, `m`, `h`, `d`,
+    /// `w`, `y`, days if omitted) or a date (`2024-01-31`, local midnight).
+    /// Directories are listed if anything below them matches, and their
+    /// totals only count the matching files. `--time=WORD` compares another
+    /// timestamp.
+    ///
+    /// # Example
+    ///
+    /// ```bash
+    /// dusk --older-than 180d -H -d 2 /data   # Stale data per directory
+    /// ```
+    #[arg(long, value_name = "AGE", value_parser = parse_age, conflicts_with = "interactive")]
+    pub older_than: Option<AgeLimit>,
+
+    /// Only count files last modified at or after AGE
+    ///
+    /// Same AGE syntax as `--older-than`; both together select a range.
+    #[arg(long, value_name = "AGE", value_parser = parse_age, conflicts_with = "interactive")]
+    pub newer_than: Option<AgeLimit>,
+
+    /// Show a histogram of bytes by file age instead of a tree
+    ///
+    /// Every regular file below the paths is sorted into an age bucket
+    /// (`< 1 day` up to `> 2 years`) by its modification time, or the
+    /// timestamp chosen with `--time=WORD`. Each bucket shows its total
+    /// size, file count and share of all bytes; `--format` selects text,
+    /// JSON, NDJSON or CSV.
+    ///
+    /// # Example
+    ///
+    /// ```bash
+    /// dusk --age-histogram -H /data
+    /// ```
+    #[arg(long, conflicts_with_all = ["tree", "interactive", "summarize", "max_depth", "threshold", "top"])]
+    pub age_histogram: bool,
+
+    /// Print a grand total of all paths after the output
+    ///
+    /// Adds a `SIZE<TAB>total` line, like `du -c`. A path inside another
+    /// analyzed path, or given twice, is counted once: `dusk -c /usr
+    /// /usr/lib` totals `/usr` alone. Only text output has the line.
+    ///
+    /// # Example
+    ///
+    /// ```bash
+    /// dusk -c -s -H ~/src ~/Downloads
+    /// ```
+    #[arg(short = 'c', long, conflicts_with_all = ["format", "interactive", "by_extension", "by_type", "by_user", "by_group", "age_histogram"])]
+    pub total: bool,
+
+    /// Compare the paths side by side instead of showing trees
+    ///
+    /// Shows one row per path with its total, its share of the combined
+    /// total (counting overlapping paths once, as `--total` does) and a
+    /// bar, followed by the total. Rows keep the order of the paths unless
+    /// `--sort` is given, and `--format` selects text, JSON, NDJSON or CSV.
+    ///
+    /// # Example
+    ///
+    /// ```bash
+    /// dusk --compare -H /var/lib/docker /var/log /home
+    /// ```
+    #[arg(long, conflicts_with_all = ["by_extension", "by_type", "by_user", "by_group", "age_histogram", "tree", "interactive", "summarize", "max_depth", "threshold", "top", "total"])]
+    pub compare: bool,
+
     /// Number of threads for parallel traversal
     ///
     /// Controls the rayon thread pool size for parallel metadata collection.
@@ -193,6 +424,380 @@ pub struct CliArgs {
     /// Colors are automatically disabled when output is not a TTY.
     #[arg(long)]
     pub no_color: bool,
+
+    /// Print apparent sizes rather than disk usage
+    ///
+    /// By default (like `du`), sizes are the disk space actually allocated
+    /// (`st_blocks`), which is what `df` accounts for. The apparent size is
+    /// the file length as shown by `ls -l`; it is larger for sparse files
+    /// and smaller for files that don't fill their last block.
+    ///
+    /// # Example
+    ///
+    /// ```bash
+    /// dusk --apparent-size vm-images/  # Logical size of sparse disk images
+    /// ```
+    #[arg(long)]
+    pub apparent_size: bool,
+
+    /// Count sizes many times if hard linked
+    ///
+    /// By default (like `du`), a file with several hard links is counted
+    /// once, at the first path that reaches it. This flag counts every link
+    /// in full, which overstates usage for Nix stores, rsnapshot backups
+    /// and similar link-heavy trees.
+    #[arg(short = 'l', long)]
+    pub count_links: bool,
+
+    /// Skip directories on different file systems
+    ///
+    /// Compares each directory's device with the starting path's and does
+    /// not descend into mount points (`/proc`, bind mounts, NFS, FUSE...).
+    /// Skipped mount points are still listed, marked `[mount point]`.
+    ///
+    /// # Example
+    ///
+    /// ```bash
+    /// dusk -x -d 1 /  # Root filesystem only
+    /// ```
+    #[arg(short = 'x', long)]
+    pub one_file_system: bool,
+
+    /// Follow all symbolic links
+    ///
+    /// Links are sized as what they point to, and linked directories are
+    /// read. A link leading back to a directory being read is reported as
+    /// a loop and skipped instead of being followed forever. By default
+    /// (like `du`), links are not followed: they are sized as the link
+    /// itself and listed with their target (`-a`).
+    ///
+    /// # Example
+    ///
+    /// ```bash
+    /// dusk -L -d 1 ~/projects  # Count linked checkouts too
+    /// ```
+    #[arg(short = 'L', long, conflicts_with = "dereference_args")]
+    pub dereference: bool,
+
+    /// Follow symbolic links given as arguments only
+    ///
+    /// A link named on the command line is analyzed as the directory or
+    /// file it points to; links found below it are not followed.
+    ///
+    /// # Example
+    ///
+    /// ```bash
+    /// dusk -D -s /var/www/current  # Size the release the link points to
+    /// ```
+    #[arg(short = 'D', long)]
+    pub dereference_args: bool,
+
+    /// Exclude paths matching a gitignore-style pattern (repeatable)
+    ///
+    /// Matching directories are pruned from the traversal: their contents
+    /// are neither read nor counted. Patterns follow `.gitignore` rules:
+    /// `*.o` matches at any depth, `build/` matches directories only and
+    /// `/target` is anchored at the analyzed path.
+    ///
+    /// # Example
+    ///
+    /// ```bash
+    /// dusk --exclude node_modules/ --exclude '*.log' .
+    /// ```
+    #[arg(long, value_name = "GLOB")]
+    pub exclude: Vec<String>,
+
+    /// Exclude paths matching any pattern in FILE (repeatable)
+    ///
+    /// One pattern per line, same syntax as `--exclude`. Blank lines and
+    /// lines starting with `#` are ignored.
+    #[arg(long, value_name = "FILE")]
+    pub exclude_from: Vec<String>,
+
+    /// Also exclude paths ignored by `.gitignore` files
+    ///
+    /// Every `.gitignore` inside the analyzed tree applies to its own
+    /// directory, with deeper files taking precedence, like in `git`.
+    #[arg(long)]
+    pub respect_gitignore: bool,
+
+    /// Aggregate sizes during the walk to keep memory bounded
+    ///
+    /// Sizes are rolled up into their parent directory as soon as they are
+    /// read, and entries below `--max-depth` are never kept, so memory grows
+    /// with the depth and width of the displayed tree instead of the number
+    /// of files. Metadata is read on a single thread (`--threads` is
+    /// ignored), which is slower on fast storage.
+    ///
+    /// # Example
+    ///
+    /// ```bash
+    /// dusk --stream -d 2 /srv/storage  # 50 million files, little RAM
+    /// ```
+    #[arg(long)]
+    pub stream: bool,
+
+    /// Reuse the listings of unchanged directories from earlier runs
+    ///
+    /// Directories whose inode and modification time match the cache are
+    /// not read again; only their subdirectories are checked. The cache
+    /// lives in `$XDG_CACHE_HOME/dusk/scan-cache.gz` (or `--cache-file`)
+    /// and is updated after every cached run. A file rewritten in place
+    /// doesn't change its directory's mtime, so its old size may be shown
+    /// until the directory changes; use `--no-cache` for exact results.
+    /// Ignored with `--stream`.
+    ///
+    /// # Example
+    ///
+    /// ```bash
+    /// dusk --cache -d 1 /srv/storage  # fast on the second run
+    /// ```
+    #[arg(long, conflicts_with = "stream")]
+    pub cache: bool,
+
+    /// Cache file to use instead of the default (implies `--cache`)
+    #[arg(long, value_name = "FILE", conflicts_with = "stream")]
+    pub cache_file: Option<String>,
+
+    /// Read everything from disk, even if `--cache` is given
+    ///
+    /// The cache is neither read nor updated. Useful to override a shell
+    /// alias or script that always passes `--cache`.
+    #[arg(long)]
+    pub no_cache: bool,
+
+    /// Don't show the progress line on stderr while scanning
+    ///
+    /// By default, when stderr is a terminal, a line with the entries and
+    /// bytes counted so far, the elapsed time, the scan rate and the
+    /// directory being read is updated during the scan and erased before
+    /// the results are printed. It is never shown when stderr is redirected.
+    #[arg(long)]
+    pub no_progress: bool,
+
+    /// Output format: text, json, ndjson, csv
+    ///
+    /// - `text` - Tab-separated `size<TAB>path` lines (default)
+    /// - `json` - One JSON array with a nested tree per path
+    /// - `ndjson` - One JSON object per line, in text output order
+    /// - `csv` - Header row plus one row per entry, for spreadsheets
+    ///
+    /// Machine-readable formats report sizes in bytes and never contain
+    /// color codes. `--max-depth`, `--all`, `--summarize` and `--sort`
+    /// apply to every format.
+    ///
+    /// # Example
+    ///
+    /// ```bash
+    /// dusk --format ndjson -d 1 /var | jq -r 'select(.size > 1e9) | .path'
+    /// ```
+    #[arg(long, default_value = "text", value_parser = clap::builder::PossibleValuesParser::new(OutputFormat::NAMES))]
+    pub format: String,
+
+    /// Browse the scanned tree interactively
+    ///
+    /// Opens a full-screen, `ncdu`-style view of the tree after the scan.
+    /// Enter descends into a directory, backspace goes up, `s` and `n`
+    /// sort by size or name, `d` and `t` delete or trash the selected entry
+    /// after confirmation, `q` quits. Navigation never rescans the disk.
+    ///
+    /// Takes a single path and needs a terminal on stdout.
+    #[arg(long, conflicts_with = "format")]
+    pub interactive: bool,
+
+    /// Simulate delete and trash actions in the interactive browser
+    ///
+    /// The browser still asks for confirmation and reports how much space
+    /// would be freed, but nothing is removed and the tree stays unchanged.
+    #[arg(long, requires = "interactive")]
+    pub dry_run: bool,
+
+    /// Save the scanned trees to a snapshot file
+    ///
+    /// The snapshot can later be compared with `dusk diff`. It stores the
+    /// trees as scanned (after `--max-depth`, `--exclude`, etc.) with both
+    /// apparent and allocated sizes.
+    ///
+    /// # Example
+    ///
+    /// ```bash
+    /// dusk --save monday.dusk -s /var
+    /// ```
+    #[arg(long, value_name = "FILE")]
+    pub save: Option<String>,
+
+    /// Keep the totals live, updating them as files change
+    ///
+    /// Scans once, then follows filesystem change notifications and
+    /// re-reads only what changed, redrawing the output every `--interval`
+    /// seconds until interrupted. When the directories can't all be watched
+    /// (e.g. the inotify watch limit is reached), the paths are rescanned
+    /// every interval instead.
+    ///
+    /// # Example
+    ///
+    /// ```bash
+    /// dusk --watch -H -d 1 target/
+    /// ```
+    #[arg(long, conflicts_with_all = ["format", "interactive", "save", "stream", "by_extension", "by_type", "by_user", "by_group", "age_histogram", "older_than", "newer_than", "timeout", "dereference", "compare"])]
+    pub watch: bool,
+
+    /// Seconds between refreshes with `--watch`
+    #[arg(long, value_name = "SECONDS", default_value = "2", requires = "watch", value_parser = clap::builder::RangedU64ValueParser::<u64>::new().range(1..))]
+    pub interval: u64,
+
+    /// Stop scanning after SECONDS and show what was read so far
+    ///
+    /// The time limit covers the whole run, all paths together. Directories
+    /// that weren't completely read when time ran out are marked
+    /// `[incomplete]` (an `incomplete` field in JSON and CSV), and dusk
+    /// exits with status 1.
+    ///
+    /// # Example
+    ///
+    /// ```bash
+    /// dusk --timeout 60 -d 1 /mnt/archive
+    /// ```
+    #[arg(long, value_name = "SECONDS", value_parser = clap::builder::RangedU64ValueParser::<u64>::new().range(1..))]
+    pub timeout: Option<u64>,
+}
+
+/// Parses `--threshold`, e.g. `10M` or `-1G`.
+fn parse_threshold(s: &str) -> Result<Threshold, String> {
+    Threshold::parse(s)
+        .ok_or_else(|| format!("invalid size '{s}' (expected e.g. 512, 10K, 1.5M or 1G)"))
+}
+
+/// Parses `--older-than` and `--newer-than`, e.g. `180d` or `2024-01-31`.
+fn parse_age(s: &str) -> Result<AgeLimit, String> {
+    AgeLimit::parse(s)
+        .ok_or_else(|| format!("invalid age '{s}' (expected e.g. 90, 12h, 180d, 2y or 2024-01-31)"))
+}
+
+/// Subcommands of `dusk`.
+#[derive(Subcommand, Debug)]
+pub enum Command {
+    /// Compare two snapshots saved with --save
+    Diff(DiffArgs),
+    /// Find files with identical contents
+    Dupes(DupesArgs),
+}
+
+/// Arguments of `dusk diff`.
+///
+/// # Examples
+///
+/// ```
+/// use disk_usage_clone::cli::{CliArgs, Command};
+/// use clap::Parser;
+///
+/// let args = CliArgs::parse_from(&["dusk", "diff", "-H", "old.dusk", "new.dusk"]);
+/// let Some(Command::Diff(diff)) = args.command else { panic!() };
+/// assert_eq!(diff.old, "old.dusk");
+/// assert!(diff.human_readable);
+/// ```
+#[derive(Args, Debug)]
+pub struct DiffArgs {
+    /// Older snapshot
+    pub old: String,
+
+    /// Newer snapshot
+    pub new: String,
+
+    /// Print sizes in human-readable format (e.g., +1.5K, -2.3M)
+    #[arg(short = 'H', long = "human-readable")]
+    pub human_readable: bool,
+
+    /// Compare apparent sizes instead of disk usage
+    #[arg(long)]
+    pub apparent_size: bool,
+
+    /// Order of the changes: change, path
+    ///
+    /// - `change` - Largest absolute change first (default)
+    /// - `path` - Alphabetical by path
+    #[arg(long, default_value = "change", value_parser = clap::builder::PossibleValuesParser::new(DiffSort::NAMES))]
+    pub sort: String,
+}
+
+impl DiffArgs {
+    /// Parses the `sort` string into a `DiffSort` enum.
+    pub fn diff_sort(&self) -> DiffSort {
+        DiffSort::parse(&self.sort).unwrap_or_default()
+    }
+
+    /// Returns which size to compare, based on `--apparent-size`.
+    pub fn size_mode(&self) -> SizeMode {
+        if self.apparent_size {
+            SizeMode::Apparent
+        } else {
+            SizeMode::Allocated
+        }
+    }
+}
+
+/// Arguments of `dusk dupes`.
+///
+/// # Examples
+///
+/// ```
+/// use disk_usage_clone::cli::{CliArgs, Command};
+/// use clap::Parser;
+///
+/// let args = CliArgs::parse_from(&["dusk", "dupes", "--min-size", "1M", "/data"]);
+/// let Some(Command::Dupes(dupes)) = args.command else { panic!() };
+/// assert_eq!(dupes.paths, vec!["/data"]);
+/// assert_eq!(dupes.min_size, 1_048_576);
+/// ```
+#[derive(Args, Debug)]
+pub struct DupesArgs {
+    /// Paths to search (defaults to current directory)
+    #[arg(default_value = ".")]
+    pub paths: Vec<String>,
+
+    /// Print sizes in human-readable format (e.g., 1K, 234M, 2G)
+    #[arg(short = 'H', long = "human-readable")]
+    pub human_readable: bool,
+
+    /// Ignore files smaller than SIZE (default 1: skip empty files)
+    ///
+    /// Accepts byte counts or K/M/G/T suffixes (`512`, `10M`). Raising it
+    /// skips the many small files that rarely matter and cost a read each.
+    #[arg(long, value_name = "SIZE", default_value = "1", value_parser = parse_min_size)]
+    pub min_size: u64,
+
+    /// Number of threads for traversal and hashing
+    #[arg(short = 'j', long)]
+    pub threads: Option<usize>,
+
+    /// Skip directories on different file systems
+    #[arg(short = 'x', long)]
+    pub one_file_system: bool,
+
+    /// Exclude paths matching a gitignore-style pattern (repeatable)
+    #[arg(long, value_name = "GLOB")]
+    pub exclude: Vec<String>,
+
+    /// Output format: text, json, ndjson, csv
+    ///
+    /// - `text` - Each group's reclaimable bytes and paths, then a total
+    /// - `json` - One JSON array of groups
+    /// - `ndjson` - One JSON object per group and line
+    /// - `csv` - Header row plus one row per file
+    #[arg(long, default_value = "text", value_parser = clap::builder::PossibleValuesParser::new(OutputFormat::NAMES))]
+    pub format: String,
+}
+
+impl DupesArgs {
+    /// Parses the `format` string into an `OutputFormat` enum.
+    pub fn output_format(&self) -> OutputFormat {
+        OutputFormat::parse(&self.format).unwrap_or_default()
+    }
+}
+
+/// Parses `dusk dupes --min-size`, e.g. `4K`.
+fn parse_min_size(s: &str) -> Result<u64, String> {
+    parse_size(s).ok_or_else(|| format!("invalid size '{s}' (expected e.g. 512, 10K, 1.5M or 1G)"))
 }
 
 impl CliArgs {
@@ -224,6 +829,147 @@ impl CliArgs {
     pub fn sort_order(&self) -> SortOrder {
         SortOrder::parse(&self.sort).unwrap_or(SortOrder::None)
     }
+
+    /// Parses the `format` string into an `OutputFormat` enum.
+    ///
+    /// clap already rejects unknown names; anything else falls back to
+    /// `OutputFormat::Text`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use disk_usage_clone::cli::CliArgs;
+    /// use disk_usage_clone::output::OutputFormat;
+    /// use clap::Parser;
+    ///
+    /// let args = CliArgs::parse_from(&["dusk", "--format", "json"]);
+    /// assert_eq!(args.output_format(), OutputFormat::Json);
+    /// ```
+    pub fn output_format(&self) -> OutputFormat {
+        OutputFormat::parse(&self.format).unwrap_or_default()
+    }
+
+    /// Returns which size to report, based on `--apparent-size`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use disk_usage_clone::cli::CliArgs;
+    /// use disk_usage_clone::entry::SizeMode;
+    /// use clap::Parser;
+    ///
+    /// let args = CliArgs::parse_from(&["dusk"]);
+    /// assert_eq!(args.size_mode(), SizeMode::Allocated);
+    ///
+    /// let args = CliArgs::parse_from(&["dusk", "--apparent-size"]);
+    /// assert_eq!(args.size_mode(), SizeMode::Apparent);
+    /// ```
+    pub fn size_mode(&self) -> SizeMode {
+        if self.apparent_size {
+            SizeMode::Apparent
+        } else {
+            SizeMode::Allocated
+        }
+    }
+
+    /// Returns who entries are charged to, if `--by-user` or `--by-group`
+    /// is given.
+    pub fn owner_by(&self) -> Option<OwnerBy> {
+        if self.by_user {
+            Some(OwnerBy::User)
+        } else if self.by_group {
+            Some(OwnerBy::Group)
+        } else {
+            None
+        }
+    }
+
+    /// Returns the timestamp selected with `--time[=WORD]`, if given.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use disk_usage_clone::cli::CliArgs;
+    /// use disk_usage_clone::entry::TimeKind;
+    /// use clap::Parser;
+    ///
+    /// let args = CliArgs::parse_from(&["dusk", "--time"]);
+    /// assert_eq!(args.time_kind(), Some(TimeKind::Modified));
+    ///
+    /// let args = CliArgs::parse_from(&["dusk", "--time=atime"]);
+    /// assert_eq!(args.time_kind(), Some(TimeKind::Accessed));
+    /// ```
+    pub fn time_kind(&self) -> Option<TimeKind> {
+        self.time.as_deref().and_then(TimeKind::parse)
+    }
+
+    /// Returns the scan cache file, if caching is enabled.
+    ///
+    /// `--no-cache` wins over `--cache` and `--cache-file`. With `--cache`
+    /// alone, the default location is used (see
+    /// [`ScanCache::default_path`]), or `None` if it can't be determined.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use disk_usage_clone::cli::CliArgs;
+    /// use clap::Parser;
+    /// use std::path::PathBuf;
+    ///
+    /// let args = CliArgs::parse_from(&["dusk", "--cache-file", "scan.gz"]);
+    /// assert_eq!(args.cache_path(), Some(PathBuf::from("scan.gz")));
+    ///
+    /// let args = CliArgs::parse_from(&["dusk", "--cache-file", "scan.gz", "--no-cache"]);
+    /// assert_eq!(args.cache_path(), None);
+    /// ```
+    pub fn cache_path(&self) -> Option<PathBuf> {
+        if self.no_cache {
+            None
+        } else if let Some(file) = &self.cache_file {
+            Some(PathBuf::from(file))
+        } else if self.cache {
+            ScanCache::default_path()
+        } else {
+            None
+        }
+    }
+
+    /// Returns which symlinks to follow, from `-L` and `-D`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use disk_usage_clone::cli::CliArgs;
+    /// use disk_usage_clone::traversal::Dereference;
+    /// use clap::Parser;
+    ///
+    /// let args = CliArgs::parse_from(&["dusk"]);
+    /// assert_eq!(args.dereference_mode(), Dereference::Never);
+    ///
+    /// let args = CliArgs::parse_from(&["dusk", "-D"]);
+    /// assert_eq!(args.dereference_mode(), Dereference::Args);
+    /// ```
+    pub fn dereference_mode(&self) -> Dereference {
+        if self.dereference {
+            Dereference::Always
+        } else if self.dereference_args {
+            Dereference::Args
+        } else {
+            Dereference::Never
+        }
+    }
+
+    /// Returns how files are grouped, if `--by-extension` or `--by-type`
+    /// is given.
+    pub fn group_by(&self) -> Option<GroupBy> {
+        if self.by_extension {
+            Some(GroupBy::Extension)
+        } else if self.by_type {
+            Some(GroupBy::Type)
+        } else {
+            None
+        }
+    }
 }
 
 #[cfg(test)]
@@ -241,6 +987,19 @@ mod tests {
         assert_eq!(args.sort, "none");
         assert_eq!(args.threads, None);
         assert!(!args.no_color);
+        assert!(!args.no_progress);
+        assert!(!args.apparent_size);
+        assert!(!args.count_links);
+        assert!(!args.one_file_system);
+        assert!(!args.dereference && !args.dereference_args);
+        assert!(!args.total && !args.compare);
+        assert!(args.exclude.is_empty());
+        assert!(args.exclude_from.is_empty());
+        assert!(!args.respect_gitignore);
+        assert_eq!(args.output_format(), OutputFormat::Text);
+        assert!(!args.interactive);
+        assert!(args.save.is_none());
+        assert!(args.command.is_none());
     }
 
     #[test]
@@ -286,6 +1045,293 @@ mod tests {
         assert!(args.no_color);
     }
 
+    #[test]
+    fn test_apparent_size_flag() {
+        let args = CliArgs::parse_from(["dusk", "--apparent-size"]);
+        assert!(args.apparent_size);
+        assert_eq!(args.size_mode(), SizeMode::Apparent);
+    }
+
+    #[test]
+    fn test_count_links_flag() {
+        let args = CliArgs::parse_from(["dusk", "-l"]);
+        assert!(args.count_links);
+        let args = CliArgs::parse_from(["dusk", "--count-links"]);
+        assert!(args.count_links);
+    }
+
+    #[test]
+    fn test_one_file_system_flag() {
+        let args = CliArgs::parse_from(["dusk", "-x"]);
+        assert!(args.one_file_system);
+        let args = CliArgs::parse_from(["dusk", "--one-file-system"]);
+        assert!(args.one_file_system);
+    }
+
+    #[test]
+    fn test_dereference_flags() {
+        let args = CliArgs::parse_from(["dusk", "-L"]);
+        assert_eq!(args.dereference_mode(), Dereference::Always);
+        let args = CliArgs::parse_from(["dusk", "--dereference-args"]);
+        assert_eq!(args.dereference_mode(), Dereference::Args);
+        assert!(CliArgs::try_parse_from(["dusk", "-L", "-D"]).is_err());
+        assert!(CliArgs::try_parse_from(["dusk", "-L", "--watch"]).is_err());
+    }
+
+    #[test]
+    fn test_total_and_compare_flags() {
+        let args = CliArgs::parse_from(["dusk", "-c", "/a", "/b"]);
+        assert!(args.total);
+        let args = CliArgs::parse_from(["dusk", "--compare", "--format", "csv", "/a", "/b"]);
+        assert!(args.compare);
+        assert!(CliArgs::try_parse_from(["dusk", "-c", "--compare"]).is_err());
+        assert!(CliArgs::try_parse_from(["dusk", "-c", "--format", "json"]).is_err());
+        assert!(CliArgs::try_parse_from(["dusk", "--compare", "--tree"]).is_err());
+    }
+
+    #[test]
+    fn test_exclude_repeatable() {
+        let args = CliArgs::parse_from([
+            "dusk",
+            "--exclude",
+            "*.log",
+            "--exclude",
+            "target/",
+            "--exclude-from",
+            "ignore.txt",
+            "--respect-gitignore",
+            "/src",
+        ]);
+        assert_eq!(args.exclude, vec!["*.log", "target/"]);
+        assert_eq!(args.exclude_from, vec!["ignore.txt"]);
+        assert!(args.respect_gitignore);
+        assert_eq!(args.paths, vec!["/src"]);
+    }
+
+    #[test]
+    fn test_stream_flag() {
+        let args = CliArgs::parse_from(["dusk", "--stream", "-d", "1"]);
+        assert!(args.stream);
+        assert_eq!(args.max_depth, Some(1));
+    }
+
+    #[test]
+    fn test_cache_flags() {
+        let args = CliArgs::parse_from(["dusk"]);
+        assert_eq!(args.cache_path(), None);
+
+        let args = CliArgs::parse_from(["dusk", "--cache", "--cache-file", "/tmp/c.gz"]);
+        assert_eq!(args.cache_path(), Some(PathBuf::from("/tmp/c.gz")));
+
+        let args = CliArgs::parse_from(["dusk", "--cache", "--no-cache"]);
+        assert_eq!(args.cache_path(), None);
+
+        assert!(CliArgs::try_parse_from(["dusk", "--cache", "--stream"]).is_err());
+    }
+
+    #[test]
+    fn test_no_progress_flag() {
+        let args = CliArgs::parse_from(["dusk", "--no-progress", "--stream"]);
+        assert!(args.no_progress);
+    }
+
+    #[test]
+    fn test_format_option() {
+        let args = CliArgs::parse_from(["dusk", "--format", "ndjson"]);
+        assert_eq!(args.output_format(), OutputFormat::Ndjson);
+    }
+
+    #[test]
+    fn test_format_csv() {
+        let args = CliArgs::parse_from(["dusk", "--format", "csv"]);
+        assert_eq!(args.output_format(), OutputFormat::Csv);
+    }
+
+    #[test]
+    fn test_format_rejects_unknown() {
+        let result = CliArgs::try_parse_from(["dusk", "--format", "xml"]);
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_interactive_flag() {
+        let args = CliArgs::parse_from(["dusk", "--interactive", "/var"]);
+        assert!(args.interactive);
+    }
+
+    #[test]
+    fn test_dry_run_requires_interactive() {
+        let args = CliArgs::parse_from(["dusk", "--interactive", "--dry-run"]);
+        assert!(args.dry_run);
+        assert!(CliArgs::try_parse_from(["dusk", "--dry-run"]).is_err());
+    }
+
+    #[test]
+    fn test_interactive_conflicts_with_format() {
+        let result = CliArgs::try_parse_from(["dusk", "--interactive", "--format", "json"]);
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_threshold_option() {
+        let args = CliArgs::parse_from(["dusk", "-t", "10M"]);
+        assert_eq!(args.threshold, Some(Threshold::AtLeast(10 * 1024 * 1024)));
+
+        let args = CliArgs::parse_from(["dusk", "--threshold", "-1K", "/var"]);
+        assert_eq!(args.threshold, Some(Threshold::AtMost(1024)));
+        assert_eq!(args.paths, vec!["/var"]);
+
+        assert!(CliArgs::try_parse_from(["dusk", "-t", "lots"]).is_err());
+    }
+
+    #[test]
+    fn test_top_option() {
+        let args = CliArgs::parse_from(["dusk", "--top", "3"]);
+        assert_eq!(args.top, Some(3));
+        assert!(CliArgs::try_parse_from(["dusk", "--top", "0"]).is_err());
+        assert!(CliArgs::try_parse_from(["dusk", "--top", "3", "--interactive"]).is_err());
+    }
+
+    #[test]
+    fn test_tree_flag() {
+        let args = CliArgs::parse_from(["dusk", "--tree", "-d", "2"]);
+        assert!(args.tree);
+        assert!(CliArgs::try_parse_from(["dusk", "--tree", "--format", "csv"]).is_err());
+    }
+
+    #[test]
+    fn test_group_flags() {
+        assert_eq!(CliArgs::parse_from(["dusk"]).group_by(), None);
+        assert_eq!(
+            CliArgs::parse_from(["dusk", "--by-extension"]).group_by(),
+            Some(GroupBy::Extension)
+        );
+        assert_eq!(
+            CliArgs::parse_from(["dusk", "--by-type", "--format", "csv"]).group_by(),
+            Some(GroupBy::Type)
+        );
+        assert!(CliArgs::try_parse_from(["dusk", "--by-type", "--by-extension"]).is_err());
+        assert!(CliArgs::try_parse_from(["dusk", "--by-type", "-d", "1"]).is_err());
+    }
+
+    #[test]
+    fn test_owner_flags() {
+        assert_eq!(CliArgs::parse_from(["dusk"]).owner_by(), None);
+        assert_eq!(
+            CliArgs::parse_from(["dusk", "--by-user"]).owner_by(),
+            Some(OwnerBy::User)
+        );
+        assert_eq!(
+            CliArgs::parse_from(["dusk", "--by-group", "/srv"]).owner_by(),
+            Some(OwnerBy::Group)
+        );
+        assert!(CliArgs::try_parse_from(["dusk", "--by-user", "--by-group"]).is_err());
+        assert!(CliArgs::try_parse_from(["dusk", "--by-user", "--by-type"]).is_err());
+    }
+
+    #[test]
+    fn test_time_and_age_options() {
+        let args = CliArgs::parse_from(["dusk"]);
+        assert_eq!(args.time_kind(), None);
+        assert_eq!(args.older_than, None);
+        assert!(!args.age_histogram);
+
+        // `--time` takes its value only with `=`, so a path may follow
+        let args = CliArgs::parse_from(["dusk", "--time", "/data"]);
+        assert_eq!(args.time_kind(), Some(TimeKind::Modified));
+        assert_eq!(args.paths, vec!["/data"]);
+        let args = CliArgs::parse_from(["dusk", "--time=ctime"]);
+        assert_eq!(args.time_kind(), Some(TimeKind::Changed));
+        assert!(CliArgs::try_parse_from(["dusk", "--time=btime"]).is_err());
+
+        let args = CliArgs::parse_from(["dusk", "--older-than", "180d", "--newer-than", "2y"]);
+        assert_eq!(args.older_than, Some(AgeLimit::Ago(180 * 86_400)));
+        assert_eq!(args.newer_than, Some(AgeLimit::Ago(2 * 365 * 86_400)));
+        assert!(CliArgs::try_parse_from(["dusk", "--older-than", "soon"]).is_err());
+
+        assert!(CliArgs::parse_from(["dusk", "--age-histogram"]).age_histogram);
+        assert!(CliArgs::try_parse_from(["dusk", "--age-histogram", "--by-type"]).is_err());
+        assert!(CliArgs::try_parse_from(["dusk", "--age-histogram", "-d", "1"]).is_err());
+    }
+
+    #[test]
+    fn test_watch_options() {
+        let args = CliArgs::parse_from(["dusk", "--watch", "target"]);
+        assert!(args.watch);
+        assert_eq!(args.interval, 2);
+
+        let args = CliArgs::parse_from(["dusk", "--watch", "--interval", "10", "-d", "1"]);
+        assert_eq!(args.interval, 10);
+
+        assert!(CliArgs::try_parse_from(["dusk", "--interval", "5"]).is_err());
+        assert!(CliArgs::try_parse_from(["dusk", "--watch", "--interval", "0"]).is_err());
+        assert!(CliArgs::try_parse_from(["dusk", "--watch", "--format", "json"]).is_err());
+        assert!(CliArgs::try_parse_from(["dusk", "--watch", "--interactive"]).is_err());
+    }
+
+    #[test]
+    fn test_timeout_option() {
+        assert_eq!(CliArgs::parse_from(["dusk"]).timeout, None);
+        let args = CliArgs::parse_from(["dusk", "--timeout", "30", "/srv"]);
+        assert_eq!(args.timeout, Some(30));
+
+        assert!(CliArgs::try_parse_from(["dusk", "--timeout", "0"]).is_err());
+        assert!(CliArgs::try_parse_from(["dusk", "--timeout", "5", "--watch"]).is_err());
+    }
+
+    #[test]
+    fn test_save_option() {
+        let args = CliArgs::parse_from(["dusk", "--save", "scan.dusk", "/var"]);
+        assert_eq!(args.save.as_deref(), Some("scan.dusk"));
+        assert_eq!(args.paths, vec!["/var"]);
+    }
+
+    #[test]
+    fn test_diff_subcommand() {
+        let args = CliArgs::parse_from(["dusk", "diff", "--sort", "path", "a.dusk", "b.dusk"]);
+        let Some(Command::Diff(diff)) = args.command else {
+            panic!("expected diff subcommand");
+        };
+        assert_eq!(diff.old, "a.dusk");
+        assert_eq!(diff.new, "b.dusk");
+        assert_eq!(diff.diff_sort(), DiffSort::Path);
+        assert_eq!(diff.size_mode(), SizeMode::Allocated);
+    }
+
+    #[test]
+    fn test_dupes_subcommand() {
+        let args = CliArgs::parse_from(["dusk", "dupes"]);
+        let Some(Command::Dupes(dupes)) = args.command else {
+            panic!("expected dupes subcommand");
+        };
+        assert_eq!(dupes.paths, vec!["."]);
+        assert_eq!(dupes.min_size, 1);
+        assert_eq!(dupes.output_format(), OutputFormat::Text);
+
+        let args =
+            CliArgs::parse_from(["dusk", "dupes", "-H", "-x", "--format", "csv", "/a", "/b"]);
+        let Some(Command::Dupes(dupes)) = args.command else {
+            panic!("expected dupes subcommand");
+        };
+        assert_eq!(dupes.paths, vec!["/a", "/b"]);
+        assert!(dupes.human_readable && dupes.one_file_system);
+        assert_eq!(dupes.output_format(), OutputFormat::Csv);
+
+        assert!(CliArgs::try_parse_from(["dusk", "dupes", "--min-size", "big"]).is_err());
+    }
+
+    #[test]
+    fn test_diff_requires_two_snapshots() {
+        assert!(CliArgs::try_parse_from(["dusk", "diff", "a.dusk"]).is_err());
+    }
+
+    #[test]
+    fn test_dot_slash_path_named_like_subcommand() {
+        let args = CliArgs::parse_from(["dusk", "./diff"]);
+        assert!(args.command.is_none());
+        assert_eq!(args.paths, vec!["./diff"]);
+    }
+
     #[test]
     fn test_multiple_paths() {
         let args = CliArgs::parse_from(["dusk", "/tmp", "/var"]);
@@ -0,0 +1,504 @@
+//! Grand totals and side-by-side comparison of paths (`--total`, `--compare`).
+//!
+//! Both add up the scanned trees into one combined total. A path inside
+//! another analyzed path (`/usr/lib` next to `/usr`), or given twice, is
+//! already part of that path's total, so it is left out of the sum:
+//!
+//! ```text
+//! dusk -c -s /usr /usr/lib /home
+//! 8.2G    /usr
+//! 3.1G    /usr/lib
+//! 20.4G   /home
+//! 28.6G   total
+//! ```
+//!
+//! Overlap is decided by path components only: the same files reached
+//! through different paths (hard links, or symlinks followed with `-L`)
+//! are counted once per path.
+//!
+//! # Comparison Table
+//!
+//! `--compare` replaces the trees with one row per path: its total, its
+//! share of the combined total and a bar, followed by the total row. Rows
+//! inside another path say which one (`[within /usr]`); their shares are
+//! part of that path's, so the shares can add up to more than 100%.
+//!
+//! ```text
+//!  8.2G   28.7%  ███████▏                  /usr
+//!  3.1G   10.8%  ██▋                       /usr/lib [within /usr]
+//! 20.4G   71.3%  █████████████████▊        /home
+//! 28.6G  100.0%                            total
+//! ```
+//!
+//! - `text` - Aligned rows as above
+//! - `json` - One object with a `paths` array and a `total` object
+//! - `ndjson` - One object per path, then the total with a `null` path
+//! - `csv` - Header row, one row per path, then the total with an empty path
+//!
+//! # Examples
+//!
+//! ```
+//! use disk_usage_clone::compare::{combined_total, compare_paths};
+//! use disk_usage_clone::entry::{DiskEntry, EntryType, SizeMode, SortOrder};
+//! use std::path::PathBuf;
+//!
+//! let trees = [
+//!     DiskEntry::new(PathBuf::from("/usr"), 300, EntryType::Directory, 0),
+//!     DiskEntry::new(PathBuf::from("/usr/lib"), 100, EntryType::Directory, 0),
+//!     DiskEntry::new(PathBuf::from("/home"), 100, EntryType::Directory, 0),
+//! ];
+//!
+//! // /usr/lib is already part of /usr
+//! assert_eq!(combined_total(&trees, SizeMode::Apparent), 400);
+//!
+//! let comparison = compare_paths(&trees, SizeMode::Apparent, &SortOrder::None);
+//! assert_eq!(comparison.paths[1].within, Some(PathBuf::from("/usr")));
+//! assert_eq!(comparison.percent(&comparison.paths[0], SizeMode::Apparent), 75.0);
+//! ```
+
+use std::cmp::Reverse;
+use std::path::PathBuf;
+
+use colored::Colorize;
+use serde_json::{Value, json};
+
+use crate::entry::{DiskEntry, SizeMode, SortOrder};
+use crate::formatter::format_size;
+use crate::output::{INCOMPLETE_MARKER, colorize_size, csv_field};
+use crate::tree_view::{bar_width, render_bar};
+
+/// Label of the combined total in text output, as in `du -c`.
+const TOTAL_LABEL: &str = "total";
+
+/// Header row of the CSV output, in column order.
+const CSV_HEADER: &str = "path,size_bytes,size_human,percent,within,incomplete";
+
+/// Total of one analyzed path.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PathTotal {
+    /// Analyzed path, as resolved by the scan.
+    pub path: PathBuf,
+    /// Apparent size of everything below the path.
+    pub apparent_bytes: u64,
+    /// Allocated size of everything below the path.
+    pub allocated_bytes: u64,
+    /// Analyzed path this one is inside (or equal to), if any; its size is
+    /// then left out of the combined total.
+    pub within: Option<PathBuf>,
+    /// The scan of this path was stopped before it was complete.
+    pub incomplete: bool,
+}
+
+impl PathTotal {
+    /// Returns the path's total in the given mode.
+    pub fn size(&self, size_mode: SizeMode) -> u64 {
+        match size_mode {
+            SizeMode::Apparent => self.apparent_bytes,
+            SizeMode::Allocated => self.allocated_bytes,
+        }
+    }
+}
+
+/// Every analyzed path with the combined total.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Comparison {
+    /// One row per analyzed path.
+    pub paths: Vec<PathTotal>,
+    /// Combined apparent size of the paths not within another one.
+    pub apparent_bytes: u64,
+    /// Combined allocated size of the paths not within another one.
+    pub allocated_bytes: u64,
+}
+
+impl Comparison {
+    /// Returns the combined total in the given mode.
+    pub fn total(&self, size_mode: SizeMode) -> u64 {
+        match size_mode {
+            SizeMode::Apparent => self.apparent_bytes,
+            SizeMode::Allocated => self.allocated_bytes,
+        }
+    }
+
+    /// Returns a path's share of the combined total, in percent.
+    ///
+    /// An empty total gives 0% for every path.
+    pub fn percent(&self, path: &PathTotal, size_mode: SizeMode) -> f64 {
+        match self.total(size_mode) {
+            0 => 0.0,
+            total => path.size(size_mode) as f64 * 100.0 / total as f64,
+        }
+    }
+
+    /// Returns whether any path's scan was stopped early.
+    pub fn is_incomplete(&self) -> bool {
+        self.paths.iter().any(|path| path.incomplete)
+    }
+}
+
+/// Returns the first analyzed path that `trees[index]` is inside.
+///
+/// A path given twice is within its first occurrence.
+fn enclosing_path(trees: &[DiskEntry], index: usize) -> Option<PathBuf> {
+    let path = &trees[index].path;
+    trees
+        .iter()
+        .enumerate()
+        .find(|&(other, tree)| {
+            other != index && path.starts_with(&tree.path) && (tree.path != *path || other < index)
+        })
+        .map(|(_, tree)| tree.path.clone())
+}
+
+/// Totals every tree and combines them, skipping overlapping paths.
+///
+/// # Arguments
+///
+/// * `trees` - Scanned trees, one per analyzed path
+/// * `size_mode` - Which size `sort` compares
+/// * `sort` - Order of the rows; `none` keeps the order of the paths
+pub fn compare_paths(trees: &[DiskEntry], size_mode: SizeMode, sort: &SortOrder) -> Comparison {
+    let mut paths: Vec<PathTotal> = (0..trees.len())
+        .map(|index| {
+            let tree = &trees[index];
+            PathTotal {
+                path: tree.path.clone(),
+                apparent_bytes: tree.total_size_for(SizeMode::Apparent),
+                allocated_bytes: tree.total_size_for(SizeMode::Allocated),
+                within: enclosing_path(trees, index),
+                incomplete: tree.incomplete,
+            }
+        })
+        .collect();
+
+    let counted = || paths.iter().filter(|path| path.within.is_none());
+    let apparent_bytes = counted().map(|path| path.apparent_bytes).sum();
+    let allocated_bytes = counted().map(|path| path.allocated_bytes).sum();
+
+    match sort {
+        SortOrder::None => {}
+        SortOrder::SizeDescending => paths.sort_by_key(|path| Reverse(path.size(size_mode))),
+        SortOrder::SizeAscending => paths.sort_by_key(|path| path.size(size_mode)),
+        SortOrder::Name => paths.sort_by(|a, b| a.path.cmp(&b.path)),
+    }
+
+    Comparison {
+        paths,
+        apparent_bytes,
+        allocated_bytes,
+    }
+}
+
+/// Returns the combined total of the trees, counting overlapping paths once.
+pub fn combined_total(trees: &[DiskEntry], size_mode: SizeMode) -> u64 {
+    compare_paths(trees, size_mode, &SortOrder::None).total(size_mode)
+}
+
+/// Renders the `SIZE<TAB>total` line that `--total` appends to the output.
+///
+/// Flagged `[incomplete]` if any tree is.
+///
+/// # Examples
+///
+/// ```
+/// use disk_usage_clone::compare::render_total_line;
+/// use disk_usage_clone::entry::{DiskEntry, EntryType, SizeMode};
+/// use std::path::PathBuf;
+///
+/// let trees = [
+///     DiskEntry::new(PathBuf::from("/a"), 1024, EntryType::Directory, 0),
+///     DiskEntry::new(PathBuf::from("/b"), 1024, EntryType::Directory, 0),
+/// ];
+/// let line = render_total_line(&trees, true, false, SizeMode::Apparent);
+/// assert_eq!(line, "2.0K\ttotal");
+/// ```
+pub fn render_total_line(
+    trees: &[DiskEntry],
+    human_readable: bool,
+    use_color: bool,
+    size_mode: SizeMode,
+) -> String {
+    let total = combined_total(trees, size_mode);
+    let size_str = format_size(total, human_readable);
+    let incomplete = if trees.iter().any(|tree| tree.incomplete) {
+        INCOMPLETE_MARKER
+    } else {
+        ""
+    };
+
+    if use_color {
+        format!(
+            "{}\t{}{}",
+            colorize_size(&size_str, total),
+            TOTAL_LABEL.bold(),
+            incomplete.yellow()
+        )
+    } else {
+        format!("{size_str}\t{TOTAL_LABEL}{incomplete}")
+    }
+}
+
+/// Renders the comparison as aligned `size  share  bar  path` rows.
+///
+/// The total row ends the table. Bars are sized for `terminal_width`
+/// columns, like the tree view's.
+pub fn render_comparison(
+    comparison: &Comparison,
+    human_readable: bool,
+    use_color: bool,
+    size_mode: SizeMode,
+    terminal_width: usize,
+) -> String {
+    let total = comparison.total(size_mode);
+    let sizes: Vec<String> = comparison
+        .paths
+        .iter()
+        .map(|path| format_size(path.size(size_mode), human_readable))
+        .collect();
+    // Room for the total row too
+    let size_width = format_size(total, human_readable)
+        .len()
+        .max(sizes.iter().map(String::len).max().unwrap_or(0));
+    let bar_width = bar_width(terminal_width);
+
+    let mut rows: Vec<String> = comparison
+        .paths
+        .iter()
+        .zip(&sizes)
+        .map(|(path, size_str)| {
+            let size = path.size(size_mode);
+            let padded = format!("{size_str:>size_width$}");
+            let percent = comparison.percent(path, size_mode);
+            let share = format!("{percent:>5.1}%");
+            let bar = render_bar(percent / 100.0, bar_width);
+            let name = path.path.display().to_string();
+            let within = path
+                .within
+                .as_ref()
+                .map(|within| format!(" [within {}]", within.display()))
+                .unwrap_or_default();
+            let incomplete = if path.incomplete {
+                INCOMPLETE_MARKER
+            } else {
+                ""
+            };
+
+            if use_color {
+                format!(
+                    "{}  {share}  {bar}  {}{}{}",
+                    colorize_size(&padded, size),
+                    name.blue().bold(),
+                    within.dimmed(),
+                    incomplete.yellow()
+                )
+            } else {
+                format!("{padded}  {share}  {bar}  {name}{within}{incomplete}")
+            }
+        })
+        .collect();
+
+    let padded = format!("{:>size_width$}", format_size(total, human_readable));
+    let share = format!("{:>5.1}%", 100.0);
+    let blank = " ".repeat(bar_width);
+    rows.push(if use_color {
+        format!(
+            "{}  {share}  {blank}  {}",
+            colorize_size(&padded, total),
+            TOTAL_LABEL.bold()
+        )
+    } else {
+        format!("{padded}  {share}  {blank}  {TOTAL_LABEL}")
+    });
+    rows.join("\n")
+}
+
+/// Builds the JSON object describing one path.
+fn path_object(comparison: &Comparison, path: &PathTotal, size_mode: SizeMode) -> Value {
+    json!({
+        "path": path.path.display().to_string(),
+        "size": path.size(size_mode),
+        "apparent_size": path.apparent_bytes,
+        "allocated_size": path.allocated_bytes,
+        "percent": comparison.percent(path, size_mode),
+        "within": path.within.as_ref().map(|within| within.display().to_string()),
+        "incomplete": path.incomplete,
+    })
+}
+
+/// Builds the JSON object describing the combined total.
+fn total_object(comparison: &Comparison, size_mode: SizeMode) -> Value {
+    json!({
+        "size": comparison.total(size_mode),
+        "apparent_size": comparison.apparent_bytes,
+        "allocated_size": comparison.allocated_bytes,
+        "incomplete": comparison.is_incomplete(),
+    })
+}
+
+/// Renders the comparison as one pretty-printed JSON object
+/// (`--format json`).
+pub fn render_comparison_json(comparison: &Comparison, size_mode: SizeMode) -> String {
+    let paths: Vec<Value> = comparison
+        .paths
+        .iter()
+        .map(|path| path_object(comparison, path, size_mode))
+        .collect();
+    let value = json!({
+        "paths": paths,
+        "total": total_object(comparison, size_mode),
+    });
+    // Serializing a Value cannot fail
+    serde_json::to_string_pretty(&value).unwrap_or_default()
+}
+
+/// Renders the comparison as one JSON object per line
+/// (`--format ndjson`), ending with the total (`"path": null`).
+pub fn render_comparison_ndjson(comparison: &Comparison, size_mode: SizeMode) -> String {
+    let mut total = total_object(comparison, size_mode);
+    total["path"] = Value::Null;
+    comparison
+        .paths
+        .iter()
+        .map(|path| path_object(comparison, path, size_mode))
+        .chain([total])
+        .map(|value| value.to_string())
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Renders the comparison as CSV with a header row (`--format csv`),
+/// ending with the total (empty `path`).
+pub fn render_comparison_csv(comparison: &Comparison, size_mode: SizeMode) -> String {
+    let mut rows = vec![CSV_HEADER.to_string()];
+    for path in &comparison.paths {
+        let size = path.size(size_mode);
+        let within = path
+            .within
+            .as_ref()
+            .map(|within| csv_field(&within.display().to_string()))
+            .unwrap_or_default();
+        rows.push(format!(
+            "{},{size},{},{:.1},{within},{}",
+            csv_field(&path.path.display().to_string()),
+            format_size(size, true),
+            comparison.percent(path, size_mode),
+            path.incomplete
+        ));
+    }
+    let total = comparison.total(size_mode);
+    rows.push(format!(
+        ",{total},{},100.0,,{}",
+        format_size(total, true),
+        comparison.is_incomplete()
+    ));
+    rows.join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::entry::EntryType;
+
+    fn dir(path: &str, size: u64) -> DiskEntry {
+        let mut entry = DiskEntry::new(PathBuf::from(path), size, EntryType::Directory, 0);
+        entry.allocated_bytes = size * 2;
+        entry
+    }
+
+    fn sample() -> Vec<DiskEntry> {
+        vec![dir("/usr", 600), dir("/usr/lib", 200), dir("/home", 200)]
+    }
+
+    #[test]
+    fn test_nested_and_repeated_paths_counted_once() {
+        let trees = [
+            dir("/a", 100),
+            dir("/a/b", 50),
+            dir("/ab", 10),
+            dir("/a", 100),
+        ];
+        let comparison = compare_paths(&trees, SizeMode::Apparent, &SortOrder::None);
+        let within: Vec<Option<PathBuf>> = comparison
+            .paths
+            .iter()
+            .map(|path| path.within.clone())
+            .collect();
+        // `/ab` only shares a prefix of characters with `/a`, not a component
+        assert_eq!(
+            within,
+            [
+                None,
+                Some(PathBuf::from("/a")),
+                None,
+                Some(PathBuf::from("/a"))
+            ]
+        );
+        assert_eq!(comparison.total(SizeMode::Apparent), 110);
+        assert_eq!(comparison.total(SizeMode::Allocated), 220);
+    }
+
+    #[test]
+    fn test_compare_sorts_rows() {
+        let paths = |sort: SortOrder| -> Vec<PathBuf> {
+            compare_paths(&sample(), SizeMode::Apparent, &sort)
+                .paths
+                .into_iter()
+                .map(|path| path.path)
+                .collect()
+        };
+        assert_eq!(paths(SortOrder::None)[0], PathBuf::from("/usr"));
+        assert_eq!(paths(SortOrder::Name)[0], PathBuf::from("/home"));
+        assert_eq!(paths(SortOrder::SizeAscending)[2], PathBuf::from("/usr"));
+    }
+
+    #[test]
+    fn test_percent_of_empty_total() {
+        let comparison = compare_paths(&[dir("/empty", 0)], SizeMode::Apparent, &SortOrder::None);
+        assert_eq!(
+            comparison.percent(&comparison.paths[0], SizeMode::Apparent),
+            0.0
+        );
+    }
+
+    #[test]
+    fn test_render_comparison_text() {
+        let comparison = compare_paths(&sample(), SizeMode::Apparent, &SortOrder::None);
+        let output = render_comparison(&comparison, false, false, SizeMode::Apparent, 40);
+        let lines: Vec<&str> = output.lines().collect();
+        assert_eq!(lines.len(), 4);
+        assert!(lines[0].starts_with("600   75.0%  "), "{output}");
+        assert!(lines[0].ends_with("  /usr"));
+        assert!(lines[1].ends_with("/usr/lib [within /usr]"));
+        assert!(lines[3].starts_with("800  100.0%"));
+        assert!(lines[3].ends_with("  total"));
+    }
+
+    #[test]
+    fn test_render_total_line_flags_incomplete() {
+        let mut trees = sample();
+        trees[2].incomplete = true;
+        let line = render_total_line(&trees, false, false, SizeMode::Allocated);
+        assert_eq!(line, "1600\ttotal [incomplete]");
+    }
+
+    #[test]
+    fn test_render_comparison_machine_formats() {
+        let comparison = compare_paths(&sample(), SizeMode::Apparent, &SortOrder::None);
+
+        let json: Value =
+            serde_json::from_str(&render_comparison_json(&comparison, SizeMode::Apparent)).unwrap();
+        assert_eq!(json["paths"][1]["within"], "/usr");
+        assert_eq!(json["paths"][0]["percent"], 75.0);
+        assert_eq!(json["total"]["size"], 800);
+
+        let ndjson = render_comparison_ndjson(&comparison, SizeMode::Apparent);
+        let last: Value = serde_json::from_str(ndjson.lines().last().unwrap()).unwrap();
+        assert_eq!(last["path"], Value::Null);
+        assert_eq!(last["size"], 800);
+
+        let csv = render_comparison_csv(&comparison, SizeMode::Apparent);
+        let rows: Vec<&str> = csv.lines().collect();
+        assert_eq!(rows[0], CSV_HEADER);
+        assert_eq!(rows[2], "/usr/lib,200,200B,25.0,/usr,false");
+        assert_eq!(rows[4], ",800,800B,100.0,,false");
+    }
+}
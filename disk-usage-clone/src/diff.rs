@@ -0,0 +1,368 @@
+//! Comparing two snapshots (`dusk diff OLD NEW`).
+//!
+//! Both sides are loaded from snapshot files (see [`crate::snapshot`]), so a
+//! diff never scans the disk. Entries are matched by path; every path whose
+//! total size differs is reported:
+//!
+//! - **added** - only in the new snapshot
+//! - **removed** - only in the old snapshot
+//! - **grown** / **shrunk** - in both, with a different total
+//!
+//! Directory totals include their descendants, so a directory shows up as
+//! grown whenever anything below it grew.
+//!
+//! # Output
+//!
+//! One tab-separated line per change, largest absolute change first by default:
+//!
+//! ```text
+//! +1.5G  grown  /var/lib/docker
+//! +800.0M  added  /var/lib/docker/overlay2/abc
+//! -200.0M  removed  /var/cache/apt/archives
+//! ```
+//!
+//! # Examples
+//!
+//! ```
+//! use disk_usage_clone::diff::{ChangeKind, DiffSort, diff_trees};
+//! use disk_usage_clone::entry::{DiskEntry, EntryType, SizeMode};
+//! use std::path::PathBuf;
+//!
+//! let old = DiskEntry::new(PathBuf::from("/data"), 100, EntryType::Directory, 0);
+//! let new = DiskEntry::new(PathBuf::from("/data"), 250, EntryType::Directory, 0);
+//!
+//! let changes = diff_trees(&[old], &[new], SizeMode::Apparent, DiffSort::Change);
+//! assert_eq!(changes.len(), 1);
+//! assert_eq!(changes[0].kind, ChangeKind::Grown);
+//! assert_eq!(changes[0].delta(), 150);
+//! ```
+
+use std::cmp::Reverse;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+use crate::entry::{DiskEntry, SizeMode};
+use crate::formatter::format_size;
+
+/// How a path changed between two snapshots.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ChangeKind {
+    Added,
+    Removed,
+    Grown,
+    Shrunk,
+}
+
+impl ChangeKind {
+    /// Lowercase name used in the output.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ChangeKind::Added => "added",
+            ChangeKind::Removed => "removed",
+            ChangeKind::Grown => "grown",
+            ChangeKind::Shrunk => "shrunk",
+        }
+    }
+}
+
+/// One changed path.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PathChange {
+    /// Path of the entry.
+    pub path: PathBuf,
+    /// Kind of change.
+    pub kind: ChangeKind,
+    /// Total size in the old snapshot (0 if added).
+    pub old_size: u64,
+    /// Total size in the new snapshot (0 if removed).
+    pub new_size: u64,
+}
+
+impl PathChange {
+    /// Signed size change, new minus old (saturating at the `i64` range).
+    pub fn delta(&self) -> i64 {
+        let delta = i128::from(self.new_size) - i128::from(self.old_size);
+        i64::try_from(delta).unwrap_or(if delta < 0 { i64::MIN } else { i64::MAX })
+    }
+}
+
+/// Order of the reported changes.
+///
+/// # Variants
+///
+/// - `Change` - Largest absolute change first (default)
+/// - `Path` - Alphabetical by path
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum DiffSort {
+    #[default]
+    Change,
+    Path,
+}
+
+impl DiffSort {
+    /// Names accepted by [`DiffSort::parse`], for CLI validation.
+    pub const NAMES: &'static [&'static str] = &["change", "path"];
+
+    /// Parses a string into a `DiffSort`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use disk_usage_clone::diff::DiffSort;
+    ///
+    /// assert_eq!(DiffSort::parse("change"), Some(DiffSort::Change));
+    /// assert_eq!(DiffSort::parse("path"), Some(DiffSort::Path));
+    /// assert_eq!(DiffSort::parse("size"), None);
+    /// ```
+    pub fn parse(s: &str) -> Option<DiffSort> {
+        match s {
+            "change" => Some(DiffSort::Change),
+            "path" => Some(DiffSort::Path),
+            _ => None,
+        }
+    }
+}
+
+/// Compares two sets of trees and returns every changed path.
+///
+/// # Arguments
+///
+/// * `old` - Trees of the older snapshot
+/// * `new` - Trees of the newer snapshot
+/// * `size_mode` - Compare apparent or allocated totals
+/// * `sort` - Order of the result
+///
+/// # Returns
+///
+/// Changed paths only; entries with equal totals are left out. Ties in the
+/// `Change` order are broken by path, so the output is deterministic.
+pub fn diff_trees(
+    old: &[DiskEntry],
+    new: &[DiskEntry],
+    size_mode: SizeMode,
+    sort: DiffSort,
+) -> Vec<PathChange> {
+    let old_sizes = total_sizes(old, size_mode);
+    let new_sizes = total_sizes(new, size_mode);
+
+    let mut changes: Vec<PathChange> = Vec::new();
+
+    for (path, &new_size) in &new_sizes {
+        let change = match old_sizes.get(path) {
+            None => Some((ChangeKind::Added, 0)),
+            Some(&old_size) if new_size > old_size => Some((ChangeKind::Grown, old_size)),
+            Some(&old_size) if new_size < old_size => Some((ChangeKind::Shrunk, old_size)),
+            Some(_) => None,
+        };
+        if let Some((kind, old_size)) = change {
+            changes.push(PathChange {
+                path: path.to_path_buf(),
+                kind,
+                old_size,
+                new_size,
+            });
+        }
+    }
+
+    for (path, &old_size) in &old_sizes {
+        if !new_sizes.contains_key(path) {
+            changes.push(PathChange {
+                path: path.to_path_buf(),
+                kind: ChangeKind::Removed,
+                old_size,
+                new_size: 0,
+            });
+        }
+    }
+
+    match sort {
+        DiffSort::Change => changes.sort_by(|a, b| {
+            Reverse(a.delta().unsigned_abs())
+                .cmp(&Reverse(b.delta().unsigned_abs()))
+                .then_with(|| a.path.cmp(&b.path))
+        }),
+        DiffSort::Path => changes.sort_by(|a, b| a.path.cmp(&b.path)),
+    }
+
+    changes
+}
+
+/// Maps every path in the trees to its total size.
+///
+/// Totals are computed bottom-up in one pass instead of calling
+/// `total_size_for` on every node.
+fn total_sizes(trees: &[DiskEntry], size_mode: SizeMode) -> HashMap<&Path, u64> {
+    fn visit<'a>(
+        entry: &'a DiskEntry,
+        size_mode: SizeMode,
+        sizes: &mut HashMap<&'a Path, u64>,
+    ) -> u64 {
+        let total = entry.size(size_mode)
+            + entry
+                .children
+                .iter()
+                .map(|child| visit(child, size_mode, sizes))
+                .sum::<u64>();
+        sizes.insert(&entry.path, total);
+        total
+    }
+
+    let mut sizes = HashMap::new();
+    for tree in trees {
+        visit(tree, size_mode, &mut sizes);
+    }
+    sizes
+}
+
+/// Formats a signed size change, e.g. `+1.5K` or `-200`.
+///
+/// # Examples
+///
+/// ```
+/// use disk_usage_clone::diff::format_delta;
+///
+/// assert_eq!(format_delta(1536, true), "+1.5K");
+/// assert_eq!(format_delta(-200, false), "-200");
+/// assert_eq!(format_delta(0, false), "0");
+/// ```
+pub fn format_delta(delta: i64, human_readable: bool) -> String {
+    let size = format_size(delta.unsigned_abs(), human_readable);
+    match delta.signum() {
+        1 => format!("+{size}"),
+        -1 => format!("-{size}"),
+        _ => size,
+    }
+}
+
+/// Renders changes as `delta<TAB>kind<TAB>path` lines.
+///
+/// Returns an empty string if nothing changed.
+pub fn render_diff(changes: &[PathChange], human_readable: bool) -> String {
+    changes
+        .iter()
+        .map(|change| {
+            format!(
+                "{}\t{}\t{}",
+                format_delta(change.delta(), human_readable),
+                change.kind.as_str(),
+                change.path.display()
+            )
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::entry::EntryType;
+
+    fn dir(path: &str, size: u64, children: Vec<DiskEntry>) -> DiskEntry {
+        let mut entry = DiskEntry::new(PathBuf::from(path), size, EntryType::Directory, 0);
+        entry.children = children;
+        entry
+    }
+
+    fn file(path: &str, size: u64) -> DiskEntry {
+        DiskEntry::new(PathBuf::from(path), size, EntryType::File, 1)
+    }
+
+    fn find<'a>(changes: &'a [PathChange], path: &str) -> Option<&'a PathChange> {
+        changes.iter().find(|c| c.path == Path::new(path))
+    }
+
+    #[test]
+    fn test_diff_kinds() {
+        let old = dir(
+            "/d",
+            0,
+            vec![
+                file("/d/same", 10),
+                file("/d/grow", 10),
+                file("/d/shrink", 50),
+                file("/d/gone", 5),
+            ],
+        );
+        let new = dir(
+            "/d",
+            0,
+            vec![
+                file("/d/same", 10),
+                file("/d/grow", 40),
+                file("/d/shrink", 20),
+                file("/d/new", 7),
+            ],
+        );
+
+        let changes = diff_trees(&[old], &[new], SizeMode::Apparent, DiffSort::Path);
+
+        assert!(find(&changes, "/d/same").is_none());
+        assert_eq!(find(&changes, "/d/grow").unwrap().kind, ChangeKind::Grown);
+        assert_eq!(find(&changes, "/d/grow").unwrap().delta(), 30);
+        assert_eq!(
+            find(&changes, "/d/shrink").unwrap().kind,
+            ChangeKind::Shrunk
+        );
+        assert_eq!(find(&changes, "/d/shrink").unwrap().delta(), -30);
+        assert_eq!(find(&changes, "/d/gone").unwrap().kind, ChangeKind::Removed);
+        assert_eq!(find(&changes, "/d/gone").unwrap().delta(), -5);
+        assert_eq!(find(&changes, "/d/new").unwrap().kind, ChangeKind::Added);
+        // Directory total: 75 -> 77
+        assert_eq!(find(&changes, "/d").unwrap().delta(), 2);
+    }
+
+    #[test]
+    fn test_diff_sorted_by_absolute_change() {
+        let old = dir("/d", 0, vec![file("/d/a", 100), file("/d/b", 0)]);
+        let new = dir("/d", 0, vec![file("/d/a", 10), file("/d/b", 50)]);
+
+        let changes = diff_trees(&[old], &[new], SizeMode::Apparent, DiffSort::Change);
+        let order: Vec<&Path> = changes.iter().map(|c| c.path.as_path()).collect();
+        assert_eq!(
+            order,
+            vec![Path::new("/d/a"), Path::new("/d/b"), Path::new("/d")]
+        );
+    }
+
+    #[test]
+    fn test_diff_size_mode() {
+        let old = file("/f", 10);
+        let mut new = file("/f", 10);
+        new.allocated_bytes = 4096;
+
+        assert!(
+            diff_trees(
+                std::slice::from_ref(&old),
+                std::slice::from_ref(&new),
+                SizeMode::Apparent,
+                DiffSort::Change
+            )
+            .is_empty()
+        );
+        let changes = diff_trees(&[old], &[new], SizeMode::Allocated, DiffSort::Change);
+        assert_eq!(changes[0].delta(), 4086);
+    }
+
+    #[test]
+    fn test_delta_saturates() {
+        let change = PathChange {
+            path: PathBuf::from("/x"),
+            kind: ChangeKind::Grown,
+            old_size: 0,
+            new_size: u64::MAX,
+        };
+        assert_eq!(change.delta(), i64::MAX);
+    }
+
+    #[test]
+    fn test_render_diff() {
+        let old = dir("/d", 0, vec![file("/d/a", 2048)]);
+        let new = dir("/d", 0, vec![]);
+        let changes = diff_trees(&[old], &[new], SizeMode::Apparent, DiffSort::Path);
+        assert_eq!(
+            render_diff(&changes, true),
+            "-2.0K\tshrunk\t/d\n-2.0K\tremoved\t/d/a"
+        );
+        assert_eq!(render_diff(&[], true), "");
+    }
+}
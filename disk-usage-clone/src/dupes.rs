@@ -0,0 +1,564 @@
+//! Duplicate file finder (`dusk dupes PATH...`).
+//!
+//! Finds regular files with identical contents and reports how much space
+//! deleting all but one copy would free. Files are narrowed down in stages,
+//! so only files that might be duplicates are ever read:
+//!
+//! ```mermaid
+//! flowchart TD
+//!     A[Parallel traversal of every path] --> B[Group regular files by size]
+//!     B --> C[One path per inode: hard links are not copies]
+//!     C --> D[Hash the first 4 KiB]
+//!     D --> E[Hash the whole file]
+//!     E --> F[Groups of two or more: duplicates]
+//! ```
+//!
+//! Each stage drops groups left with a single file. Metadata and hashing
+//! run on the rayon pool (`-j` threads), and contents are compared by their
+//! BLAKE3 hash. Files of at most 4 KiB are fully read by the first hash and
+//! skip the second.
+//!
+//! # Hard Links
+//!
+//! Several links to one inode share their data, so removing one frees
+//! nothing. Each inode is kept once, under its first path in sort order;
+//! a group whose files are all links to the same inode is not reported.
+//!
+//! # Output
+//!
+//! Groups are sorted by reclaimable bytes, largest first:
+//!
+//! ```text
+//! 3.0M    3 copies of 1.5M
+//!         /data/a/model.bin
+//!         /data/b/model.bin
+//!         /data/old/model.bin
+//!
+//! 3.0M    reclaimable in 1 group
+//! ```
+//!
+//! `--format` selects `json` (an array of groups), `ndjson` (one group per
+//! line) or `csv` (one row per file).
+//!
+//! # Examples
+//!
+//! ```
+//! use disk_usage_clone::dupes::find_duplicates;
+//! use disk_usage_clone::traversal::TraversalOptions;
+//! use std::fs;
+//!
+//! let dir = tempfile::tempdir().unwrap();
+//! fs::write(dir.path().join("a.txt"), "same").unwrap();
+//! fs::write(dir.path().join("b.txt"), "same").unwrap();
+//! fs::write(dir.path().join("c.txt"), "diff").unwrap();
+//!
+//! let report = find_duplicates(&[dir.path()], &TraversalOptions::default(), 1).unwrap();
+//! assert_eq!(report.groups.len(), 1);
+//! assert_eq!(report.groups[0].paths.len(), 2);
+//! assert_eq!(report.groups[0].reclaimable(), 4);
+//! ```
+
+use std::cmp::Reverse;
+use std::collections::{HashMap, HashSet};
+use std::fs::{self, File};
+use std::hash::Hash;
+use std::io::{self, Read};
+use std::path::{Path, PathBuf};
+
+use rayon::prelude::*;
+use serde_json::{Value, json};
+
+use crate::entry::{DiskEntry, EntryType};
+use crate::error::DuskError;
+use crate::formatter::format_size;
+use crate::output::csv_field;
+use crate::traversal::{
+    SkipAction, SkippedEntry, TraversalOptions, file_id, traverse_parallel_report,
+};
+
+/// Bytes read from the start of each file by the partial hash.
+const PARTIAL_HASH_BYTES: u64 = 4096;
+
+/// Header row of the CSV output, in column order.
+const CSV_HEADER: &str = "group,path,size_bytes,copies,reclaimable_bytes";
+
+/// Files with identical contents.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DuplicateGroup {
+    /// Size of each copy in bytes.
+    pub size: u64,
+    /// Paths of the copies, sorted; at least two.
+    pub paths: Vec<PathBuf>,
+}
+
+impl DuplicateGroup {
+    /// Bytes freed by keeping a single copy.
+    pub fn reclaimable(&self) -> u64 {
+        self.size * (self.paths.len() as u64 - 1)
+    }
+}
+
+/// Duplicate groups plus every path that couldn't be examined.
+#[derive(Debug)]
+pub struct DupesReport {
+    /// Duplicate groups, most reclaimable bytes first.
+    pub groups: Vec<DuplicateGroup>,
+    /// Directories, metadata and files that couldn't be read. Duplicates
+    /// among them are missing from `groups`.
+    pub skipped: Vec<SkippedEntry>,
+}
+
+impl DupesReport {
+    /// Bytes freed by keeping a single copy of every group.
+    pub fn reclaimable(&self) -> u64 {
+        self.groups.iter().map(DuplicateGroup::reclaimable).sum()
+    }
+}
+
+/// A file that may have duplicates.
+#[derive(Debug, Clone)]
+struct Candidate {
+    path: PathBuf,
+    size: u64,
+}
+
+/// Finds files with identical contents below `paths`.
+///
+/// # Arguments
+///
+/// * `paths` - Paths to search; overlapping paths are fine
+/// * `options` - Traversal settings (exclusions, `-x`, threads); every hard
+///   link is listed whatever `count_links` says, and `max_depth` is ignored
+/// * `min_size` - Smallest file size considered, in bytes
+///
+/// # Errors
+///
+/// - `PathNotFound` if a path doesn't exist
+/// - `TraversalError` if the thread pool can't be created
+///
+/// Unreadable directories and files are reported in
+/// [`DupesReport::skipped`] instead.
+pub fn find_duplicates<P: AsRef<Path>>(
+    paths: &[P],
+    options: &TraversalOptions,
+    min_size: u64,
+) -> Result<DupesReport, DuskError> {
+    // Sizes must be real for every link; inodes are deduplicated below
+    let options = TraversalOptions {
+        max_depth: None,
+        count_links: true,
+        ..options.clone()
+    };
+
+    let mut files = Vec::new();
+    let mut skipped = Vec::new();
+    for path in paths {
+        let mut report = traverse_parallel_report(path.as_ref(), &options)?;
+        collect_files(&report.tree, min_size, &mut files);
+        skipped.append(&mut report.skipped);
+    }
+
+    // Hash on a pool of the requested size, like the traversal
+    let pool = match options.threads {
+        Some(n) => rayon::ThreadPoolBuilder::new().num_threads(n).build(),
+        None => rayon::ThreadPoolBuilder::new().build(),
+    }
+    .map_err(|e| DuskError::TraversalError(e.to_string()))?;
+    let groups = pool.install(|| duplicate_groups(files, &mut skipped));
+
+    Ok(DupesReport { groups, skipped })
+}
+
+/// Appends every regular file of at least `min_size` bytes.
+fn collect_files(tree: &DiskEntry, min_size: u64, files: &mut Vec<Candidate>) {
+    // Explicit stack: trees can be deeper than the call stack allows
+    let mut pending = vec![tree];
+    while let Some(entry) = pending.pop() {
+        pending.extend(&entry.children);
+        if entry.entry_type == EntryType::File && entry.size_bytes >= min_size {
+            files.push(Candidate {
+                path: entry.path.clone(),
+                size: entry.size_bytes,
+            });
+        }
+    }
+}
+
+/// Narrows files down to groups with identical contents.
+///
+/// Must run inside the rayon pool that should do the work.
+fn duplicate_groups(files: Vec<Candidate>, skipped: &mut Vec<SkippedEntry>) -> Vec<DuplicateGroup> {
+    let mut by_size: HashMap<u64, Vec<Candidate>> = HashMap::new();
+    for file in files {
+        by_size.entry(file.size).or_default().push(file);
+    }
+    let groups: Vec<Vec<Candidate>> = by_size
+        .into_values()
+        .filter(|group| group.len() > 1)
+        .map(|mut group| {
+            // Overlapping paths list the same file twice
+            group.sort_by(|a, b| a.path.cmp(&b.path));
+            group.dedup_by(|a, b| a.path == b.path);
+            group
+        })
+        .collect();
+
+    let groups = distinct_inodes(groups, skipped);
+    let groups = split_groups(groups, skipped, |file| {
+        hash_file(&file.path, Some(PARTIAL_HASH_BYTES))
+    });
+    // The partial hash already covered small files completely
+    let (complete, partial): (Vec<_>, Vec<_>) = groups
+        .into_iter()
+        .partition(|group| group[0].size <= PARTIAL_HASH_BYTES);
+    let partial = split_groups(partial, skipped, |file| hash_file(&file.path, None));
+
+    let mut duplicates: Vec<DuplicateGroup> = complete
+        .into_iter()
+        .chain(partial)
+        .map(|group| DuplicateGroup {
+            size: group[0].size,
+            paths: group.into_iter().map(|file| file.path).collect(),
+        })
+        .collect();
+    duplicates.sort_by(|a, b| {
+        Reverse(a.reclaimable())
+            .cmp(&Reverse(b.reclaimable()))
+            .then_with(|| a.paths.cmp(&b.paths))
+    });
+    duplicates
+}
+
+/// Keeps one path per inode in each group, dropping groups left with one.
+///
+/// Groups are sorted by path, so each inode keeps its first path. Files
+/// whose metadata can't be read are recorded in `skipped` and dropped.
+fn distinct_inodes(
+    groups: Vec<Vec<Candidate>>,
+    skipped: &mut Vec<SkippedEntry>,
+) -> Vec<Vec<Candidate>> {
+    let identified = compute_keys(groups, |file| {
+        fs::symlink_metadata(&file.path).map(|metadata| file_id(&metadata))
+    });
+
+    let mut result = Vec::new();
+    for group in identified {
+        let mut seen = HashSet::new();
+        let mut kept = Vec::new();
+        for (file, id) in group {
+            match id {
+                // A further link to an inode already in the group
+                Ok(Some(id)) if !seen.insert(id) => {}
+                Ok(_) => kept.push(file),
+                Err(err) => skipped.push(SkippedEntry::new(&file.path, SkipAction::Access, err)),
+            }
+        }
+        if kept.len() > 1 {
+            result.push(kept);
+        }
+    }
+    result
+}
+
+/// Files of a group paired with their key, or the error computing it.
+type Keyed<K> = Vec<(Candidate, io::Result<K>)>;
+
+/// Computes the key of every file in every group on the rayon pool.
+///
+/// Groups and files keep their order.
+fn compute_keys<K, F>(groups: Vec<Vec<Candidate>>, key: F) -> Vec<Keyed<K>>
+where
+    K: Send,
+    F: Fn(&Candidate) -> io::Result<K> + Sync,
+{
+    groups
+        .into_par_iter()
+        .map(|group| {
+            group
+                .into_par_iter()
+                .map(|file| {
+                    let key = key(&file);
+                    (file, key)
+                })
+                .collect()
+        })
+        .collect()
+}
+
+/// Splits every group by a key computed on the rayon pool.
+///
+/// Returns the parts with two or more files, in the order of their first
+/// file. Files whose key can't be computed are recorded in `skipped` and
+/// dropped.
+fn split_groups<K, F>(
+    groups: Vec<Vec<Candidate>>,
+    skipped: &mut Vec<SkippedEntry>,
+    key: F,
+) -> Vec<Vec<Candidate>>
+where
+    K: Hash + Eq + Send,
+    F: Fn(&Candidate) -> io::Result<K> + Sync,
+{
+    let mut result = Vec::new();
+    for group in compute_keys(groups, key) {
+        let mut index: HashMap<K, usize> = HashMap::new();
+        let mut parts: Vec<Vec<Candidate>> = Vec::new();
+        for (file, key) in group {
+            match key {
+                Ok(key) => {
+                    let part = *index.entry(key).or_insert_with(|| {
+                        parts.push(Vec::new());
+                        parts.len() - 1
+                    });
+                    parts[part].push(file);
+                }
+                Err(err) => skipped.push(SkippedEntry::new(&file.path, SkipAction::ReadFile, err)),
+            }
+        }
+        result.extend(parts.into_iter().filter(|part| part.len() > 1));
+    }
+    result
+}
+
+/// Hashes a file's contents with BLAKE3.
+///
+/// Reads at most `limit` bytes from the start, or the whole file if `None`.
+fn hash_file(path: &Path, limit: Option<u64>) -> io::Result<blake3::Hash> {
+    let file = File::open(path)?;
+    let mut hasher = blake3::Hasher::new();
+    match limit {
+        Some(limit) => hasher.update_reader(file.take(limit))?,
+        None => hasher.update_reader(file)?,
+    };
+    Ok(hasher.finalize())
+}
+
+/// Renders groups as text: a `reclaimable<TAB>N copies of SIZE` line per
+/// group followed by its tab-indented paths, then a total line.
+pub fn render_dupes(groups: &[DuplicateGroup], human_readable: bool) -> String {
+    let mut blocks: Vec<String> = groups
+        .iter()
+        .map(|group| {
+            let mut lines = vec![format!(
+                "{}\t{} copies of {}",
+                format_size(group.reclaimable(), human_readable),
+                group.paths.len(),
+                format_size(group.size, human_readable)
+            )];
+            lines.extend(group.paths.iter().map(|p| format!("\t{}", p.display())));
+            lines.join("\n")
+        })
+        .collect();
+
+    let total: u64 = groups.iter().map(DuplicateGroup::reclaimable).sum();
+    blocks.push(format!(
+        "{}\treclaimable in {} {}",
+        format_size(total, human_readable),
+        groups.len(),
+        if groups.len() == 1 { "group" } else { "groups" }
+    ));
+    blocks.join("\n\n")
+}
+
+/// Builds the JSON object describing one group.
+fn group_object(group: &DuplicateGroup) -> Value {
+    json!({
+        "size": group.size,
+        "copies": group.paths.len(),
+        "reclaimable": group.reclaimable(),
+        "paths": group
+            .paths
+            .iter()
+            .map(|p| p.display().to_string())
+            .collect::<Vec<_>>(),
+    })
+}
+
+/// Renders groups as one pretty-printed JSON array (`--format json`).
+pub fn render_dupes_json(groups: &[DuplicateGroup]) -> String {
+    let values: Vec<Value> = groups.iter().map(group_object).collect();
+    // Serializing a Value cannot fail
+    serde_json::to_string_pretty(&Value::Array(values)).unwrap_or_default()
+}
+
+/// Renders groups as one JSON object per line (`--format ndjson`).
+pub fn render_dupes_ndjson(groups: &[DuplicateGroup]) -> String {
+    groups
+        .iter()
+        .map(|g| group_object(g).to_string())
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Renders groups as CSV, one row per file (`--format csv`).
+///
+/// `group` numbers the groups from 1 in output order.
+pub fn render_dupes_csv(groups: &[DuplicateGroup]) -> String {
+    let mut rows = vec![CSV_HEADER.to_string()];
+    for (number, group) in groups.iter().enumerate() {
+        for path in &group.paths {
+            rows.push(format!(
+                "{},{},{},{},{}",
+                number + 1,
+                csv_field(&path.display().to_string()),
+                group.size,
+                group.paths.len(),
+                group.reclaimable()
+            ));
+        }
+    }
+    rows.join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn options() -> TraversalOptions {
+        TraversalOptions::default()
+    }
+
+    fn names(group: &DuplicateGroup) -> Vec<String> {
+        group
+            .paths
+            .iter()
+            .map(|p| p.file_name().unwrap().to_string_lossy().into_owned())
+            .collect()
+    }
+
+    #[test]
+    fn test_finds_groups_by_content() {
+        let tmp = TempDir::new().unwrap();
+        let root = tmp.path();
+        fs::create_dir(root.join("sub")).unwrap();
+        fs::write(root.join("a"), "same").unwrap();
+        fs::write(root.join("sub/b"), "same").unwrap();
+        fs::write(root.join("c"), "diff").unwrap();
+        fs::write(root.join("d"), "other content").unwrap();
+
+        let report = find_duplicates(&[root], &options(), 1).unwrap();
+        assert_eq!(report.groups.len(), 1);
+        assert_eq!(names(&report.groups[0]), vec!["a", "b"]);
+        assert_eq!(report.groups[0].size, 4);
+        assert!(report.skipped.is_empty());
+    }
+
+    #[test]
+    fn test_large_files_differing_after_partial_hash() {
+        let tmp = TempDir::new().unwrap();
+        let root = tmp.path();
+        let mut content = vec![b'x'; 10_000];
+        fs::write(root.join("a"), &content).unwrap();
+        fs::write(root.join("b"), &content).unwrap();
+        content[9_999] = b'y';
+        fs::write(root.join("c"), &content).unwrap();
+
+        let report = find_duplicates(&[root], &options(), 1).unwrap();
+        assert_eq!(report.groups.len(), 1);
+        assert_eq!(names(&report.groups[0]), vec!["a", "b"]);
+        assert_eq!(report.reclaimable(), 10_000);
+    }
+
+    #[test]
+    fn test_sorted_by_reclaimable_bytes() {
+        let tmp = TempDir::new().unwrap();
+        let root = tmp.path();
+        for name in ["s1", "s2", "s3"] {
+            fs::write(root.join(name), "ab").unwrap();
+        }
+        for name in ["l1", "l2"] {
+            fs::write(root.join(name), "abcdefgh").unwrap();
+        }
+
+        let report = find_duplicates(&[root], &options(), 1).unwrap();
+        let summary: Vec<(u64, usize)> = report
+            .groups
+            .iter()
+            .map(|g| (g.reclaimable(), g.paths.len()))
+            .collect();
+        assert_eq!(summary, vec![(8, 2), (4, 3)]);
+    }
+
+    #[test]
+    fn test_min_size_skips_small_and_empty_files() {
+        let tmp = TempDir::new().unwrap();
+        let root = tmp.path();
+        fs::write(root.join("e1"), "").unwrap();
+        fs::write(root.join("e2"), "").unwrap();
+        fs::write(root.join("a"), "12345").unwrap();
+        fs::write(root.join("b"), "12345").unwrap();
+
+        let report = find_duplicates(&[root], &options(), 1).unwrap();
+        assert_eq!(report.groups.len(), 1);
+        let report = find_duplicates(&[root], &options(), 6).unwrap();
+        assert!(report.groups.is_empty());
+    }
+
+    #[test]
+    fn test_overlapping_paths_are_not_duplicates() {
+        let tmp = TempDir::new().unwrap();
+        let root = tmp.path();
+        fs::create_dir(root.join("sub")).unwrap();
+        fs::write(root.join("sub/only"), "unique").unwrap();
+
+        let report =
+            find_duplicates(&[root.to_path_buf(), root.join("sub")], &options(), 1).unwrap();
+        assert!(report.groups.is_empty());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_hard_links_are_not_duplicates() {
+        let tmp = TempDir::new().unwrap();
+        let root = tmp.path();
+        fs::write(root.join("a"), "linked").unwrap();
+        fs::hard_link(root.join("a"), root.join("b")).unwrap();
+
+        let report = find_duplicates(&[root], &options(), 1).unwrap();
+        assert!(report.groups.is_empty());
+
+        // A real copy is reported once, next to the first link only
+        fs::write(root.join("c"), "linked").unwrap();
+        let report = find_duplicates(&[root], &options(), 1).unwrap();
+        assert_eq!(report.groups.len(), 1);
+        assert_eq!(names(&report.groups[0]), vec!["a", "c"]);
+    }
+
+    #[test]
+    fn test_render_dupes_formats() {
+        let groups = vec![
+            DuplicateGroup {
+                size: 100,
+                paths: vec![
+                    PathBuf::from("/a"),
+                    PathBuf::from("/b"),
+                    PathBuf::from("/c"),
+                ],
+            },
+            DuplicateGroup {
+                size: 50,
+                paths: vec![PathBuf::from("/d"), PathBuf::from("/e,f")],
+            },
+        ];
+
+        assert_eq!(
+            render_dupes(&groups, false),
+            "200\t3 copies of 100\n\t/a\n\t/b\n\t/c\n\n\
+             50\t2 copies of 50\n\t/d\n\t/e,f\n\n\
+             250\treclaimable in 2 groups"
+        );
+        assert_eq!(render_dupes(&[], false), "0\treclaimable in 0 groups");
+
+        let csv = render_dupes_csv(&groups);
+        let rows: Vec<&str> = csv.lines().collect();
+        assert_eq!(rows[0], CSV_HEADER);
+        assert_eq!(rows[1], "1,/a,100,3,200");
+        assert_eq!(rows[5], "2,\"/e,f\",50,2,50");
+
+        let parsed: Value = serde_json::from_str(&render_dupes_json(&groups)).unwrap();
+        assert_eq!(parsed[0]["reclaimable"], 200);
+        assert_eq!(parsed[1]["paths"][1], "/e,f");
+        assert_eq!(render_dupes_ndjson(&groups).lines().count(), 2);
+    }
+}
@@ -4,6 +4,9 @@
 //! - [`DiskEntry`]: A node in the filesystem tree
 //! - [`EntryType`]: File, directory, symlink, or other
 //! - [`SortOrder`]: How to sort entries in the tree
+//! - [`SizeMode`]: Which size (apparent or allocated) to aggregate
+//! - [`Owner`]: Numeric user and group owning an entry
+//! - [`Timestamps`]: Modification, access and change times of an entry
 //!
 //! # Tree Structure
 //!
@@ -52,6 +55,10 @@ use std::path::PathBuf;
 /// - `Directory` - Directory (can have children)
 /// - `Symlink` - Symbolic link
 /// - `Other` - Special files (devices, pipes, sockets, etc.)
+/// - `MountPoint` - Directory on another filesystem, not descended into
+///   (see `--one-file-system`)
+/// - `Aggregate` - Synthetic entry standing in for several entries rolled
+///   up by `--top` (see [`crate::prune`])
 ///
 /// # Examples
 ///
@@ -69,6 +76,53 @@ pub enum EntryType {
     Directory,
     Symlink,
     Other,
+    MountPoint,
+    Aggregate,
+}
+
+impl EntryType {
+    /// Returns a stable lowercase name, used by machine-readable output.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use disk_usage_clone::entry::EntryType;
+    ///
+    /// assert_eq!(EntryType::Directory.as_str(), "directory");
+    /// assert_eq!(EntryType::MountPoint.as_str(), "mount_point");
+    /// ```
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            EntryType::File => "file",
+            EntryType::Directory => "directory",
+            EntryType::Symlink => "symlink",
+            EntryType::Other => "other",
+            EntryType::MountPoint => "mount_point",
+            EntryType::Aggregate => "aggregate",
+        }
+    }
+
+    /// Parses a name produced by [`EntryType::as_str`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use disk_usage_clone::entry::EntryType;
+    ///
+    /// assert_eq!(EntryType::parse("symlink"), Some(EntryType::Symlink));
+    /// assert_eq!(EntryType::parse("socket"), None);
+    /// ```
+    pub fn parse(s: &str) -> Option<EntryType> {
+        match s {
+            "file" => Some(EntryType::File),
+            "directory" => Some(EntryType::Directory),
+            "symlink" => Some(EntryType::Symlink),
+            "other" => Some(EntryType::Other),
+            "mount_point" => Some(EntryType::MountPoint),
+            "aggregate" => Some(EntryType::Aggregate),
+            _ => None,
+        }
+    }
 }
 
 /// Sort order for directory entries.
@@ -136,6 +190,129 @@ impl SortOrder {
     }
 }
 
+/// Which size of an entry to report and aggregate.
+///
+/// Files have two sizes:
+/// - **Apparent size**: the length of the file's contents (`ls -l`)
+/// - **Allocated size**: the disk space actually reserved for it (`st_blocks`)
+///
+/// They differ for sparse files (allocated < apparent) and for small files on
+/// block-based filesystems (a 10-byte file still occupies a 4K block).
+/// Like `du`, allocated size is the default.
+///
+/// # Variants
+///
+/// - `Allocated` - Disk blocks in use (default, matches `du` and `df`)
+/// - `Apparent` - Byte length of contents (`du --apparent-size`)
+///
+/// # Examples
+///
+/// ```
+/// use disk_usage_clone::entry::SizeMode;
+///
+/// assert_eq!(SizeMode::default(), SizeMode::Allocated);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SizeMode {
+    #[default]
+    Allocated,
+    Apparent,
+}
+
+/// Numeric user and group owning an entry (`st_uid`, `st_gid`).
+///
+/// Names are resolved only when reporting (see [`crate::owner`]).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Owner {
+    pub uid: u32,
+    pub gid: u32,
+}
+
+/// Which timestamp of an entry to show or compare (`--time=WORD`).
+///
+/// # Variants
+///
+/// - `Modified` - Last content change (`mtime`, default)
+/// - `Accessed` - Last read (`atime`); often coarse with `relatime` mounts
+/// - `Changed` - Last inode change (`ctime`): content, owner, mode or links
+///
+/// # Examples
+///
+/// ```
+/// use disk_usage_clone::entry::TimeKind;
+///
+/// assert_eq!(TimeKind::parse("mtime"), Some(TimeKind::Modified));
+/// assert_eq!(TimeKind::parse("atime"), Some(TimeKind::Accessed));
+/// assert_eq!(TimeKind::parse("ctime"), Some(TimeKind::Changed));
+/// assert_eq!(TimeKind::parse("btime"), None);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum TimeKind {
+    #[default]
+    Modified,
+    Accessed,
+    Changed,
+}
+
+impl TimeKind {
+    /// Accepted CLI spellings, in the order of the variants.
+    pub const NAMES: [&'static str; 3] = ["mtime", "atime", "ctime"];
+
+    /// Parses a CLI spelling (`mtime`, `atime`, `ctime`).
+    pub fn parse(s: &str) -> Option<TimeKind> {
+        match s {
+            "mtime" => Some(TimeKind::Modified),
+            "atime" => Some(TimeKind::Accessed),
+            "ctime" => Some(TimeKind::Changed),
+            _ => None,
+        }
+    }
+}
+
+/// Timestamps of an entry, in seconds since the Unix epoch.
+///
+/// Traversal fills them from `st_mtime`, `st_atime` and `st_ctime`; times
+/// before 1970 are negative.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Timestamps {
+    pub modified: i64,
+    pub accessed: i64,
+    pub changed: i64,
+}
+
+impl Timestamps {
+    /// Returns the timestamp of the given kind.
+    pub fn get(&self, kind: TimeKind) -> i64 {
+        match kind {
+            TimeKind::Modified => self.modified,
+            TimeKind::Accessed => self.accessed,
+            TimeKind::Changed => self.changed,
+        }
+    }
+
+    /// Returns the later of each timestamp of `self` and `other`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use disk_usage_clone::entry::Timestamps;
+    ///
+    /// let a = Timestamps { modified: 10, accessed: 50, changed: 10 };
+    /// let b = Timestamps { modified: 20, accessed: 30, changed: 5 };
+    /// assert_eq!(
+    ///     a.latest(b),
+    ///     Timestamps { modified: 20, accessed: 50, changed: 10 }
+    /// );
+    /// ```
+    pub fn latest(self, other: Timestamps) -> Timestamps {
+        Timestamps {
+            modified: self.modified.max(other.modified),
+            accessed: self.accessed.max(other.accessed),
+            changed: self.changed.max(other.changed),
+        }
+    }
+}
+
 /// A node in the disk usage tree.
 ///
 /// Represents a single filesystem entry (file or directory) with its size,
@@ -144,9 +321,17 @@ impl SortOrder {
 /// # Fields
 ///
 /// - `path`: Full path to this entry
-/// - `size_bytes`: Size in bytes (just this entry, not including children)
+/// - `size_bytes`: Apparent size in bytes (just this entry, not including children)
+/// - `allocated_bytes`: Allocated disk space in bytes (just this entry)
 /// - `entry_type`: File, directory, symlink, or other
 /// - `depth`: Depth in the tree (0 = root)
+/// - `owner`: User and group ids, if known (Unix traversals only)
+/// - `times`: Timestamps of this entry itself, if known (Unix traversals only)
+/// - `link_target`: Where a symlink points, for links that weren't followed
+/// - `incomplete`: The scan was stopped before everything below this entry
+///   was read (see [`crate::cancel`]); totals are too low
+/// - `folded_children`: Number of direct children folded into this entry by
+///   [`DiskEntry::collapse_to_depth`] (0 if it wasn't collapsed)
 /// - `children`: Child entries (empty for files)
 ///
 /// # Tree Structure
@@ -206,8 +391,14 @@ impl SortOrder {
 pub struct DiskEntry {
     pub path: PathBuf,
     pub size_bytes: u64,
+    pub allocated_bytes: u64,
     pub entry_type: EntryType,
     pub depth: usize,
+    pub owner: Option<Owner>,
+    pub times: Option<Timestamps>,
+    pub link_target: Option<PathBuf>,
+    pub incomplete: bool,
+    pub folded_children: usize,
     pub children: Vec<DiskEntry>,
 }
 
@@ -223,7 +414,10 @@ impl DiskEntry {
     ///
     /// # Returns
     ///
-    /// A new `DiskEntry` with no children.
+    /// A new `DiskEntry` with no children, owner, timestamps or link
+    /// target, not flagged incomplete. The allocated size starts out equal to `size_bytes`;
+    /// traversal overwrites it with the real block usage and records the
+    /// owner and timestamps.
     ///
     /// # Examples
     ///
@@ -246,13 +440,43 @@ impl DiskEntry {
         DiskEntry {
             path,
             size_bytes,
+            allocated_bytes: size_bytes,
             entry_type,
             depth,
+            owner: None,
+            times: None,
+            link_target: None,
+            incomplete: false,
+            folded_children: 0,
             children: Vec::new(),
         }
     }
 
-    /// Calculates the total size including all descendants.
+    /// Returns this entry's own size (excluding children) in the given mode.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use disk_usage_clone::entry::{DiskEntry, EntryType, SizeMode};
+    /// use std::path::PathBuf;
+    ///
+    /// let mut file = DiskEntry::new(PathBuf::from("small.txt"), 10, EntryType::File, 0);
+    /// file.allocated_bytes = 4096;
+    ///
+    /// assert_eq!(file.size(SizeMode::Apparent), 10);
+    /// assert_eq!(file.size(SizeMode::Allocated), 4096);
+    /// ```
+    pub fn size(&self, mode: SizeMode) -> u64 {
+        match mode {
+            SizeMode::Apparent => self.size_bytes,
+            SizeMode::Allocated => self.allocated_bytes,
+        }
+    }
+
+    /// Calculates the total apparent size including all descendants.
+    ///
+    /// Shorthand for [`total_size_for`](Self::total_size_for) with
+    /// [`SizeMode::Apparent`].
     ///
     /// For files, returns `size_bytes`. For directories, recursively
     /// sums the directory's own size plus all children's total sizes.
@@ -304,19 +528,76 @@ impl DiskEntry {
     /// multiple times during rendering. Consider caching if performance
     /// becomes an issue.
     pub fn total_size(&self) -> u64 {
+        self.total_size_for(SizeMode::Apparent)
+    }
+
+    /// Calculates the total size in the given mode, including all descendants.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use disk_usage_clone::entry::{DiskEntry, EntryType, SizeMode};
+    /// use std::path::PathBuf;
+    ///
+    /// let mut dir = DiskEntry::new(PathBuf::from("dir"), 4096, EntryType::Directory, 0);
+    /// let mut file = DiskEntry::new(PathBuf::from("dir/a.txt"), 10, EntryType::File, 1);
+    /// file.allocated_bytes = 4096;
+    /// dir.children.push(file);
+    ///
+    /// assert_eq!(dir.total_size_for(SizeMode::Apparent), 4096 + 10);
+    /// assert_eq!(dir.total_size_for(SizeMode::Allocated), 4096 + 4096);
+    /// ```
+    pub fn total_size_for(&self, mode: SizeMode) -> u64 {
         if self.children.is_empty() {
             // Leaf node: just return own size
-            self.size_bytes
+            self.size(mode)
         } else {
             // Directory: own size + sum of all children's total sizes
-            self.size_bytes + self.children.iter().map(|c| c.total_size()).sum::<u64>()
+            self.size(mode)
+                + self
+                    .children
+                    .iter()
+                    .map(|c| c.total_size_for(mode))
+                    .sum::<u64>()
         }
     }
 
+    /// Returns the latest timestamps anywhere in this subtree.
+    ///
+    /// Like `du --time`, a directory is as recent as the most recently
+    /// touched entry below it. Each kind is maximized separately; entries
+    /// without timestamps are skipped, and `None` means none had any.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use disk_usage_clone::entry::{DiskEntry, EntryType, Timestamps};
+    /// use std::path::PathBuf;
+    ///
+    /// let mut dir = DiskEntry::new(PathBuf::from("/d"), 0, EntryType::Directory, 0);
+    /// dir.times = Some(Timestamps { modified: 100, accessed: 100, changed: 100 });
+    /// let mut file = DiskEntry::new(PathBuf::from("/d/f"), 1, EntryType::File, 1);
+    /// file.times = Some(Timestamps { modified: 300, accessed: 50, changed: 300 });
+    /// dir.children.push(file);
+    ///
+    /// let latest = dir.latest_times().unwrap();
+    /// assert_eq!(latest.modified, 300);
+    /// assert_eq!(latest.accessed, 100);
+    /// ```
+    pub fn latest_times(&self) -> Option<Timestamps> {
+        self.children
+            .iter()
+            .filter_map(DiskEntry::latest_times)
+            .fold(self.times, |acc, times| {
+                Some(acc.map_or(times, |acc| acc.latest(times)))
+            })
+    }
+
     /// Collapses the tree to a maximum depth.
     ///
     /// Entries at `max_depth` have their descendants "collapsed" into them:
-    /// - All child sizes are summed into `size_bytes`
+    /// - All child sizes are summed into `size_bytes` and `allocated_bytes`
+    /// - `times` becomes the latest timestamps of the subtree
     /// - The `children` vector is cleared
     ///
     /// This is useful for implementing the `--max-depth` flag, which limits
@@ -385,9 +666,13 @@ impl DiskEntry {
     fn collapse_recursive(&mut self, current_depth: usize, max_depth: usize) {
         if current_depth >= max_depth {
             // We're at or past max depth: collapse all descendants
-            // Calculate total size including all descendants
-            self.size_bytes = self.total_size();
+            // Calculate total sizes including all descendants
+            self.size_bytes = self.total_size_for(SizeMode::Apparent);
+            self.allocated_bytes = self.total_size_for(SizeMode::Allocated);
+            // Keep the latest times of the descendants, as `du --time` shows
+            self.times = self.latest_times();
             // Remove all children (they're now part of size_bytes)
+            self.folded_children = self.child_count();
             self.children.clear();
         } else {
             // Still within max depth: recurse into children
@@ -397,10 +682,42 @@ impl DiskEntry {
         }
     }
 
+    /// Returns the number of direct children, counting those folded in by
+    /// [`DiskEntry::collapse_to_depth`].
+    ///
+    /// An [`EntryType::Aggregate`] child counts as one, however many
+    /// entries it stands for.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use disk_usage_clone::entry::{DiskEntry, EntryType};
+    /// use std::path::PathBuf;
+    ///
+    /// let mut dir = DiskEntry::new(PathBuf::from("/data"), 4096, EntryType::Directory, 0);
+    /// for name in ["a", "b"] {
+    ///     let path = PathBuf::from(format!("/data/{name}"));
+    ///     dir.children.push(DiskEntry::new(path, 10, EntryType::File, 1));
+    /// }
+    ///
+    /// dir.collapse_to_depth(0);
+    /// assert!(dir.children.is_empty());
+    /// assert_eq!(dir.child_count(), 2);
+    /// ```
+    pub fn child_count(&self) -> usize {
+        if self.children.is_empty() {
+            self.folded_children
+        } else {
+            self.children.len()
+        }
+    }
+
     /// Sorts children according to the specified order.
     ///
     /// Sorting is applied recursively to the entire tree.
     /// Each directory's children are sorted independently.
+    /// Size orders compare apparent sizes; use
+    /// [`sort_entries_for`](Self::sort_entries_for) to pick the size mode.
     ///
     /// # Arguments
     ///
@@ -453,9 +770,19 @@ impl DiskEntry {
     /// Uses Rust's `sort_by_key` which is O(n log n). For trees with many
     /// children at each level, this may add noticeable overhead.
     pub fn sort_entries(&mut self, order: &SortOrder) {
+        self.sort_entries_for(order, SizeMode::Apparent);
+    }
+
+    /// Sorts children recursively, comparing sizes in the given mode.
+    ///
+    /// # Arguments
+    ///
+    /// * `order` - How to sort children
+    /// * `mode` - Which size the size-based orders compare
+    pub fn sort_entries_for(&mut self, order: &SortOrder, mode: SizeMode) {
         // First, recursively sort all descendants
         for child in &mut self.children {
-            child.sort_entries(order);
+            child.sort_entries_for(order, mode);
         }
 
         // Then sort this node's children
@@ -465,12 +792,13 @@ impl DiskEntry {
             }
             SortOrder::SizeAscending => {
                 // Smallest first (least common, but useful for finding small files)
-                self.children.sort_by_key(|e| e.total_size());
+                self.children.sort_by_key(|e| e.total_size_for(mode));
             }
             SortOrder::SizeDescending => {
                 // Largest first (most useful for finding large directories)
                 // Uses Reverse to invert the comparison
-                self.children.sort_by_key(|e| Reverse(e.total_size()));
+                self.children
+                    .sort_by_key(|e| Reverse(e.total_size_for(mode)));
             }
             SortOrder::Name => {
                 // Alphabetical by full path
@@ -490,7 +818,34 @@ mod tests {
         assert_eq!(EntryType::Directory, EntryType::Directory);
         assert_eq!(EntryType::Symlink, EntryType::Symlink);
         assert_eq!(EntryType::Other, EntryType::Other);
+        assert_eq!(EntryType::MountPoint, EntryType::MountPoint);
         assert_ne!(EntryType::File, EntryType::Directory);
+        assert_ne!(EntryType::MountPoint, EntryType::Directory);
+    }
+
+    #[test]
+    fn test_entry_type_as_str() {
+        assert_eq!(EntryType::File.as_str(), "file");
+        assert_eq!(EntryType::Directory.as_str(), "directory");
+        assert_eq!(EntryType::Symlink.as_str(), "symlink");
+        assert_eq!(EntryType::Other.as_str(), "other");
+        assert_eq!(EntryType::MountPoint.as_str(), "mount_point");
+        assert_eq!(EntryType::Aggregate.as_str(), "aggregate");
+    }
+
+    #[test]
+    fn test_entry_type_parse_round_trip() {
+        for entry_type in [
+            EntryType::File,
+            EntryType::Directory,
+            EntryType::Symlink,
+            EntryType::Other,
+            EntryType::MountPoint,
+            EntryType::Aggregate,
+        ] {
+            assert_eq!(EntryType::parse(entry_type.as_str()), Some(entry_type));
+        }
+        assert_eq!(EntryType::parse("fifo"), None);
     }
 
     #[test]
@@ -667,6 +1022,11 @@ mod tests {
         root.collapse_to_depth(0);
         assert!(root.children.is_empty());
         assert_eq!(root.size_bytes, total);
+        assert_eq!(root.child_count(), 1);
+
+        // Collapsing again doesn't lose the count
+        root.collapse_to_depth(0);
+        assert_eq!(root.child_count(), 1);
     }
 
     #[test]
@@ -693,6 +1053,55 @@ mod tests {
         assert_eq!(root.total_size(), total_before);
     }
 
+    #[test]
+    fn test_total_size_for_modes() {
+        let mut dir = DiskEntry::new(PathBuf::from("/dir"), 4096, EntryType::Directory, 0);
+        let mut sparse = DiskEntry::new(
+            PathBuf::from("/dir/sparse.img"),
+            1_000_000,
+            EntryType::File,
+            1,
+        );
+        sparse.allocated_bytes = 8192;
+        dir.children.push(sparse);
+        assert_eq!(dir.total_size_for(SizeMode::Apparent), 4096 + 1_000_000);
+        assert_eq!(dir.total_size_for(SizeMode::Allocated), 4096 + 8192);
+        assert_eq!(dir.total_size(), dir.total_size_for(SizeMode::Apparent));
+    }
+
+    #[test]
+    fn test_collapse_to_depth_preserves_allocated_sizes() {
+        let mut root = DiskEntry::new(PathBuf::from("/root"), 100, EntryType::Directory, 0);
+        let mut sub = DiskEntry::new(PathBuf::from("/root/sub"), 200, EntryType::Directory, 1);
+        let mut deep = DiskEntry::new(PathBuf::from("/root/sub/deep"), 300, EntryType::File, 2);
+        deep.allocated_bytes = 4096;
+        sub.children.push(deep);
+        root.children.push(sub);
+
+        let allocated_before = root.total_size_for(SizeMode::Allocated);
+        root.collapse_to_depth(1);
+        assert_eq!(root.children[0].allocated_bytes, 200 + 4096);
+        assert_eq!(root.total_size_for(SizeMode::Allocated), allocated_before);
+    }
+
+    #[test]
+    fn test_sort_entries_for_allocated() {
+        let mut dir = DiskEntry::new(PathBuf::from("/dir"), 0, EntryType::Directory, 0);
+        let mut sparse = DiskEntry::new(PathBuf::from("/dir/sparse"), 9999, EntryType::File, 1);
+        sparse.allocated_bytes = 0;
+        let dense = DiskEntry::new(PathBuf::from("/dir/dense"), 500, EntryType::File, 1);
+        dir.children.push(sparse);
+        dir.children.push(dense);
+
+        dir.sort_entries_for(&SortOrder::SizeDescending, SizeMode::Allocated);
+        let names: Vec<&str> = dir
+            .children
+            .iter()
+            .map(|c| c.path.file_name().unwrap().to_str().unwrap())
+            .collect();
+        assert_eq!(names, vec!["dense", "sparse"]);
+    }
+
     #[test]
     fn test_collapse_to_depth_no_op_when_shallow() {
         let mut root = DiskEntry::new(PathBuf::from("/root"), 100, EntryType::Directory, 0);
@@ -46,6 +46,14 @@ use std::path::PathBuf;
 /// - `PermissionDenied` - Insufficient permissions to access path
 /// - `IoError` - Generic I/O error (disk full, read error, etc.)
 /// - `TraversalError` - Error during directory traversal
+/// - `InvalidPattern` - An exclude pattern could not be compiled
+/// - `InvalidArgument` - Options that can't be combined or used here
+/// - `TerminalError` - Terminal setup or I/O failed in interactive mode
+/// - `RemoveFailed` - A path could not be deleted or moved to the trash
+/// - `InvalidSnapshot` - A snapshot file is damaged or has an unknown version
+/// - `IncompleteScan` - Some paths could not be read, so totals are too low
+/// - `TimedOut` - The scan was stopped by `--timeout`, so totals are too low
+/// - `SymlinkLoop` - A followed symlink leads back to one of its ancestors
 ///
 /// # Error Messages
 ///
@@ -111,10 +119,85 @@ pub enum DuskError {
     /// Error during directory traversal.
     ///
     /// Wraps errors from the `walkdir` crate. Can occur when:
-    /// - Symbolic link loop detected
     /// - Filename encoding issues
     /// - Permission denied on subdirectory
     TraversalError(String),
+
+    /// Invalid exclude pattern.
+    ///
+    /// Returned when an `--exclude` or `--exclude-from` pattern is not
+    /// valid gitignore syntax (e.g. an unclosed `{` group).
+    ///
+    /// # User Action
+    ///
+    /// Fix or quote the pattern.
+    InvalidPattern(String),
+
+    /// Invalid combination of command-line arguments.
+    ///
+    /// Returned for usage errors clap can't detect on its own, such as
+    /// `--interactive` with more than one path.
+    ///
+    /// # User Action
+    ///
+    /// Adjust the command line as the message describes.
+    InvalidArgument(String),
+
+    /// Terminal error in interactive mode.
+    ///
+    /// Returned when raw mode, the alternate screen or reading key events
+    /// fails, or when `--interactive` is used without a terminal.
+    TerminalError(String),
+
+    /// Failed to delete a path or move it to the trash.
+    ///
+    /// Carries the path and the reason. Permission problems are reported
+    /// as `PermissionDenied` instead.
+    RemoveFailed(PathBuf, String),
+
+    /// Snapshot file could not be decoded.
+    ///
+    /// Returned by `dusk diff` for files that are not dusk snapshots, are
+    /// truncated, or were written by a newer, incompatible version.
+    ///
+    /// # User Action
+    ///
+    /// Re-create the snapshot with `dusk --save`.
+    InvalidSnapshot(PathBuf, String),
+
+    /// Some directories or entries could not be read during a scan.
+    ///
+    /// Carries the number of skipped paths. Returned by `run` after the
+    /// output and the individual diagnostics have been printed, so the
+    /// process exits with status 1 like `du` does.
+    ///
+    /// # User Action
+    ///
+    /// Check the printed paths' permissions, or run with more privileges.
+    IncompleteScan(usize),
+
+    /// The scan was stopped by `--timeout` before it was complete.
+    ///
+    /// Carries the time limit in seconds. Returned by `run` after the
+    /// partial output has been printed, with unfinished directories marked
+    /// `[incomplete]`.
+    ///
+    /// # User Action
+    ///
+    /// Raise the limit, or scan a smaller part of the tree.
+    TimedOut(u64),
+
+    /// A followed symlink leads back to a directory being read.
+    ///
+    /// Carries the ancestor directory the link resolves to. Only occurs
+    /// with `--dereference`; the link is reported and left out instead of
+    /// being read again, so the scan still finishes.
+    ///
+    /// # User Action
+    ///
+    /// None needed; the ancestor's contents are already counted. Exclude
+    /// the link to silence the diagnostic.
+    SymlinkLoop(PathBuf),
 }
 
 impl fmt::Display for DuskError {
@@ -150,6 +233,33 @@ impl fmt::Display for DuskError {
             DuskError::TraversalError(msg) => {
                 write!(f, "traversal error: {msg}")
             }
+            DuskError::InvalidPattern(msg) => {
+                write!(f, "invalid pattern: {msg}")
+            }
+            DuskError::InvalidArgument(msg) => {
+                write!(f, "invalid argument: {msg}")
+            }
+            DuskError::TerminalError(msg) => {
+                write!(f, "terminal error: {msg}")
+            }
+            DuskError::RemoveFailed(path, reason) => {
+                write!(f, "cannot remove {}: {reason}", path.display())
+            }
+            DuskError::InvalidSnapshot(path, reason) => {
+                write!(f, "invalid snapshot {}: {reason}", path.display())
+            }
+            DuskError::IncompleteScan(1) => {
+                write!(f, "1 path could not be read; totals are incomplete")
+            }
+            DuskError::IncompleteScan(count) => {
+                write!(f, "{count} paths could not be read; totals are incomplete")
+            }
+            DuskError::TimedOut(secs) => {
+                write!(f, "scan timed out after {secs}s; totals are incomplete")
+            }
+            DuskError::SymlinkLoop(ancestor) => {
+                write!(f, "symlink loop back to {}", ancestor.display())
+            }
         }
     }
 }
@@ -271,7 +381,7 @@ mod tests {
 
     #[test]
     fn test_io_error_display() {
-        let io_err = io::Error::new(io::ErrorKind::Other, "disk full");
+        let io_err = io::Error::other("disk full");
         let err = DuskError::IoError(io_err);
         assert_eq!(err.to_string(), "I/O error: disk full");
     }
@@ -282,6 +392,62 @@ mod tests {
         assert_eq!(err.to_string(), "traversal error: something broke");
     }
 
+    #[test]
+    fn test_invalid_pattern_display() {
+        let err = DuskError::InvalidPattern("{a,b".to_string());
+        assert_eq!(err.to_string(), "invalid pattern: {a,b");
+    }
+
+    #[test]
+    fn test_invalid_argument_display() {
+        let err = DuskError::InvalidArgument("too many paths".to_string());
+        assert_eq!(err.to_string(), "invalid argument: too many paths");
+    }
+
+    #[test]
+    fn test_terminal_error_display() {
+        let err = DuskError::TerminalError("cannot enter raw mode".to_string());
+        assert_eq!(err.to_string(), "terminal error: cannot enter raw mode");
+    }
+
+    #[test]
+    fn test_remove_failed_display() {
+        let err = DuskError::RemoveFailed(PathBuf::from("/mnt/x"), "device busy".to_string());
+        assert_eq!(err.to_string(), "cannot remove /mnt/x: device busy");
+    }
+
+    #[test]
+    fn test_invalid_snapshot_display() {
+        let err = DuskError::InvalidSnapshot(PathBuf::from("old.dusk"), "bad magic".to_string());
+        assert_eq!(err.to_string(), "invalid snapshot old.dusk: bad magic");
+    }
+
+    #[test]
+    fn test_incomplete_scan_display() {
+        assert_eq!(
+            DuskError::IncompleteScan(1).to_string(),
+            "1 path could not be read; totals are incomplete"
+        );
+        assert_eq!(
+            DuskError::IncompleteScan(3).to_string(),
+            "3 paths could not be read; totals are incomplete"
+        );
+    }
+
+    #[test]
+    fn test_timed_out_display() {
+        assert_eq!(
+            DuskError::TimedOut(30).to_string(),
+            "scan timed out after 30s; totals are incomplete"
+        );
+    }
+
+    #[test]
+    fn test_symlink_loop_display() {
+        let err = DuskError::SymlinkLoop(PathBuf::from("/data"));
+        assert_eq!(err.to_string(), "symlink loop back to /data");
+    }
+
     #[test]
     fn test_from_io_error() {
         let io_err = io::Error::new(io::ErrorKind::NotFound, "not found");
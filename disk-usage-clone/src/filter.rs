@@ -0,0 +1,292 @@
+//! Path exclusion filters applied during traversal.
+//!
+//! This module decides which paths the traversal skips. Excluded directories
+//! are pruned as a whole: their contents are never read, stat'ed or counted.
+//!
+//! # Pattern Syntax
+//!
+//! Patterns use `.gitignore` syntax (via the `ignore` crate):
+//! - `*.o` - Matches a name at any depth
+//! - `build/` - Trailing slash matches directories only
+//! - `/target` - Leading slash anchors the pattern at the scanned root
+//! - `docs/**/*.pdf` - `**` matches any number of directories
+//! - `!keep.o` - Negation re-includes a previously excluded path
+//!
+//! # Sources
+//!
+//! - `--exclude GLOB` patterns (repeatable)
+//! - `--exclude-from FILE` files, one pattern per line (`#` starts a comment)
+//! - With `--respect-gitignore`, every `.gitignore` file found in the scanned
+//!   tree, applied to its own directory like `git` does
+//!
+//! # Examples
+//!
+//! ```
+//! use disk_usage_clone::filter::PathFilter;
+//! use disk_usage_clone::traversal::TraversalOptions;
+//! use std::path::Path;
+//!
+//! let options = TraversalOptions {
+//!     exclude: vec!["node_modules/".to_string(), "*.log".to_string()],
+//!     ..TraversalOptions::default()
+//! };
+//! let filter = PathFilter::new(Path::new("/project"), &options).unwrap();
+//!
+//! assert!(filter.is_excluded(Path::new("/project/web/node_modules"), true));
+//! assert!(!filter.is_excluded(Path::new("/project/src/main.rs"), false));
+//! ```
+
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex, PoisonError};
+
+use ignore::gitignore::{Gitignore, GitignoreBuilder};
+
+use crate::error::DuskError;
+use crate::traversal::TraversalOptions;
+
+/// Name of the per-directory ignore files honored by `--respect-gitignore`.
+const GITIGNORE_FILE: &str = ".gitignore";
+
+/// Decides which paths under a root are excluded from traversal.
+///
+/// Built once per scanned root. `is_excluded` takes `&self` and the
+/// `.gitignore` cache is behind a `Mutex`, so a filter can be shared by
+/// several threads.
+pub struct PathFilter {
+    /// Root of the scan; anchored patterns are relative to it.
+    root: PathBuf,
+    /// Matcher for `--exclude` and `--exclude-from` patterns.
+    excludes: Gitignore,
+    /// Whether `.gitignore` files in the tree are honored.
+    respect_gitignore: bool,
+    /// Parsed `.gitignore` per directory (`None` = directory has none).
+    gitignores: Mutex<HashMap<PathBuf, Option<Arc<Gitignore>>>>,
+}
+
+impl PathFilter {
+    /// Builds a filter for the tree under `root`.
+    ///
+    /// Reads every `options.exclude_from` file and compiles all patterns.
+    ///
+    /// # Arguments
+    ///
+    /// * `root` - Canonicalized root of the scan
+    /// * `options` - Traversal options holding the exclusion settings
+    ///
+    /// # Errors
+    ///
+    /// - `PathNotFound` if an `--exclude-from` file doesn't exist
+    /// - `IoError` if an `--exclude-from` file can't be read
+    /// - `InvalidPattern` if a pattern doesn't compile
+    pub fn new(root: &Path, options: &TraversalOptions) -> Result<Self, DuskError> {
+        let mut builder = GitignoreBuilder::new(root);
+
+        for pattern in &options.exclude {
+            builder
+                .add_line(None, pattern)
+                .map_err(|e| DuskError::InvalidPattern(e.to_string()))?;
+        }
+
+        for file in &options.exclude_from {
+            let contents = fs::read_to_string(file).map_err(|e| match e.kind() {
+                std::io::ErrorKind::NotFound => DuskError::PathNotFound(file.clone()),
+                _ => DuskError::IoError(e),
+            })?;
+            for line in contents.lines() {
+                // Blank lines and `#` comments are skipped by the builder
+                builder
+                    .add_line(Some(file.clone()), line)
+                    .map_err(|e| DuskError::InvalidPattern(e.to_string()))?;
+            }
+        }
+
+        let excludes = builder
+            .build()
+            .map_err(|e| DuskError::InvalidPattern(e.to_string()))?;
+
+        Ok(PathFilter {
+            root: root.to_path_buf(),
+            excludes,
+            respect_gitignore: options.respect_gitignore,
+            gitignores: Mutex::new(HashMap::new()),
+        })
+    }
+
+    /// Returns true if the filter excludes nothing, so callers can skip it.
+    pub fn is_empty(&self) -> bool {
+        self.excludes.is_empty() && !self.respect_gitignore
+    }
+
+    /// Checks whether a path should be skipped.
+    ///
+    /// Only the path itself is tested, not its parents: the traversal never
+    /// reaches entries below an excluded directory in the first place.
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - Absolute path below the root
+    /// * `is_dir` - Whether the path is a directory (for `dir/` patterns)
+    ///
+    /// # Precedence
+    ///
+    /// `--exclude` patterns are checked first and always win. Then
+    /// `.gitignore` files are consulted from the deepest directory upwards;
+    /// the first one with a matching rule (ignore or `!` whitelist) decides.
+    pub fn is_excluded(&self, path: &Path, is_dir: bool) -> bool {
+        // The root itself is never excluded
+        if path == self.root {
+            return false;
+        }
+
+        if self.excludes.matched(path, is_dir).is_ignore() {
+            return true;
+        }
+
+        if self.respect_gitignore {
+            for dir in path.ancestors().skip(1) {
+                if let Some(gitignore) = self.gitignore_for(dir) {
+                    let matched = gitignore.matched(path, is_dir);
+                    if matched.is_ignore() {
+                        return true;
+                    }
+                    if matched.is_whitelist() {
+                        return false;
+                    }
+                }
+                if dir == self.root {
+                    break;
+                }
+            }
+        }
+
+        false
+    }
+
+    /// Returns the parsed `.gitignore` of a directory, loading it on first use.
+    ///
+    /// Unreadable or malformed files are treated as absent: a broken
+    /// `.gitignore` should not abort a disk usage scan.
+    fn gitignore_for(&self, dir: &Path) -> Option<Arc<Gitignore>> {
+        let mut cache = self
+            .gitignores
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner);
+
+        cache
+            .entry(dir.to_path_buf())
+            .or_insert_with(|| {
+                let file = dir.join(GITIGNORE_FILE);
+                if !file.is_file() {
+                    return None;
+                }
+                let (gitignore, _errors) = Gitignore::new(&file);
+                (!gitignore.is_empty()).then(|| Arc::new(gitignore))
+            })
+            .clone()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn filter_with(root: &Path, exclude: &[&str], respect_gitignore: bool) -> PathFilter {
+        let options = TraversalOptions {
+            exclude: exclude.iter().map(|p| p.to_string()).collect(),
+            respect_gitignore,
+            ..TraversalOptions::default()
+        };
+        PathFilter::new(root, &options).unwrap()
+    }
+
+    #[test]
+    fn test_empty_filter_excludes_nothing() {
+        let filter = filter_with(Path::new("/project"), &[], false);
+        assert!(filter.is_empty());
+        assert!(!filter.is_excluded(Path::new("/project/a.txt"), false));
+    }
+
+    #[test]
+    fn test_exclude_glob_matches_any_depth() {
+        let filter = filter_with(Path::new("/project"), &["*.log"], false);
+        assert!(filter.is_excluded(Path::new("/project/app.log"), false));
+        assert!(filter.is_excluded(Path::new("/project/var/deep/app.log"), false));
+        assert!(!filter.is_excluded(Path::new("/project/app.txt"), false));
+    }
+
+    #[test]
+    fn test_exclude_directory_only_pattern() {
+        let filter = filter_with(Path::new("/project"), &["build/"], false);
+        assert!(filter.is_excluded(Path::new("/project/build"), true));
+        assert!(!filter.is_excluded(Path::new("/project/build"), false));
+    }
+
+    #[test]
+    fn test_exclude_anchored_pattern() {
+        let filter = filter_with(Path::new("/project"), &["/target"], false);
+        assert!(filter.is_excluded(Path::new("/project/target"), true));
+        assert!(!filter.is_excluded(Path::new("/project/sub/target"), true));
+    }
+
+    #[test]
+    fn test_root_never_excluded() {
+        let filter = filter_with(Path::new("/project"), &["*"], false);
+        assert!(!filter.is_excluded(Path::new("/project"), true));
+    }
+
+    #[test]
+    fn test_exclude_from_file() {
+        let tmp = TempDir::new().unwrap();
+        let list = tmp.path().join("excludes.txt");
+        fs::write(&list, "# caches\n\n*.tmp\ncache/\n").unwrap();
+
+        let options = TraversalOptions {
+            exclude_from: vec![list],
+            ..TraversalOptions::default()
+        };
+        let filter = PathFilter::new(Path::new("/project"), &options).unwrap();
+        assert!(filter.is_excluded(Path::new("/project/x.tmp"), false));
+        assert!(filter.is_excluded(Path::new("/project/a/cache"), true));
+        assert!(!filter.is_excluded(Path::new("/project/caches.txt"), false));
+    }
+
+    #[test]
+    fn test_exclude_from_missing_file() {
+        let options = TraversalOptions {
+            exclude_from: vec![PathBuf::from("/nonexistent/excludes.txt")],
+            ..TraversalOptions::default()
+        };
+        let result = PathFilter::new(Path::new("/project"), &options);
+        assert!(matches!(result, Err(DuskError::PathNotFound(_))));
+    }
+
+    #[test]
+    fn test_respect_gitignore_nested_files() {
+        let tmp = TempDir::new().unwrap();
+        let root = tmp.path();
+        fs::write(root.join(".gitignore"), "*.o\n").unwrap();
+        fs::create_dir(root.join("sub")).unwrap();
+        fs::write(root.join("sub/.gitignore"), "!keep.o\ngenerated/\n").unwrap();
+
+        let filter = filter_with(root, &[], true);
+        assert!(filter.is_excluded(&root.join("main.o"), false));
+        assert!(filter.is_excluded(&root.join("sub/other.o"), false));
+        // Deeper .gitignore whitelists it again
+        assert!(!filter.is_excluded(&root.join("sub/keep.o"), false));
+        assert!(filter.is_excluded(&root.join("sub/generated"), true));
+        // Rules from sub/.gitignore don't apply to siblings
+        assert!(!filter.is_excluded(&root.join("generated"), true));
+    }
+
+    #[test]
+    fn test_gitignore_ignored_without_flag() {
+        let tmp = TempDir::new().unwrap();
+        fs::write(tmp.path().join(".gitignore"), "*.o\n").unwrap();
+
+        let filter = filter_with(tmp.path(), &[], false);
+        assert!(!filter.is_excluded(&tmp.path().join("main.o"), false));
+    }
+}
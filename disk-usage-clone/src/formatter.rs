@@ -1,7 +1,9 @@
-//! Size formatting utilities.
+//! Size and time formatting utilities.
 //!
 //! This module provides functions for formatting file sizes in both
-//! human-readable (K, M, G, T) and raw byte formats.
+//! human-readable (K, M, G, T) and raw byte formats, for parsing sizes
+//! given on the command line ([`parse_size`]), and for formatting the
+//! timestamps of the `--time` column ([`format_timestamp`]).
 //!
 //! # Human-Readable Formats
 //!
@@ -121,6 +123,50 @@ pub fn format_size(bytes: u64, human_readable: bool) -> String {
     }
 }
 
+/// Parses a size such as `512`, `10K`, `1.5M` or `1G`.
+///
+/// The inverse of [`format_size`]: suffixes are binary units (K = 1024),
+/// case-insensitive, and may be followed by `B` (`10MB`, `512B`). Fractional
+/// values are rounded down to whole bytes.
+///
+/// # Returns
+///
+/// The size in bytes, or `None` if the string is not a size or overflows.
+///
+/// # Examples
+///
+/// ```
+/// use disk_usage_clone::formatter::parse_size;
+///
+/// assert_eq!(parse_size("512"), Some(512));
+/// assert_eq!(parse_size("10K"), Some(10 * 1024));
+/// assert_eq!(parse_size("1.5m"), Some(1_572_864));
+/// assert_eq!(parse_size("1GB"), Some(1_073_741_824));
+/// assert_eq!(parse_size("ten"), None);
+/// ```
+pub fn parse_size(s: &str) -> Option<u64> {
+    let upper = s.trim().to_ascii_uppercase();
+    let digits = upper.strip_suffix('B').unwrap_or(&upper);
+
+    let (number, unit) = match digits.char_indices().last() {
+        Some((i, 'K')) => (&digits[..i], KB),
+        Some((i, 'M')) => (&digits[..i], MB),
+        Some((i, 'G')) => (&digits[..i], GB),
+        Some((i, 'T')) => (&digits[..i], TB),
+        _ => (digits, 1),
+    };
+
+    // Plain integers are parsed exactly; only fractions go through f64
+    if let Ok(value) = number.parse::<u64>() {
+        return value.checked_mul(unit);
+    }
+    if !number.chars().all(|c| c.is_ascii_digit() || c == '.') {
+        return None;
+    }
+    let value = number.parse::<f64>().ok()? * unit as f64;
+    (value < u64::MAX as f64).then_some(value as u64)
+}
+
 /// Formats a size with padding for alignment.
 ///
 /// Useful for creating columnar output where sizes should align:
@@ -248,6 +294,31 @@ pub fn format_entry_line(
     format!("{size_str}\t{indent}{path_str}")
 }
 
+/// Formats a timestamp (seconds since the Unix epoch) in local time.
+///
+/// Uses `du --time`'s default style, `YYYY-MM-DD HH:MM`. Timestamps outside
+/// the representable range fall back to the raw number of seconds.
+///
+/// # Examples
+///
+/// ```
+/// use disk_usage_clone::formatter::format_timestamp;
+///
+/// // The exact text depends on the local time zone
+/// let text = format_timestamp(1_700_000_000);
+/// assert_eq!(text.len(), "2023-11-14 22:13".len());
+/// assert!(text.starts_with("2023-11-1"));
+/// ```
+pub fn format_timestamp(secs: i64) -> String {
+    match chrono::DateTime::from_timestamp(secs, 0) {
+        Some(utc) => utc
+            .with_timezone(&chrono::Local)
+            .format("%Y-%m-%d %H:%M")
+            .to_string(),
+        None => secs.to_string(),
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -302,6 +373,34 @@ mod tests {
         assert_eq!(result, "     12345");
     }
 
+    #[test]
+    fn test_parse_size_units() {
+        assert_eq!(parse_size("0"), Some(0));
+        assert_eq!(parse_size("512B"), Some(512));
+        assert_eq!(parse_size("2k"), Some(2 * KB));
+        assert_eq!(parse_size("10M"), Some(10 * MB));
+        assert_eq!(parse_size("1G"), Some(GB));
+        assert_eq!(parse_size("3TB"), Some(3 * TB));
+        assert_eq!(parse_size("0.5K"), Some(512));
+    }
+
+    #[test]
+    fn test_parse_size_rejects_invalid() {
+        assert_eq!(parse_size(""), None);
+        assert_eq!(parse_size("K"), None);
+        assert_eq!(parse_size("-5"), None);
+        assert_eq!(parse_size("1.5X"), None);
+        assert_eq!(parse_size("1e3"), None);
+        assert_eq!(parse_size("99999999T"), None);
+    }
+
+    #[test]
+    fn test_parse_size_round_trips_format_size() {
+        for bytes in [0, 1, 512, KB, 5 * MB, 2 * GB] {
+            assert_eq!(parse_size(&format_size(bytes, true)), Some(bytes));
+        }
+    }
+
     #[test]
     fn test_format_entry_line_no_indent() {
         let line = format_entry_line("1.0K", "./src", 0, 2);
@@ -319,4 +418,21 @@ mod tests {
         let line = format_entry_line("1.0M", "data", 1, 4);
         assert_eq!(line, "1.0M\t    data");
     }
+
+    #[test]
+    fn test_format_timestamp_shape() {
+        let text = format_timestamp(0);
+        let bytes = text.as_bytes();
+        assert_eq!(text.len(), 16);
+        assert_eq!(
+            (bytes[4], bytes[7], bytes[10], bytes[13]),
+            (b'-', b'-', b' ', b':')
+        );
+        assert!(text.starts_with("1970-01-01") || text.starts_with("1969-12-31"));
+    }
+
+    #[test]
+    fn test_format_timestamp_out_of_range() {
+        assert_eq!(format_timestamp(i64::MAX), i64::MAX.to_string());
+    }
 }
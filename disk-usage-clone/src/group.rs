@@ -0,0 +1,454 @@
+//! Usage grouped by file extension or file type (`--by-extension`, `--by-type`).
+//!
+//! Instead of a tree, these modes answer "how much of this is `.png`?": every
+//! regular file in the scanned trees is assigned to a group, and each group
+//! reports its file count and total size. Several paths are combined into a
+//! single table.
+//!
+//! # Groups
+//!
+//! - **By extension**: the lowercased extension with its dot (`.png`,
+//!   `.gz` for `a.tar.gz`); files without one go to `(none)`
+//! - **By type**: a built-in category of the extension, see [`CATEGORIES`];
+//!   unknown extensions go to `other`
+//!
+//! Only regular files are counted. Directories, symlinks and special files
+//! have no meaningful extension and are left out, so the table total can be
+//! slightly lower than the tree's.
+//!
+//! # Output
+//!
+//! Groups are rendered in every output format:
+//!
+//! ```text
+//! 1.2G    1834    .png
+//! 300.0M  12      .wasm
+//! 4.0K    1       (none)
+//! ```
+//!
+//! - `text` - `size<TAB>files<TAB>group` lines
+//! - `json` - One array of `{"group", "files", "size", ...}` objects
+//! - `ndjson` - One such object per line
+//! - `csv` - Header row plus one row per group
+//!
+//! # Examples
+//!
+//! ```
+//! use disk_usage_clone::entry::{DiskEntry, EntryType, SizeMode};
+//! use disk_usage_clone::group::{GroupBy, GroupSort, group_files};
+//! use std::path::PathBuf;
+//!
+//! let mut dir = DiskEntry::new(PathBuf::from("/site"), 0, EntryType::Directory, 0);
+//! for (name, size) in [("a.png", 300), ("b.PNG", 200), ("app.wasm", 100)] {
+//!     dir.children.push(DiskEntry::new(
+//!         PathBuf::from(format!("/site/{name}")),
+//!         size,
+//!         EntryType::File,
+//!         1,
+//!     ));
+//! }
+//!
+//! let groups = group_files(&[dir], GroupBy::Extension, SizeMode::Apparent, GroupSort::Size);
+//! assert_eq!(groups[0].name, ".png");
+//! assert_eq!(groups[0].files, 2);
+//! assert_eq!(groups[0].size(SizeMode::Apparent), 500);
+//! assert_eq!(groups[1].name, ".wasm");
+//! ```
+
+use std::cmp::Reverse;
+use std::collections::HashMap;
+use std::path::Path;
+
+use colored::Colorize;
+use serde_json::{Value, json};
+
+use crate::entry::{DiskEntry, EntryType, SizeMode, SortOrder};
+use crate::formatter::format_size;
+use crate::output::{colorize_size, csv_field};
+
+/// Group of files without an extension.
+const NO_EXTENSION: &str = "(none)";
+
+/// Category of extensions that are not in [`CATEGORIES`].
+const OTHER_CATEGORY: &str = "other";
+
+/// Header row of the CSV output, in column order.
+const CSV_HEADER: &str = "group,files,size_bytes,size_human";
+
+/// Built-in file categories used by `--by-type`, with their extensions.
+///
+/// Extensions are lowercase and without the dot. Matching is
+/// case-insensitive, so `photo.JPG` is an image.
+pub const CATEGORIES: &[(&str, &[&str])] = &[
+    (
+        "images",
+        &[
+            "png", "jpg", "jpeg", "gif", "bmp", "tif", "tiff", "webp", "svg", "ico", "heic",
+            "avif", "psd", "raw",
+        ],
+    ),
+    (
+        "video",
+        &[
+            "mp4", "mkv", "avi", "mov", "webm", "wmv", "flv", "m4v", "mpg", "mpeg",
+        ],
+    ),
+    (
+        "archives",
+        &[
+            "zip", "tar", "gz", "tgz", "bz2", "xz", "zst", "7z", "rar", "jar", "deb", "rpm", "iso",
+            "dmg",
+        ],
+    ),
+    (
+        "source",
+        &[
+            "rs", "c", "h", "cc", "cpp", "hpp", "go", "java", "kt", "py", "rb", "php", "js", "mjs",
+            "ts", "jsx", "tsx", "cs", "swift", "sh", "html", "css", "scss", "json", "toml", "yaml",
+            "yml",
+        ],
+    ),
+    (
+        "binaries",
+        &[
+            "exe", "dll", "so", "dylib", "a", "o", "lib", "bin", "wasm", "class", "pyc",
+        ],
+    ),
+];
+
+/// How files are grouped.
+///
+/// # Variants
+///
+/// - `Extension` - By file extension (`--by-extension`)
+/// - `Type` - By built-in category (`--by-type`)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GroupBy {
+    Extension,
+    Type,
+}
+
+/// Order of the groups in the table.
+///
+/// # Variants
+///
+/// - `Size` - Largest first (default)
+/// - `SizeAscending` - Smallest first
+/// - `Name` - Alphabetical by group name
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum GroupSort {
+    #[default]
+    Size,
+    SizeAscending,
+    Name,
+}
+
+impl GroupSort {
+    /// Maps the tree's `--sort` order onto the table.
+    ///
+    /// A table has no filesystem order, so `none` sorts by size like `size`.
+    pub fn from_sort_order(order: &SortOrder) -> GroupSort {
+        match order {
+            SortOrder::None | SortOrder::SizeDescending => GroupSort::Size,
+            SortOrder::SizeAscending => GroupSort::SizeAscending,
+            SortOrder::Name => GroupSort::Name,
+        }
+    }
+}
+
+/// Totals of one group.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GroupTotal {
+    /// Extension (`.png`) or category (`images`).
+    pub name: String,
+    /// Number of files in the group.
+    pub files: u64,
+    /// Sum of the files' apparent sizes.
+    pub apparent_bytes: u64,
+    /// Sum of the files' allocated sizes.
+    pub allocated_bytes: u64,
+}
+
+impl GroupTotal {
+    /// Returns the group's total in the given mode.
+    pub fn size(&self, size_mode: SizeMode) -> u64 {
+        match size_mode {
+            SizeMode::Apparent => self.apparent_bytes,
+            SizeMode::Allocated => self.allocated_bytes,
+        }
+    }
+}
+
+/// Returns the group a file name belongs to.
+///
+/// # Examples
+///
+/// ```
+/// use disk_usage_clone::group::{GroupBy, group_name};
+/// use std::path::Path;
+///
+/// assert_eq!(group_name(Path::new("logo.PNG"), GroupBy::Extension), ".png");
+/// assert_eq!(group_name(Path::new("logo.PNG"), GroupBy::Type), "images");
+/// assert_eq!(group_name(Path::new("Makefile"), GroupBy::Extension), "(none)");
+/// assert_eq!(group_name(Path::new("notes.txt"), GroupBy::Type), "other");
+/// ```
+pub fn group_name(path: &Path, by: GroupBy) -> String {
+    let extension = path
+        .extension()
+        .map(|ext| ext.to_string_lossy().to_lowercase());
+
+    match by {
+        GroupBy::Extension => match extension {
+            Some(ext) => format!(".{ext}"),
+            None => NO_EXTENSION.to_string(),
+        },
+        GroupBy::Type => extension
+            .and_then(|ext| {
+                CATEGORIES
+                    .iter()
+                    .find(|(_, extensions)| extensions.contains(&ext.as_str()))
+                    .map(|(category, _)| category.to_string())
+            })
+            .unwrap_or_else(|| OTHER_CATEGORY.to_string()),
+    }
+}
+
+/// Groups every regular file of the trees.
+///
+/// # Arguments
+///
+/// * `trees` - Scanned trees (uncollapsed, so every file is present)
+/// * `by` - Extension or category
+/// * `size_mode` - Which size `sort` compares
+/// * `sort` - Order of the result; ties are broken by name
+pub fn group_files(
+    trees: &[DiskEntry],
+    by: GroupBy,
+    size_mode: SizeMode,
+    sort: GroupSort,
+) -> Vec<GroupTotal> {
+    let mut groups: HashMap<String, GroupTotal> = HashMap::new();
+    // Explicit stack: trees can be deeper than the call stack allows
+    let mut pending: Vec<&DiskEntry> = trees.iter().collect();
+
+    while let Some(entry) = pending.pop() {
+        pending.extend(&entry.children);
+        if entry.entry_type != EntryType::File {
+            continue;
+        }
+
+        let name = group_name(&entry.path, by);
+        let group = groups.entry(name.clone()).or_insert_with(|| GroupTotal {
+            name,
+            files: 0,
+            apparent_bytes: 0,
+            allocated_bytes: 0,
+        });
+        group.files += 1;
+        group.apparent_bytes += entry.size_bytes;
+        group.allocated_bytes += entry.allocated_bytes;
+    }
+
+    let mut groups: Vec<GroupTotal> = groups.into_values().collect();
+    groups.sort_by(|a, b| a.name.cmp(&b.name));
+    match sort {
+        GroupSort::Size => groups.sort_by_key(|g| Reverse(g.size(size_mode))),
+        GroupSort::SizeAscending => groups.sort_by_key(|g| g.size(size_mode)),
+        GroupSort::Name => {}
+    }
+    groups
+}
+
+/// Renders groups as `size<TAB>files<TAB>group` lines.
+pub fn render_groups(
+    groups: &[GroupTotal],
+    human_readable: bool,
+    use_color: bool,
+    size_mode: SizeMode,
+) -> String {
+    groups
+        .iter()
+        .map(|group| {
+            let size = group.size(size_mode);
+            let size_str = format_size(size, human_readable);
+            if use_color {
+                format!(
+                    "{}\t{}\t{}",
+                    colorize_size(&size_str, size),
+                    group.files,
+                    group.name.bold()
+                )
+            } else {
+                format!("{size_str}\t{}\t{}", group.files, group.name)
+            }
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Builds the JSON object describing one group.
+fn group_object(group: &GroupTotal, size_mode: SizeMode) -> Value {
+    json!({
+        "group": group.name,
+        "files": group.files,
+        "size": group.size(size_mode),
+        "apparent_size": group.apparent_bytes,
+        "allocated_size": group.allocated_bytes,
+    })
+}
+
+/// Renders groups as one pretty-printed JSON array (`--format json`).
+pub fn render_groups_json(groups: &[GroupTotal], size_mode: SizeMode) -> String {
+    let values: Vec<Value> = groups.iter().map(|g| group_object(g, size_mode)).collect();
+    // Serializing a Value cannot fail
+    serde_json::to_string_pretty(&Value::Array(values)).unwrap_or_default()
+}
+
+/// Renders groups as one JSON object per line (`--format ndjson`).
+pub fn render_groups_ndjson(groups: &[GroupTotal], size_mode: SizeMode) -> String {
+    groups
+        .iter()
+        .map(|g| group_object(g, size_mode).to_string())
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Renders groups as CSV with a header row (`--format csv`).
+pub fn render_groups_csv(groups: &[GroupTotal], size_mode: SizeMode) -> String {
+    let mut rows = vec![CSV_HEADER.to_string()];
+    for group in groups {
+        let size = group.size(size_mode);
+        rows.push(format!(
+            "{},{},{size},{}",
+            csv_field(&group.name),
+            group.files,
+            format_size(size, true)
+        ));
+    }
+    rows.join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+
+    fn file(path: &str, size: u64) -> DiskEntry {
+        let mut entry = DiskEntry::new(PathBuf::from(path), size, EntryType::File, 1);
+        entry.allocated_bytes = 4096;
+        entry
+    }
+
+    fn sample() -> DiskEntry {
+        let mut sub = DiskEntry::new(PathBuf::from("/r/img"), 4096, EntryType::Directory, 1);
+        sub.children = vec![file("/r/img/a.png", 300), file("/r/img/b.JPG", 200)];
+        let mut root = DiskEntry::new(PathBuf::from("/r"), 4096, EntryType::Directory, 0);
+        root.children = vec![
+            sub,
+            file("/r/main.rs", 50),
+            file("/r/lib.rs", 70),
+            file("/r/LICENSE", 10),
+            DiskEntry::new(PathBuf::from("/r/link.png"), 9, EntryType::Symlink, 1),
+        ];
+        root
+    }
+
+    #[test]
+    fn test_group_by_extension() {
+        let groups = group_files(
+            &[sample()],
+            GroupBy::Extension,
+            SizeMode::Apparent,
+            GroupSort::Name,
+        );
+        let summary: Vec<(&str, u64, u64)> = groups
+            .iter()
+            .map(|g| (g.name.as_str(), g.files, g.apparent_bytes))
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                ("(none)", 1, 10),
+                (".jpg", 1, 200),
+                (".png", 1, 300),
+                (".rs", 2, 120),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_group_by_type() {
+        let groups = group_files(
+            &[sample()],
+            GroupBy::Type,
+            SizeMode::Apparent,
+            GroupSort::Size,
+        );
+        let names: Vec<&str> = groups.iter().map(|g| g.name.as_str()).collect();
+        assert_eq!(names, vec!["images", "source", "other"]);
+        assert_eq!(groups[0].files, 2);
+    }
+
+    #[test]
+    fn test_group_sort_by_allocated_size_breaks_ties_by_name() {
+        let groups = group_files(
+            &[sample()],
+            GroupBy::Extension,
+            SizeMode::Allocated,
+            GroupSort::Size,
+        );
+        let names: Vec<&str> = groups.iter().map(|g| g.name.as_str()).collect();
+        assert_eq!(names, vec![".rs", "(none)", ".jpg", ".png"]);
+    }
+
+    #[test]
+    fn test_groups_combine_trees() {
+        let groups = group_files(
+            &[sample(), sample()],
+            GroupBy::Type,
+            SizeMode::Apparent,
+            GroupSort::Size,
+        );
+        assert_eq!(groups[0].files, 4);
+        assert_eq!(groups[0].apparent_bytes, 1000);
+    }
+
+    #[test]
+    fn test_group_name_dotfile_has_no_extension() {
+        assert_eq!(
+            group_name(Path::new("/home/u/.bashrc"), GroupBy::Extension),
+            "(none)"
+        );
+        assert_eq!(group_name(Path::new("a.tar.gz"), GroupBy::Extension), ".gz");
+        assert_eq!(group_name(Path::new("a.tar.gz"), GroupBy::Type), "archives");
+    }
+
+    #[test]
+    fn test_render_groups_formats() {
+        let groups = group_files(
+            &[sample()],
+            GroupBy::Type,
+            SizeMode::Apparent,
+            GroupSort::Size,
+        );
+
+        assert_eq!(
+            render_groups(&groups, false, false, SizeMode::Apparent),
+            "500\t2\timages\n120\t2\tsource\n10\t1\tother"
+        );
+        assert_eq!(
+            render_groups_csv(&groups, SizeMode::Apparent)
+                .lines()
+                .nth(1),
+            Some("images,2,500,500B")
+        );
+
+        let parsed: Value =
+            serde_json::from_str(&render_groups_json(&groups, SizeMode::Apparent)).unwrap();
+        assert_eq!(parsed[1]["group"], "source");
+        assert_eq!(parsed[1]["allocated_size"], 8192);
+
+        let ndjson = render_groups_ndjson(&groups, SizeMode::Apparent);
+        assert_eq!(ndjson.lines().count(), 3);
+    }
+}
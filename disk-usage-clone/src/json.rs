@@ -0,0 +1,408 @@
+//! Machine-readable JSON output.
+//!
+//! Two flavors are supported:
+//! - **JSON** (`--format json`): one document, an array holding one nested
+//!   tree per analyzed path
+//! - **NDJSON** (`--format ndjson`): one compact object per line and entry,
+//!   in the same order as the text output (children before parents)
+//!
+//! Both honor `--max-depth` (the tree is already collapsed), `--all`,
+//! `--summarize` and `--sort` (children keep the tree's order), so they can
+//! replace scripts that parse the tab-separated text output.
+//!
+//! # Object Fields
+//!
+//! | Field | Description |
+//! |-------|-------------|
+//! | `path` | Full path of the entry |
+//! | `type` | `file`, `directory`, `symlink`, `other`, `mount_point` or `aggregate` (the `<K other entries>` row of `--top`) |
+//! | `target` | Where a symlink points (only for links that weren't followed) |
+//! | `depth` | Depth below the analyzed path (0 = the path itself) |
+//! | `size` | Total size in the selected mode (`--apparent-size` or not) |
+//! | `apparent_size` | Total apparent size in bytes |
+//! | `allocated_size` | Total allocated disk space in bytes |
+//! | `child_count` | Number of direct children, listed or not: those folded in by `--max-depth`, files without `--all` and everything under `--summarize` count too, while the entries rolled up by `--top` count as one |
+//! | `incomplete` | `true` if a stopped scan didn't read everything below it |
+//! | `children` | Nested child objects (JSON only) |
+//!
+//! # Examples
+//!
+//! ```
+//! use disk_usage_clone::entry::{DiskEntry, EntryType, SizeMode};
+//! use disk_usage_clone::json::render_ndjson;
+//! use std::path::PathBuf;
+//!
+//! let mut dir = DiskEntry::new(PathBuf::from("/tmp"), 4096, EntryType::Directory, 0);
+//! dir.children.push(DiskEntry::new(
+//!     PathBuf::from("/tmp/file.txt"),
+//!     1024,
+//!     EntryType::File,
+//!     1,
+//! ));
+//!
+//! let output = render_ndjson(&dir, true, false, SizeMode::Apparent);
+//! let lines: Vec<&str> = output.lines().collect();
+//! assert_eq!(lines.len(), 2);
+//! assert!(lines[0].contains(r#""path":"/tmp/file.txt""#));
+//! assert!(lines[1].contains(r#""size":5120"#));
+//! ```
+
+use serde_json::{Map, Value, json};
+
+use crate::entry::{DiskEntry, EntryType, SizeMode};
+
+/// Builds the flat JSON object describing one entry (without `children`).
+///
+/// # Arguments
+///
+/// * `entry` - Entry to describe
+/// * `size_mode` - Which total goes into the `size` field
+fn entry_object(entry: &DiskEntry, size_mode: SizeMode) -> Map<String, Value> {
+    let mut object = Map::new();
+    object.insert("path".to_string(), json!(entry.path.display().to_string()));
+    object.insert("type".to_string(), json!(entry.entry_type.as_str()));
+    if let Some(target) = &entry.link_target {
+        object.insert("target".to_string(), json!(target.display().to_string()));
+    }
+    object.insert("depth".to_string(), json!(entry.depth));
+    object.insert("size".to_string(), json!(entry.total_size_for(size_mode)));
+    object.insert(
+        "apparent_size".to_string(),
+        json!(entry.total_size_for(SizeMode::Apparent)),
+    );
+    object.insert(
+        "allocated_size".to_string(),
+        json!(entry.total_size_for(SizeMode::Allocated)),
+    );
+    object.insert("child_count".to_string(), json!(entry.child_count()));
+    object.insert("incomplete".to_string(), json!(entry.incomplete));
+    object
+}
+
+/// Returns whether an entry is listed, following the text output rules.
+///
+/// Directories, skipped mount points and `--top` remainders are always
+/// listed, files only with `show_all`.
+fn is_listed(entry: &DiskEntry, show_all: bool) -> bool {
+    match entry.entry_type {
+        EntryType::Directory | EntryType::MountPoint | EntryType::Aggregate => true,
+        _ => show_all,
+    }
+}
+
+/// Converts a tree into a nested JSON value.
+///
+/// # Arguments
+///
+/// * `entry` - Root of the tree
+/// * `show_all` - Include files, or directories only?
+/// * `summarize` - Omit all children?
+/// * `size_mode` - Which total goes into the `size` field
+///
+/// # Returns
+///
+/// A JSON object with a `children` array. The root is always included,
+/// even if it is a file and `show_all` is false.
+///
+/// # Examples
+///
+/// ```
+/// use disk_usage_clone::entry::{DiskEntry, EntryType, SizeMode};
+/// use disk_usage_clone::json::tree_to_json;
+/// use std::path::PathBuf;
+///
+/// let dir = DiskEntry::new(PathBuf::from("/tmp"), 4096, EntryType::Directory, 0);
+/// let value = tree_to_json(&dir, false, false, SizeMode::Apparent);
+///
+/// assert_eq!(value["path"], "/tmp");
+/// assert_eq!(value["type"], "directory");
+/// assert_eq!(value["size"], 4096);
+/// assert!(value["children"].as_array().unwrap().is_empty());
+/// ```
+pub fn tree_to_json(
+    entry: &DiskEntry,
+    show_all: bool,
+    summarize: bool,
+    size_mode: SizeMode,
+) -> Value {
+    let mut object = entry_object(entry, size_mode);
+
+    let children: Vec<Value> = if summarize {
+        Vec::new()
+    } else {
+        entry
+            .children
+            .iter()
+            .filter(|child| is_listed(child, show_all))
+            .map(|child| tree_to_json(child, show_all, false, size_mode))
+            .collect()
+    };
+    object.insert("children".to_string(), Value::Array(children));
+
+    Value::Object(object)
+}
+
+/// Renders several trees as one pretty-printed JSON array.
+///
+/// Used for `--format json`: the document always is an array with one
+/// element per analyzed path, so scripts don't need to special-case a
+/// single path.
+///
+/// # Arguments
+///
+/// * `trees` - Roots of the analyzed paths, in command-line order
+/// * `show_all` - Include files, or directories only?
+/// * `summarize` - Omit all children?
+/// * `size_mode` - Which total goes into the `size` field
+///
+/// # Examples
+///
+/// ```
+/// use disk_usage_clone::entry::{DiskEntry, EntryType, SizeMode};
+/// use disk_usage_clone::json::render_json;
+/// use std::path::PathBuf;
+///
+/// let dir = DiskEntry::new(PathBuf::from("/tmp"), 4096, EntryType::Directory, 0);
+/// let output = render_json(&[dir], false, false, SizeMode::Apparent);
+///
+/// let parsed: serde_json::Value = serde_json::from_str(&output).unwrap();
+/// assert_eq!(parsed[0]["path"], "/tmp");
+/// ```
+pub fn render_json(
+    trees: &[DiskEntry],
+    show_all: bool,
+    summarize: bool,
+    size_mode: SizeMode,
+) -> String {
+    let values: Vec<Value> = trees
+        .iter()
+        .map(|tree| tree_to_json(tree, show_all, summarize, size_mode))
+        .collect();
+
+    // Serializing a Value cannot fail
+    serde_json::to_string_pretty(&Value::Array(values)).unwrap_or_default()
+}
+
+/// Renders a tree as newline-delimited JSON, one object per entry.
+///
+/// Entries appear in the same order and with the same filtering as the
+/// text output from [`render_tree`](crate::output::render_tree): children
+/// before parents, files only with `show_all`, only the root with
+/// `summarize`. The root itself is always emitted, even when it is a file.
+///
+/// # Arguments
+///
+/// * `entry` - Root of the tree
+/// * `show_all` - Include files, or directories only?
+/// * `summarize` - Only emit the root?
+/// * `size_mode` - Which total goes into the `size` field
+pub fn render_ndjson(
+    entry: &DiskEntry,
+    show_all: bool,
+    summarize: bool,
+    size_mode: SizeMode,
+) -> String {
+    let mut lines = Vec::new();
+
+    if summarize {
+        lines.push(Value::Object(entry_object(entry, size_mode)).to_string());
+    } else {
+        collect_ndjson(entry, show_all, size_mode, &mut lines);
+    }
+
+    lines.join("\n")
+}
+
+/// Recursively collects NDJSON lines in post-order (children first).
+fn collect_ndjson(entry: &DiskEntry, show_all: bool, size_mode: SizeMode, lines: &mut Vec<String>) {
+    for child in &entry.children {
+        collect_ndjson(child, show_all, size_mode, lines);
+    }
+
+    if entry.depth == 0 || is_listed(entry, show_all) {
+        lines.push(Value::Object(entry_object(entry, size_mode)).to_string());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+
+    fn sample_tree() -> DiskEntry {
+        let mut root = DiskEntry::new(PathBuf::from("/root"), 100, EntryType::Directory, 0);
+        let mut sub = DiskEntry::new(PathBuf::from("/root/sub"), 200, EntryType::Directory, 1);
+        let mut file = DiskEntry::new(
+            PathBuf::from("/root/sub/data.bin"),
+            1000,
+            EntryType::File,
+            2,
+        );
+        file.allocated_bytes = 4096;
+        sub.children.push(file);
+        root.children.push(sub);
+        root.children.push(DiskEntry::new(
+            PathBuf::from("/root/a \"quoted\".txt"),
+            50,
+            EntryType::File,
+            1,
+        ));
+        root
+    }
+
+    #[test]
+    fn test_tree_to_json_fields() {
+        let value = tree_to_json(&sample_tree(), true, false, SizeMode::Allocated);
+        assert_eq!(value["path"], "/root");
+        assert_eq!(value["depth"], 0);
+        assert_eq!(value["size"], 100 + 200 + 4096 + 50);
+        assert_eq!(value["apparent_size"], 100 + 200 + 1000 + 50);
+        assert_eq!(value["allocated_size"], 100 + 200 + 4096 + 50);
+        assert_eq!(value["child_count"], 2);
+        assert_eq!(value["children"][0]["children"][0]["type"], "file");
+    }
+
+    #[test]
+    fn test_json_flags_incomplete_entries() {
+        let mut tree = sample_tree();
+        tree.incomplete = true;
+        tree.children[0].incomplete = true;
+        let value = tree_to_json(&tree, true, false, SizeMode::Apparent);
+        assert_eq!(value["incomplete"], true);
+        assert_eq!(value["children"][0]["incomplete"], true);
+        assert_eq!(value["children"][1]["incomplete"], false);
+
+        let ndjson = render_ndjson(&tree, false, false, SizeMode::Apparent);
+        assert!(
+            ndjson
+                .lines()
+                .all(|line| line.contains(r#""incomplete":true"#))
+        );
+    }
+
+    #[test]
+    fn test_json_shows_symlink_targets() {
+        let mut tree = sample_tree();
+        let mut link = DiskEntry::new(PathBuf::from("/root/link"), 7, EntryType::Symlink, 1);
+        link.link_target = Some(PathBuf::from("../elsewhere"));
+        tree.children.push(link);
+
+        let value = tree_to_json(&tree, true, false, SizeMode::Apparent);
+        assert_eq!(value["children"][2]["type"], "symlink");
+        assert_eq!(value["children"][2]["target"], "../elsewhere");
+        assert!(value["children"][0].get("target").is_none());
+    }
+
+    #[test]
+    fn test_tree_to_json_directories_only() {
+        let value = tree_to_json(&sample_tree(), false, false, SizeMode::Apparent);
+        let children = value["children"].as_array().unwrap();
+        assert_eq!(children.len(), 1);
+        assert_eq!(children[0]["path"], "/root/sub");
+        assert!(children[0]["children"].as_array().unwrap().is_empty());
+        // Totals still include the hidden files
+        assert_eq!(value["size"], 1350);
+    }
+
+    #[test]
+    fn test_tree_to_json_summarize() {
+        let value = tree_to_json(&sample_tree(), true, true, SizeMode::Apparent);
+        assert!(value["children"].as_array().unwrap().is_empty());
+        assert_eq!(value["child_count"], 2);
+    }
+
+    #[test]
+    fn test_top_remainder_is_one_aggregate_child() {
+        let mut tree = sample_tree();
+        tree.children.push(DiskEntry::new(
+            PathBuf::from("/root/b.txt"),
+            20,
+            EntryType::File,
+            1,
+        ));
+        crate::prune::keep_top(&mut tree, 1, true, SizeMode::Apparent);
+        let value = tree_to_json(&tree, true, false, SizeMode::Apparent);
+        assert_eq!(value["child_count"], 2);
+        assert_eq!(value["children"][1]["type"], "aggregate");
+        assert_eq!(value["children"][1]["size"], 70);
+    }
+
+    #[test]
+    fn test_child_count_includes_collapsed_children() {
+        let mut tree = sample_tree();
+        tree.collapse_to_depth(1);
+        let value = tree_to_json(&tree, true, false, SizeMode::Apparent);
+        assert_eq!(value["child_count"], 2);
+        assert!(
+            value["children"][0]["children"]
+                .as_array()
+                .unwrap()
+                .is_empty()
+        );
+        assert_eq!(value["children"][0]["child_count"], 1);
+
+        let ndjson = render_ndjson(&tree, true, false, SizeMode::Apparent);
+        assert!(
+            ndjson
+                .lines()
+                .next()
+                .unwrap()
+                .contains(r#""child_count":1"#)
+        );
+    }
+
+    #[test]
+    fn test_render_json_is_array_of_trees() {
+        let output = render_json(
+            &[sample_tree(), sample_tree()],
+            true,
+            false,
+            SizeMode::Apparent,
+        );
+        let parsed: Value = serde_json::from_str(&output).unwrap();
+        assert_eq!(parsed.as_array().unwrap().len(), 2);
+        assert_eq!(parsed[1]["children"][1]["path"], "/root/a \"quoted\".txt");
+    }
+
+    #[test]
+    fn test_render_ndjson_order_matches_text() {
+        let output = render_ndjson(&sample_tree(), true, false, SizeMode::Apparent);
+        let paths: Vec<String> = output
+            .lines()
+            .map(|line| {
+                let value: Value = serde_json::from_str(line).unwrap();
+                value["path"].as_str().unwrap().to_string()
+            })
+            .collect();
+        assert_eq!(
+            paths,
+            vec![
+                "/root/sub/data.bin",
+                "/root/sub",
+                "/root/a \"quoted\".txt",
+                "/root"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_render_ndjson_directories_only() {
+        let output = render_ndjson(&sample_tree(), false, false, SizeMode::Apparent);
+        assert_eq!(output.lines().count(), 2);
+    }
+
+    #[test]
+    fn test_render_ndjson_summarize() {
+        let output = render_ndjson(&sample_tree(), true, true, SizeMode::Apparent);
+        assert_eq!(output.lines().count(), 1);
+        let value: Value = serde_json::from_str(&output).unwrap();
+        assert_eq!(value["path"], "/root");
+    }
+
+    #[test]
+    fn test_render_ndjson_file_root() {
+        let file = DiskEntry::new(PathBuf::from("/f.txt"), 5, EntryType::File, 0);
+        let output = render_ndjson(&file, false, false, SizeMode::Apparent);
+        assert_eq!(output.lines().count(), 1);
+    }
+}
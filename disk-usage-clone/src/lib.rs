@@ -7,15 +7,54 @@
 //! # Architecture
 //!
 //! The crate is organized into focused modules:
+//! - [`age`]: Age filters and histogram (`--older-than`, `--age-histogram`)
+//! - [`browser`]: Interactive tree browser (`--interactive`)
+//! - [`cache`]: Persistent scan cache (`--cache`)
+//! - [`cancel`]: Stopping scans early (`--timeout`)
 //! - [`cli`]: Command-line argument parsing
+//! - [`compare`]: Grand totals and path comparison (`--total`, `--compare`)
+//! - [`diff`]: Comparing two snapshots (`dusk diff`)
+//! - [`dupes`]: Duplicate file finder (`dusk dupes`)
 //! - [`entry`]: Core data structures (DiskEntry tree)
 //! - [`error`]: Error types and handling
+//! - [`filter`]: Path exclusion (`--exclude`, `.gitignore`)
 //! - [`formatter`]: Size formatting utilities
+//! - [`group`]: Usage per extension or file type (`--by-extension`, `--by-type`)
+//! - [`json`]: JSON and NDJSON output
 //! - [`output`]: Terminal rendering and colorization
+//! - [`owner`]: Usage per user or group (`--by-user`, `--by-group`)
+//! - [`progress`]: Progress reports during scans
+//! - [`prune`]: Output pruning (`--threshold`, `--top`)
+//! - [`remove`]: Deleting and trashing entries from the browser
+//! - [`render`]: Renderers for each output format, for library use
+//! - [`scan`]: Scanning paths with a builder, for library use
+//! - [`snapshot`]: Snapshot files (`--save`)
+//! - [`terminal`]: Terminal abstraction used by the browser
 //! - [`traversal`]: Filesystem traversal with parallelization
+//! - [`tree_view`]: Graphical tree output (`--tree`)
+//! - [`watch`]: Live totals from change notifications (`--watch`)
 //!
 //! # Quick Start
 //!
+//! Programs scan with a [`scan::ScanOptions`] builder and render the
+//! resulting trees with one of the [`render`] structs:
+//!
+//! ```no_run
+//! use disk_usage_clone::render::{Render, TextRenderer};
+//! use disk_usage_clone::scan::ScanOptions;
+//!
+//! let result = ScanOptions::new().path("/tmp").max_depth(1).scan()?;
+//! println!("{}", TextRenderer::new().human_readable(true).render(&result.trees));
+//! # Ok::<(), disk_usage_clone::error::DuskError>(())
+//! ```
+//!
+//! The command line is a thin adapter over both: [`run`] and
+//! [`run_to_string`] take parsed [`cli::CliArgs`].
+//!
+//! # Examples
+//!
+//! ## Running the CLI
+//!
 //! ```no_run
 //! use disk_usage_clone::{cli::CliArgs, run};
 //! use clap::Parser;  // Required for parse_from
@@ -27,43 +66,89 @@
 //! run(&args).expect("Analysis failed");
 //! ```
 //!
-//! # Examples
-//!
-//! ## Basic Usage
+//! ## Capturing CLI Output
 //!
 //! ```no_run
 //! use disk_usage_clone::{cli::CliArgs, run_to_string};
 //! use clap::Parser;  // Required for parse_from
 //!
-//! let args = CliArgs::parse_from(&["dusk", "."]);
+//! // Human-readable sizes, sorted by size, summarize only
+//! let args = CliArgs::parse_from(&["dusk", "-H", "-s", "--sort", "size", "/var"]);
 //! let output = run_to_string(&args).expect("Failed to analyze");
-//! println!("{}", output);
 //! ```
 //!
-//! ## With Options
+//! ## Several Formats From One Scan
 //!
 //! ```no_run
-//! use disk_usage_clone::{cli::CliArgs, run_to_string};
-//! use clap::Parser;  // Required for parse_from
+//! use disk_usage_clone::entry::{SizeMode, SortOrder};
+//! use disk_usage_clone::render::{CsvRenderer, JsonRenderer, Render};
+//! use disk_usage_clone::scan::ScanOptions;
 //!
-//! // Human-readable sizes, sorted by size, summarize only
-//! let args = CliArgs::parse_from(&["dusk", "-H", "-s", "--sort", "size", "/var"]);
-//! let output = run_to_string(&args).expect("Failed to analyze");
+//! let result = ScanOptions::new()
+//!     .paths(["/home", "/srv"])
+//!     .exclude("node_modules/")
+//!     .size_mode(SizeMode::Apparent)
+//!     .sort(SortOrder::SizeDescending)
+//!     .scan()?;
+//!
+//! let json = JsonRenderer::new().size_mode(SizeMode::Apparent).render(&result.trees);
+//! let csv = CsvRenderer::new().show_all(true).render(&result.trees);
+//! # Ok::<(), disk_usage_clone::error::DuskError>(())
 //! ```
-
+pub mod age;
+pub mod browser;
+pub mod cache;
+pub mod cancel;
 pub mod cli;
+pub mod compare;
+pub mod diff;
+pub mod dupes;
 pub mod entry;
 pub mod error;
+pub mod filter;
 pub mod formatter;
+pub mod group;
+pub mod json;
 pub mod output;
+pub mod owner;
+pub mod progress;
+pub mod prune;
+pub mod remove;
+pub mod render;
+pub mod scan;
+pub mod snapshot;
+pub mod terminal;
 pub mod traversal;
+pub mod tree_view;
+pub mod watch;
 
+use std::io::IsTerminal;
 use std::path::Path;
+use std::sync::Arc;
+use std::time::Duration;
 
-use cli::CliArgs;
+use age::{AgeFilter, age_histogram};
+use browser::{BrowseSort, run_browser};
+use cache::ScanCache;
+use cancel::CancelToken;
+use cli::{CliArgs, Command, DiffArgs, DupesArgs};
+use diff::{diff_trees, render_diff};
+use dupes::find_duplicates;
+use entry::{DiskEntry, SizeMode, SortOrder};
 use error::DuskError;
-use output::render_tree;
-use traversal::traverse_parallel;
+use group::{GroupSort, group_files};
+use output::OutputFormat;
+use owner::{NameTable, usage_by_owner};
+use progress::ProgressLine;
+use render::{CsvRenderer, JsonRenderer, NdjsonRenderer, Render, TextRenderer, TreeViewRenderer};
+use scan::{ScanOptions, ScanResult};
+use snapshot::{load_snapshot, save_snapshot};
+use terminal::CrosstermTerminal;
+use traversal::{SkippedEntry, TraversalOptions};
+use watch::{LiveTree, WatchMode, WatchSession};
+
+/// Output width assumed when it can't be determined.
+const DEFAULT_TERMINAL_WIDTH: usize = 80;
 
 /// Runs disk usage analysis and prints results to stdout.
 ///
@@ -73,6 +158,20 @@ use traversal::traverse_parallel;
 /// 3. Renders output with colorization
 /// 4. Prints to stdout
 ///
+/// With `--interactive`, the tree is shown in the [`browser`] instead, and
+/// with `--watch` it is kept up to date until interrupted (see [`watch`]).
+///
+/// While scanning, a progress line is shown on stderr if it is a terminal
+/// (unless `--no-progress`); it is erased before anything is printed.
+///
+/// Paths that couldn't be read are printed to stderr after the output, as
+/// `dusk: cannot read directory 'PATH': REASON`, and make the run fail with
+/// [`DuskError::IncompleteScan`] (exit code 1, like `du`).
+///
+/// With `--timeout`, scans still running when time is up stop and show
+/// what they read, marked `[incomplete]`; the run then fails with
+/// [`DuskError::TimedOut`].
+///
 /// # Arguments
 ///
 /// * `args` - Parsed command-line arguments containing paths and options
@@ -98,12 +197,25 @@ use traversal::traverse_parallel;
 /// - Any path doesn't exist
 /// - Permission denied on any directory
 /// - I/O error during traversal
+/// - `--interactive` is used with several paths or without a terminal
+/// - Some directories or entries could not be read (after printing)
+/// - `--timeout` stopped a scan (after printing the partial results)
 pub fn run(args: &CliArgs) -> Result<(), DuskError> {
-    // Delegate to run_to_string for the actual work
-    let output = run_to_string(args)?;
+    if args.interactive {
+        return run_interactive(args);
+    }
+    if args.watch {
+        return run_watch(args);
+    }
+
+    // Shared with run_to_string, which has no use for the diagnostics
+    let analysis = analyze(args, progress_line(args).as_ref())?;
     // Print result to stdout
-    println!("{output}");
-    Ok(())
+    println!("{}", analysis.output);
+    report_skipped(
+        &analysis.skipped,
+        args.timeout.filter(|_| analysis.incomplete),
+    )
 }
 
 /// Runs disk usage analysis and returns formatted output as a string.
@@ -111,17 +223,26 @@ pub fn run(args: &CliArgs) -> Result<(), DuskError> {
 /// This function performs the same analysis as [`run`] but returns the output
 /// as a `String` instead of printing it. This is useful for:
 /// - Testing (assertions on output)
-/// - Programmatic usage
-/// - Capturing output for further processing
+/// - Capturing the output of a command line for further processing
+///
+/// Programs that don't start from a command line should use
+/// [`scan::ScanOptions`] and the [`render`] structs instead, which this
+/// function is built on.
 ///
 /// # Workflow
 ///
 /// 1. For each path in `args.paths`:
-///    - Traverse filesystem (parallel metadata collection)
+///    - Traverse filesystem (parallel metadata collection, or streaming
+///      aggregation with `--stream`)
 ///    - Build `DiskEntry` tree
 ///    - Apply sorting if requested
-///    - Render to string
-/// 2. Join all outputs with newlines
+/// 2. Save the trees to a snapshot if `--save` is given
+/// 3. Render the trees in the requested format
+///
+/// For `dusk diff`, the two snapshots are compared instead.
+///
+/// Unlike [`run`], paths that couldn't be read are not reported: the
+/// output covers everything that was readable.
 ///
 /// # Arguments
 ///
@@ -150,41 +271,577 @@ pub fn run(args: &CliArgs) -> Result<(), DuskError> {
 ///
 /// # Errors
 ///
-/// Same error conditions as [`run`].
+/// Same error conditions as [`run`], except for unreadable paths.
 pub fn run_to_string(args: &CliArgs) -> Result<String, DuskError> {
+    analyze(args, None).map(|analysis| analysis.output)
+}
+
+/// What [`analyze`] produced, and what the scans missed.
+struct Analysis {
+    /// Rendered output, without trailing newline.
+    output: String,
+    /// Paths that couldn't be read.
+    skipped: Vec<SkippedEntry>,
+    /// Some scan was stopped by `--timeout` before it was complete.
+    incomplete: bool,
+}
+
+/// Runs the analysis, returning the rendered output and what was missed.
+///
+/// Scans report to `progress` if given; it is cleared before returning.
+///
+/// # Errors
+///
+/// Same error conditions as [`run_to_string`].
+fn analyze(args: &CliArgs, progress: Option<&ProgressLine>) -> Result<Analysis, DuskError> {
+    // Subcommands work on saved snapshots instead of scanning
+    let output = match &args.command {
+        Some(Command::Diff(diff)) => Some((run_diff(diff)?, Vec::new())),
+        Some(Command::Dupes(dupes)) => Some(run_dupes(dupes)?),
+        None => None,
+    };
+    if let Some((output, skipped)) = output {
+        return Ok(Analysis {
+            output,
+            skipped,
+            incomplete: false,
+        });
+    }
+
     // Determine if color should be used (inverted from --no-color flag)
     let use_color = !args.no_color;
 
     // Parse the sort order string into an enum
-    let sort_order = args.sort_order();
+    let sort_order = display_sort_order(args);
+
+    // Apparent size or allocated disk usage (default)
+    let size_mode = args.size_mode();
+
+    // Files are dated against one clock reading for the whole run
+    let now = age::now();
+    let time_kind = args.time_kind().unwrap_or_default();
+
+    // Traversal, filters and sorting shared by every path
+    let mut options = scan_options(args).sort(sort_order.clone());
+    if args.older_than.is_some() || args.newer_than.is_some() {
+        options = options.age_filter(AgeFilter::new(
+            args.older_than,
+            args.newer_than,
+            time_kind,
+            now,
+        ));
+    }
+    if let Some(line) = progress {
+        options = options.progress(line.handler());
+    }
 
-    // Collect results for all requested paths
-    let mut results = Vec::new();
+    // Reuse unchanged directories from earlier runs (--cache)
+    let cache_path = args.cache_path();
+    let cache = cache_path
+        .as_ref()
+        .map(|file| Arc::new(ScanCache::load(file)));
+    if let Some(cache) = &cache {
+        options = options.cache(Arc::clone(cache));
+    } else if args.cache && !args.no_cache {
+        eprintln!("dusk: no cache directory (set XDG_CACHE_HOME or use --cache-file)");
+    }
+
+    // Output format: text (default), json, ndjson or csv
+    let format = args.output_format();
+
+    // Scan every requested path independently
+    let result = options.scan()?;
+
+    // Scans erase the line when done, but not if one failed halfway
+    if let Some(line) = progress {
+        line.clear();
+    }
+
+    // A scan stopped by --timeout returns what it read so far
+    let incomplete = result.is_incomplete();
+    let ScanResult { trees, skipped } = result;
 
-    // Process each path independently
-    for path_str in &args.paths {
-        let path = Path::new(path_str);
+    // Remember the directories for the next run; a cache that can't be
+    // written only costs time, so the run still succeeds. Saving after a
+    // timeout would drop the listings of directories it didn't reach
+    if let (Some(file), Some(cache), false) = (&cache_path, &cache, incomplete)
+        && let Err(err) = cache.save(file)
+    {
+        eprintln!("dusk: cannot write cache {}: {err}", file.display());
+    }
 
-        // Traverse filesystem and build DiskEntry tree
-        // Uses parallel metadata collection for performance
-        let mut tree = traverse_parallel(path, args.max_depth, args.threads)?;
+    // Keep the scan for a later `dusk diff`
+    if let Some(save) = &args.save {
+        save_snapshot(Path::new(save), &trees)?;
+    }
 
-        // Apply sorting if requested (recursive on entire tree)
-        tree.sort_entries(&sort_order);
+    // A table per extension or type replaces the trees
+    if let Some(by) = args.group_by() {
+        let groups = group_files(
+            &trees,
+            by,
+            size_mode,
+            GroupSort::from_sort_order(&sort_order),
+        );
+        let output = match format {
+            OutputFormat::Text => {
+                group::render_groups(&groups, args.human_readable, use_color, size_mode)
+            }
+            OutputFormat::Json => group::render_groups_json(&groups, size_mode),
+            OutputFormat::Ndjson => group::render_groups_ndjson(&groups, size_mode),
+            OutputFormat::Csv => group::render_groups_csv(&groups, size_mode),
+        };
+        return Ok(Analysis {
+            output: output + &incomplete_note(&trees, format),
+            skipped,
+            incomplete,
+        });
+    }
 
-        // Render the tree to a string with requested formatting
-        let output = render_tree(
-            &tree,
-            args.human_readable,  // Format as K, M, G or raw bytes?
-            args.all,              // Show files or directories only?
-            args.summarize,        // Show only totals?
-            use_color,             // Colorize output?
+    // Likewise for accounting per user or group
+    if let Some(by) = args.owner_by() {
+        let owners = usage_by_owner(
+            &trees,
+            by,
+            &NameTable::load(),
+            size_mode,
+            GroupSort::from_sort_order(&sort_order),
         );
-        results.push(output);
+        let output = match format {
+            OutputFormat::Text => {
+                owner::render_owners(&owners, args.human_readable, use_color, size_mode)
+            }
+            OutputFormat::Json => owner::render_owners_json(&owners, size_mode),
+            OutputFormat::Ndjson => owner::render_owners_ndjson(&owners, size_mode),
+            OutputFormat::Csv => owner::render_owners_csv(&owners, size_mode),
+        };
+        return Ok(Analysis {
+            output: output + &incomplete_note(&trees, format),
+            skipped,
+            incomplete,
+        });
+    }
+
+    // And for bytes by age
+    if args.age_histogram {
+        let buckets = age_histogram(&trees, time_kind, now);
+        let output = match format {
+            OutputFormat::Text => age::render_histogram(
+                &buckets,
+                args.human_readable,
+                use_color,
+                size_mode,
+                terminal_width(),
+            ),
+            OutputFormat::Json => age::render_histogram_json(&buckets, size_mode),
+            OutputFormat::Ndjson => age::render_histogram_ndjson(&buckets, size_mode),
+            OutputFormat::Csv => age::render_histogram_csv(&buckets, size_mode),
+        };
+        return Ok(Analysis {
+            output: output + &incomplete_note(&trees, format),
+            skipped,
+            incomplete,
+        });
+    }
+
+    // And for the paths side by side
+    if args.compare {
+        return Ok(Analysis {
+            output: render_comparison(args, &trees, &sort_order, format, use_color, size_mode),
+            skipped,
+            incomplete,
+        });
+    }
+
+    // Prune and render what is shown; the snapshot above keeps the full scan
+    let output = render_trees(args, trees, format, use_color, size_mode);
+    Ok(Analysis {
+        output,
+        skipped,
+        incomplete,
+    })
+}
+
+/// Returns a line flagging a text table as incomplete, or nothing.
+///
+/// Tables per extension, owner, age or path have no rows for directories, so
+/// the `[incomplete]` marker of the tree renderers goes below them.
+/// Machine-readable tables rely on the exit status instead.
+fn incomplete_note(trees: &[DiskEntry], format: OutputFormat) -> String {
+    if format == OutputFormat::Text && trees.iter().any(|tree| tree.incomplete) {
+        "\n[incomplete] scan stopped early; totals are too low".to_string()
+    } else {
+        String::new()
+    }
+}
+
+/// Renders the `--compare` table of the scanned paths in `format`.
+///
+/// Rows are sorted by `sort_order`, the display order the other tables use.
+/// Like them, a text comparison of stopped scans ends with the incomplete
+/// note.
+fn render_comparison(
+    args: &CliArgs,
+    trees: &[DiskEntry],
+    sort_order: &SortOrder,
+    format: OutputFormat,
+    use_color: bool,
+    size_mode: SizeMode,
+) -> String {
+    let comparison = compare::compare_paths(trees, size_mode, sort_order);
+    let output = match format {
+        OutputFormat::Text => compare::render_comparison(
+            &comparison,
+            args.human_readable,
+            use_color,
+            size_mode,
+            terminal_width(),
+        ),
+        OutputFormat::Json => compare::render_comparison_json(&comparison, size_mode),
+        OutputFormat::Ndjson => compare::render_comparison_ndjson(&comparison, size_mode),
+        OutputFormat::Csv => compare::render_comparison_csv(&comparison, size_mode),
+    };
+    output + &incomplete_note(trees, format)
+}
+
+/// Returns the sort order for displayed trees.
+///
+/// The tree view defaults to largest first, since its bars are meant to be
+/// compared.
+fn display_sort_order(args: &CliArgs) -> SortOrder {
+    match args.sort_order() {
+        SortOrder::None if args.tree => SortOrder::SizeDescending,
+        sort_order => sort_order,
+    }
+}
+
+/// Prunes scanned trees for display and renders them in `format`.
+///
+/// Applies `--threshold` and `--top`, then the tree view, text, JSON, NDJSON
+/// or CSV renderer. The trees must already be sorted and depth-limited.
+fn render_trees(
+    args: &CliArgs,
+    mut trees: Vec<DiskEntry>,
+    format: OutputFormat,
+    use_color: bool,
+    size_mode: SizeMode,
+) -> String {
+    for tree in &mut trees {
+        if let Some(threshold) = args.threshold {
+            prune::apply_threshold(tree, threshold, size_mode);
+        }
+        if let Some(n) = args.top {
+            prune::keep_top(tree, n, args.all, size_mode);
+        }
+    }
+
+    // Render the trees with requested formatting
+    tree_renderer(args, format, use_color, size_mode).render(&trees)
+}
+
+/// Returns the renderer for `format` configured from the arguments.
+///
+/// Text output is the graphical tree with `--tree`. Only text renderers
+/// use color.
+fn tree_renderer(
+    args: &CliArgs,
+    format: OutputFormat,
+    use_color: bool,
+    size_mode: SizeMode,
+) -> Box<dyn Render> {
+    match format {
+        OutputFormat::Text if args.tree => Box::new(
+            TreeViewRenderer::new()
+                .human_readable(args.human_readable)
+                .show_all(args.all)
+                .summarize(args.summarize)
+                .color(use_color)
+                .size_mode(size_mode)
+                .width(terminal_width())
+                .total(args.total),
+        ),
+        OutputFormat::Text => {
+            let mut renderer = TextRenderer::new()
+                .human_readable(args.human_readable) // Format as K, M, G or raw bytes?
+                .show_all(args.all) // Show files or directories only?
+                .summarize(args.summarize) // Show only totals?
+                .color(use_color) // Colorize output?
+                .size_mode(size_mode) // Apparent size or disk usage?
+                .total(args.total); // Grand total of all paths?
+            if let Some(kind) = args.time_kind() {
+                renderer = renderer.time_column(kind);
+            }
+            Box::new(renderer)
+        }
+        OutputFormat::Json => Box::new(
+            JsonRenderer::new()
+                .show_all(args.all)
+                .summarize(args.summarize)
+                .size_mode(size_mode),
+        ),
+        OutputFormat::Ndjson => Box::new(
+            NdjsonRenderer::new()
+                .show_all(args.all)
+                .summarize(args.summarize)
+                .size_mode(size_mode),
+        ),
+        OutputFormat::Csv => Box::new(
+            CsvRenderer::new()
+                .show_all(args.all)
+                .summarize(args.summarize)
+                .size_mode(size_mode),
+        ),
+    }
+}
+
+/// Returns the width available for output, in columns.
+///
+/// Uses the terminal size when stdout is a terminal, then `$COLUMNS`, and
+/// falls back to 80 columns (e.g. when piped).
+fn terminal_width() -> usize {
+    if std::io::stdout().is_terminal()
+        && let Ok((columns, _)) = crossterm::terminal::size()
+    {
+        return usize::from(columns);
+    }
+    std::env::var("COLUMNS")
+        .ok()
+        .and_then(|columns| columns.parse().ok())
+        .unwrap_or(DEFAULT_TERMINAL_WIDTH)
+}
+
+/// Prints skipped paths to stderr and turns them into the run's result.
+///
+/// `timed_out` is the `--timeout` limit if it stopped a scan.
+///
+/// # Errors
+///
+/// Returns `TimedOut` if a scan was stopped, otherwise `IncompleteScan`
+/// with the number of skipped paths, if any.
+fn report_skipped(skipped: &[SkippedEntry], timed_out: Option<u64>) -> Result<(), DuskError> {
+    for entry in skipped {
+        eprintln!("dusk: {entry}");
     }
+    if let Some(secs) = timed_out {
+        Err(DuskError::TimedOut(secs))
+    } else if skipped.is_empty() {
+        Ok(())
+    } else {
+        Err(DuskError::IncompleteScan(skipped.len()))
+    }
+}
+
+/// Compares two snapshot files and renders the changes.
+///
+/// Only reads the snapshots; nothing is scanned.
+///
+/// # Errors
+///
+/// Returns an error if either snapshot can't be loaded.
+fn run_diff(args: &DiffArgs) -> Result<String, DuskError> {
+    let old = load_snapshot(Path::new(&args.old))?;
+    let new = load_snapshot(Path::new(&args.new))?;
+
+    let changes = diff_trees(&old, &new, args.size_mode(), args.diff_sort());
+    Ok(render_diff(&changes, args.human_readable))
+}
+
+/// Searches the paths of `dusk dupes` and renders the duplicate groups.
+///
+/// # Errors
+///
+/// Same as [`dupes::find_duplicates`]. Unreadable paths are returned with
+/// the output, like a scan's.
+fn run_dupes(args: &DupesArgs) -> Result<(String, Vec<SkippedEntry>), DuskError> {
+    let options = TraversalOptions {
+        threads: args.threads,
+        one_file_system: args.one_file_system,
+        exclude: args.exclude.clone(),
+        ..TraversalOptions::default()
+    };
+    let report = find_duplicates(&args.paths, &options, args.min_size)?;
+
+    let output = match args.output_format() {
+        OutputFormat::Text => dupes::render_dupes(&report.groups, args.human_readable),
+        OutputFormat::Json => dupes::render_dupes_json(&report.groups),
+        OutputFormat::Ndjson => dupes::render_dupes_ndjson(&report.groups),
+        OutputFormat::Csv => dupes::render_dupes_csv(&report.groups),
+    };
+    Ok((output, report.skipped))
+}
+
+/// Scans the paths once, then redraws them as they change.
+///
+/// On a terminal the screen is cleared before each redraw, under a header
+/// line like `watch(1)`'s; otherwise every refresh is printed in full,
+/// followed by a blank line. Redraws happen at most once per `--interval`,
+/// and only when something changed. Runs until interrupted.
+///
+/// # Errors
+///
+/// Returns an error if the initial scan of any path fails, or if writing
+/// to the terminal fails.
+fn run_watch(args: &CliArgs) -> Result<(), DuskError> {
+    // Only the initial scans report progress (see LiveTree::scan)
+    let progress = progress_line(args);
+    let options = TraversalOptions {
+        progress: progress.as_ref().map(ProgressLine::handler),
+        ..scan_options(args).traversal_options().clone()
+    };
+    let trees = args
+        .paths
+        .iter()
+        .map(|path| LiveTree::scan(Path::new(path), &options))
+        .collect::<Result<Vec<_>, _>>()?;
+    drop(progress);
+
+    let interval = Duration::from_secs(args.interval);
+    let mut session = WatchSession::new(trees, interval);
+    let sort_order = display_sort_order(args);
+    let size_mode = args.size_mode();
+    let on_terminal = std::io::stdout().is_terminal();
+    let mut fallback_reported = false;
+
+    loop {
+        if let WatchMode::Polling { reason } = session.mode()
+            && !fallback_reported
+        {
+            eprintln!("dusk: {reason}; rescanning every {}s", args.interval);
+            fallback_reported = true;
+        }
+
+        // The live trees keep every level; limit and sort copies for display
+        let trees = session
+            .trees()
+            .iter()
+            .map(|live| {
+                let mut tree = live.tree().clone();
+                if let Some(max_depth) = args.max_depth {
+                    tree.collapse_to_depth(max_depth);
+                }
+                tree.sort_entries_for(&sort_order, size_mode);
+                tree
+            })
+            .collect();
+        let output = render_trees(args, trees, OutputFormat::Text, !args.no_color, size_mode);
+
+        if on_terminal {
+            let header = format!(
+                "Every {}s: dusk {}    {}",
+                args.interval,
+                args.paths.join(" "),
+                formatter::format_timestamp(age::now())
+            );
+            crossterm::execute!(
+                std::io::stdout(),
+                crossterm::terminal::Clear(crossterm::terminal::ClearType::All),
+                crossterm::cursor::MoveTo(0, 0)
+            )
+            .map_err(|e| DuskError::TerminalError(e.to_string()))?;
+            println!("{header}\n\n{output}");
+        } else {
+            println!("{output}\n");
+        }
+
+        while !session.next_update() {}
+    }
+}
+
+/// Scans a single path and opens the interactive browser on the result.
+///
+/// The scan finishes before the terminal switches to the alternate screen;
+/// browsing afterwards only reads the in-memory tree. Skipped paths and
+/// removal failures from the session are printed to stderr once the
+/// terminal is restored.
+///
+/// # Errors
+///
+/// - `InvalidArgument` if more than one path is given
+/// - `TerminalError` if stdout is not a terminal or terminal I/O fails
+/// - Any traversal error from the scan
+/// - `IncompleteScan` if some paths couldn't be read
+fn run_interactive(args: &CliArgs) -> Result<(), DuskError> {
+    if args.paths.len() != 1 {
+        return Err(DuskError::InvalidArgument(
+            "--interactive takes a single path".to_string(),
+        ));
+    }
+
+    if !std::io::stdout().is_terminal() {
+        return Err(DuskError::TerminalError(
+            "--interactive needs a terminal on stdout".to_string(),
+        ));
+    }
+
+    let progress = progress_line(args);
+    let mut options = scan_options(args);
+    if let Some(line) = &progress {
+        options = options.progress(line.handler());
+    }
+    let ScanResult { mut trees, skipped } = options.scan()?;
+    drop(progress);
+    // One path, one tree
+    let mut tree = trees.remove(0);
+    let incomplete = tree.incomplete;
+
+    if let Some(save) = &args.save {
+        save_snapshot(Path::new(save), std::slice::from_ref(&tree))?;
+    }
+
+    let mut term = CrosstermTerminal::new();
+    let failures = run_browser(
+        &mut term,
+        &mut tree,
+        args.size_mode(),
+        BrowseSort::from_sort_order(&args.sort_order()),
+        args.dry_run,
+    )?;
+
+    for failure in failures {
+        eprintln!("dusk: {failure}");
+    }
+    report_skipped(&skipped, args.timeout.filter(|_| incomplete))
+}
+
+/// Builds the scan settings from the command-line arguments.
+///
+/// Sorting and age filters are left to [`analyze`]; the browser and watch
+/// mode sort on their own.
+fn scan_options(args: &CliArgs) -> ScanOptions {
+    let mut options = ScanOptions::new()
+        .paths(&args.paths)
+        .count_links(args.count_links)
+        .one_file_system(args.one_file_system)
+        .follow_links(args.dereference_mode())
+        .respect_gitignore(args.respect_gitignore)
+        .size_mode(args.size_mode())
+        .stream(args.stream);
+    if let Some(max_depth) = args.max_depth {
+        options = options.max_depth(max_depth);
+    }
+    if let Some(threads) = args.threads {
+        options = options.threads(threads);
+    }
+    for pattern in &args.exclude {
+        options = options.exclude(pattern);
+    }
+    for file in &args.exclude_from {
+        options = options.exclude_from(file);
+    }
+    // One budget for the whole run, all paths together
+    if let Some(secs) = args.timeout {
+        options = options.cancel(CancelToken::with_timeout(Duration::from_secs(secs)));
+    }
+    options
+}
 
-    // Join all path outputs with newlines
-    Ok(results.join("\n"))
+/// Returns the progress line for a scan started from [`run`], if shown.
+fn progress_line(args: &CliArgs) -> Option<ProgressLine> {
+    if args.no_progress {
+        None
+    } else {
+        ProgressLine::for_stderr(args.size_mode())
+    }
 }
 
 #[cfg(test)]
@@ -249,6 +906,25 @@ mod tests {
         assert!(result.is_err());
     }
 
+    #[test]
+    fn test_run_to_string_apparent_size() {
+        let tmp = create_test_dir();
+        let args = CliArgs::parse_from([
+            "dusk",
+            "-a",
+            "--apparent-size",
+            "--no-color",
+            tmp.path().to_str().unwrap(),
+        ]);
+        let output = run_to_string(&args).unwrap();
+        // "hello world" is 11 bytes long
+        assert!(
+            output
+                .lines()
+                .any(|l| l.starts_with("11\t") && l.ends_with("hello.txt"))
+        );
+    }
+
     #[test]
     fn test_run_to_string_sorted_by_name() {
         let tmp = create_test_dir();
@@ -279,5 +955,77 @@ mod tests {
         assert_eq!(output.lines().count(), 1);
     }
 
+    #[test]
+    fn test_run_to_string_json() {
+        let tmp = create_test_dir();
+        let path = tmp.path().to_str().unwrap();
+        let args = CliArgs::parse_from(["dusk", "--format", "json", path, path]);
+        let output = run_to_string(&args).unwrap();
+        let parsed: serde_json::Value = serde_json::from_str(&output).unwrap();
+        assert_eq!(parsed.as_array().unwrap().len(), 2);
+        assert_eq!(parsed[0]["type"], "directory");
+    }
+
+    #[test]
+    fn test_run_to_string_ndjson() {
+        let tmp = create_test_dir();
+        let args = CliArgs::parse_from([
+            "dusk",
+            "--format",
+            "ndjson",
+            "-a",
+            tmp.path().to_str().unwrap(),
+        ]);
+        let output = run_to_string(&args).unwrap();
+        // hello.txt, subdir/data.bin, subdir and the root
+        assert_eq!(output.lines().count(), 4);
+        for line in output.lines() {
+            assert!(serde_json::from_str::<serde_json::Value>(line).is_ok());
+        }
+    }
+
+    #[test]
+    fn test_run_to_string_stream_matches_default() {
+        let tmp = create_test_dir();
+        let path = tmp.path().to_str().unwrap();
+        for depth in ["0", "1"] {
+            let default = CliArgs::parse_from(["dusk", "--no-color", "-a", "-d", depth, path]);
+            let stream =
+                CliArgs::parse_from(["dusk", "--no-color", "-a", "--stream", "-d", depth, path]);
+            assert_eq!(
+                run_to_string(&default).unwrap(),
+                run_to_string(&stream).unwrap()
+            );
+        }
+    }
+
+    #[test]
+    fn test_comparison_of_stopped_scans_is_flagged() {
+        let tmp = create_test_dir();
+        let path = tmp.path().to_str().unwrap();
+        let args = CliArgs::parse_from(["dusk", "--no-color", "--compare", path, path]);
+        let mut trees = vec![
+            traversal::traverse(tmp.path(), None).unwrap(),
+            traversal::traverse(tmp.path(), None).unwrap(),
+        ];
+
+        let note = "[incomplete] scan stopped early";
+        let sort_order = display_sort_order(&args);
+        let render = |trees: &[DiskEntry], format| {
+            render_comparison(&args, trees, &sort_order, format, false, SizeMode::Apparent)
+        };
+        let output = render(&trees, OutputFormat::Text);
+        assert!(!output.contains(note), "{output}");
+
+        trees[1].incomplete = true;
+        let output = render(&trees, OutputFormat::Text);
+        assert!(
+            output.ends_with(&format!("\n{note}; totals are too low")),
+            "{output}"
+        );
+        let json = render(&trees, OutputFormat::Json);
+        assert!(!json.contains(note));
+    }
+
     use clap::Parser;
 }
@@ -8,7 +8,9 @@
 //! - Size-based colorization (large = red, small = green)
 //! - Type-based colorization (directories = blue)
 //! - Human-readable formatting support
+//! - Apparent or allocated sizes ([`SizeMode`])
 //! - Summarize mode (totals only)
+//! - Optional timestamp column (`--time`), like `du --time`
 //! - Show-all mode (files + directories)
 //!
 //! # Color Scheme
@@ -25,13 +27,17 @@
 //!
 //! - **Blue (bold)**: Directories
 //! - **Cyan**: Symlinks
+//! - **Magenta**: Mount points skipped by `--one-file-system`
 //! - **Default**: Files and other
 //!
+//! Skipped mount points are always listed (even without `--all`) and carry a
+//! `[mount point]` marker after the path, so they stay visible without color.
+//!
 //! # Examples
 //!
 //! ```no_run
 //! use disk_usage_clone::output::render_tree;
-//! use disk_usage_clone::entry::{DiskEntry, EntryType};
+//! use disk_usage_clone::entry::{DiskEntry, EntryType, SizeMode};
 //! use std::path::PathBuf;
 //!
 //! let entry = DiskEntry::new(
@@ -42,14 +48,14 @@
 //! );
 //!
 //! // Render with color and human-readable sizes
-//! let output = render_tree(&entry, true, false, false, true);
+//! let output = render_tree(&entry, true, false, false, true, SizeMode::Allocated, None);
 //! println!("{}", output);
 //! ```
 
 use colored::Colorize;
 
-use crate::entry::{DiskEntry, EntryType};
-use crate::formatter::format_size;
+use crate::entry::{DiskEntry, EntryType, SizeMode, TimeKind};
+use crate::formatter::{format_size, format_timestamp};
 
 /// Kilobyte constant for size thresholds.
 const KB: u64 = 1024;
@@ -60,6 +66,76 @@ const MB: u64 = 1024 * KB;
 /// Gigabyte constant for size thresholds.
 const GB: u64 = 1024 * MB;
 
+/// Marker appended to mount points that were not crossed.
+pub(crate) const MOUNT_POINT_MARKER: &str = " [mount point]";
+
+/// Marker appended to directories a stopped scan didn't finish reading.
+pub(crate) const INCOMPLETE_MARKER: &str = " [incomplete]";
+
+/// Returns ` -> TARGET` for a symlink that wasn't followed, else nothing.
+///
+/// Appended after the path, like `ls -l` shows links.
+pub(crate) fn link_suffix(entry: &DiskEntry) -> String {
+    entry
+        .link_target
+        .as_ref()
+        .map(|target| format!(" -> {}", target.display()))
+        .unwrap_or_default()
+}
+
+/// Header row of the CSV output, in column order.
+const CSV_HEADER: &str = "path,size_bytes,size_human,type,depth,parent,incomplete";
+
+/// Output format for analysis results.
+///
+/// # Variants
+///
+/// - `Text` - Tab-separated `size<TAB>path` lines, optionally colorized (default)
+/// - `Json` - One JSON document with a nested tree per path (see [`crate::json`])
+/// - `Ndjson` - One JSON object per line and entry (see [`crate::json`])
+/// - `Csv` - Comma-separated rows with a header (see [`render_csv`])
+///
+/// # Examples
+///
+/// ```
+/// use disk_usage_clone::output::OutputFormat;
+///
+/// assert_eq!(OutputFormat::parse("text"), Some(OutputFormat::Text));
+/// assert_eq!(OutputFormat::parse("json"), Some(OutputFormat::Json));
+/// assert_eq!(OutputFormat::parse("ndjson"), Some(OutputFormat::Ndjson));
+/// assert_eq!(OutputFormat::parse("csv"), Some(OutputFormat::Csv));
+/// assert_eq!(OutputFormat::parse("xml"), None);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum OutputFormat {
+    #[default]
+    Text,
+    Json,
+    Ndjson,
+    Csv,
+}
+
+impl OutputFormat {
+    /// Names accepted by [`OutputFormat::parse`], for CLI validation.
+    pub const NAMES: &'static [&'static str] = &["text", "json", "ndjson", "csv"];
+
+    /// Parses a string into an `OutputFormat`.
+    ///
+    /// # Returns
+    ///
+    /// - `Some(OutputFormat)` if the string is valid
+    /// - `None` for invalid/unknown strings
+    pub fn parse(s: &str) -> Option<OutputFormat> {
+        match s {
+            "text" => Some(OutputFormat::Text),
+            "json" => Some(OutputFormat::Json),
+            "ndjson" => Some(OutputFormat::Ndjson),
+            "csv" => Some(OutputFormat::Csv),
+            _ => None,
+        }
+    }
+}
+
 /// Applies color to a size string based on magnitude.
 ///
 /// Uses a semantic color scheme where:
@@ -107,7 +183,7 @@ const GB: u64 = 1024 * MB;
 /// terminal, these produce colored output. When written to a file or
 /// piped, the `colored` crate automatically omits the codes (unless
 /// overridden).
-fn colorize_size(size_str: &str, bytes: u64) -> String {
+pub(crate) fn colorize_size(size_str: &str, bytes: u64) -> String {
     // Check thresholds from largest to smallest
     if bytes >= GB {
         // Very large: red and bold for urgency
@@ -132,6 +208,7 @@ fn colorize_size(size_str: &str, bytes: u64) -> String {
 /// Follows common Unix conventions:
 /// - Directories are blue (like `ls --color`)
 /// - Symlinks are cyan
+/// - Skipped mount points are magenta
 /// - Files are default color
 ///
 /// # Arguments
@@ -158,7 +235,7 @@ fn colorize_size(size_str: &str, bytes: u64) -> String {
 /// // File - no color
 /// let plain = colorize_path("/tmp/data.txt", &EntryType::File);
 /// ```
-fn colorize_path(path_str: &str, entry_type: &EntryType) -> String {
+pub(crate) fn colorize_path(path_str: &str, entry_type: &EntryType) -> String {
     match entry_type {
         EntryType::Directory => {
             // Directories: blue and bold
@@ -170,6 +247,14 @@ fn colorize_path(path_str: &str, entry_type: &EntryType) -> String {
             // Also follows `ls --color` convention
             path_str.cyan().to_string()
         }
+        EntryType::MountPoint => {
+            // Mount points: magenta, to stand out from regular directories
+            path_str.magenta().to_string()
+        }
+        EntryType::Aggregate => {
+            // Rolled-up entries (`--top`): dimmed, since they are not real paths
+            path_str.dimmed().to_string()
+        }
         EntryType::File | EntryType::Other => {
             // Files: default color (no ANSI codes)
             path_str.to_string()
@@ -196,16 +281,21 @@ fn colorize_path(path_str: &str, entry_type: &EntryType) -> String {
 /// * `entry` - Entry to render
 /// * `human_readable` - Use K/M/G suffixes?
 /// * `use_color` - Apply colorization?
+/// * `size_mode` - Show apparent or allocated size?
+/// * `time` - Timestamp to show between size and path, if any
 ///
 /// # Returns
 ///
-/// Formatted string: `<size>\t<path>`
+/// Formatted string: `<size>\t<path>`, or `<size>\t<time>\t<path>` with a
+/// timestamp. Symlinks that weren't followed show their target after the
+/// path (`<path> -> <target>`). The time is the latest one in the entry's subtree (see
+/// [`DiskEntry::latest_times`]), in local time; `-` if it is unknown.
 ///
 /// # Examples
 ///
 /// ```
 /// use disk_usage_clone::output::render_entry;
-/// use disk_usage_clone::entry::{DiskEntry, EntryType};
+/// use disk_usage_clone::entry::{DiskEntry, EntryType, SizeMode};
 /// use std::path::PathBuf;
 ///
 /// let entry = DiskEntry::new(
@@ -216,36 +306,77 @@ fn colorize_path(path_str: &str, entry_type: &EntryType) -> String {
 /// );
 ///
 /// // No color, raw bytes
-/// let output = render_entry(&entry, false, false);
+/// let output = render_entry(&entry, false, false, SizeMode::Apparent, None);
 /// assert_eq!(output, "1024\ttest.txt");
 ///
 /// // Human-readable, no color
-/// let output = render_entry(&entry, true, false);
+/// let output = render_entry(&entry, true, false, SizeMode::Apparent, None);
 /// assert_eq!(output, "1.0K\ttest.txt");
 /// ```
 ///
 /// # Total Size
 ///
-/// Uses `entry.total_size()`, which includes all descendants for directories.
-pub fn render_entry(entry: &DiskEntry, human_readable: bool, use_color: bool) -> String {
+/// Uses `entry.total_size_for(size_mode)`, which includes all descendants
+/// for directories.
+pub fn render_entry(
+    entry: &DiskEntry,
+    human_readable: bool,
+    use_color: bool,
+    size_mode: SizeMode,
+    time: Option<TimeKind>,
+) -> String {
     // Calculate total size (includes children for directories)
-    let size = entry.total_size();
+    let size = entry.total_size_for(size_mode);
 
     // Format the size as string
     let size_str = format_size(size, human_readable);
 
+    // Optional timestamp column, with its trailing separator
+    let time_str = match time {
+        Some(kind) => {
+            let time = entry
+                .latest_times()
+                .map(|times| format_timestamp(times.get(kind)))
+                .unwrap_or_else(|| "-".to_string());
+            format!("{time}\t")
+        }
+        None => String::new(),
+    };
+
     // Convert path to string
     let path_str = entry.path.display().to_string();
 
+    // Show where symlinks point, like `ls -l`
+    let target = link_suffix(entry);
+
+    // Flag mount points that were not crossed (--one-file-system)
+    let marker = if entry.entry_type == EntryType::MountPoint {
+        MOUNT_POINT_MARKER
+    } else {
+        ""
+    };
+
+    // Flag directories a stopped scan didn't finish (--timeout)
+    let incomplete = if entry.incomplete {
+        INCOMPLETE_MARKER
+    } else {
+        ""
+    };
+
     if use_color {
         // Apply semantic colorization
         let colored_size = colorize_size(&size_str, size);
         let colored_path = colorize_path(&path_str, &entry.entry_type);
-        // Tab-separated: size <TAB> path
-        format!("{colored_size}\t{colored_path}")
+        // Tab-separated: size <TAB> [time <TAB>] path
+        format!(
+            "{colored_size}\t{}{colored_path}{target}{}{}",
+            time_str.dimmed(),
+            marker.dimmed(),
+            incomplete.yellow()
+        )
     } else {
         // Plain output (no ANSI codes)
-        format!("{size_str}\t{path_str}")
+        format!("{size_str}\t{time_str}{path_str}{target}{marker}{incomplete}")
     }
 }
 
@@ -288,6 +419,8 @@ pub fn render_entry(entry: &DiskEntry, human_readable: bool, use_color: bool) ->
 /// * `show_all` - Show files, or directories only?
 /// * `summarize` - Show only total?
 /// * `use_color` - Apply colorization?
+/// * `size_mode` - Show apparent or allocated sizes?
+/// * `time` - Timestamp column to show, if any
 ///
 /// # Returns
 ///
@@ -297,7 +430,7 @@ pub fn render_entry(entry: &DiskEntry, human_readable: bool, use_color: bool) ->
 ///
 /// ```
 /// use disk_usage_clone::output::render_tree;
-/// use disk_usage_clone::entry::{DiskEntry, EntryType};
+/// use disk_usage_clone::entry::{DiskEntry, EntryType, SizeMode};
 /// use std::path::PathBuf;
 ///
 /// let mut dir = DiskEntry::new(
@@ -314,11 +447,11 @@ pub fn render_entry(entry: &DiskEntry, human_readable: bool, use_color: bool) ->
 /// ));
 ///
 /// // Summarize: show only total
-/// let output = render_tree(&dir, true, false, true, false);
+/// let output = render_tree(&dir, true, false, true, false, SizeMode::Apparent, None);
 /// assert_eq!(output, "5.0K\t/tmp");
 ///
 /// // Show all: directories and files
-/// let output = render_tree(&dir, false, true, false, false);
+/// let output = render_tree(&dir, false, true, false, false, SizeMode::Apparent, None);
 /// let lines: Vec<&str> = output.lines().collect();
 /// assert_eq!(lines.len(), 2);
 /// assert_eq!(lines[0], "1024\t/tmp/file.txt");  // Child first
@@ -330,15 +463,31 @@ pub fn render_tree(
     show_all: bool,
     summarize: bool,
     use_color: bool,
+    size_mode: SizeMode,
+    time: Option<TimeKind>,
 ) -> String {
     let mut lines = Vec::new();
 
     if summarize {
         // Summarize mode: only show the root
-        lines.push(render_entry(entry, human_readable, use_color));
+        lines.push(render_entry(
+            entry,
+            human_readable,
+            use_color,
+            size_mode,
+            time,
+        ));
     } else {
         // Normal mode: recursively collect lines
-        collect_lines(entry, human_readable, show_all, use_color, &mut lines);
+        collect_lines(
+            entry,
+            human_readable,
+            show_all,
+            use_color,
+            size_mode,
+            time,
+            &mut lines,
+        );
     }
 
     // Join all lines with newlines
@@ -359,7 +508,7 @@ pub fn render_tree(
 ///
 /// # Filtering
 ///
-/// - Directories always shown
+/// - Directories and skipped mount points always shown
 /// - Files shown only if `show_all = true`
 /// - Other entry types treated like files
 ///
@@ -369,13 +518,15 @@ pub fn render_tree(
 /// * `human_readable` - Format sizes?
 /// * `show_all` - Show files?
 /// * `use_color` - Colorize output?
+/// * `size_mode` - Apparent or allocated sizes?
+/// * `time` - Timestamp column, if any
 /// * `lines` - Output accumulator (mutated)
 ///
 /// # Examples
 ///
 /// ```ignore
 /// let mut lines = Vec::new();
-/// collect_lines(&root, true, false, false, &mut lines);
+/// collect_lines(&root, true, false, false, SizeMode::Allocated, None, &mut lines);
 /// for line in lines {
 ///     println!("{}", line);
 /// }
@@ -385,23 +536,167 @@ fn collect_lines(
     human_readable: bool,
     show_all: bool,
     use_color: bool,
+    size_mode: SizeMode,
+    time: Option<TimeKind>,
     lines: &mut Vec<String>,
 ) {
     // Determine if we should show this entry
     let should_show = match entry.entry_type {
-        EntryType::Directory => true,      // Always show directories
-        _ => show_all,                      // Files only if show_all
+        // Always show directories, skipped mount points and `--top` remainders
+        EntryType::Directory | EntryType::MountPoint | EntryType::Aggregate => true,
+        _ => show_all, // Files only if show_all
     };
 
     // Recurse into children first (depth-first traversal)
     // This ensures children appear before parents in output
     for child in &entry.children {
-        collect_lines(child, human_readable, show_all, use_color, lines);
+        collect_lines(
+            child,
+            human_readable,
+            show_all,
+            use_color,
+            size_mode,
+            time,
+            lines,
+        );
     }
 
     // After processing children, add this entry
     if should_show {
-        lines.push(render_entry(entry, human_readable, use_color));
+        lines.push(render_entry(
+            entry,
+            human_readable,
+            use_color,
+            size_mode,
+            time,
+        ));
+    }
+}
+
+/// Renders trees as CSV, one row per entry, for spreadsheet import.
+///
+/// The output starts with a single header row, followed by the rows of every
+/// tree in order. Rows follow the text output: children before parents,
+/// files only with `show_all`, only the roots with `summarize`. Each root is
+/// always included, even when it is a file.
+///
+/// # Columns
+///
+/// | Column | Description |
+/// |--------|-------------|
+/// | `path` | Full path of the entry |
+/// | `size_bytes` | Total size in bytes, in the selected [`SizeMode`] |
+/// | `size_human` | Same size with K/M/G/T suffix |
+/// | `type` | `file`, `directory`, `symlink`, `other`, `mount_point` or `aggregate` (the `<K other entries>` row of `--top`) |
+/// | `depth` | Depth below the analyzed path (0 = the path itself) |
+/// | `parent` | Path of the containing directory (empty if there is none) |
+/// | `incomplete` | `true` if a stopped scan didn't read everything below it |
+///
+/// Fields containing commas, double quotes or line breaks are quoted as
+/// described in RFC 4180. The output never contains color codes.
+///
+/// # Arguments
+///
+/// * `trees` - Roots of the analyzed paths, in command-line order
+/// * `show_all` - Show files, or directories only?
+/// * `summarize` - Show only the roots?
+/// * `size_mode` - Show apparent or allocated sizes?
+///
+/// # Examples
+///
+/// ```
+/// use disk_usage_clone::output::render_csv;
+/// use disk_usage_clone::entry::{DiskEntry, EntryType, SizeMode};
+/// use std::path::PathBuf;
+///
+/// let mut dir = DiskEntry::new(PathBuf::from("/tmp"), 4096, EntryType::Directory, 0);
+/// dir.children.push(DiskEntry::new(
+///     PathBuf::from("/tmp/a,b.txt"),
+///     1024,
+///     EntryType::File,
+///     1,
+/// ));
+///
+/// let output = render_csv(&[dir], true, false, SizeMode::Apparent);
+/// let lines: Vec<&str> = output.lines().collect();
+/// assert_eq!(lines[0], "path,size_bytes,size_human,type,depth,parent,incomplete");
+/// assert_eq!(lines[1], "\"/tmp/a,b.txt\",1024,1.0K,file,1,/tmp,false");
+/// assert_eq!(lines[2], "/tmp,5120,5.0K,directory,0,/,false");
+/// ```
+pub fn render_csv(
+    trees: &[DiskEntry],
+    show_all: bool,
+    summarize: bool,
+    size_mode: SizeMode,
+) -> String {
+    let mut rows = vec![CSV_HEADER.to_string()];
+
+    for tree in trees {
+        if summarize {
+            rows.push(csv_row(tree, size_mode));
+        } else {
+            collect_csv_rows(tree, show_all, size_mode, &mut rows);
+        }
+    }
+
+    rows.join("\n")
+}
+
+/// Recursively collects CSV rows in post-order (children first).
+///
+/// Uses the same filtering as [`collect_lines`], except that the root of the
+/// tree is always included.
+fn collect_csv_rows(
+    entry: &DiskEntry,
+    show_all: bool,
+    size_mode: SizeMode,
+    rows: &mut Vec<String>,
+) {
+    let should_show = match entry.entry_type {
+        EntryType::Directory | EntryType::MountPoint | EntryType::Aggregate => true,
+        _ => show_all || entry.depth == 0,
+    };
+
+    for child in &entry.children {
+        collect_csv_rows(child, show_all, size_mode, rows);
+    }
+
+    if should_show {
+        rows.push(csv_row(entry, size_mode));
+    }
+}
+
+/// Formats one entry as a CSV row (without line terminator).
+fn csv_row(entry: &DiskEntry, size_mode: SizeMode) -> String {
+    let size = entry.total_size_for(size_mode);
+    let parent = entry
+        .path
+        .parent()
+        .map(|p| p.display().to_string())
+        .unwrap_or_default();
+
+    [
+        csv_field(&entry.path.display().to_string()),
+        size.to_string(),
+        format_size(size, true),
+        entry.entry_type.as_str().to_string(),
+        entry.depth.to_string(),
+        csv_field(&parent),
+        entry.incomplete.to_string(),
+    ]
+    .join(",")
+}
+
+/// Quotes a CSV field if needed (RFC 4180).
+///
+/// Fields containing a comma, double quote, carriage return or newline are
+/// wrapped in double quotes, with embedded quotes doubled. Other fields are
+/// returned unchanged.
+pub(crate) fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
     }
 }
 
@@ -420,20 +715,96 @@ mod tests {
         entry
     }
 
+    #[test]
+    fn test_csv_field_quoting() {
+        assert_eq!(csv_field("plain/path"), "plain/path");
+        assert_eq!(csv_field("a,b"), "\"a,b\"");
+        assert_eq!(csv_field("say \"hi\""), "\"say \"\"hi\"\"\"");
+        assert_eq!(csv_field("line\nbreak"), "\"line\nbreak\"");
+        assert_eq!(csv_field("cr\rhere"), "\"cr\rhere\"");
+    }
+
+    #[test]
+    fn test_render_csv_rows() {
+        let mut root = make_dir("/data", 100, vec![make_file("/data/x.bin", 2048)]);
+        root.children.push({
+            let mut sub = make_dir("/data/sub", 50, Vec::new());
+            sub.depth = 1;
+            sub
+        });
+        let output = render_csv(&[root], true, false, SizeMode::Apparent);
+        let lines: Vec<&str> = output.lines().collect();
+        assert_eq!(lines.len(), 4);
+        assert_eq!(lines[0], CSV_HEADER);
+        assert_eq!(lines[1], "/data/x.bin,2048,2.0K,file,1,/data,false");
+        assert_eq!(lines[2], "/data/sub,50,50B,directory,1,/data,false");
+        assert_eq!(lines[3], "/data,2198,2.1K,directory,0,/,false");
+    }
+
+    #[test]
+    fn test_render_csv_directories_only_and_summarize() {
+        let root = make_dir("/data", 100, vec![make_file("/data/x.bin", 2048)]);
+        let output = render_csv(
+            std::slice::from_ref(&root),
+            false,
+            false,
+            SizeMode::Apparent,
+        );
+        assert_eq!(output.lines().count(), 2);
+
+        let output = render_csv(&[root.clone(), root], true, true, SizeMode::Apparent);
+        // One header for all trees, one row per root
+        assert_eq!(output.lines().count(), 3);
+    }
+
+    #[test]
+    fn test_render_csv_quotes_newline_in_path() {
+        let root = make_dir("/odd\nname", 0, Vec::new());
+        let output = render_csv(&[root], false, false, SizeMode::Apparent);
+        assert!(output.ends_with("\"/odd\nname\",0,0B,directory,0,/,false"));
+    }
+
+    #[test]
+    fn test_render_csv_no_color_codes() {
+        let root = make_dir("/data", 2 * GB, vec![make_file("/data/x.bin", 2048)]);
+        let output = render_csv(&[root], true, false, SizeMode::Apparent);
+        assert!(!output.contains('\x1b'));
+    }
+
+    #[test]
+    fn test_output_format_parse() {
+        for name in OutputFormat::NAMES {
+            assert!(OutputFormat::parse(name).is_some());
+        }
+        assert_eq!(OutputFormat::parse("bogus"), None);
+        assert_eq!(OutputFormat::default(), OutputFormat::Text);
+    }
+
     #[test]
     fn test_render_entry_no_color_raw() {
         let entry = make_file("test.txt", 1024);
-        let result = render_entry(&entry, false, false);
+        let result = render_entry(&entry, false, false, SizeMode::Apparent, None);
         assert_eq!(result, "1024\ttest.txt");
     }
 
     #[test]
     fn test_render_entry_no_color_human() {
         let entry = make_file("test.txt", 1024);
-        let result = render_entry(&entry, true, false);
+        let result = render_entry(&entry, true, false, SizeMode::Apparent, None);
         assert_eq!(result, "1.0K\ttest.txt");
     }
 
+    #[test]
+    fn test_render_entry_shows_link_target() {
+        let mut link = DiskEntry::new(PathBuf::from("/srv/current"), 7, EntryType::Symlink, 1);
+        link.link_target = Some(PathBuf::from("releases/42"));
+        let result = render_entry(&link, false, false, SizeMode::Apparent, None);
+        assert_eq!(result, "7\t/srv/current -> releases/42");
+
+        let colored = render_entry(&link, false, true, SizeMode::Apparent, None);
+        assert!(colored.ends_with(" -> releases/42"));
+    }
+
     #[test]
     fn test_render_entry_directory_total_size() {
         let dir = make_dir(
@@ -444,7 +815,7 @@ mod tests {
                 make_file("/mydir/b.txt", 200),
             ],
         );
-        let result = render_entry(&dir, false, false);
+        let result = render_entry(&dir, false, false, SizeMode::Apparent, None);
         // total_size = 4096 + 100 + 200 = 4396
         assert_eq!(result, "4396\t/mydir");
     }
@@ -452,14 +823,14 @@ mod tests {
     #[test]
     fn test_render_tree_summarize() {
         let dir = make_dir("/mydir", 4096, vec![make_file("/mydir/a.txt", 100)]);
-        let result = render_tree(&dir, false, false, true, false);
+        let result = render_tree(&dir, false, false, true, false, SizeMode::Apparent, None);
         assert_eq!(result, "4196\t/mydir");
     }
 
     #[test]
     fn test_render_tree_directories_only() {
         let dir = make_dir("/root", 100, vec![make_file("/root/file.txt", 50)]);
-        let result = render_tree(&dir, false, false, false, false);
+        let result = render_tree(&dir, false, false, false, false, SizeMode::Apparent, None);
         // Only directory should be shown (not file), and du prints children before parent
         assert_eq!(result, "150\t/root");
     }
@@ -467,7 +838,7 @@ mod tests {
     #[test]
     fn test_render_tree_show_all() {
         let dir = make_dir("/root", 100, vec![make_file("/root/file.txt", 50)]);
-        let result = render_tree(&dir, false, true, false, false);
+        let result = render_tree(&dir, false, true, false, false, SizeMode::Apparent, None);
         let lines: Vec<&str> = result.lines().collect();
         // du order: children first, then parent
         assert_eq!(lines.len(), 2);
@@ -485,7 +856,7 @@ mod tests {
         let mut root = DiskEntry::new(PathBuf::from("/root"), 100, EntryType::Directory, 0);
         root.children.push(inner);
 
-        let result = render_tree(&root, false, false, false, false);
+        let result = render_tree(&root, false, false, false, false, SizeMode::Apparent, None);
         let lines: Vec<&str> = result.lines().collect();
         // sub printed before root (du order)
         assert_eq!(lines.len(), 2);
@@ -497,7 +868,7 @@ mod tests {
     fn test_render_entry_with_color() {
         // Just verify it doesn't panic and produces non-empty output
         let entry = make_file("test.txt", 500);
-        let result = render_entry(&entry, true, true);
+        let result = render_entry(&entry, true, true, SizeMode::Apparent, None);
         assert!(!result.is_empty());
     }
 
@@ -517,19 +888,66 @@ mod tests {
         let _ = colorize_path("file.txt", &EntryType::File);
         let _ = colorize_path("link", &EntryType::Symlink);
         let _ = colorize_path("other", &EntryType::Other);
+        let _ = colorize_path("/mnt", &EntryType::MountPoint);
+        let _ = colorize_path("<2 other entries>", &EntryType::Aggregate);
+    }
+
+    #[test]
+    fn test_render_tree_shows_mount_points() {
+        let mount = DiskEntry::new(PathBuf::from("/root/proc"), 0, EntryType::MountPoint, 1);
+        let dir = make_dir("/root", 100, vec![mount, make_file("/root/file.txt", 50)]);
+        let result = render_tree(&dir, false, false, false, false, SizeMode::Apparent, None);
+        let lines: Vec<&str> = result.lines().collect();
+        // Mount point shown without --all, file hidden
+        assert_eq!(lines, vec!["0\t/root/proc [mount point]", "150\t/root"]);
+    }
+
+    #[test]
+    fn test_render_marks_incomplete_entries() {
+        let mut sub = make_dir("/root/sub", 10, vec![]);
+        sub.incomplete = true;
+        let mut dir = make_dir("/root", 100, vec![sub, make_dir("/root/done", 5, vec![])]);
+        dir.incomplete = true;
+
+        let result = render_tree(&dir, false, false, false, false, SizeMode::Apparent, None);
+        let lines: Vec<&str> = result.lines().collect();
+        assert_eq!(
+            lines,
+            vec![
+                "10\t/root/sub [incomplete]",
+                "5\t/root/done",
+                "115\t/root [incomplete]"
+            ]
+        );
+
+        let csv = render_csv(&[dir], false, false, SizeMode::Apparent);
+        assert!(csv.contains("\n/root/sub,10,10B,directory,0,/root,true\n"));
+        assert!(csv.ends_with("/root,115,115B,directory,0,/,true"));
     }
 
     #[test]
     fn test_render_tree_empty_dir() {
         let dir = DiskEntry::new(PathBuf::from("/empty"), 4096, EntryType::Directory, 0);
-        let result = render_tree(&dir, true, false, false, false);
+        let result = render_tree(&dir, true, false, false, false, SizeMode::Apparent, None);
         assert_eq!(result, "4.0K\t/empty");
     }
 
+    #[test]
+    fn test_render_tree_allocated_size_mode() {
+        let mut file = make_file("/data/tiny.txt", 10);
+        file.allocated_bytes = 4096;
+        let dir = make_dir("/data", 0, vec![file]);
+
+        let apparent = render_tree(&dir, false, true, false, false, SizeMode::Apparent, None);
+        let allocated = render_tree(&dir, false, true, false, false, SizeMode::Allocated, None);
+        assert_eq!(apparent, "10\t/data/tiny.txt\n10\t/data");
+        assert_eq!(allocated, "4096\t/data/tiny.txt\n4096\t/data");
+    }
+
     #[test]
     fn test_render_tree_human_readable() {
         let dir = make_dir("/data", 0, vec![make_file("/data/big.bin", 5 * MB)]);
-        let result = render_tree(&dir, true, true, false, false);
+        let result = render_tree(&dir, true, true, false, false, SizeMode::Apparent, None);
         let lines: Vec<&str> = result.lines().collect();
         assert_eq!(lines.len(), 2);
         assert!(lines[0].contains("5.0M"));
@@ -0,0 +1,594 @@
+//! Usage per owning user or group (`--by-user`, `--by-group`).
+//!
+//! For accounting on shared machines: every entry in the scanned trees is
+//! charged to its owner (see [`Owner`]), and each owner's total is broken
+//! down by top-level directory, i.e. by the subdirectories directly inside
+//! each analyzed path:
+//!
+//! ```text
+//! 12.0G   48211   alice
+//! 11.5G   47002   alice   /srv/build/alice-ws
+//! 500.0M  1209    alice   /srv/build/cache
+//! 3.1G    9120    bob
+//! 3.1G    9120    bob     /srv/build/bob-ws
+//! ```
+//!
+//! Directories count like any other entry, since their blocks are charged
+//! to their owner too. The analyzed path itself is one more row, holding
+//! the path's own size and the files directly inside it (a large image or
+//! archive next to the directories is charged there, not listed on its
+//! own).
+//!
+//! # Names
+//!
+//! Ids are resolved through the local databases, `/etc/passwd` and
+//! `/etc/group` (see [`NameTable`]). Ids without an entry there, e.g. from
+//! a removed account or a network directory service, are shown as numbers.
+//!
+//! # Output
+//!
+//! - `text` - An owner line `size<TAB>entries<TAB>owner`, followed by
+//!   `size<TAB>entries<TAB>owner<TAB>path` lines per top-level directory
+//! - `json` - One array of owner objects with nested `directories`
+//! - `ndjson` - One owner object per line
+//! - `csv` - One row per owner (empty `directory`) and per top-level directory
+//!
+//! # Examples
+//!
+//! ```
+//! use disk_usage_clone::entry::{DiskEntry, EntryType, Owner, SizeMode};
+//! use disk_usage_clone::group::GroupSort;
+//! use disk_usage_clone::owner::{NameTable, OwnerBy, usage_by_owner};
+//! use std::path::PathBuf;
+//!
+//! let alice = Some(Owner { uid: 1000, gid: 1000 });
+//! let mut root = DiskEntry::new(PathBuf::from("/srv"), 0, EntryType::Directory, 0);
+//! let mut dir = DiskEntry::new(PathBuf::from("/srv/build"), 100, EntryType::Directory, 1);
+//! dir.owner = alice;
+//! let mut file = DiskEntry::new(PathBuf::from("/srv/log"), 300, EntryType::File, 1);
+//! file.owner = alice;
+//! root.children.extend([dir, file]);
+//!
+//! let names = NameTable::parse("alice:x:1000:1000::/home/alice:/bin/sh\n", "");
+//! let usage = usage_by_owner(&[root], OwnerBy::User, &names, SizeMode::Apparent, GroupSort::Size);
+//! assert_eq!(usage[0].name, "alice");
+//! assert_eq!(usage[0].size(SizeMode::Apparent), 400);
+//! // The file directly in /srv is charged to /srv itself
+//! assert_eq!(usage[0].directories[0].path, PathBuf::from("/srv"));
+//! assert_eq!(usage[0].directories[1].path, PathBuf::from("/srv/build"));
+//! ```
+
+use std::cmp::Reverse;
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use colored::Colorize;
+use serde_json::{Value, json};
+
+use crate::entry::{DiskEntry, EntryType, Owner, SizeMode};
+use crate::formatter::format_size;
+use crate::group::GroupSort;
+use crate::output::{colorize_size, csv_field};
+
+/// Local user database.
+const PASSWD_FILE: &str = "/etc/passwd";
+
+/// Local group database.
+const GROUP_FILE: &str = "/etc/group";
+
+/// Header row of the CSV output, in column order.
+const CSV_HEADER: &str = "owner,id,directory,entries,size_bytes,size_human";
+
+/// Which id entries are charged to.
+///
+/// # Variants
+///
+/// - `User` - The owning user (`--by-user`)
+/// - `Group` - The owning group (`--by-group`)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OwnerBy {
+    User,
+    Group,
+}
+
+/// User and group names by id.
+#[derive(Debug, Clone, Default)]
+pub struct NameTable {
+    users: HashMap<u32, String>,
+    groups: HashMap<u32, String>,
+}
+
+impl NameTable {
+    /// Reads `/etc/passwd` and `/etc/group`.
+    ///
+    /// A missing or unreadable file just leaves its ids unresolved.
+    pub fn load() -> NameTable {
+        let read = |path: &str| fs::read_to_string(path).unwrap_or_default();
+        NameTable::parse(&read(PASSWD_FILE), &read(GROUP_FILE))
+    }
+
+    /// Builds a table from the contents of `passwd` and `group` files.
+    ///
+    /// Both formats start with `name:password:id:`; malformed lines and
+    /// comments are ignored. The first name listed for an id wins.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use disk_usage_clone::owner::{NameTable, OwnerBy};
+    ///
+    /// let names = NameTable::parse("root:x:0:0:root:/root:/bin/sh\n", "wheel:x:10:alice\n");
+    /// assert_eq!(names.name(OwnerBy::User, 0), "root");
+    /// assert_eq!(names.name(OwnerBy::Group, 10), "wheel");
+    /// assert_eq!(names.name(OwnerBy::User, 4242), "4242");
+    /// ```
+    pub fn parse(passwd: &str, group: &str) -> NameTable {
+        NameTable {
+            users: parse_database(passwd),
+            groups: parse_database(group),
+        }
+    }
+
+    /// Returns the name of a user or group id, or the id as a number.
+    pub fn name(&self, by: OwnerBy, id: u32) -> String {
+        let names = match by {
+            OwnerBy::User => &self.users,
+            OwnerBy::Group => &self.groups,
+        };
+        names.get(&id).cloned().unwrap_or_else(|| id.to_string())
+    }
+}
+
+/// Maps ids to names from `name:password:id:...` lines.
+fn parse_database(contents: &str) -> HashMap<u32, String> {
+    let mut names = HashMap::new();
+    for line in contents.lines() {
+        if line.starts_with('#') {
+            continue;
+        }
+        let mut fields = line.split(':');
+        if let (Some(name), Some(_), Some(id)) = (fields.next(), fields.next(), fields.next())
+            && let Ok(id) = id.parse()
+            && !name.is_empty()
+        {
+            names.entry(id).or_insert_with(|| name.to_string());
+        }
+    }
+    names
+}
+
+/// Usage of one owner below one top-level directory.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DirectoryUsage {
+    /// Top-level directory, or the analyzed path itself for its own size
+    /// and the other entries directly inside it.
+    pub path: PathBuf,
+    /// Number of entries owned below it.
+    pub entries: u64,
+    /// Sum of their apparent sizes.
+    pub apparent_bytes: u64,
+    /// Sum of their allocated sizes.
+    pub allocated_bytes: u64,
+}
+
+impl DirectoryUsage {
+    /// Returns the total in the given mode.
+    pub fn size(&self, size_mode: SizeMode) -> u64 {
+        match size_mode {
+            SizeMode::Apparent => self.apparent_bytes,
+            SizeMode::Allocated => self.allocated_bytes,
+        }
+    }
+}
+
+/// Usage of one owner, with the breakdown per top-level directory.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OwnerUsage {
+    /// Numeric user or group id.
+    pub id: u32,
+    /// Resolved name, or the id as a number.
+    pub name: String,
+    /// Number of entries owned.
+    pub entries: u64,
+    /// Sum of their apparent sizes.
+    pub apparent_bytes: u64,
+    /// Sum of their allocated sizes.
+    pub allocated_bytes: u64,
+    /// Breakdown per top-level directory.
+    pub directories: Vec<DirectoryUsage>,
+}
+
+impl OwnerUsage {
+    /// Returns the total in the given mode.
+    pub fn size(&self, size_mode: SizeMode) -> u64 {
+        match size_mode {
+            SizeMode::Apparent => self.apparent_bytes,
+            SizeMode::Allocated => self.allocated_bytes,
+        }
+    }
+}
+
+/// Charges every entry of the trees to its owner.
+///
+/// Entries without a known owner (trees not built by a Unix traversal) are
+/// left out.
+///
+/// # Arguments
+///
+/// * `trees` - Scanned trees (uncollapsed, so every entry is present)
+/// * `by` - Charge users or groups
+/// * `names` - Names to show for the ids
+/// * `size_mode` - Which size `sort` compares
+/// * `sort` - Order of the owners and of each owner's breakdown; ties are
+///   broken by name or path
+pub fn usage_by_owner(
+    trees: &[DiskEntry],
+    by: OwnerBy,
+    names: &NameTable,
+    size_mode: SizeMode,
+    sort: GroupSort,
+) -> Vec<OwnerUsage> {
+    // id -> top-level path -> usage
+    let mut usage: HashMap<u32, HashMap<&Path, DirectoryUsage>> = HashMap::new();
+
+    for tree in trees {
+        // The analyzed path's own size is charged to itself
+        charge(&mut usage, by, tree, &tree.path);
+
+        for top in &tree.children {
+            // Only directories get a row; files directly inside go to the path
+            let row = if top.entry_type == EntryType::Directory {
+                &top.path
+            } else {
+                &tree.path
+            };
+
+            // Explicit stack: trees can be deeper than the call stack allows
+            let mut pending = vec![top];
+            while let Some(entry) = pending.pop() {
+                pending.extend(&entry.children);
+                charge(&mut usage, by, entry, row);
+            }
+        }
+    }
+
+    let mut owners: Vec<OwnerUsage> = usage
+        .into_iter()
+        .map(|(id, directories)| {
+            let mut directories: Vec<DirectoryUsage> = directories.into_values().collect();
+            directories.sort_by(|a, b| a.path.cmp(&b.path));
+            sort_by_size(&mut directories, sort, |d| d.size(size_mode));
+
+            OwnerUsage {
+                id,
+                name: names.name(by, id),
+                entries: directories.iter().map(|d| d.entries).sum(),
+                apparent_bytes: directories.iter().map(|d| d.apparent_bytes).sum(),
+                allocated_bytes: directories.iter().map(|d| d.allocated_bytes).sum(),
+                directories,
+            }
+        })
+        .collect();
+
+    owners.sort_by(|a, b| a.name.cmp(&b.name).then(a.id.cmp(&b.id)));
+    sort_by_size(&mut owners, sort, |o| o.size(size_mode));
+    owners
+}
+
+/// Adds one entry to its owner's usage below `top`.
+fn charge<'a>(
+    usage: &mut HashMap<u32, HashMap<&'a Path, DirectoryUsage>>,
+    by: OwnerBy,
+    entry: &DiskEntry,
+    top: &'a Path,
+) {
+    let Some(Owner { uid, gid }) = entry.owner else {
+        return;
+    };
+    let id = match by {
+        OwnerBy::User => uid,
+        OwnerBy::Group => gid,
+    };
+
+    let directory = usage
+        .entry(id)
+        .or_default()
+        .entry(top)
+        .or_insert_with(|| DirectoryUsage {
+            path: top.to_path_buf(),
+            entries: 0,
+            apparent_bytes: 0,
+            allocated_bytes: 0,
+        });
+    directory.entries += 1;
+    directory.apparent_bytes += entry.size_bytes;
+    directory.allocated_bytes += entry.allocated_bytes;
+}
+
+/// Stable-sorts by size as requested; `Name` keeps the existing order.
+fn sort_by_size<T>(items: &mut [T], sort: GroupSort, size: impl Fn(&T) -> u64) {
+    match sort {
+        GroupSort::Size => items.sort_by_key(|item| Reverse(size(item))),
+        GroupSort::SizeAscending => items.sort_by_key(|item| size(item)),
+        GroupSort::Name => {}
+    }
+}
+
+/// Renders usage as owner lines, each followed by its top-level directories.
+pub fn render_owners(
+    owners: &[OwnerUsage],
+    human_readable: bool,
+    use_color: bool,
+    size_mode: SizeMode,
+) -> String {
+    let size_field = |size: u64| {
+        let size_str = format_size(size, human_readable);
+        if use_color {
+            colorize_size(&size_str, size)
+        } else {
+            size_str
+        }
+    };
+
+    let mut lines = Vec::new();
+    for owner in owners {
+        let name = if use_color {
+            owner.name.bold().to_string()
+        } else {
+            owner.name.clone()
+        };
+        lines.push(format!(
+            "{}\t{}\t{name}",
+            size_field(owner.size(size_mode)),
+            owner.entries
+        ));
+        for directory in &owner.directories {
+            lines.push(format!(
+                "{}\t{}\t{name}\t{}",
+                size_field(directory.size(size_mode)),
+                directory.entries,
+                directory.path.display()
+            ));
+        }
+    }
+    lines.join("\n")
+}
+
+/// Builds the JSON object describing one owner.
+fn owner_object(owner: &OwnerUsage, size_mode: SizeMode) -> Value {
+    let directories: Vec<Value> = owner
+        .directories
+        .iter()
+        .map(|directory| {
+            json!({
+                "path": directory.path.display().to_string(),
+                "entries": directory.entries,
+                "size": directory.size(size_mode),
+                "apparent_size": directory.apparent_bytes,
+                "allocated_size": directory.allocated_bytes,
+            })
+        })
+        .collect();
+
+    json!({
+        "owner": owner.name,
+        "id": owner.id,
+        "entries": owner.entries,
+        "size": owner.size(size_mode),
+        "apparent_size": owner.apparent_bytes,
+        "allocated_size": owner.allocated_bytes,
+        "directories": directories,
+    })
+}
+
+/// Renders usage as one pretty-printed JSON array (`--format json`).
+pub fn render_owners_json(owners: &[OwnerUsage], size_mode: SizeMode) -> String {
+    let values: Vec<Value> = owners.iter().map(|o| owner_object(o, size_mode)).collect();
+    // Serializing a Value cannot fail
+    serde_json::to_string_pretty(&Value::Array(values)).unwrap_or_default()
+}
+
+/// Renders usage as one JSON object per owner and line (`--format ndjson`).
+pub fn render_owners_ndjson(owners: &[OwnerUsage], size_mode: SizeMode) -> String {
+    owners
+        .iter()
+        .map(|o| owner_object(o, size_mode).to_string())
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Renders usage as CSV (`--format csv`).
+///
+/// Each owner has a total row with an empty `directory`, followed by one row
+/// per top-level directory.
+pub fn render_owners_csv(owners: &[OwnerUsage], size_mode: SizeMode) -> String {
+    let row = |owner: &OwnerUsage, directory: &str, entries: u64, size: u64| {
+        format!(
+            "{},{},{},{entries},{size},{}",
+            csv_field(&owner.name),
+            owner.id,
+            csv_field(directory),
+            format_size(size, true)
+        )
+    };
+
+    let mut rows = vec![CSV_HEADER.to_string()];
+    for owner in owners {
+        rows.push(row(owner, "", owner.entries, owner.size(size_mode)));
+        for directory in &owner.directories {
+            rows.push(row(
+                owner,
+                &directory.path.display().to_string(),
+                directory.entries,
+                directory.size(size_mode),
+            ));
+        }
+    }
+    rows.join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(path: &str, size: u64, entry_type: EntryType, uid: u32, gid: u32) -> DiskEntry {
+        let mut entry = DiskEntry::new(PathBuf::from(path), size, entry_type, 0);
+        entry.owner = Some(Owner { uid, gid });
+        entry
+    }
+
+    /// /srv (root) with alice's and bob's workspaces, a shared cache and
+    /// alice's disk image.
+    fn sample() -> DiskEntry {
+        let mut alice = entry("/srv/alice", 10, EntryType::Directory, 1000, 100);
+        alice.children = vec![
+            entry("/srv/alice/a.o", 500, EntryType::File, 1000, 100),
+            entry("/srv/alice/b.o", 200, EntryType::File, 1000, 100),
+        ];
+        let mut bob = entry("/srv/bob", 10, EntryType::Directory, 1001, 100);
+        bob.children = vec![entry("/srv/bob/out", 300, EntryType::File, 1001, 100)];
+        let mut cache = entry("/srv/cache", 10, EntryType::Directory, 0, 0);
+        cache.children = vec![
+            entry("/srv/cache/x", 40, EntryType::File, 1000, 100),
+            entry("/srv/cache/y", 60, EntryType::File, 4242, 4242),
+        ];
+        let image = entry("/srv/sparse.img", 1000, EntryType::File, 1000, 100);
+        let mut root = entry("/srv", 5, EntryType::Directory, 0, 0);
+        root.children = vec![alice, bob, cache, image];
+        root
+    }
+
+    fn names() -> NameTable {
+        NameTable::parse(
+            "# local users\nroot:x:0:0:root:/root:/bin/sh\nalice:x:1000:100::/home/alice:/bin/sh\nbob:x:1001:100::/home/bob:/bin/sh\nbroken line\n",
+            "root:x:0:\nstaff:x:100:alice,bob\n",
+        )
+    }
+
+    #[test]
+    fn test_usage_by_user() {
+        let owners = usage_by_owner(
+            &[sample()],
+            OwnerBy::User,
+            &names(),
+            SizeMode::Apparent,
+            GroupSort::Size,
+        );
+
+        let totals: Vec<(&str, u64, u64)> = owners
+            .iter()
+            .map(|o| (o.name.as_str(), o.entries, o.apparent_bytes))
+            .collect();
+        assert_eq!(
+            totals,
+            vec![
+                ("alice", 5, 1750),
+                ("bob", 2, 310),
+                ("4242", 1, 60),
+                ("root", 2, 15),
+            ]
+        );
+
+        let alice: Vec<(&Path, u64)> = owners[0]
+            .directories
+            .iter()
+            .map(|d| (d.path.as_path(), d.apparent_bytes))
+            .collect();
+        // The image directly in the root is charged to the root, not listed
+        assert_eq!(
+            alice,
+            vec![
+                (Path::new("/srv"), 1000),
+                (Path::new("/srv/alice"), 710),
+                (Path::new("/srv/cache"), 40)
+            ]
+        );
+        // The root's own size is charged to the root path
+        assert_eq!(owners[3].directories[0].path, Path::new("/srv/cache"));
+        assert_eq!(owners[3].directories[1].path, Path::new("/srv"));
+    }
+
+    #[test]
+    fn test_usage_by_group() {
+        let owners = usage_by_owner(
+            &[sample()],
+            OwnerBy::Group,
+            &names(),
+            SizeMode::Apparent,
+            GroupSort::Name,
+        );
+        let totals: Vec<(&str, u64)> = owners
+            .iter()
+            .map(|o| (o.name.as_str(), o.apparent_bytes))
+            .collect();
+        assert_eq!(totals, vec![("4242", 60), ("root", 15), ("staff", 2060)]);
+    }
+
+    #[test]
+    fn test_entries_without_owner_are_skipped() {
+        let mut tree = sample();
+        tree.children.push(DiskEntry::new(
+            PathBuf::from("/srv/unknown"),
+            99,
+            EntryType::File,
+            1,
+        ));
+        let owners = usage_by_owner(
+            &[tree],
+            OwnerBy::User,
+            &names(),
+            SizeMode::Apparent,
+            GroupSort::Size,
+        );
+        let total: u64 = owners.iter().map(|o| o.apparent_bytes).sum();
+        assert_eq!(total, 2135);
+    }
+
+    #[test]
+    fn test_name_table_falls_back_to_ids() {
+        let names = NameTable::parse("", "");
+        assert_eq!(names.name(OwnerBy::User, 1000), "1000");
+        assert_eq!(names.name(OwnerBy::Group, 0), "0");
+    }
+
+    #[test]
+    fn test_render_owners_formats() {
+        let mut tree = entry("/r", 0, EntryType::Directory, 0, 0);
+        let mut dir = entry("/r/a", 0, EntryType::Directory, 1000, 100);
+        dir.children = vec![entry("/r/a/f", 100, EntryType::File, 1000, 100)];
+        tree.children = vec![dir];
+        let owners = usage_by_owner(
+            &[tree],
+            OwnerBy::User,
+            &names(),
+            SizeMode::Apparent,
+            GroupSort::Size,
+        );
+
+        assert_eq!(
+            render_owners(&owners, false, false, SizeMode::Apparent),
+            "100\t2\talice\n100\t2\talice\t/r/a\n0\t1\troot\n0\t1\troot\t/r"
+        );
+        assert_eq!(
+            render_owners_csv(&owners, SizeMode::Apparent),
+            "owner,id,directory,entries,size_bytes,size_human\n\
+             alice,1000,,2,100,100B\n\
+             alice,1000,/r/a,2,100,100B\n\
+             root,0,,1,0,0B\n\
+             root,0,/r,1,0,0B"
+        );
+
+        let parsed: Value =
+            serde_json::from_str(&render_owners_json(&owners, SizeMode::Apparent)).unwrap();
+        assert_eq!(parsed[0]["owner"], "alice");
+        assert_eq!(parsed[0]["id"], 1000);
+        assert_eq!(parsed[0]["directories"][0]["path"], "/r/a");
+        assert_eq!(
+            render_owners_ndjson(&owners, SizeMode::Apparent)
+                .lines()
+                .count(),
+            2
+        );
+    }
+}
@@ -0,0 +1,438 @@
+//! Progress reporting during scans.
+//!
+//! A scan of a large volume can take minutes. Traversals report how far
+//! they got through a [`ProgressHandler`] set in
+//! [`TraversalOptions::progress`](crate::traversal::TraversalOptions::progress):
+//!
+//! - While walking: entries and bytes counted so far, the directory just
+//!   read, elapsed time (see [`Progress`])
+//! - Once when the walk is over and the tree is being built
+//!   ([`ScanPhase::BuildingTree`]; the streaming traversal builds as it
+//!   walks and skips it)
+//! - Once when the scan is done ([`ScanPhase::Done`])
+//!
+//! Walking reports are throttled to one every 100 ms, so handlers may do
+//! I/O; phase changes are always reported. Handlers run on traversal
+//! worker threads, one call at a time.
+//!
+//! The command line shows a [`ProgressLine`] on stderr when it is a
+//! terminal, erased before the results are printed.
+//!
+//! # Examples
+//!
+//! ```no_run
+//! use disk_usage_clone::progress::ProgressHandler;
+//! use disk_usage_clone::traversal::{TraversalOptions, traverse_parallel_with_options};
+//! use std::path::Path;
+//!
+//! let (handler, updates) = ProgressHandler::channel();
+//! let options = TraversalOptions {
+//!     progress: Some(handler),
+//!     ..TraversalOptions::default()
+//! };
+//! let scan = std::thread::spawn(move || traverse_parallel_with_options(Path::new("/srv"), &options));
+//! for progress in updates {
+//!     eprintln!("{} entries, {:.0}/s", progress.entries, progress.rate());
+//! }
+//! let tree = scan.join().unwrap().unwrap();
+//! ```
+
+use std::fmt;
+use std::io::{IsTerminal, Write};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Arc, Mutex, PoisonError, mpsc};
+use std::time::{Duration, Instant};
+
+use crate::entry::SizeMode;
+use crate::formatter::format_size;
+
+/// Minimum time between two walking reports.
+const REPORT_INTERVAL: Duration = Duration::from_millis(100);
+
+/// What a scan is doing.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ScanPhase {
+    /// Reading directories and entry metadata.
+    Walking,
+    /// Assembling the tree from the collected entries.
+    BuildingTree,
+    /// Finished; the tree is about to be returned.
+    Done,
+}
+
+/// A snapshot of a running scan.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Progress {
+    /// What the scan is doing.
+    pub phase: ScanPhase,
+    /// Entries counted so far (files, directories and others).
+    pub entries: u64,
+    /// Apparent size of those entries, in bytes.
+    pub apparent_bytes: u64,
+    /// Disk space allocated to those entries, in bytes.
+    pub allocated_bytes: u64,
+    /// Directory read most recently, if any.
+    pub current_dir: Option<PathBuf>,
+    /// Time since the scan started.
+    pub elapsed: Duration,
+}
+
+impl Progress {
+    /// Returns the bytes counted so far in the given mode.
+    pub fn bytes(&self, mode: SizeMode) -> u64 {
+        match mode {
+            SizeMode::Apparent => self.apparent_bytes,
+            SizeMode::Allocated => self.allocated_bytes,
+        }
+    }
+
+    /// Returns the average number of entries per second so far.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use disk_usage_clone::progress::{Progress, ScanPhase};
+    /// use std::time::Duration;
+    ///
+    /// let progress = Progress {
+    ///     phase: ScanPhase::Walking,
+    ///     entries: 5000,
+    ///     apparent_bytes: 0,
+    ///     allocated_bytes: 0,
+    ///     current_dir: None,
+    ///     elapsed: Duration::from_secs(2),
+    /// };
+    /// assert_eq!(progress.rate(), 2500.0);
+    /// ```
+    pub fn rate(&self) -> f64 {
+        let secs = self.elapsed.as_secs_f64();
+        if secs > 0.0 {
+            self.entries as f64 / secs
+        } else {
+            0.0
+        }
+    }
+}
+
+/// Receives progress reports from a traversal.
+///
+/// Cheap to clone; clones share the same handler.
+#[derive(Clone)]
+pub struct ProgressHandler(Arc<dyn Fn(&Progress) + Send + Sync>);
+
+impl ProgressHandler {
+    /// Creates a handler that calls `f` with every report.
+    pub fn new(f: impl Fn(&Progress) + Send + Sync + 'static) -> ProgressHandler {
+        ProgressHandler(Arc::new(f))
+    }
+
+    /// Creates a handler that sends every report over a channel.
+    ///
+    /// The receiver sees the reports in order and is disconnected once the
+    /// handler (and the options holding it) are dropped. Reports sent
+    /// after the receiver is dropped are discarded.
+    pub fn channel() -> (ProgressHandler, mpsc::Receiver<Progress>) {
+        let (sender, receiver) = mpsc::channel();
+        let handler = ProgressHandler::new(move |progress| {
+            let _ = sender.send(progress.clone());
+        });
+        (handler, receiver)
+    }
+
+    /// Calls the handler.
+    fn report(&self, progress: &Progress) {
+        (self.0)(progress);
+    }
+}
+
+impl fmt::Debug for ProgressHandler {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("ProgressHandler")
+    }
+}
+
+/// Counts what a traversal has seen and reports it to a handler.
+///
+/// Shared by all workers of a scan. Without a handler every call returns
+/// immediately.
+pub(crate) struct ProgressTracker {
+    handler: Option<ProgressHandler>,
+    started: Instant,
+    entries: AtomicU64,
+    apparent_bytes: AtomicU64,
+    allocated_bytes: AtomicU64,
+    /// Time of the last report; held while reporting, so reports never overlap.
+    last_report: Mutex<Instant>,
+}
+
+impl ProgressTracker {
+    /// Starts tracking a scan.
+    pub(crate) fn new(handler: Option<&ProgressHandler>) -> ProgressTracker {
+        let started = Instant::now();
+        ProgressTracker {
+            handler: handler.cloned(),
+            started,
+            entries: AtomicU64::new(0),
+            apparent_bytes: AtomicU64::new(0),
+            allocated_bytes: AtomicU64::new(0),
+            last_report: Mutex::new(started),
+        }
+    }
+
+    /// Counts entries read in `dir`, reporting if the last report is old
+    /// enough and no other worker is reporting.
+    pub(crate) fn add(&self, dir: &Path, entries: u64, apparent: u64, allocated: u64) {
+        let Some(handler) = &self.handler else {
+            return;
+        };
+        self.entries.fetch_add(entries, Ordering::Relaxed);
+        self.apparent_bytes.fetch_add(apparent, Ordering::Relaxed);
+        self.allocated_bytes.fetch_add(allocated, Ordering::Relaxed);
+
+        // Busy workers skip the report rather than wait for it
+        let Ok(mut last_report) = self.last_report.try_lock() else {
+            return;
+        };
+        let now = Instant::now();
+        if now.duration_since(*last_report) >= REPORT_INTERVAL {
+            *last_report = now;
+            handler.report(&self.snapshot(ScanPhase::Walking, Some(dir)));
+        }
+    }
+
+    /// Reports a phase change.
+    pub(crate) fn phase(&self, phase: ScanPhase) {
+        if let Some(handler) = &self.handler {
+            let _guard = self
+                .last_report
+                .lock()
+                .unwrap_or_else(PoisonError::into_inner);
+            handler.report(&self.snapshot(phase, None));
+        }
+    }
+
+    /// Returns the current counts.
+    fn snapshot(&self, phase: ScanPhase, dir: Option<&Path>) -> Progress {
+        Progress {
+            phase,
+            entries: self.entries.load(Ordering::Relaxed),
+            apparent_bytes: self.apparent_bytes.load(Ordering::Relaxed),
+            allocated_bytes: self.allocated_bytes.load(Ordering::Relaxed),
+            current_dir: dir.map(Path::to_path_buf),
+            elapsed: self.started.elapsed(),
+        }
+    }
+}
+
+/// A one-line progress display on stderr, for the command line.
+///
+/// Each report overwrites the line:
+///
+/// ```text
+/// 1,234,567 entries  45.6G  12.3s  100,370/s  /srv/storage/projects/alpha
+/// ```
+///
+/// The line is erased when the scan is done, and at the latest when the
+/// `ProgressLine` is dropped, so output printed afterwards starts on a
+/// clean line.
+pub struct ProgressLine {
+    /// Whether something is currently drawn; shared with the handler.
+    drawn: Arc<AtomicBool>,
+    size_mode: SizeMode,
+}
+
+impl ProgressLine {
+    /// Creates a progress line if stderr is a terminal.
+    pub fn for_stderr(size_mode: SizeMode) -> Option<ProgressLine> {
+        std::io::stderr().is_terminal().then(|| ProgressLine {
+            drawn: Arc::new(AtomicBool::new(false)),
+            size_mode,
+        })
+    }
+
+    /// Returns a handler that draws reports on this line.
+    pub fn handler(&self) -> ProgressHandler {
+        let drawn = Arc::clone(&self.drawn);
+        let size_mode = self.size_mode;
+        ProgressHandler::new(move |progress| {
+            let mut stderr = std::io::stderr().lock();
+            if progress.phase == ScanPhase::Done {
+                if drawn.swap(false, Ordering::Relaxed) {
+                    let _ = write!(stderr, "\r\x1b[2K");
+                }
+            } else {
+                let width = crossterm::terminal::size().map_or(80, |(columns, _)| columns);
+                let line = format_progress(progress, size_mode, usize::from(width));
+                let _ = write!(stderr, "\r\x1b[2K{line}");
+                drawn.store(true, Ordering::Relaxed);
+            }
+            let _ = stderr.flush();
+        })
+    }
+
+    /// Erases the line if something is drawn.
+    pub fn clear(&self) {
+        if self.drawn.swap(false, Ordering::Relaxed) {
+            let mut stderr = std::io::stderr().lock();
+            let _ = write!(stderr, "\r\x1b[2K");
+            let _ = stderr.flush();
+        }
+    }
+}
+
+impl Drop for ProgressLine {
+    fn drop(&mut self) {
+        self.clear();
+    }
+}
+
+/// Formats a report as a single line of at most `width` characters.
+///
+/// The current directory is shortened from the left (`…/deep/dir`) to fit;
+/// the counters are never cut.
+///
+/// # Examples
+///
+/// ```
+/// use disk_usage_clone::entry::SizeMode;
+/// use disk_usage_clone::progress::{Progress, ScanPhase, format_progress};
+/// use std::path::PathBuf;
+/// use std::time::Duration;
+///
+/// let progress = Progress {
+///     phase: ScanPhase::Walking,
+///     entries: 12_345,
+///     apparent_bytes: 1536,
+///     allocated_bytes: 4096,
+///     current_dir: Some(PathBuf::from("/srv/data")),
+///     elapsed: Duration::from_secs(2),
+/// };
+/// assert_eq!(
+///     format_progress(&progress, SizeMode::Apparent, 80),
+///     "12,345 entries  1.5K  2.0s  6,173/s  /srv/data"
+/// );
+/// ```
+pub fn format_progress(progress: &Progress, size_mode: SizeMode, width: usize) -> String {
+    let mut line = format!(
+        "{} entries  {}  {:.1}s  {}/s",
+        group_thousands(progress.entries),
+        format_size(progress.bytes(size_mode), true),
+        progress.elapsed.as_secs_f64(),
+        group_thousands(progress.rate().round() as u64),
+    );
+    match progress.phase {
+        ScanPhase::BuildingTree => line.push_str("  building tree"),
+        _ => {
+            if let Some(dir) = &progress.current_dir {
+                let dir = dir.display().to_string();
+                let room = width.saturating_sub(line.chars().count() + 2);
+                if room > 1 {
+                    line.push_str("  ");
+                    line.push_str(&shorten_left(&dir, room));
+                }
+            }
+        }
+    }
+    line
+}
+
+/// Keeps the last `max` characters of `s`, marking a cut with `…`.
+fn shorten_left(s: &str, max: usize) -> String {
+    let count = s.chars().count();
+    if count <= max {
+        return s.to_string();
+    }
+    let kept: String = s.chars().skip(count - (max - 1)).collect();
+    format!("…{kept}")
+}
+
+/// Formats a count with `,` between groups of three digits.
+fn group_thousands(n: u64) -> String {
+    let digits = n.to_string();
+    let mut grouped = String::new();
+    for (i, digit) in digits.chars().enumerate() {
+        if i > 0 && (digits.len() - i).is_multiple_of(3) {
+            grouped.push(',');
+        }
+        grouped.push(digit);
+    }
+    grouped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample(phase: ScanPhase, dir: Option<&str>) -> Progress {
+        Progress {
+            phase,
+            entries: 1_234_567,
+            apparent_bytes: 10,
+            allocated_bytes: 2 * 1024 * 1024,
+            current_dir: dir.map(PathBuf::from),
+            elapsed: Duration::from_millis(12_340),
+        }
+    }
+
+    #[test]
+    fn test_group_thousands() {
+        assert_eq!(group_thousands(0), "0");
+        assert_eq!(group_thousands(999), "999");
+        assert_eq!(group_thousands(1000), "1,000");
+        assert_eq!(group_thousands(1_234_567), "1,234,567");
+    }
+
+    #[test]
+    fn test_format_progress_uses_size_mode() {
+        let line = format_progress(&sample(ScanPhase::Walking, None), SizeMode::Allocated, 80);
+        assert_eq!(line, "1,234,567 entries  2.0M  12.3s  100,046/s");
+    }
+
+    #[test]
+    fn test_format_progress_shortens_directory() {
+        let progress = sample(ScanPhase::Walking, Some("/a/very/long/directory/name"));
+        let line = format_progress(&progress, SizeMode::Apparent, 50);
+        assert_eq!(line.chars().count(), 50);
+        assert!(line.ends_with("…ry/name"), "{line}");
+
+        // No room at all: the directory is left out
+        let line = format_progress(&progress, SizeMode::Apparent, 20);
+        assert!(line.ends_with("/s"));
+    }
+
+    #[test]
+    fn test_format_progress_building_tree() {
+        let progress = sample(ScanPhase::BuildingTree, None);
+        assert!(format_progress(&progress, SizeMode::Apparent, 80).ends_with("  building tree"));
+    }
+
+    #[test]
+    fn test_tracker_throttles_walking_reports() {
+        let (handler, updates) = ProgressHandler::channel();
+        let tracker = ProgressTracker::new(Some(&handler));
+        for _ in 0..1000 {
+            tracker.add(Path::new("/d"), 1, 10, 20);
+        }
+        tracker.phase(ScanPhase::Done);
+        drop((tracker, handler));
+
+        let reports: Vec<Progress> = updates.iter().collect();
+        // The loop takes far less than a report interval
+        assert_eq!(reports.len(), 1);
+        let done = &reports[0];
+        assert_eq!(done.phase, ScanPhase::Done);
+        assert_eq!(
+            (done.entries, done.apparent_bytes, done.allocated_bytes),
+            (1000, 10_000, 20_000)
+        );
+    }
+
+    #[test]
+    fn test_tracker_without_handler_is_inert() {
+        let tracker = ProgressTracker::new(None);
+        tracker.add(Path::new("/d"), 5, 5, 5);
+        tracker.phase(ScanPhase::Done);
+        assert_eq!(tracker.entries.load(Ordering::Relaxed), 0);
+    }
+}
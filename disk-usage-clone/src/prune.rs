@@ -0,0 +1,358 @@
+//! Pruning a scanned tree before rendering (`--threshold`, `--top`).
+//!
+//! Both passes rewrite the [`DiskEntry`] tree in place and run after sorting,
+//! so every renderer (text, JSON, NDJSON, CSV) sees the same pruned tree.
+//! Neither pass changes a surviving entry's total: whatever is removed is
+//! folded into its parent, so the listed sizes still add up.
+//!
+//! # Threshold
+//!
+//! `-t SIZE` hides entries whose total is smaller than `SIZE`; a negative
+//! `-t -SIZE` hides entries larger than `SIZE` (like `du --threshold`):
+//!
+//! - A hidden entry's total moves into its parent's own size
+//! - With a negative threshold, the children of a hidden directory may still
+//!   be small enough to show; they are moved up to the nearest shown ancestor
+//! - The root of a tree is always shown
+//!
+//! # Top N
+//!
+//! `--top N` keeps the N largest entries of each directory. The rest are
+//! replaced by one synthetic [`EntryType::Aggregate`] entry:
+//!
+//! ```text
+//! 1.2G  /var/lib/docker
+//! 300M  /var/lib/apt
+//!  45M  /var/lib/<12 other entries>
+//! 1.5G  /var/lib
+//! ```
+//!
+//! Only entries that would be listed count towards N: directories always,
+//! files only with `--all`. Unlisted files stay where they are and are not
+//! rolled up.
+//!
+//! # Examples
+//!
+//! ```
+//! use disk_usage_clone::entry::{DiskEntry, EntryType, SizeMode};
+//! use disk_usage_clone::prune::keep_top;
+//! use std::path::PathBuf;
+//!
+//! let mut dir = DiskEntry::new(PathBuf::from("/d"), 0, EntryType::Directory, 0);
+//! for (name, size) in [("a", 30), ("b", 20), ("c", 10)] {
+//!     dir.children.push(DiskEntry::new(
+//!         PathBuf::from(format!("/d/{name}")),
+//!         size,
+//!         EntryType::File,
+//!         1,
+//!     ));
+//! }
+//!
+//! keep_top(&mut dir, 1, true, SizeMode::Apparent);
+//! assert_eq!(dir.children.len(), 2);
+//! assert_eq!(dir.children[1].path, PathBuf::from("/d/<2 other entries>"));
+//! assert_eq!(dir.children[1].size_bytes, 30);
+//! assert_eq!(dir.total_size_for(SizeMode::Apparent), 60);
+//! ```
+
+use std::cmp::Reverse;
+
+use crate::entry::{DiskEntry, EntryType, SizeMode};
+use crate::formatter::parse_size;
+
+/// Size limit given with `-t/--threshold`.
+///
+/// # Variants
+///
+/// - `AtLeast` - Show entries of at least this size (positive `SIZE`)
+/// - `AtMost` - Show entries of at most this size (negative `-SIZE`)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Threshold {
+    AtLeast(u64),
+    AtMost(u64),
+}
+
+impl Threshold {
+    /// Parses a threshold such as `10M` or `-1G`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use disk_usage_clone::prune::Threshold;
+    ///
+    /// assert_eq!(Threshold::parse("10K"), Some(Threshold::AtLeast(10240)));
+    /// assert_eq!(Threshold::parse("-512"), Some(Threshold::AtMost(512)));
+    /// assert_eq!(Threshold::parse("big"), None);
+    /// ```
+    pub fn parse(s: &str) -> Option<Threshold> {
+        match s.strip_prefix('-') {
+            Some(size) => parse_size(size).map(Threshold::AtMost),
+            None => parse_size(s).map(Threshold::AtLeast),
+        }
+    }
+
+    /// Returns whether an entry with this total size is shown.
+    pub fn allows(&self, size: u64) -> bool {
+        match *self {
+            Threshold::AtLeast(min) => size >= min,
+            Threshold::AtMost(max) => size <= max,
+        }
+    }
+}
+
+/// Removes the entries outside the threshold, keeping every total intact.
+///
+/// # Arguments
+///
+/// * `tree` - Tree to prune; its root is always kept
+/// * `threshold` - Which entry totals to keep
+/// * `size_mode` - Compare apparent or allocated totals
+pub fn apply_threshold(tree: &mut DiskEntry, threshold: Threshold, size_mode: SizeMode) {
+    for mut child in std::mem::take(&mut tree.children) {
+        let shown = threshold.allows(child.total_size_for(size_mode));
+
+        match threshold {
+            _ if shown => {
+                apply_threshold(&mut child, threshold, size_mode);
+                tree.children.push(child);
+            }
+            // Everything below a too-small entry is too small as well
+            Threshold::AtLeast(_) => {
+                tree.size_bytes += child.total_size_for(SizeMode::Apparent);
+                tree.allocated_bytes += child.total_size_for(SizeMode::Allocated);
+            }
+            // A too-large entry may still contain entries small enough to show
+            Threshold::AtMost(_) => {
+                apply_threshold(&mut child, threshold, size_mode);
+                tree.size_bytes += child.size_bytes;
+                tree.allocated_bytes += child.allocated_bytes;
+                for mut grandchild in child.children {
+                    set_depth(&mut grandchild, tree.depth + 1);
+                    tree.children.push(grandchild);
+                }
+            }
+        }
+    }
+}
+
+/// Renumbers the depths of a subtree moved to a new level.
+fn set_depth(entry: &mut DiskEntry, depth: usize) {
+    entry.depth = depth;
+    for child in &mut entry.children {
+        set_depth(child, depth + 1);
+    }
+}
+
+/// Keeps the `n` largest listed entries of every directory.
+///
+/// The remaining listed entries are replaced by a single
+/// [`EntryType::Aggregate`] entry named `<K other entries>`, appended after
+/// the kept ones. Kept entries stay in their current (sorted) order.
+///
+/// # Arguments
+///
+/// * `tree` - Tree to prune
+/// * `n` - Entries to keep per directory
+/// * `show_all` - Whether files are listed (`--all`)
+/// * `size_mode` - Rank by apparent or allocated totals
+pub fn keep_top(tree: &mut DiskEntry, n: usize, show_all: bool, size_mode: SizeMode) {
+    let is_listed = |entry: &DiskEntry| {
+        matches!(
+            entry.entry_type,
+            EntryType::Directory | EntryType::MountPoint
+        ) || show_all
+    };
+
+    // Rank the listed children, largest first; ties keep the current order
+    let mut ranked: Vec<(usize, u64)> = tree
+        .children
+        .iter()
+        .enumerate()
+        .filter(|(_, child)| is_listed(child))
+        .map(|(index, child)| (index, child.total_size_for(size_mode)))
+        .collect();
+
+    if ranked.len() > n {
+        ranked.sort_by_key(|&(index, size)| (Reverse(size), index));
+        let mut rolled = vec![false; tree.children.len()];
+        for &(index, _) in &ranked[n..] {
+            rolled[index] = true;
+        }
+
+        let mut other = DiskEntry::new(
+            tree.path.join(other_entries_name(ranked.len() - n)),
+            0,
+            EntryType::Aggregate,
+            tree.depth + 1,
+        );
+        let children = std::mem::take(&mut tree.children);
+        for (child, rolled) in children.into_iter().zip(rolled) {
+            if rolled {
+                other.size_bytes += child.total_size_for(SizeMode::Apparent);
+                other.allocated_bytes += child.total_size_for(SizeMode::Allocated);
+            } else {
+                tree.children.push(child);
+            }
+        }
+        tree.children.push(other);
+    }
+
+    for child in &mut tree.children {
+        keep_top(child, n, show_all, size_mode);
+    }
+}
+
+/// Name of the synthetic entry holding `count` rolled-up entries.
+fn other_entries_name(count: usize) -> String {
+    if count == 1 {
+        "<1 other entry>".to_string()
+    } else {
+        format!("<{count} other entries>")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+
+    fn dir(path: &str, size: u64, depth: usize, children: Vec<DiskEntry>) -> DiskEntry {
+        let mut entry = DiskEntry::new(PathBuf::from(path), size, EntryType::Directory, depth);
+        entry.allocated_bytes = size;
+        entry.children = children;
+        entry
+    }
+
+    fn file(path: &str, size: u64, depth: usize) -> DiskEntry {
+        let mut entry = DiskEntry::new(PathBuf::from(path), size, EntryType::File, depth);
+        entry.allocated_bytes = size;
+        entry
+    }
+
+    /// /r (1)
+    /// ├── big (1) [huge 100, small 2]
+    /// ├── mid (1) [f 20]
+    /// └── tiny 3
+    fn sample() -> DiskEntry {
+        dir(
+            "/r",
+            1,
+            0,
+            vec![
+                dir(
+                    "/r/big",
+                    1,
+                    1,
+                    vec![file("/r/big/huge", 100, 2), file("/r/big/small", 2, 2)],
+                ),
+                dir("/r/mid", 1, 1, vec![file("/r/mid/f", 20, 2)]),
+                file("/r/tiny", 3, 1),
+            ],
+        )
+    }
+
+    fn paths(entry: &DiskEntry) -> Vec<String> {
+        let mut out = vec![entry.path.display().to_string()];
+        for child in &entry.children {
+            out.extend(paths(child));
+        }
+        out
+    }
+
+    #[test]
+    fn test_threshold_parse() {
+        assert_eq!(Threshold::parse("1M"), Some(Threshold::AtLeast(1_048_576)));
+        assert_eq!(Threshold::parse("-1G"), Some(Threshold::AtMost(1 << 30)));
+        assert_eq!(Threshold::parse("--1"), None);
+        assert_eq!(Threshold::parse("-"), None);
+    }
+
+    #[test]
+    fn test_threshold_at_least() {
+        let mut tree = sample();
+        apply_threshold(&mut tree, Threshold::AtLeast(20), SizeMode::Apparent);
+
+        assert_eq!(
+            paths(&tree),
+            vec!["/r", "/r/big", "/r/big/huge", "/r/mid", "/r/mid/f"]
+        );
+        // tiny folded into the root, small into big
+        assert_eq!(tree.size_bytes, 4);
+        assert_eq!(tree.children[0].size_bytes, 3);
+        assert_eq!(tree.total_size_for(SizeMode::Apparent), 128);
+        assert_eq!(tree.total_size_for(SizeMode::Allocated), 128);
+    }
+
+    #[test]
+    fn test_threshold_at_most_hoists_children() {
+        let mut tree = sample();
+        apply_threshold(&mut tree, Threshold::AtMost(25), SizeMode::Apparent);
+
+        // big (103) and huge (100) are hidden; small moves up to the root
+        assert_eq!(
+            paths(&tree),
+            vec!["/r", "/r/big/small", "/r/mid", "/r/mid/f", "/r/tiny"]
+        );
+        assert_eq!(tree.children[0].depth, 1);
+        assert_eq!(tree.size_bytes, 102);
+        assert_eq!(tree.total_size_for(SizeMode::Apparent), 128);
+    }
+
+    #[test]
+    fn test_threshold_keeps_root() {
+        let mut tree = sample();
+        apply_threshold(&mut tree, Threshold::AtLeast(1 << 40), SizeMode::Apparent);
+        assert_eq!(paths(&tree), vec!["/r"]);
+        assert_eq!(tree.size_bytes, 128);
+    }
+
+    #[test]
+    fn test_keep_top_rolls_up_remainder() {
+        let mut tree = sample();
+        keep_top(&mut tree, 1, true, SizeMode::Apparent);
+
+        assert_eq!(
+            paths(&tree),
+            vec![
+                "/r",
+                "/r/big",
+                "/r/big/huge",
+                "/r/big/<1 other entry>",
+                "/r/<2 other entries>",
+            ]
+        );
+        let other = &tree.children[1];
+        assert_eq!(other.entry_type, EntryType::Aggregate);
+        assert_eq!(other.depth, 1);
+        assert_eq!(other.size_bytes, 24);
+        assert_eq!(other.allocated_bytes, 24);
+        assert_eq!(tree.total_size_for(SizeMode::Apparent), 128);
+    }
+
+    #[test]
+    fn test_keep_top_ignores_unlisted_files() {
+        let mut tree = sample();
+        keep_top(&mut tree, 1, false, SizeMode::Apparent);
+
+        // Only the two directories compete; tiny stays as an unlisted file
+        assert_eq!(
+            paths(&tree),
+            vec![
+                "/r",
+                "/r/big",
+                "/r/big/huge",
+                "/r/big/small",
+                "/r/tiny",
+                "/r/<1 other entry>"
+            ]
+        );
+        assert_eq!(tree.total_size_for(SizeMode::Apparent), 128);
+    }
+
+    #[test]
+    fn test_keep_top_without_excess_is_unchanged() {
+        let mut tree = sample();
+        keep_top(&mut tree, 3, true, SizeMode::Apparent);
+        assert_eq!(paths(&tree), paths(&sample()));
+    }
+}
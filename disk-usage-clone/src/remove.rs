@@ -0,0 +1,615 @@
+//! Deleting and trashing entries of a scanned tree.
+//!
+//! Used by the interactive browser to remove what a scan turned up without
+//! leaving the tool. [`remove_entry`] removes one node of a [`DiskEntry`]
+//! tree from disk and prunes it from the tree, so the totals of all its
+//! ancestors drop accordingly (totals are always summed from the children).
+//!
+//! # Modes
+//!
+//! - [`RemoveMode::Delete`] - Deletes files and directories for good
+//! - [`RemoveMode::Trash`] - Moves the entry to the desktop trash
+//!
+//! With `dry_run`, nothing is touched: the report says what would be removed
+//! and how much space it would free, and the tree stays unchanged.
+//!
+//! # Partial Failures
+//!
+//! Deleting a directory continues past entries that can't be removed (e.g.
+//! permission denied deep inside the subtree). Every failure is reported as a
+//! [`DuskError`] in [`RemoveReport::failures`]; the directories holding the
+//! survivors stay, everything else is gone from disk and from the tree.
+//!
+//! Removal never crosses into another filesystem: mount points that were
+//! not scanned (`-x`) are refused, and a deleted directory's contents on
+//! another device (a mount below it) are left alone and reported.
+//!
+//! A symlink followed during the scan (`-L`) is listed with its target's
+//! contents, but removing it only removes the link: the target is left
+//! alone, and only the link's own size counts as freed.
+//!
+//! Directories collapsed by `--max-depth` have no children in the tree; if
+//! such a directory survives a partial removal, its size is not updated until
+//! the next scan.
+//!
+//! # Examples
+//!
+//! ```no_run
+//! use disk_usage_clone::entry::SizeMode;
+//! use disk_usage_clone::remove::{RemoveMode, remove_entry};
+//! use disk_usage_clone::traversal::traverse;
+//! use std::path::Path;
+//!
+//! let mut tree = traverse(Path::new("/home/user/project"), None).unwrap();
+//! let report = remove_entry(
+//!     &mut tree,
+//!     Path::new("/home/user/project/target"),
+//!     RemoveMode::Trash,
+//!     false,
+//!     SizeMode::Allocated,
+//! )
+//! .unwrap();
+//!
+//! println!("freed {} bytes", report.freed_bytes);
+//! for failure in &report.failures {
+//!     eprintln!("dusk: {failure}");
+//! }
+//! ```
+
+use std::fs;
+use std::io;
+use std::path::Path;
+
+use crate::entry::{DiskEntry, EntryType, SizeMode};
+use crate::error::DuskError;
+use crate::traversal::{allocated_size, device_id};
+
+/// How an entry is removed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RemoveMode {
+    /// Delete permanently.
+    Delete,
+    /// Move to the trash (freedesktop.org trash on Linux).
+    Trash,
+}
+
+impl RemoveMode {
+    /// Verb for prompts and messages, e.g. "Delete /tmp/x?".
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use disk_usage_clone::remove::RemoveMode;
+    ///
+    /// assert_eq!(RemoveMode::Delete.verb(), "delete");
+    /// assert_eq!(RemoveMode::Trash.verb(), "move to trash");
+    /// ```
+    pub fn verb(&self) -> &'static str {
+        match self {
+            RemoveMode::Delete => "delete",
+            RemoveMode::Trash => "move to trash",
+        }
+    }
+}
+
+/// Outcome of a [`remove_entry`] call.
+#[derive(Debug, Default)]
+pub struct RemoveReport {
+    /// Whether this was a dry run (nothing was touched).
+    pub dry_run: bool,
+    /// Whether the entry is completely gone (or would be, in a dry run).
+    pub removed: bool,
+    /// How much the tree's total shrank (or would shrink), in the requested
+    /// size mode.
+    pub freed_bytes: u64,
+    /// Everything that could not be removed.
+    pub failures: Vec<DuskError>,
+}
+
+impl RemoveReport {
+    /// Returns true if the removal had no failures.
+    pub fn is_complete(&self) -> bool {
+        self.failures.is_empty()
+    }
+}
+
+/// Removes an entry of a scanned tree from disk and from the tree.
+///
+/// # Arguments
+///
+/// * `tree` - Scanned tree containing the entry; updated in place
+/// * `path` - Path of the entry to remove (as stored in the tree)
+/// * `mode` - Delete permanently or move to trash
+/// * `dry_run` - Only report what would happen
+/// * `size_mode` - Size mode used for `freed_bytes`
+///
+/// # Returns
+///
+/// A [`RemoveReport`]. Failures to remove parts of the entry are collected
+/// in the report rather than returned as `Err`, so the caller learns what
+/// was removed as well. Removing a followed symlink frees only the link
+/// itself, whatever the tree listed under it.
+///
+/// # Errors
+///
+/// - `InvalidArgument` if `path` is the root of the tree, or a mount
+///   point that was not scanned (its contents were never shown)
+/// - `PathNotFound` if `path` is not part of the tree
+pub fn remove_entry(
+    tree: &mut DiskEntry,
+    path: &Path,
+    mode: RemoveMode,
+    dry_run: bool,
+    size_mode: SizeMode,
+) -> Result<RemoveReport, DuskError> {
+    // The scanned root is the whole view; never remove it from under it
+    if path == tree.path {
+        return Err(DuskError::InvalidArgument(format!(
+            "refusing to remove the scanned root {}",
+            path.display()
+        )));
+    }
+
+    let Some(entry) = find_entry(tree, path) else {
+        return Err(DuskError::PathNotFound(path.to_path_buf()));
+    };
+
+    // Listed with 0 bytes, but may hold a whole other filesystem
+    if entry.entry_type == EntryType::MountPoint {
+        return Err(DuskError::InvalidArgument(format!(
+            "refusing to remove the mount point {}, which was not scanned",
+            path.display()
+        )));
+    }
+
+    // Followed with -L, a link lists its target's contents, but only the
+    // link itself is removed
+    let link_size = fs::symlink_metadata(path)
+        .ok()
+        .filter(|metadata| metadata.file_type().is_symlink())
+        .map(|metadata| match size_mode {
+            SizeMode::Apparent => metadata.len(),
+            SizeMode::Allocated => allocated_size(&metadata),
+        });
+
+    if dry_run {
+        return Ok(RemoveReport {
+            dry_run: true,
+            removed: true,
+            freed_bytes: link_size.unwrap_or_else(|| entry.total_size_for(size_mode)),
+            failures: Vec::new(),
+        });
+    }
+
+    let mut failures = Vec::new();
+    match mode {
+        RemoveMode::Delete => delete_path(path, None, &mut failures),
+        RemoveMode::Trash => {
+            if let Err(err) = trash::delete(path) {
+                failures.push(DuskError::RemoveFailed(path.to_path_buf(), err.to_string()));
+            }
+        }
+    }
+
+    // Drop whatever is gone from disk; ancestor totals follow automatically
+    let before = tree.total_size_for(size_mode);
+    let parent_path = path.parent().unwrap_or(path);
+    if let Some(parent) = find_entry_mut(tree, parent_path) {
+        parent
+            .children
+            .retain(|child| child.path != path || exists(&child.path));
+        if let Some(entry) = parent.children.iter_mut().find(|c| c.path == path) {
+            prune_missing(entry);
+        }
+    }
+    let after = tree.total_size_for(size_mode);
+    let removed = !exists(path);
+    let freed_bytes = match link_size {
+        Some(size) if removed => size,
+        Some(_) => 0,
+        None => before.saturating_sub(after),
+    };
+
+    Ok(RemoveReport {
+        dry_run: false,
+        removed,
+        freed_bytes,
+        failures,
+    })
+}
+
+/// Finds the entry with the given path in a tree.
+///
+/// Descends only into children whose path is a prefix of `path`, so the
+/// lookup costs one pass over each directory on the way down.
+pub fn find_entry<'a>(entry: &'a DiskEntry, path: &Path) -> Option<&'a DiskEntry> {
+    if entry.path == path {
+        return Some(entry);
+    }
+    entry
+        .children
+        .iter()
+        .find(|child| path.starts_with(&child.path))
+        .and_then(|child| find_entry(child, path))
+}
+
+/// Mutable variant of [`find_entry`].
+fn find_entry_mut<'a>(entry: &'a mut DiskEntry, path: &Path) -> Option<&'a mut DiskEntry> {
+    if entry.path == path {
+        return Some(entry);
+    }
+    entry
+        .children
+        .iter_mut()
+        .find(|child| path.starts_with(&child.path))
+        .and_then(|child| find_entry_mut(child, path))
+}
+
+/// Returns whether a path still exists, without following symlinks.
+fn exists(path: &Path) -> bool {
+    fs::symlink_metadata(path).is_ok()
+}
+
+/// Recursively drops children that no longer exist on disk.
+fn prune_missing(entry: &mut DiskEntry) {
+    entry.children.retain(|child| exists(&child.path));
+    for child in &mut entry.children {
+        prune_missing(child);
+    }
+}
+
+/// Deletes a file or directory tree, collecting failures.
+///
+/// Symlinks are removed themselves, never followed. A directory is only
+/// removed once all of its contents are; if something inside fails, the
+/// failure is recorded once and the directories above it are left alone.
+///
+/// `device` is the filesystem of the entry being removed (`None` for the
+/// entry itself). Anything on another device, i.e. a filesystem mounted
+/// below it, is recorded as a failure and not touched.
+fn delete_path(path: &Path, device: Option<u64>, failures: &mut Vec<DuskError>) {
+    let metadata = match fs::symlink_metadata(path) {
+        Ok(metadata) => metadata,
+        Err(err) => {
+            failures.extend(remove_error(path, err));
+            return;
+        }
+    };
+
+    let own_device = device_id(&metadata);
+    if let (Some(device), Some(own_device)) = (device, own_device)
+        && device != own_device
+    {
+        failures.push(DuskError::RemoveFailed(
+            path.to_path_buf(),
+            "on another file system".to_string(),
+        ));
+        return;
+    }
+    let device = device.or(own_device);
+
+    if !metadata.is_dir() {
+        if let Err(err) = fs::remove_file(path) {
+            failures.extend(remove_error(path, err));
+        }
+        return;
+    }
+
+    let failures_before = failures.len();
+    match fs::read_dir(path) {
+        Ok(entries) => {
+            for entry in entries {
+                match entry {
+                    Ok(entry) => delete_path(&entry.path(), device, failures),
+                    Err(err) => failures.extend(remove_error(path, err)),
+                }
+            }
+        }
+        Err(err) => failures.extend(remove_error(path, err)),
+    }
+
+    // Something below survived, so this directory can't be empty
+    if failures.len() > failures_before {
+        return;
+    }
+
+    if let Err(err) = fs::remove_dir(path) {
+        failures.extend(remove_error(path, err));
+    }
+}
+
+/// Converts a removal error into a `DuskError`.
+///
+/// Returns `None` for `NotFound`: an entry that vanished in the meantime is
+/// as good as removed.
+fn remove_error(path: &Path, err: io::Error) -> Option<DuskError> {
+    match err.kind() {
+        io::ErrorKind::NotFound => None,
+        io::ErrorKind::PermissionDenied => Some(DuskError::PermissionDenied(path.to_path_buf())),
+        _ => Some(DuskError::RemoveFailed(path.to_path_buf(), err.to_string())),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::traversal::traverse;
+    use std::fs;
+    use tempfile::TempDir;
+
+    /// root/keep.txt (5), root/cache/a.bin (100), root/cache/deep/b.bin (1000)
+    fn create_tree() -> (TempDir, DiskEntry) {
+        let tmp = TempDir::new().unwrap();
+        let root = tmp.path();
+        fs::write(root.join("keep.txt"), "hello").unwrap();
+        fs::create_dir_all(root.join("cache/deep")).unwrap();
+        fs::write(root.join("cache/a.bin"), vec![0u8; 100]).unwrap();
+        fs::write(root.join("cache/deep/b.bin"), vec![0u8; 1000]).unwrap();
+        let tree = traverse(root, None).unwrap();
+        (tmp, tree)
+    }
+
+    fn child_total(tree: &DiskEntry, name: &str) -> u64 {
+        find_entry(tree, &tree.path.join(name))
+            .unwrap()
+            .total_size_for(SizeMode::Apparent)
+    }
+
+    #[test]
+    fn test_delete_directory_updates_ancestors() {
+        let (_tmp, mut tree) = create_tree();
+        let cache = tree.path.join("cache");
+        let cache_size = child_total(&tree, "cache");
+        let total_before = tree.total_size_for(SizeMode::Apparent);
+
+        let report = remove_entry(
+            &mut tree,
+            &cache,
+            RemoveMode::Delete,
+            false,
+            SizeMode::Apparent,
+        )
+        .unwrap();
+
+        assert!(report.is_complete());
+        assert!(report.removed);
+        assert_eq!(report.freed_bytes, cache_size);
+        assert!(!cache.exists());
+        assert!(find_entry(&tree, &cache).is_none());
+        assert_eq!(
+            tree.total_size_for(SizeMode::Apparent),
+            total_before - cache_size
+        );
+    }
+
+    #[test]
+    fn test_delete_nested_entry_updates_all_ancestors() {
+        let (_tmp, mut tree) = create_tree();
+        let deep = tree.path.join("cache/deep");
+        let cache_before = child_total(&tree, "cache");
+        let deep_size = child_total(&tree, "cache/deep");
+
+        remove_entry(
+            &mut tree,
+            &deep,
+            RemoveMode::Delete,
+            false,
+            SizeMode::Apparent,
+        )
+        .unwrap();
+
+        assert_eq!(child_total(&tree, "cache"), cache_before - deep_size);
+        assert!(tree.path.join("cache/a.bin").exists());
+    }
+
+    #[test]
+    fn test_dry_run_touches_nothing() {
+        let (_tmp, mut tree) = create_tree();
+        let cache = tree.path.join("cache");
+        let total_before = tree.total_size_for(SizeMode::Apparent);
+
+        let report = remove_entry(
+            &mut tree,
+            &cache,
+            RemoveMode::Delete,
+            true,
+            SizeMode::Apparent,
+        )
+        .unwrap();
+
+        assert!(report.dry_run);
+        assert_eq!(report.freed_bytes, child_total(&tree, "cache"));
+        assert!(cache.join("deep/b.bin").exists());
+        assert_eq!(tree.total_size_for(SizeMode::Apparent), total_before);
+    }
+
+    #[test]
+    fn test_refuses_root_and_unknown_paths() {
+        let (_tmp, mut tree) = create_tree();
+        let root = tree.path.clone();
+        let result = remove_entry(
+            &mut tree,
+            &root,
+            RemoveMode::Delete,
+            false,
+            SizeMode::Apparent,
+        );
+        assert!(matches!(result, Err(DuskError::InvalidArgument(_))));
+
+        let elsewhere = Path::new("/definitely/not/in/tree");
+        let result = remove_entry(
+            &mut tree,
+            elsewhere,
+            RemoveMode::Delete,
+            false,
+            SizeMode::Apparent,
+        );
+        assert!(matches!(result, Err(DuskError::PathNotFound(_))));
+        assert!(root.join("keep.txt").exists());
+    }
+
+    #[test]
+    fn test_refuses_mount_points() {
+        let (_tmp, mut tree) = create_tree();
+        // As listed by `-x`: 0 bytes, contents never read
+        let cache = tree.path.join("cache");
+        let entry = find_entry_mut(&mut tree, &cache).unwrap();
+        entry.entry_type = EntryType::MountPoint;
+        entry.children.clear();
+
+        for dry_run in [true, false] {
+            let result = remove_entry(
+                &mut tree,
+                &cache,
+                RemoveMode::Delete,
+                dry_run,
+                SizeMode::Apparent,
+            );
+            assert!(matches!(result, Err(DuskError::InvalidArgument(_))));
+        }
+        assert!(cache.join("deep/b.bin").exists());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_delete_stops_at_other_devices() {
+        let (tmp, _tree) = create_tree();
+        let cache = tmp.path().join("cache");
+
+        // Pretend the tree's entries sit on another filesystem
+        let other_device = device_id(&fs::metadata(&cache).unwrap()).unwrap() + 1;
+        let mut failures = Vec::new();
+        delete_path(&cache, Some(other_device), &mut failures);
+
+        assert_eq!(failures.len(), 1);
+        assert!(failures[0].to_string().contains("on another file system"));
+        assert!(cache.join("deep/b.bin").exists());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_delete_does_not_follow_symlinks() {
+        let (tmp, mut tree) = create_tree();
+        let outside = TempDir::new().unwrap();
+        fs::write(outside.path().join("precious"), "x").unwrap();
+        std::os::unix::fs::symlink(outside.path(), tmp.path().join("cache/link")).unwrap();
+        tree = traverse(&tree.path, None).unwrap();
+
+        let cache = tree.path.join("cache");
+        remove_entry(
+            &mut tree,
+            &cache,
+            RemoveMode::Delete,
+            false,
+            SizeMode::Apparent,
+        )
+        .unwrap();
+        assert!(outside.path().join("precious").exists());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_remove_followed_symlink_frees_only_the_link() {
+        use crate::traversal::{Dereference, TraversalOptions, traverse_with_options};
+
+        let (tmp, _) = create_tree();
+        let root = tmp.path().canonicalize().unwrap();
+        let outside = TempDir::new().unwrap();
+        fs::write(outside.path().join("precious"), vec![0u8; 5000]).unwrap();
+        std::os::unix::fs::symlink(outside.path(), root.join("link")).unwrap();
+        let options = TraversalOptions {
+            dereference: Dereference::Always,
+            ..TraversalOptions::default()
+        };
+        let mut tree = traverse_with_options(&root, &options).unwrap();
+        let link = root.join("link");
+        assert!(child_total(&tree, "link") > 5000);
+        let link_size = fs::symlink_metadata(&link).unwrap().len();
+
+        let dry = remove_entry(
+            &mut tree,
+            &link,
+            RemoveMode::Delete,
+            true,
+            SizeMode::Apparent,
+        );
+        assert_eq!(dry.unwrap().freed_bytes, link_size);
+
+        let report = remove_entry(
+            &mut tree,
+            &link,
+            RemoveMode::Delete,
+            false,
+            SizeMode::Apparent,
+        )
+        .unwrap();
+        assert!(report.removed && report.is_complete());
+        assert_eq!(report.freed_bytes, link_size);
+        assert!(fs::symlink_metadata(&link).is_err());
+        assert!(outside.path().join("precious").exists());
+        assert!(find_entry(&tree, &link).is_none());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_partial_failure_reports_permission_denied() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let (_tmp, mut tree) = create_tree();
+        let deep = tree.path.join("cache/deep");
+        fs::set_permissions(&deep, fs::Permissions::from_mode(0o555)).unwrap();
+
+        // Privileged users (e.g. root in containers) ignore the permission bits
+        let probe = deep.join("probe");
+        if fs::write(&probe, "").is_ok() {
+            fs::remove_file(&probe).unwrap();
+            fs::set_permissions(&deep, fs::Permissions::from_mode(0o755)).unwrap();
+            return;
+        }
+
+        let cache = tree.path.join("cache");
+        let report = remove_entry(
+            &mut tree,
+            &cache,
+            RemoveMode::Delete,
+            false,
+            SizeMode::Apparent,
+        )
+        .unwrap();
+        fs::set_permissions(&deep, fs::Permissions::from_mode(0o755)).unwrap();
+
+        assert!(!report.removed);
+        assert_eq!(report.failures.len(), 1);
+        assert!(matches!(
+            &report.failures[0],
+            DuskError::PermissionDenied(p) if p.ends_with("deep/b.bin")
+        ));
+        // a.bin is gone, deep/b.bin and its directories remain
+        assert_eq!(report.freed_bytes, 100);
+        assert!(find_entry(&tree, &cache.join("a.bin")).is_none());
+        assert!(find_entry(&tree, &cache.join("deep/b.bin")).is_some());
+    }
+
+    #[test]
+    fn test_remove_error_mapping() {
+        let path = Path::new("/x");
+        assert!(remove_error(path, io::Error::from(io::ErrorKind::NotFound)).is_none());
+        assert!(matches!(
+            remove_error(path, io::Error::from(io::ErrorKind::PermissionDenied)),
+            Some(DuskError::PermissionDenied(_))
+        ));
+        assert!(matches!(
+            remove_error(path, io::Error::other("busy")),
+            Some(DuskError::RemoveFailed(_, _))
+        ));
+    }
+
+    #[test]
+    fn test_find_entry() {
+        let (_tmp, tree) = create_tree();
+        let deep = find_entry(&tree, &tree.path.join("cache/deep")).unwrap();
+        assert_eq!(deep.depth, 2);
+        assert!(find_entry(&tree, &tree.path.join("cache/missing")).is_none());
+        assert!(find_entry(&tree, &tree.path).is_some());
+    }
+}
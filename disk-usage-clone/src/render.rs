@@ -0,0 +1,447 @@
+//! Renderers for scanned trees, configured without the command line.
+//!
+//! Each output format has a renderer struct holding its settings, built
+//! with chained setters from defaults that match `dusk` without options
+//! (except color, which is off). All implement [`Render`], so a program
+//! can choose the format at runtime:
+//!
+//! | Renderer | Output | `dusk` option |
+//! |----------|--------|---------------|
+//! | [`TextRenderer`] | `SIZE<TAB>PATH` lines, children first | (default) |
+//! | [`TreeViewRenderer`] | Indented tree with percentages and bars | `--tree` |
+//! | [`JsonRenderer`] | One JSON document with every tree | `--format json` |
+//! | [`NdjsonRenderer`] | One JSON object per entry | `--format ndjson` |
+//! | [`CsvRenderer`] | One CSV row per entry, with a header | `--format csv` |
+//!
+//! Renderers only format: sorting happens while scanning (see
+//! [`crate::scan`]), and trees can be pruned beforehand with
+//! [`crate::prune`].
+//!
+//! # Examples
+//!
+//! ```
+//! use disk_usage_clone::entry::{DiskEntry, EntryType, SizeMode};
+//! use disk_usage_clone::render::{JsonRenderer, Render, TextRenderer};
+//! use std::path::PathBuf;
+//!
+//! let mut dir = DiskEntry::new(PathBuf::from("/tmp"), 4096, EntryType::Directory, 0);
+//! dir.children.push(DiskEntry::new(
+//!     PathBuf::from("/tmp/file.txt"),
+//!     1024,
+//!     EntryType::File,
+//!     1,
+//! ));
+//! let trees = [dir];
+//!
+//! let text = TextRenderer::new()
+//!     .human_readable(true)
+//!     .size_mode(SizeMode::Apparent);
+//! assert_eq!(text.render(&trees), "5.0K\t/tmp");
+//!
+//! let renderers: Vec<Box<dyn Render>> = vec![Box::new(text), Box::new(JsonRenderer::new())];
+//! assert!(renderers.iter().all(|renderer| !renderer.render(&trees).is_empty()));
+//! ```
+
+use crate::compare::render_total_line;
+use crate::entry::{DiskEntry, SizeMode, TimeKind};
+use crate::json::{render_json, render_ndjson};
+use crate::output::{render_csv, render_tree};
+use crate::tree_view::render_tree_view;
+
+/// Width of the tree view when none is set, in columns.
+const DEFAULT_WIDTH: usize = 80;
+
+/// Renders scanned trees in one output format.
+pub trait Render {
+    /// Renders `trees`, one per scanned path, without trailing newline.
+    fn render(&self, trees: &[DiskEntry]) -> String;
+}
+
+/// Plain `du`-style lines: size, optional timestamp and path.
+///
+/// Several trees are rendered one after the other, optionally followed by
+/// their combined total.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct TextRenderer {
+    human_readable: bool,
+    show_all: bool,
+    summarize: bool,
+    color: bool,
+    size_mode: SizeMode,
+    time: Option<TimeKind>,
+    total: bool,
+}
+
+impl TextRenderer {
+    /// Creates a renderer listing directories with sizes in bytes.
+    pub fn new() -> TextRenderer {
+        TextRenderer::default()
+    }
+
+    /// Formats sizes as K, M, G (`-H`).
+    pub fn human_readable(mut self, human_readable: bool) -> TextRenderer {
+        self.human_readable = human_readable;
+        self
+    }
+
+    /// Lists files too, not only directories (`-a`).
+    pub fn show_all(mut self, show_all: bool) -> TextRenderer {
+        self.show_all = show_all;
+        self
+    }
+
+    /// Lists only the total of each tree (`-s`).
+    pub fn summarize(mut self, summarize: bool) -> TextRenderer {
+        self.summarize = summarize;
+        self
+    }
+
+    /// Colors sizes and paths with ANSI escapes.
+    pub fn color(mut self, color: bool) -> TextRenderer {
+        self.color = color;
+        self
+    }
+
+    /// Shows apparent or allocated sizes.
+    pub fn size_mode(mut self, size_mode: SizeMode) -> TextRenderer {
+        self.size_mode = size_mode;
+        self
+    }
+
+    /// Adds a column with the latest timestamp of this kind (`--time`).
+    pub fn time_column(mut self, kind: TimeKind) -> TextRenderer {
+        self.time = Some(kind);
+        self
+    }
+
+    /// Ends with a `SIZE<TAB>total` line for all trees (`-c`).
+    ///
+    /// Trees inside another one are counted once (see [`crate::compare`]).
+    pub fn total(mut self, total: bool) -> TextRenderer {
+        self.total = total;
+        self
+    }
+}
+
+impl Render for TextRenderer {
+    fn render(&self, trees: &[DiskEntry]) -> String {
+        trees
+            .iter()
+            .map(|tree| {
+                render_tree(
+                    tree,
+                    self.human_readable,
+                    self.show_all,
+                    self.summarize,
+                    self.color,
+                    self.size_mode,
+                    self.time,
+                )
+            })
+            .chain(
+                self.total.then(|| {
+                    render_total_line(trees, self.human_readable, self.color, self.size_mode)
+                }),
+            )
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+}
+
+/// Indented tree, root first, with each entry's share of its root.
+///
+/// See [`crate::tree_view`]. Entries are shown in the order of the tree;
+/// sort it by size first for comparable bars.
+#[derive(Debug, Clone, Copy)]
+pub struct TreeViewRenderer {
+    human_readable: bool,
+    show_all: bool,
+    summarize: bool,
+    color: bool,
+    size_mode: SizeMode,
+    width: usize,
+    total: bool,
+}
+
+impl Default for TreeViewRenderer {
+    fn default() -> Self {
+        TreeViewRenderer {
+            human_readable: false,
+            show_all: false,
+            summarize: false,
+            color: false,
+            size_mode: SizeMode::default(),
+            width: DEFAULT_WIDTH,
+            total: false,
+        }
+    }
+}
+
+impl TreeViewRenderer {
+    /// Creates a renderer for 80 columns listing directories.
+    pub fn new() -> TreeViewRenderer {
+        TreeViewRenderer::default()
+    }
+
+    /// Formats sizes as K, M, G (`-H`).
+    pub fn human_readable(mut self, human_readable: bool) -> TreeViewRenderer {
+        self.human_readable = human_readable;
+        self
+    }
+
+    /// Lists files too, not only directories (`-a`).
+    pub fn show_all(mut self, show_all: bool) -> TreeViewRenderer {
+        self.show_all = show_all;
+        self
+    }
+
+    /// Lists only the root of each tree (`-s`).
+    pub fn summarize(mut self, summarize: bool) -> TreeViewRenderer {
+        self.summarize = summarize;
+        self
+    }
+
+    /// Colors sizes, bars and names with ANSI escapes.
+    pub fn color(mut self, color: bool) -> TreeViewRenderer {
+        self.color = color;
+        self
+    }
+
+    /// Shows and compares apparent or allocated sizes.
+    pub fn size_mode(mut self, size_mode: SizeMode) -> TreeViewRenderer {
+        self.size_mode = size_mode;
+        self
+    }
+
+    /// Sizes the bars for `width` columns of output.
+    pub fn width(mut self, width: usize) -> TreeViewRenderer {
+        self.width = width;
+        self
+    }
+
+    /// Ends with a `SIZE<TAB>total` line for all trees (`-c`).
+    pub fn total(mut self, total: bool) -> TreeViewRenderer {
+        self.total = total;
+        self
+    }
+}
+
+impl Render for TreeViewRenderer {
+    fn render(&self, trees: &[DiskEntry]) -> String {
+        trees
+            .iter()
+            .map(|tree| {
+                render_tree_view(
+                    tree,
+                    self.human_readable,
+                    self.show_all,
+                    self.summarize,
+                    self.color,
+                    self.size_mode,
+                    self.width,
+                )
+            })
+            .chain(
+                self.total.then(|| {
+                    render_total_line(trees, self.human_readable, self.color, self.size_mode)
+                }),
+            )
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+}
+
+/// A single pretty-printed JSON array holding every tree.
+///
+/// See [`crate::json`] for the fields of each entry.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct JsonRenderer {
+    show_all: bool,
+    summarize: bool,
+    size_mode: SizeMode,
+}
+
+impl JsonRenderer {
+    /// Creates a renderer including directories only.
+    pub fn new() -> JsonRenderer {
+        JsonRenderer::default()
+    }
+
+    /// Includes files too, not only directories (`-a`).
+    pub fn show_all(mut self, show_all: bool) -> JsonRenderer {
+        self.show_all = show_all;
+        self
+    }
+
+    /// Includes only the root of each tree (`-s`).
+    pub fn summarize(mut self, summarize: bool) -> JsonRenderer {
+        self.summarize = summarize;
+        self
+    }
+
+    /// Selects the total reported in the `size` field.
+    pub fn size_mode(mut self, size_mode: SizeMode) -> JsonRenderer {
+        self.size_mode = size_mode;
+        self
+    }
+}
+
+impl Render for JsonRenderer {
+    fn render(&self, trees: &[DiskEntry]) -> String {
+        render_json(trees, self.show_all, self.summarize, self.size_mode)
+    }
+}
+
+/// Newline-delimited JSON: one object per entry, children first.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct NdjsonRenderer {
+    show_all: bool,
+    summarize: bool,
+    size_mode: SizeMode,
+}
+
+impl NdjsonRenderer {
+    /// Creates a renderer including directories only.
+    pub fn new() -> NdjsonRenderer {
+        NdjsonRenderer::default()
+    }
+
+    /// Includes files too, not only directories (`-a`).
+    pub fn show_all(mut self, show_all: bool) -> NdjsonRenderer {
+        self.show_all = show_all;
+        self
+    }
+
+    /// Includes only the root of each tree (`-s`).
+    pub fn summarize(mut self, summarize: bool) -> NdjsonRenderer {
+        self.summarize = summarize;
+        self
+    }
+
+    /// Selects the total reported in the `size` field.
+    pub fn size_mode(mut self, size_mode: SizeMode) -> NdjsonRenderer {
+        self.size_mode = size_mode;
+        self
+    }
+}
+
+impl Render for NdjsonRenderer {
+    fn render(&self, trees: &[DiskEntry]) -> String {
+        trees
+            .iter()
+            .map(|tree| render_ndjson(tree, self.show_all, self.summarize, self.size_mode))
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+}
+
+/// CSV with a header row and one row per entry of every tree.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct CsvRenderer {
+    show_all: bool,
+    summarize: bool,
+    size_mode: SizeMode,
+}
+
+impl CsvRenderer {
+    /// Creates a renderer including directories only.
+    pub fn new() -> CsvRenderer {
+        CsvRenderer::default()
+    }
+
+    /// Includes files too, not only directories (`-a`).
+    pub fn show_all(mut self, show_all: bool) -> CsvRenderer {
+        self.show_all = show_all;
+        self
+    }
+
+    /// Includes only the root of each tree (`-s`).
+    pub fn summarize(mut self, summarize: bool) -> CsvRenderer {
+        self.summarize = summarize;
+        self
+    }
+
+    /// Selects the total in the size columns.
+    pub fn size_mode(mut self, size_mode: SizeMode) -> CsvRenderer {
+        self.size_mode = size_mode;
+        self
+    }
+}
+
+impl Render for CsvRenderer {
+    fn render(&self, trees: &[DiskEntry]) -> String {
+        render_csv(trees, self.show_all, self.summarize, self.size_mode)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::entry::EntryType;
+    use std::path::PathBuf;
+
+    fn sample_trees() -> Vec<DiskEntry> {
+        ["/a", "/b"]
+            .into_iter()
+            .map(|root| {
+                let mut dir = DiskEntry::new(PathBuf::from(root), 100, EntryType::Directory, 0);
+                dir.children.push(DiskEntry::new(
+                    PathBuf::from(format!("{root}/file")),
+                    20,
+                    EntryType::File,
+                    1,
+                ));
+                dir
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_text_renders_every_tree() {
+        let output = TextRenderer::new()
+            .show_all(true)
+            .size_mode(SizeMode::Apparent)
+            .render(&sample_trees());
+        assert_eq!(output, "20\t/a/file\n120\t/a\n20\t/b/file\n120\t/b");
+    }
+
+    #[test]
+    fn test_total_follows_every_tree() {
+        let output = TextRenderer::new()
+            .summarize(true)
+            .size_mode(SizeMode::Apparent)
+            .total(true)
+            .render(&sample_trees());
+        assert_eq!(output, "120\t/a\n120\t/b\n240\ttotal");
+
+        let tree = TreeViewRenderer::new().total(true).render(&sample_trees());
+        assert!(tree.ends_with("\ttotal"));
+    }
+
+    #[test]
+    fn test_defaults_list_directories_only() {
+        let trees = sample_trees();
+        assert_eq!(TextRenderer::new().render(&trees).lines().count(), 2);
+        assert_eq!(NdjsonRenderer::new().render(&trees).lines().count(), 2);
+        // Header and one row per root
+        assert_eq!(CsvRenderer::new().render(&trees).lines().count(), 3);
+        assert!(!TextRenderer::new().render(&trees).contains('\x1b'));
+    }
+
+    #[test]
+    fn test_tree_view_uses_width() {
+        let trees = sample_trees();
+        let narrow = TreeViewRenderer::new().show_all(true).width(40);
+        let wide = TreeViewRenderer::new().show_all(true).width(120);
+        assert!(narrow.render(&trees).len() < wide.render(&trees).len());
+        assert!(narrow.render(&trees).contains("└── file"));
+    }
+
+    #[test]
+    fn test_json_holds_every_tree() {
+        let output = JsonRenderer::new().summarize(true).render(&sample_trees());
+        let parsed: serde_json::Value = serde_json::from_str(&output).unwrap();
+        assert_eq!(parsed.as_array().unwrap().len(), 2);
+        assert_eq!(parsed[1]["path"], "/b");
+    }
+}
@@ -0,0 +1,431 @@
+//! Scanning paths from a program, without the command line.
+//!
+//! [`ScanOptions`] collects what a scan needs (paths, depth, threads,
+//! filters, size mode, sorting) through builder methods, and
+//! [`ScanOptions::scan`] returns a [`ScanResult`]: one [`DiskEntry`] tree
+//! per path, plus the entries that couldn't be read.
+//!
+//! Rendering is a separate step (see [`crate::render`]), so a program can
+//! scan once and render the trees several ways, or walk them itself.
+//! [`run`](crate::run) is a thin adapter over both: it turns the
+//! [`CliArgs`](crate::cli::CliArgs) into a `ScanOptions` and a renderer.
+//!
+//! # Examples
+//!
+//! ```no_run
+//! use disk_usage_clone::entry::SortOrder;
+//! use disk_usage_clone::render::{Render, TextRenderer};
+//! use disk_usage_clone::scan::ScanOptions;
+//!
+//! let result = ScanOptions::new()
+//!     .path("/var")
+//!     .max_depth(1)
+//!     .exclude("*.log")
+//!     .sort(SortOrder::SizeDescending)
+//!     .scan()?;
+//!
+//! println!("{}", TextRenderer::new().human_readable(true).render(&result.trees));
+//! for skipped in &result.skipped {
+//!     eprintln!("{skipped}");
+//! }
+//! # Ok::<(), disk_usage_clone::error::DuskError>(())
+//! ```
+
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use crate::age::{AgeFilter, filter_by_age};
+use crate::cache::ScanCache;
+use crate::cancel::CancelToken;
+use crate::entry::{DiskEntry, SizeMode, SortOrder};
+use crate::error::DuskError;
+use crate::progress::ProgressHandler;
+use crate::traversal::{
+    Dereference, SkippedEntry, TraversalOptions, TraversalReport, traverse_parallel_report,
+    traverse_streaming_report,
+};
+
+/// Settings for scanning one or more paths.
+///
+/// Starts from the same defaults as `dusk` without options: unlimited
+/// depth, one thread per core, allocated sizes, traversal order, and the
+/// current directory if no path is added. Every builder method consumes
+/// and returns the options, so they chain.
+///
+/// # Examples
+///
+/// ```
+/// use disk_usage_clone::entry::SizeMode;
+/// use disk_usage_clone::scan::ScanOptions;
+///
+/// let options = ScanOptions::new()
+///     .paths(["/srv/a", "/srv/b"])
+///     .threads(4)
+///     .one_file_system(true)
+///     .size_mode(SizeMode::Apparent);
+/// assert_eq!(options.traversal_options().threads, Some(4));
+/// ```
+#[derive(Debug, Clone)]
+pub struct ScanOptions {
+    paths: Vec<PathBuf>,
+    traversal: TraversalOptions,
+    stream: bool,
+    size_mode: SizeMode,
+    sort: SortOrder,
+    age_filter: Option<AgeFilter>,
+}
+
+impl Default for ScanOptions {
+    fn default() -> Self {
+        ScanOptions {
+            paths: Vec::new(),
+            traversal: TraversalOptions::default(),
+            stream: false,
+            size_mode: SizeMode::default(),
+            sort: SortOrder::None,
+            age_filter: None,
+        }
+    }
+}
+
+impl ScanOptions {
+    /// Creates options with the defaults and no paths.
+    pub fn new() -> ScanOptions {
+        ScanOptions::default()
+    }
+
+    /// Adds a path to scan. Each path gets its own tree in the result.
+    pub fn path(mut self, path: impl Into<PathBuf>) -> ScanOptions {
+        self.paths.push(path.into());
+        self
+    }
+
+    /// Adds several paths to scan, in order.
+    pub fn paths<P: Into<PathBuf>>(mut self, paths: impl IntoIterator<Item = P>) -> ScanOptions {
+        self.paths.extend(paths.into_iter().map(Into::into));
+        self
+    }
+
+    /// Limits the trees to `depth` levels below each path (`-d`).
+    ///
+    /// Deeper entries still count towards the totals of their ancestors.
+    pub fn max_depth(mut self, depth: usize) -> ScanOptions {
+        self.traversal.max_depth = Some(depth);
+        self
+    }
+
+    /// Uses `threads` worker threads instead of one per core (`-j`).
+    pub fn threads(mut self, threads: usize) -> ScanOptions {
+        self.traversal.threads = Some(threads);
+        self
+    }
+
+    /// Counts every hard link to a file instead of the first one only.
+    pub fn count_links(mut self, count_links: bool) -> ScanOptions {
+        self.traversal.count_links = count_links;
+        self
+    }
+
+    /// Skips directories on other filesystems than their path's (`-x`).
+    pub fn one_file_system(mut self, one_file_system: bool) -> ScanOptions {
+        self.traversal.one_file_system = one_file_system;
+        self
+    }
+
+    /// Follows no symlinks, those given as paths (`-D`) or all (`-L`).
+    pub fn follow_links(mut self, dereference: Dereference) -> ScanOptions {
+        self.traversal.dereference = dereference;
+        self
+    }
+
+    /// Adds a gitignore-style pattern of paths to skip (`--exclude`).
+    pub fn exclude(mut self, pattern: impl Into<String>) -> ScanOptions {
+        self.traversal.exclude.push(pattern.into());
+        self
+    }
+
+    /// Adds a file listing patterns to skip, one per line
+    /// (`--exclude-from`).
+    pub fn exclude_from(mut self, file: impl Into<PathBuf>) -> ScanOptions {
+        self.traversal.exclude_from.push(file.into());
+        self
+    }
+
+    /// Skips paths ignored by `.gitignore` files (`--respect-gitignore`).
+    pub fn respect_gitignore(mut self, respect_gitignore: bool) -> ScanOptions {
+        self.traversal.respect_gitignore = respect_gitignore;
+        self
+    }
+
+    /// Selects the size that sorting by size compares.
+    ///
+    /// Trees always carry both sizes; renderers pick theirs separately.
+    pub fn size_mode(mut self, size_mode: SizeMode) -> ScanOptions {
+        self.size_mode = size_mode;
+        self
+    }
+
+    /// Sorts the entries of every directory (`--sort`).
+    pub fn sort(mut self, sort: SortOrder) -> ScanOptions {
+        self.sort = sort;
+        self
+    }
+
+    /// Keeps only files matching `filter`, and the directories leading to
+    /// them (`--older-than`, `--newer-than`).
+    ///
+    /// The whole tree is scanned and filtered before the depth limit is
+    /// applied, so totals only include matching files.
+    pub fn age_filter(mut self, filter: AgeFilter) -> ScanOptions {
+        self.age_filter = Some(filter);
+        self
+    }
+
+    /// Uses the bounded-memory streaming traversal (`--stream`).
+    pub fn stream(mut self, stream: bool) -> ScanOptions {
+        self.stream = stream;
+        self
+    }
+
+    /// Reuses the listings of unchanged directories from `cache`, and
+    /// records the directories read into it (see [`crate::cache`]).
+    ///
+    /// Loading and saving the cache file stays with the caller.
+    pub fn cache(mut self, cache: Arc<ScanCache>) -> ScanOptions {
+        self.traversal.cache = Some(cache);
+        self
+    }
+
+    /// Sends progress reports to `handler` while scanning.
+    pub fn progress(mut self, handler: ProgressHandler) -> ScanOptions {
+        self.traversal.progress = Some(handler);
+        self
+    }
+
+    /// Stops the scan early once `token` is cancelled; the result then
+    /// holds partial trees (see [`crate::cancel`]).
+    pub fn cancel(mut self, token: CancelToken) -> ScanOptions {
+        self.traversal.cancel = Some(token);
+        self
+    }
+
+    /// Returns the settings passed to the traversal of each path.
+    ///
+    /// Useful to run a traversal directly, e.g. for a
+    /// [`LiveTree`](crate::watch::LiveTree).
+    pub fn traversal_options(&self) -> &TraversalOptions {
+        &self.traversal
+    }
+
+    /// Scans every path, one after the other.
+    ///
+    /// Each tree is filtered by age, limited in depth and sorted as
+    /// configured. Entries that couldn't be read don't fail the scan; they
+    /// are collected in [`ScanResult::skipped`].
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a path doesn't exist or can't be read at all.
+    /// Paths after it are not scanned.
+    pub fn scan(&self) -> Result<ScanResult, DuskError> {
+        // Age filters must see every file, so they scan the full depth and
+        // collapse afterwards
+        let mut options = self.traversal.clone();
+        if self.age_filter.is_some() {
+            options.max_depth = None;
+        }
+
+        // Like du, no paths means the current directory
+        let current = [PathBuf::from(".")];
+        let paths = if self.paths.is_empty() {
+            &current[..]
+        } else {
+            &self.paths[..]
+        };
+
+        let mut result = ScanResult::default();
+        for path in paths {
+            let mut report = self.traverse(path, &options)?;
+
+            // Keep only files of the requested age, then limit the depth
+            if let Some(filter) = &self.age_filter {
+                filter_by_age(&mut report.tree, filter);
+                if let Some(max_depth) = self.traversal.max_depth {
+                    report.tree.collapse_to_depth(max_depth);
+                }
+            }
+
+            // Recursive on the entire tree
+            report.tree.sort_entries_for(&self.sort, self.size_mode);
+
+            result.trees.push(report.tree);
+            result.skipped.append(&mut report.skipped);
+        }
+        Ok(result)
+    }
+
+    /// Scans one path with the selected traversal: parallel metadata
+    /// collection by default, or streaming aggregation.
+    fn traverse(
+        &self,
+        path: &Path,
+        options: &TraversalOptions,
+    ) -> Result<TraversalReport, DuskError> {
+        if self.stream {
+            traverse_streaming_report(path, options)
+        } else {
+            traverse_parallel_report(path, options)
+        }
+    }
+}
+
+/// What [`ScanOptions::scan`] found.
+#[derive(Debug, Default)]
+pub struct ScanResult {
+    /// One tree per path, in the order the paths were added.
+    pub trees: Vec<DiskEntry>,
+    /// Paths that couldn't be read, for all trees. Skipped directories
+    /// appear in the trees without contents.
+    pub skipped: Vec<SkippedEntry>,
+}
+
+impl ScanResult {
+    /// Returns `true` if a cancelled scan left some tree incomplete.
+    pub fn is_incomplete(&self) -> bool {
+        self.trees.iter().any(|tree| tree.incomplete)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::entry::TimeKind;
+    use std::fs;
+    use tempfile::TempDir;
+
+    fn create_test_dir() -> TempDir {
+        let tmp = TempDir::new().unwrap();
+        fs::write(tmp.path().join("small.txt"), "hi").unwrap();
+        fs::create_dir(tmp.path().join("subdir")).unwrap();
+        fs::write(tmp.path().join("subdir/large.bin"), "0123456789").unwrap();
+        tmp
+    }
+
+    #[test]
+    fn test_scan_returns_one_tree_per_path() {
+        let tmp = create_test_dir();
+        let result = ScanOptions::new()
+            .path(tmp.path())
+            .path(tmp.path().join("subdir"))
+            .scan()
+            .unwrap();
+
+        assert_eq!(result.trees.len(), 2);
+        assert!(result.trees[1].path.ends_with("subdir"));
+        assert!(result.skipped.is_empty());
+        assert!(!result.is_incomplete());
+    }
+
+    #[test]
+    fn test_scan_sorts_and_limits_depth() {
+        let tmp = create_test_dir();
+        let result = ScanOptions::new()
+            .path(tmp.path())
+            .max_depth(1)
+            .size_mode(SizeMode::Apparent)
+            .sort(SortOrder::SizeDescending)
+            .scan()
+            .unwrap();
+
+        // subdir (10 bytes and itself) before small.txt (2 bytes)
+        let tree = &result.trees[0];
+        assert!(tree.children[0].path.ends_with("subdir"));
+        assert!(tree.children[0].children.is_empty());
+        assert!(tree.children[1].path.ends_with("small.txt"));
+    }
+
+    #[test]
+    fn test_scan_matches_stream() {
+        let tmp = create_test_dir();
+        let options = ScanOptions::new().path(tmp.path()).sort(SortOrder::Name);
+        let paths = |result: ScanResult| {
+            let tree = &result.trees[0];
+            let mut paths = vec![(tree.path.clone(), tree.total_size())];
+            for child in &tree.children {
+                paths.push((child.path.clone(), child.total_size()));
+            }
+            paths
+        };
+        let parallel = paths(options.scan().unwrap());
+        let stream = paths(options.stream(true).scan().unwrap());
+        assert_eq!(parallel, stream);
+    }
+
+    #[test]
+    fn test_age_filter_applies_before_depth_limit() {
+        let tmp = create_test_dir();
+        // Everything was written just now, so nothing is older than that
+        let filter = AgeFilter {
+            before: Some(0),
+            since: None,
+            kind: TimeKind::Modified,
+        };
+        let result = ScanOptions::new()
+            .path(tmp.path())
+            .max_depth(0)
+            .age_filter(filter)
+            .scan()
+            .unwrap();
+
+        // Only the root itself is left
+        let tree = &result.trees[0];
+        assert!(tree.children.is_empty());
+        assert_eq!(tree.total_size(), tree.size_bytes);
+    }
+
+    #[test]
+    fn test_cancelled_scan_is_incomplete() {
+        let tmp = create_test_dir();
+        let token = CancelToken::new();
+        token.cancel();
+        let result = ScanOptions::new()
+            .path(tmp.path())
+            .cancel(token)
+            .scan()
+            .unwrap();
+        assert!(result.is_incomplete());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_follow_links_reports_loops() {
+        let tmp = create_test_dir();
+        std::os::unix::fs::symlink("..", tmp.path().join("subdir/up")).unwrap();
+
+        let result = ScanOptions::new()
+            .path(tmp.path())
+            .follow_links(Dereference::Always)
+            .scan()
+            .unwrap();
+        assert_eq!(result.skipped.len(), 1);
+        assert!(result.skipped[0].path.ends_with("subdir/up"));
+
+        // Not followed, the link is listed with its target
+        let result = ScanOptions::new()
+            .path(tmp.path())
+            .sort(SortOrder::Name)
+            .scan()
+            .unwrap();
+        assert!(result.skipped.is_empty());
+        let subdir = &result.trees[0].children[1];
+        assert!(subdir.children[1].path.ends_with("up"));
+        assert_eq!(subdir.children[1].link_target, Some(PathBuf::from("..")));
+    }
+
+    #[test]
+    fn test_missing_path_fails() {
+        let tmp = create_test_dir();
+        let result = ScanOptions::new().path(tmp.path().join("missing")).scan();
+        assert!(matches!(result, Err(DuskError::PathNotFound(_))));
+    }
+}
@@ -0,0 +1,370 @@
+//! Saving scans to snapshot files and loading them back.
+//!
+//! `dusk --save FILE` writes the scanned trees to a snapshot, and
+//! `dusk diff OLD NEW` loads two snapshots to compare them (see
+//! [`crate::diff`]) without touching the disk they describe.
+//!
+//! # File Format
+//!
+//! A snapshot is a gzip-compressed JSON document:
+//!
+//! ```json
+//! {
+//!   "format": "dusk-snapshot",
+//!   "version": 1,
+//!   "created": 1767225600,
+//!   "entries": [
+//!     {"path": "/var", "type": "directory", "depth": 0, "size": 4096, "allocated": 4096},
+//!     {"path": "/var/log", "type": "directory", "depth": 1, "size": 4096, "allocated": 4096}
+//!   ]
+//! }
+//! ```
+//!
+//! - `version` is bumped on incompatible changes; newer versions are rejected
+//! - `created` is the save time in seconds since the Unix epoch
+//! - `entries` lists every entry of every tree in pre-order (parents before
+//!   children); an entry with depth 0 starts a new tree
+//! - `size` and `allocated` are the entry's own apparent and allocated sizes,
+//!   so a diff can use either mode
+//! - `incomplete` is only present (and `true`) on directories a stopped scan
+//!   didn't finish reading (see [`crate::cancel`])
+//!
+//! The flat list keeps nesting shallow, so arbitrarily deep trees load fine.
+//! Paths are stored as UTF-8; non-UTF-8 bytes are replaced when saving.
+//!
+//! # Examples
+//!
+//! ```no_run
+//! use disk_usage_clone::snapshot::{load_snapshot, save_snapshot};
+//! use disk_usage_clone::traversal::traverse;
+//! use std::path::Path;
+//!
+//! let tree = traverse(Path::new("/var"), None).unwrap();
+//! save_snapshot(Path::new("var.dusk"), &[tree]).unwrap();
+//!
+//! let trees = load_snapshot(Path::new("var.dusk")).unwrap();
+//! assert_eq!(trees.len(), 1);
+//! ```
+
+use std::fs::File;
+use std::io::{self, BufReader, BufWriter, Read, Write};
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use flate2::Compression;
+use flate2::read::GzDecoder;
+use flate2::write::GzEncoder;
+use serde_json::{Value, json};
+
+use crate::entry::{DiskEntry, EntryType};
+use crate::error::DuskError;
+
+/// Value of the `format` field, identifying dusk snapshots.
+const SNAPSHOT_FORMAT: &str = "dusk-snapshot";
+
+/// Current snapshot format version.
+pub const SNAPSHOT_VERSION: u64 = 1;
+
+/// Writes trees to a snapshot file, replacing it if it exists.
+///
+/// # Arguments
+///
+/// * `path` - File to write
+/// * `trees` - Scanned trees, one per analyzed path
+///
+/// # Errors
+///
+/// Returns `IoError` if the file can't be created or written.
+pub fn save_snapshot(path: &Path, trees: &[DiskEntry]) -> Result<(), DuskError> {
+    let mut entries = Vec::new();
+    for tree in trees {
+        collect_entries(tree, 0, &mut entries);
+    }
+
+    let created = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |d| d.as_secs());
+    let document = json!({
+        "format": SNAPSHOT_FORMAT,
+        "version": SNAPSHOT_VERSION,
+        "created": created,
+        "entries": entries,
+    });
+
+    let file = BufWriter::new(File::create(path)?);
+    let mut encoder = GzEncoder::new(file, Compression::default());
+    serde_json::to_writer(&mut encoder, &document).map_err(io::Error::from)?;
+    encoder.finish()?.flush()?;
+    Ok(())
+}
+
+/// Appends an entry and its descendants in pre-order.
+///
+/// The depth is counted from the tree's root rather than taken from
+/// `entry.depth`, so the file is consistent whatever the tree claims.
+fn collect_entries(entry: &DiskEntry, depth: usize, entries: &mut Vec<Value>) {
+    let mut value = json!({
+        "path": entry.path.to_string_lossy(),
+        "type": entry.entry_type.as_str(),
+        "depth": depth,
+        "size": entry.size_bytes,
+        "allocated": entry.allocated_bytes,
+    });
+    if entry.incomplete {
+        value["incomplete"] = json!(true);
+    }
+    entries.push(value);
+    for child in &entry.children {
+        collect_entries(child, depth + 1, entries);
+    }
+}
+
+/// Reads the trees stored in a snapshot file.
+///
+/// # Errors
+///
+/// - `PathNotFound` if the file doesn't exist
+/// - `IoError` if the file can't be read
+/// - `InvalidSnapshot` if it is not a dusk snapshot, is damaged, or uses a
+///   newer format version
+pub fn load_snapshot(path: &Path) -> Result<Vec<DiskEntry>, DuskError> {
+    let file = File::open(path).map_err(|e| match e.kind() {
+        io::ErrorKind::NotFound => DuskError::PathNotFound(path.to_path_buf()),
+        _ => DuskError::IoError(e),
+    })?;
+
+    let invalid = |reason: String| DuskError::InvalidSnapshot(path.to_path_buf(), reason);
+
+    let mut contents = String::new();
+    GzDecoder::new(BufReader::new(file))
+        .read_to_string(&mut contents)
+        .map_err(|e| invalid(format!("not a compressed dusk snapshot ({e})")))?;
+
+    let document: Value = serde_json::from_str(&contents).map_err(|e| invalid(e.to_string()))?;
+
+    if document["format"] != SNAPSHOT_FORMAT {
+        return Err(invalid("not a dusk snapshot".to_string()));
+    }
+    let version = document["version"]
+        .as_u64()
+        .ok_or_else(|| invalid("missing format version".to_string()))?;
+    if version > SNAPSHOT_VERSION {
+        return Err(invalid(format!(
+            "unsupported format version {version} (this dusk reads up to {SNAPSHOT_VERSION})"
+        )));
+    }
+
+    let entries = document["entries"]
+        .as_array()
+        .ok_or_else(|| invalid("missing entries".to_string()))?;
+    build_trees(entries).map_err(invalid)
+}
+
+/// Rebuilds trees from a pre-order list of entries with depths.
+///
+/// Keeps the chain of open ancestors on a stack: an entry at depth `d`
+/// closes everything at depth `d` or deeper, then becomes the newest
+/// ancestor itself.
+fn build_trees(entries: &[Value]) -> Result<Vec<DiskEntry>, String> {
+    let mut roots = Vec::new();
+    let mut stack: Vec<DiskEntry> = Vec::new();
+
+    for (index, value) in entries.iter().enumerate() {
+        let entry = decode_entry(value).ok_or_else(|| format!("malformed entry #{index}"))?;
+        if entry.depth > stack.len() {
+            return Err(format!("entry #{index} has no parent"));
+        }
+
+        while stack.len() > entry.depth {
+            close_top(&mut stack, &mut roots);
+        }
+        stack.push(entry);
+    }
+
+    while !stack.is_empty() {
+        close_top(&mut stack, &mut roots);
+    }
+
+    Ok(roots)
+}
+
+/// Pops the deepest open entry and attaches it to its parent (or the roots).
+fn close_top(stack: &mut Vec<DiskEntry>, roots: &mut Vec<DiskEntry>) {
+    if let Some(entry) = stack.pop() {
+        match stack.last_mut() {
+            Some(parent) => parent.children.push(entry),
+            None => roots.push(entry),
+        }
+    }
+}
+
+/// Decodes one entry object, or `None` if a field is missing or invalid.
+fn decode_entry(value: &Value) -> Option<DiskEntry> {
+    let mut entry = DiskEntry::new(
+        PathBuf::from(value["path"].as_str()?),
+        value["size"].as_u64()?,
+        EntryType::parse(value["type"].as_str()?)?,
+        usize::try_from(value["depth"].as_u64()?).ok()?,
+    );
+    entry.allocated_bytes = value["allocated"].as_u64()?;
+    entry.incomplete = value["incomplete"].as_bool().unwrap_or(false);
+    Some(entry)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::entry::SizeMode;
+    use std::fs;
+    use tempfile::TempDir;
+
+    fn sample_tree(root: &str) -> DiskEntry {
+        let mut tree = DiskEntry::new(PathBuf::from(root), 4096, EntryType::Directory, 0);
+        let mut sub = DiskEntry::new(
+            PathBuf::from(format!("{root}/sub")),
+            4096,
+            EntryType::Directory,
+            1,
+        );
+        let mut file = DiskEntry::new(
+            PathBuf::from(format!("{root}/sub/data.bin")),
+            10,
+            EntryType::File,
+            2,
+        );
+        file.allocated_bytes = 4096;
+        sub.children.push(file);
+        tree.children.push(sub);
+        tree.children.push(DiskEntry::new(
+            PathBuf::from(format!("{root}/link")),
+            7,
+            EntryType::Symlink,
+            1,
+        ));
+        tree
+    }
+
+    /// Flattens a tree into comparable tuples.
+    fn flatten(entry: &DiskEntry, out: &mut Vec<(PathBuf, EntryType, usize, u64, u64)>) {
+        out.push((
+            entry.path.clone(),
+            entry.entry_type.clone(),
+            entry.depth,
+            entry.size_bytes,
+            entry.allocated_bytes,
+        ));
+        for child in &entry.children {
+            flatten(child, out);
+        }
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let tmp = TempDir::new().unwrap();
+        let file = tmp.path().join("scan.dusk");
+        let trees = vec![sample_tree("/a"), sample_tree("/b")];
+        save_snapshot(&file, &trees).unwrap();
+
+        let loaded = load_snapshot(&file).unwrap();
+        assert_eq!(loaded.len(), 2);
+        for (original, loaded) in trees.iter().zip(&loaded) {
+            let (mut a, mut b) = (Vec::new(), Vec::new());
+            flatten(original, &mut a);
+            flatten(loaded, &mut b);
+            assert_eq!(a, b);
+            assert_eq!(
+                original.total_size_for(SizeMode::Allocated),
+                loaded.total_size_for(SizeMode::Allocated)
+            );
+        }
+    }
+
+    #[test]
+    fn test_round_trip_keeps_incomplete_flag() {
+        let tmp = TempDir::new().unwrap();
+        let file = tmp.path().join("partial.dusk");
+        let mut tree = sample_tree("/a");
+        tree.incomplete = true;
+        tree.children[0].incomplete = true;
+        save_snapshot(&file, &[tree]).unwrap();
+
+        let loaded = load_snapshot(&file).unwrap();
+        assert!(loaded[0].incomplete);
+        assert!(loaded[0].children[0].incomplete);
+        assert!(!loaded[0].children[1].incomplete);
+    }
+
+    #[test]
+    fn test_round_trip_deep_tree() {
+        let tmp = TempDir::new().unwrap();
+        let file = tmp.path().join("deep.dusk");
+
+        // Deeper than serde_json's nesting limit for nested documents
+        let mut path = PathBuf::from("/deep");
+        let mut entries = Vec::new();
+        for depth in 0..300 {
+            entries.push(DiskEntry::new(path.clone(), 1, EntryType::Directory, depth));
+            path.push("d");
+        }
+        let mut tree = entries.pop().unwrap();
+        while let Some(mut parent) = entries.pop() {
+            parent.children.push(tree);
+            tree = parent;
+        }
+
+        save_snapshot(&file, &[tree]).unwrap();
+        let loaded = load_snapshot(&file).unwrap();
+        assert_eq!(loaded[0].total_size_for(SizeMode::Apparent), 300);
+    }
+
+    #[test]
+    fn test_load_missing_file() {
+        let result = load_snapshot(Path::new("/nonexistent/scan.dusk"));
+        assert!(matches!(result, Err(DuskError::PathNotFound(_))));
+    }
+
+    #[test]
+    fn test_load_rejects_garbage() {
+        let tmp = TempDir::new().unwrap();
+        let file = tmp.path().join("garbage.dusk");
+        fs::write(&file, "not gzip at all").unwrap();
+        assert!(matches!(
+            load_snapshot(&file),
+            Err(DuskError::InvalidSnapshot(_, _))
+        ));
+    }
+
+    /// Writes a gzip-compressed JSON document.
+    fn write_document(file: &Path, document: &Value) {
+        let mut encoder = GzEncoder::new(File::create(file).unwrap(), Compression::default());
+        serde_json::to_writer(&mut encoder, document).unwrap();
+        encoder.finish().unwrap();
+    }
+
+    #[test]
+    fn test_load_rejects_newer_version() {
+        let tmp = TempDir::new().unwrap();
+        let file = tmp.path().join("future.dusk");
+        write_document(
+            &file,
+            &json!({"format": SNAPSHOT_FORMAT, "version": SNAPSHOT_VERSION + 1, "entries": []}),
+        );
+
+        let err = load_snapshot(&file).unwrap_err();
+        assert!(err.to_string().contains("unsupported format version"));
+    }
+
+    #[test]
+    fn test_load_rejects_orphan_entry() {
+        let tmp = TempDir::new().unwrap();
+        let file = tmp.path().join("orphan.dusk");
+        let entry = json!({"path": "/x", "type": "file", "depth": 2, "size": 1, "allocated": 1});
+        write_document(
+            &file,
+            &json!({"format": SNAPSHOT_FORMAT, "version": 1, "entries": [entry]}),
+        );
+
+        let err = load_snapshot(&file).unwrap_err();
+        assert!(err.to_string().contains("has no parent"));
+    }
+}
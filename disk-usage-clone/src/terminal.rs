@@ -0,0 +1,244 @@
+//! Terminal abstraction for the interactive browser.
+//!
+//! Defines the [`Terminal`] trait for all terminal I/O done by `--interactive`,
+//! with a production [`CrosstermTerminal`] implementation and a `MockTerminal`
+//! for unit testing. The browser only talks to `dyn Terminal`, so its view
+//! logic can be tested without a real TTY.
+
+use std::io::{self, Write};
+
+use crossterm::event::Event;
+
+use crate::error::DuskError;
+
+/// Abstraction over terminal operations for testability.
+pub trait Terminal {
+    /// Enter alternate screen and raw mode, hide the cursor.
+    fn enter(&mut self) -> Result<(), DuskError>;
+    /// Leave alternate screen and raw mode, show the cursor.
+    fn leave(&mut self) -> Result<(), DuskError>;
+    /// Get terminal size as (columns, rows).
+    fn size(&self) -> Result<(u16, u16), DuskError>;
+    /// Clear the screen.
+    fn clear(&mut self) -> Result<(), DuskError>;
+    /// Move cursor to position (col, row), 0-based.
+    fn move_to(&mut self, col: u16, row: u16) -> Result<(), DuskError>;
+    /// Print text at the current cursor position.
+    fn print(&mut self, text: &str) -> Result<(), DuskError>;
+    /// Print text with inverted colors (header, status line, selection).
+    fn print_inverted(&mut self, text: &str) -> Result<(), DuskError>;
+    /// Flush output.
+    fn flush(&mut self) -> Result<(), DuskError>;
+    /// Read the next terminal event (blocking).
+    fn read_event(&mut self) -> Result<Event, DuskError>;
+}
+
+/// Converts a crossterm I/O error into a [`DuskError::TerminalError`].
+fn terminal_error(err: io::Error) -> DuskError {
+    DuskError::TerminalError(err.to_string())
+}
+
+/// Production terminal using crossterm.
+pub struct CrosstermTerminal {
+    stdout: io::Stdout,
+}
+
+impl CrosstermTerminal {
+    /// Create a new crossterm-backed terminal writing to stdout.
+    pub fn new() -> Self {
+        CrosstermTerminal {
+            stdout: io::stdout(),
+        }
+    }
+}
+
+impl Default for CrosstermTerminal {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Terminal for CrosstermTerminal {
+    fn enter(&mut self) -> Result<(), DuskError> {
+        crossterm::terminal::enable_raw_mode().map_err(terminal_error)?;
+        crossterm::execute!(
+            self.stdout,
+            crossterm::terminal::EnterAlternateScreen,
+            crossterm::cursor::Hide
+        )
+        .map_err(terminal_error)
+    }
+
+    fn leave(&mut self) -> Result<(), DuskError> {
+        crossterm::execute!(
+            self.stdout,
+            crossterm::cursor::Show,
+            crossterm::terminal::LeaveAlternateScreen
+        )
+        .map_err(terminal_error)?;
+        crossterm::terminal::disable_raw_mode().map_err(terminal_error)
+    }
+
+    fn size(&self) -> Result<(u16, u16), DuskError> {
+        crossterm::terminal::size().map_err(terminal_error)
+    }
+
+    fn clear(&mut self) -> Result<(), DuskError> {
+        crossterm::queue!(
+            self.stdout,
+            crossterm::terminal::Clear(crossterm::terminal::ClearType::All)
+        )
+        .map_err(terminal_error)
+    }
+
+    fn move_to(&mut self, col: u16, row: u16) -> Result<(), DuskError> {
+        crossterm::queue!(self.stdout, crossterm::cursor::MoveTo(col, row)).map_err(terminal_error)
+    }
+
+    fn print(&mut self, text: &str) -> Result<(), DuskError> {
+        write!(self.stdout, "{text}").map_err(terminal_error)
+    }
+
+    fn print_inverted(&mut self, text: &str) -> Result<(), DuskError> {
+        crossterm::queue!(
+            self.stdout,
+            crossterm::style::SetAttribute(crossterm::style::Attribute::Reverse),
+            crossterm::style::Print(text),
+            crossterm::style::SetAttribute(crossterm::style::Attribute::Reset),
+        )
+        .map_err(terminal_error)
+    }
+
+    fn flush(&mut self) -> Result<(), DuskError> {
+        self.stdout.flush().map_err(terminal_error)
+    }
+
+    fn read_event(&mut self) -> Result<Event, DuskError> {
+        crossterm::event::read().map_err(terminal_error)
+    }
+}
+
+/// Mock terminal for unit testing. Records output and provides scripted events.
+#[cfg(test)]
+pub struct MockTerminal {
+    pub width: u16,
+    pub height: u16,
+    pub output: Vec<String>,
+    pub events: Vec<Event>,
+    event_index: usize,
+    pub entered: bool,
+    pub left: bool,
+}
+
+#[cfg(test)]
+impl MockTerminal {
+    pub fn new(width: u16, height: u16, events: Vec<Event>) -> Self {
+        MockTerminal {
+            width,
+            height,
+            output: Vec::new(),
+            events,
+            event_index: 0,
+            entered: false,
+            left: false,
+        }
+    }
+
+    /// Returns everything printed since the last `clear`, i.e. the last frame.
+    pub fn last_frame(&self) -> &[String] {
+        let start = self
+            .output
+            .iter()
+            .rposition(|s| s == "[CLEAR]")
+            .map_or(0, |i| i + 1);
+        &self.output[start..]
+    }
+}
+
+#[cfg(test)]
+impl Terminal for MockTerminal {
+    fn enter(&mut self) -> Result<(), DuskError> {
+        self.entered = true;
+        Ok(())
+    }
+
+    fn leave(&mut self) -> Result<(), DuskError> {
+        self.left = true;
+        Ok(())
+    }
+
+    fn size(&self) -> Result<(u16, u16), DuskError> {
+        Ok((self.width, self.height))
+    }
+
+    fn clear(&mut self) -> Result<(), DuskError> {
+        self.output.push("[CLEAR]".to_string());
+        Ok(())
+    }
+
+    fn move_to(&mut self, col: u16, row: u16) -> Result<(), DuskError> {
+        self.output.push(format!("[MOVE {col},{row}]"));
+        Ok(())
+    }
+
+    fn print(&mut self, text: &str) -> Result<(), DuskError> {
+        self.output.push(text.to_string());
+        Ok(())
+    }
+
+    fn print_inverted(&mut self, text: &str) -> Result<(), DuskError> {
+        self.output.push(format!("[INV]{text}[/INV]"));
+        Ok(())
+    }
+
+    fn flush(&mut self) -> Result<(), DuskError> {
+        Ok(())
+    }
+
+    fn read_event(&mut self) -> Result<Event, DuskError> {
+        if self.event_index < self.events.len() {
+            let event = self.events[self.event_index].clone();
+            self.event_index += 1;
+            Ok(event)
+        } else {
+            Err(DuskError::TerminalError("No more events".to_string()))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+
+    #[test]
+    fn test_mock_terminal_enter_leave() {
+        let mut term = MockTerminal::new(80, 24, vec![]);
+        assert!(!term.entered);
+        term.enter().unwrap();
+        assert!(term.entered);
+        term.leave().unwrap();
+        assert!(term.left);
+    }
+
+    #[test]
+    fn test_mock_terminal_events() {
+        let events = vec![Event::Key(KeyEvent::new(
+            KeyCode::Char('q'),
+            KeyModifiers::NONE,
+        ))];
+        let mut term = MockTerminal::new(80, 24, events);
+        assert!(matches!(term.read_event().unwrap(), Event::Key(_)));
+        // No more events
+        assert!(term.read_event().is_err());
+    }
+
+    #[test]
+    fn test_mock_terminal_last_frame() {
+        let mut term = MockTerminal::new(80, 24, vec![]);
+        term.print("old").unwrap();
+        term.clear().unwrap();
+        term.print("new").unwrap();
+        assert_eq!(term.last_frame(), ["new".to_string()]);
+    }
+}
@@ -1,43 +1,63 @@
-//! Filesystem traversal with parallel metadata collection.
+//! Filesystem traversal with parallel directory reading.
 //!
-//! This module implements the core disk usage analysis logic using a hybrid approach:
-//! 1. Single-threaded directory traversal with `walkdir`
-//! 2. Parallel metadata collection with `rayon`
-//! 3. Tree construction from flat entry list
+//! This module implements the core disk usage analysis logic. Two walkers
+//! share the same entry selection rules (exclusions, mount points, which
+//! symlinks to follow) and produce identical trees:
+//! 1. [`traverse`]: single-threaded walk with `walkdir`
+//! 2. [`traverse_parallel`]: work-stealing walk on a `rayon` pool, where
+//!    every directory is a separate task
 //!
 //! # Architecture
 //!
 //! ```text
-//! ┌─────────────┐
-//! │  walkdir    │  Single-threaded directory enumeration (fast readdir)
-//! │   (fast)    │  Collects all DirEntry objects
-//! └──────┬──────┘
-//!        │
-//!        ├──▶ Vec<DirEntry>
-//!        │
-//! ┌──────▼──────┐
-//! │   rayon     │  Parallel metadata collection (.metadata() syscalls)
-//! │ (parallel)  │  Converts to FlatEntry with sizes
-//! └──────┬──────┘
-//!        │
-//!        ├──▶ Vec<FlatEntry>
-//!        │
-//! ┌──────▼──────┐
-//! │ build_tree  │  Single-threaded tree construction
-//! │  (fast)     │  Assembles DiskEntry tree
-//! └──────┬──────┘
-//!        │
-//!        └──▶ DiskEntry (root)
+//! ┌──────────────────────────────┐
+//! │ rayon pool                   │  One task per directory:
+//! │  ┌────────┐   ┌────────┐     │   readdir → filter → stat entries
+//! │  │ dir A  │──▶│ dir A/x│ ... │   → spawn a task per subdirectory
+//! │  └────────┘   └────────┘     │  Idle workers steal pending directories
+//! └──────────────┬───────────────┘
+//!                │
+//!                ├──▶ Vec<FlatEntry>
+//!                │
+//! ┌──────────────▼───────────────┐
+//! │ build_tree                   │  Single-threaded tree construction
+//! │  (fast)                      │  Assembles DiskEntry tree
+//! └──────────────┬───────────────┘
+//!                │
+//!                └──▶ DiskEntry (root)
 //! ```
 //!
 //! # Performance
 //!
-//! This approach optimizes for the common case where:
-//! - Directory enumeration is fast (readdir is quick)
-//! - Metadata collection is slow (many stat syscalls)
-//! - Tree building is fast (in-memory data structure)
+//! Both `readdir` and `stat` are I/O-bound. On network filesystems and
+//! spinning disks the latency of reading a directory dominates, so reading
+//! many directories at once keeps every worker busy instead of one thread
+//! enumerating while the others wait. Within a large directory the `stat`
+//! calls are spread over the pool as well.
 //!
-//! Parallelizing metadata collection gives the biggest performance boost.
+//! # Streaming
+//!
+//! Both phases above hold one record per entry, which is too much memory on
+//! trees with tens of millions of files. [`traverse_streaming`] instead
+//! folds each entry into the tree as the walk reaches it:
+//!
+//! ```text
+//! walkdir (pre-order) ──▶ stack of open entries ──▶ DiskEntry (root)
+//!                              │
+//!                              └─ entries below max_depth: size added to
+//!                                 the deepest kept ancestor, then dropped
+//! ```
+//!
+//! Memory is proportional to the depth and fan-out of the kept tree, not to
+//! the number of files. The result equals [`traverse`] with the same
+//! options.
+//!
+//! # Caching
+//!
+//! With [`TraversalOptions::cache`] set, the parallel walker reuses the
+//! listings of directories that haven't changed since an earlier run (see
+//! [`crate::cache`]) and reads only the modified ones. The other walkers
+//! ignore the cache.
 //!
 //! # Examples
 //!
@@ -57,22 +77,370 @@
 //! use disk_usage_clone::traversal::traverse_parallel;
 //! use std::path::Path;
 //!
-//! // Use 4 threads for parallel metadata collection
+//! // Use 4 threads for parallel directory reading
 //! let tree = traverse_parallel(
 //!     Path::new("/tmp"),
 //!     None,       // No max depth limit
 //!     Some(4),    // Use 4 threads
 //! ).expect("Traversal failed");
 //! ```
-
-use std::collections::HashMap;
+//!
+//! ## Custom options
+//!
+//! ```no_run
+//! use disk_usage_clone::traversal::{traverse_parallel_with_options, TraversalOptions};
+//! use std::path::Path;
+//!
+//! // Count every hard link in full (like `du -l`)
+//! let options = TraversalOptions {
+//!     count_links: true,
+//!     ..TraversalOptions::default()
+//! };
+//! let tree = traverse_parallel_with_options(Path::new("/nix/store"), &options)
+//!     .expect("Traversal failed");
+//! ```
+//!
+//! # Hard Links
+//!
+//! A file with several hard links is one inode reachable from several paths.
+//! By default each inode is counted once (the first path in walk order gets
+//! its size, the others report 0), matching `du`. The parallel walker
+//! decides this after the walk, so its result doesn't depend on scheduling. Set
+//! [`TraversalOptions::count_links`] to count every link in full.
+//!
+//! # Mount Points
+//!
+//! With [`TraversalOptions::one_file_system`], directories on a different
+//! device than the root are not descended into. They still appear in the
+//! tree, as [`EntryType::MountPoint`] entries with a size of 0, so renderers
+//! can show what was skipped.
+//!
+//! # Exclusions
+//!
+//! [`TraversalOptions::exclude`], [`TraversalOptions::exclude_from`] and
+//! [`TraversalOptions::respect_gitignore`] prune matching paths during the
+//! walk (see [`crate::filter`]). Excluded directories are neither read nor
+//! stat'ed, and don't appear in the tree at all.
+//!
+//! # Symlinks
+//!
+//! Like `du`, symlinks are not followed by default: each is an
+//! [`EntryType::Symlink`] entry sized as the link itself, with its target
+//! in [`DiskEntry::link_target`]. A path given to a traversal that is a
+//! symlink is listed the same way. [`TraversalOptions::dereference`]
+//! follows the links given as paths ([`Dereference::Args`], `du -D`) or
+//! all of them ([`Dereference::Always`], `du -L`).
+//!
+//! Followed links are listed under their own path, as the file or
+//! directory they point to. A directory that leads back to one of its own
+//! ancestors (same device and inode) is not entered: it is left out of the
+//! tree and reported as a [`SkippedEntry`], so a symlink loop can't make
+//! the walk run forever. Links that point nowhere are reported as
+//! unreadable.
+//!
+//! When following every link, like `du -L`, each file and directory is
+//! counted once however many links reach it: the first path in walk order
+//! gets the size, and a directory reached again is listed with a size of 0
+//! and without its contents. Every walker reads such a directory only once.
+//! [`TraversalOptions::count_links`] turns this off along with hard-link
+//! deduplication.
+//!
+//! # Unreadable Paths
+//!
+//! A directory that can't be listed stays in the tree without its contents,
+//! and an entry whose metadata can't be read counts as 0 bytes. Either way
+//! the walk goes on, and the `*_report` functions ([`traverse_report`],
+//! [`traverse_parallel_report`], [`traverse_streaming_report`]) return each
+//! such path as a [`SkippedEntry`] next to the tree, so callers can warn
+//! that totals are too low.
+
+use std::cmp::Reverse;
+use std::collections::hash_map::Entry;
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+use std::fs;
+use std::io;
 use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex, PoisonError};
 
 use rayon::prelude::*;
 use walkdir::WalkDir;
 
-use crate::entry::{DiskEntry, EntryType};
+use crate::cache::{CachedEntry, DirKey, ScanCache};
+use crate::cancel::{self, CancelToken};
+use crate::entry::{DiskEntry, EntryType, Owner, Timestamps};
 use crate::error::DuskError;
+use crate::filter::PathFilter;
+use crate::progress::{ProgressHandler, ProgressTracker, ScanPhase};
+
+/// Which symlinks a traversal follows (`-L`, `-D`).
+///
+/// # Variants
+///
+/// - `Never` - List every symlink as the link itself (default, `du -P`)
+/// - `Args` - Follow the path given to the traversal if it is a symlink,
+///   but no links found below it (`du -D`)
+/// - `Always` - Follow every symlink (`du -L`), with loop detection
+///
+/// # Examples
+///
+/// ```
+/// use disk_usage_clone::traversal::Dereference;
+///
+/// assert_eq!(Dereference::default(), Dereference::Never);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Dereference {
+    #[default]
+    Never,
+    Args,
+    Always,
+}
+
+/// Options controlling a traversal.
+///
+/// All fields have sensible defaults, so callers usually set only what they
+/// need with struct update syntax.
+///
+/// # Examples
+///
+/// ```
+/// use disk_usage_clone::traversal::TraversalOptions;
+///
+/// let options = TraversalOptions {
+///     max_depth: Some(2),
+///     threads: Some(4),
+///     ..TraversalOptions::default()
+/// };
+/// assert!(!options.count_links);
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct TraversalOptions {
+    /// Depth limit applied after traversal (None = unlimited).
+    pub max_depth: Option<usize>,
+    /// Thread count for parallel traversal (None = CPU count).
+    pub threads: Option<usize>,
+    /// Count every hard link in full instead of once per inode.
+    pub count_links: bool,
+    /// Don't descend into directories on other filesystems (`du -x`).
+    pub one_file_system: bool,
+    /// Gitignore-style patterns of paths to skip (`--exclude`).
+    pub exclude: Vec<String>,
+    /// Files with one exclude pattern per line (`--exclude-from`).
+    pub exclude_from: Vec<PathBuf>,
+    /// Also skip paths ignored by `.gitignore` files in the tree.
+    pub respect_gitignore: bool,
+    /// Which symlinks to follow (see [`Dereference`]).
+    pub dereference: Dereference,
+    /// Directory listings reused between runs (parallel traversal only,
+    /// ignored when following all symlinks).
+    pub cache: Option<Arc<ScanCache>>,
+    /// Receives progress reports while scanning (see [`crate::progress`]).
+    pub progress: Option<ProgressHandler>,
+    /// Stops the scan early, returning a partial tree (see [`crate::cancel`]).
+    pub cancel: Option<CancelToken>,
+}
+
+/// Remembers which multiply-linked inodes have already been counted.
+///
+/// The set is behind a `Mutex` so walkers can share it by reference. Only
+/// inodes with a link count above 1 are recorded, which keeps the set
+/// small on typical trees, unless every symlink is followed: then any
+/// entry can be reached by several paths, and every inode is recorded.
+#[derive(Default)]
+struct LinkTracker {
+    seen: Mutex<HashSet<(u64, u64)>>,
+    /// Track every inode, not only multiply-linked files (`-L`).
+    every_inode: bool,
+}
+
+impl LinkTracker {
+    /// Returns the tracker for a walk with `options`, or `None` when
+    /// counting every link.
+    fn for_options(options: &TraversalOptions) -> Option<LinkTracker> {
+        (!options.count_links).then(|| LinkTracker {
+            seen: Mutex::default(),
+            every_inode: options.dereference == Dereference::Always,
+        })
+    }
+
+    /// Records a `(device, inode)` pair.
+    ///
+    /// Returns `true` the first time the pair is seen, `false` afterwards.
+    fn first_sighting(&self, dev: u64, ino: u64) -> bool {
+        self.seen
+            .lock()
+            // A panicking worker can't leave the set half-updated
+            .unwrap_or_else(PoisonError::into_inner)
+            .insert((dev, ino))
+    }
+}
+
+/// What the traversal was doing when a path had to be skipped.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SkipAction {
+    /// Listing a directory failed; its contents are missing from the tree.
+    ReadDirectory,
+    /// Reading an entry's metadata failed; it counts as 0 bytes.
+    Access,
+    /// Reading a file's contents failed (`dusk dupes`); it can't be compared.
+    ReadFile,
+    /// A followed symlink leads back to an ancestor directory; it is left
+    /// out of the tree.
+    Follow,
+}
+
+impl SkipAction {
+    /// Message prefix, worded like `du`'s diagnostics.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            SkipAction::ReadDirectory => "cannot read directory",
+            SkipAction::Access => "cannot access",
+            SkipAction::ReadFile => "cannot read",
+            SkipAction::Follow => "cannot follow",
+        }
+    }
+}
+
+/// A path the traversal could not read, so totals above it are too low.
+///
+/// # Examples
+///
+/// ```
+/// use disk_usage_clone::traversal::{SkipAction, SkippedEntry};
+/// use std::io;
+/// use std::path::Path;
+///
+/// let skipped = SkippedEntry::new(
+///     Path::new("/srv/private"),
+///     SkipAction::ReadDirectory,
+///     io::Error::from(io::ErrorKind::PermissionDenied),
+/// );
+/// assert_eq!(
+///     skipped.to_string(),
+///     "cannot read directory '/srv/private': Permission denied"
+/// );
+/// ```
+#[derive(Debug)]
+pub struct SkippedEntry {
+    /// Path that couldn't be read.
+    pub path: PathBuf,
+    /// What failed.
+    pub action: SkipAction,
+    /// `PermissionDenied` or `IoError` with the underlying cause.
+    pub error: DuskError,
+}
+
+impl SkippedEntry {
+    /// Builds a diagnostic from the I/O error of a failed read.
+    ///
+    /// Permission errors become [`DuskError::PermissionDenied`], anything
+    /// else is kept as [`DuskError::IoError`].
+    pub fn new(path: &Path, action: SkipAction, err: io::Error) -> Self {
+        let error = match err.kind() {
+            io::ErrorKind::PermissionDenied => DuskError::PermissionDenied(path.to_path_buf()),
+            _ => DuskError::IoError(err),
+        };
+        SkippedEntry {
+            path: path.to_path_buf(),
+            action,
+            error,
+        }
+    }
+}
+
+impl fmt::Display for SkippedEntry {
+    /// Formats as `cannot read directory 'PATH': REASON`, like GNU `du`.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let reason = match &self.error {
+            DuskError::PermissionDenied(_) => "Permission denied".to_string(),
+            // Drop the " (os error N)" suffix; the description is enough
+            DuskError::IoError(err) => match err.raw_os_error() {
+                Some(code) => err
+                    .to_string()
+                    .trim_end_matches(&format!(" (os error {code})"))
+                    .to_string(),
+                None => err.to_string(),
+            },
+            other => other.to_string(),
+        };
+        write!(
+            f,
+            "{} '{}': {reason}",
+            self.action.as_str(),
+            self.path.display()
+        )
+    }
+}
+
+/// A traversed tree plus every path that had to be skipped.
+#[derive(Debug)]
+pub struct TraversalReport {
+    /// The scanned tree. Skipped directories appear without contents and
+    /// unreadable entries with a size of 0.
+    pub tree: DiskEntry,
+    /// Skipped paths, sorted by path.
+    pub skipped: Vec<SkippedEntry>,
+}
+
+impl TraversalReport {
+    /// Returns true if every path was read, so the totals are exact.
+    pub fn is_complete(&self) -> bool {
+        self.skipped.is_empty()
+    }
+}
+
+/// Collects skipped paths during a traversal.
+///
+/// Shared by all workers of the parallel walker, hence the `Mutex`.
+/// Unreadable paths are rare, so the lock is almost never contended.
+#[derive(Default)]
+struct SkipLog {
+    skipped: Mutex<Vec<SkippedEntry>>,
+}
+
+impl SkipLog {
+    /// Records a path that couldn't be read.
+    fn record(&self, path: &Path, action: SkipAction, err: io::Error) {
+        self.skipped
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .push(SkippedEntry::new(path, action, err));
+    }
+
+    /// Records a directory that leads back to its ancestor `ancestor`.
+    fn record_loop(&self, path: &Path, ancestor: &Path) {
+        self.skipped
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .push(SkippedEntry {
+                path: path.to_path_buf(),
+                action: SkipAction::Follow,
+                error: DuskError::SymlinkLoop(ancestor.to_path_buf()),
+            });
+    }
+
+    /// Records a walkdir error: a symlink loop, or an I/O error.
+    fn record_walkdir(&self, err: walkdir::Error, path: &Path, action: SkipAction) {
+        if let Some(ancestor) = err.loop_ancestor() {
+            self.record_loop(path, ancestor);
+        } else if let Some(err) = err.into_io_error() {
+            self.record(path, action, err);
+        }
+    }
+
+    /// Returns the skipped paths, sorted so parallel walks report them in a
+    /// stable order.
+    fn into_entries(self) -> Vec<SkippedEntry> {
+        let mut skipped = self
+            .skipped
+            .into_inner()
+            .unwrap_or_else(PoisonError::into_inner);
+        skipped.sort_by(|a, b| a.path.cmp(&b.path));
+        skipped
+    }
+}
 
 /// Flat representation of a filesystem entry during traversal.
 ///
@@ -87,9 +455,27 @@ use crate::error::DuskError;
 /// 3. Tree building is deferred until all metadata is collected
 struct FlatEntry {
     path: PathBuf,
-    size: u64,
+    stats: EntryStats,
     entry_type: EntryType,
     depth: usize,
+    /// Target of a symlink that wasn't followed.
+    target: Option<PathBuf>,
+}
+
+/// What traversal keeps from an entry's metadata.
+///
+/// Entries without readable metadata (and uncrossed mount points) keep the
+/// default: no size, owner or timestamps. The scan cache stores these
+/// stats as read, before hard links are deduplicated (see [`counted`]).
+#[derive(Debug, Clone, Copy, Default)]
+pub(crate) struct EntryStats {
+    pub(crate) size: u64,
+    pub(crate) allocated: u64,
+    pub(crate) owner: Option<Owner>,
+    pub(crate) times: Option<Timestamps>,
+    /// `(device, inode)` of a file with more than one link, or of any entry
+    /// when following every symlink.
+    pub(crate) link: Option<(u64, u64)>,
 }
 
 /// Converts a `walkdir::DirEntry` into an `EntryType`.
@@ -111,7 +497,14 @@ struct FlatEntry {
 /// let entry_type = dir_entry_to_entry_type(&dir_entry);
 /// ```
 fn dir_entry_to_entry_type(de: &walkdir::DirEntry) -> EntryType {
-    let ft = de.file_type();
+    file_type_to_entry_type(de.file_type())
+}
+
+/// Maps a file type (as reported by `readdir`/`lstat`) to an `EntryType`.
+///
+/// Shared by the walkdir-based traversals and the parallel walker, so both
+/// classify entries identically.
+pub(crate) fn file_type_to_entry_type(ft: fs::FileType) -> EntryType {
     if ft.is_dir() {
         EntryType::Directory
     } else if ft.is_symlink() {
@@ -124,6 +517,1157 @@ fn dir_entry_to_entry_type(de: &walkdir::DirEntry) -> EntryType {
     }
 }
 
+/// Returns the target of a symlink entry, as stored in the link.
+///
+/// `None` for other entries, and for links that can't be read.
+fn link_target(path: &Path, entry_type: &EntryType) -> Option<PathBuf> {
+    if *entry_type == EntryType::Symlink {
+        fs::read_link(path).ok()
+    } else {
+        None
+    }
+}
+
+/// Makes a root path absolute, resolving symlinks as `options` asks.
+///
+/// With [`Dereference::Never`], a root that is itself a symlink stays a
+/// link, like `du` lists it: only its parent directories are resolved.
+/// Otherwise the whole path is resolved, so the root is what it points to.
+/// A trailing slash (`link/`) always resolves the link, as in `du`.
+///
+/// # Errors
+///
+/// [`DuskError::PathNotFound`] if the path (or, when resolving it, its
+/// target) doesn't exist.
+fn resolve_root(path: &Path, options: &TraversalOptions) -> Result<PathBuf, DuskError> {
+    let not_found = |_| DuskError::PathNotFound(path.to_path_buf());
+
+    if options.dereference == Dereference::Never
+        && fs::symlink_metadata(path).is_ok_and(|metadata| metadata.file_type().is_symlink())
+        && let Some(name) = path.file_name()
+    {
+        let parent = match path.parent() {
+            Some(parent) if !parent.as_os_str().is_empty() => parent,
+            _ => Path::new("."),
+        };
+        return parent
+            .canonicalize()
+            .map(|parent| parent.join(name))
+            .map_err(not_found);
+    }
+
+    path.canonicalize().map_err(not_found)
+}
+
+/// Returns the disk space allocated to an entry.
+///
+/// On Unix this is `st_blocks * 512` (`st_blocks` is always counted in
+/// 512-byte units, regardless of the filesystem block size). Other platforms
+/// don't expose block counts, so the apparent size is used instead.
+///
+/// # Arguments
+///
+/// * `metadata` - Metadata of the entry (from `lstat`)
+#[cfg(unix)]
+pub(crate) fn allocated_size(metadata: &fs::Metadata) -> u64 {
+    use std::os::unix::fs::MetadataExt;
+
+    metadata.blocks() * 512
+}
+
+/// Returns the disk space allocated to an entry.
+///
+/// Non-Unix fallback: block counts aren't available, so report the apparent size.
+#[cfg(not(unix))]
+pub(crate) fn allocated_size(metadata: &fs::Metadata) -> u64 {
+    metadata.len()
+}
+
+/// Returns the `(device, inode)` pair identifying an entry.
+#[cfg(unix)]
+pub(crate) fn file_id(metadata: &fs::Metadata) -> Option<(u64, u64)> {
+    use std::os::unix::fs::MetadataExt;
+
+    Some((metadata.dev(), metadata.ino()))
+}
+
+/// Returns the `(device, inode)` pair identifying an entry.
+///
+/// Non-Unix fallback: inode numbers aren't available, so hard links are
+/// treated like copies and symlink loops are not detected.
+#[cfg(not(unix))]
+pub(crate) fn file_id(_metadata: &fs::Metadata) -> Option<(u64, u64)> {
+    None
+}
+
+/// Returns the `(device, inode)` pair of a multiply-linked entry.
+///
+/// Directories never count (they can't be hard-linked on any filesystem
+/// dusk supports), nor do files with a single link.
+#[cfg(unix)]
+fn hard_link(metadata: &fs::Metadata) -> Option<(u64, u64)> {
+    use std::os::unix::fs::MetadataExt;
+
+    (!metadata.is_dir() && metadata.nlink() > 1).then(|| (metadata.dev(), metadata.ino()))
+}
+
+/// Returns the `(device, inode)` pair of a multiply-linked entry.
+///
+/// Non-Unix fallback: inode numbers aren't available, so nothing is deduplicated.
+#[cfg(not(unix))]
+fn hard_link(_metadata: &fs::Metadata) -> Option<(u64, u64)> {
+    None
+}
+
+/// Applies hard-link deduplication to stats as read.
+///
+/// Sizes become 0 if the entry is a hard link to an inode that was already
+/// counted; owner and times are kept.
+///
+/// # Arguments
+///
+/// * `stats` - Stats of the entry, from disk or from the scan cache
+/// * `links` - Tracker of counted inodes, or `None` when counting every link
+fn counted(mut stats: EntryStats, links: Option<&LinkTracker>) -> EntryStats {
+    if let (Some(tracker), Some((dev, ino))) = (links, stats.link)
+        && !tracker.first_sighting(dev, ino)
+    {
+        stats.size = 0;
+        stats.allocated = 0;
+    }
+    stats
+}
+
+/// Reads sizes, owner and timestamps for a walkdir entry.
+///
+/// Sizes are 0 if the entry is a hard link to an inode that was already
+/// counted. If metadata can't be read, the entry keeps the default stats
+/// and is recorded in `skipped`.
+fn entry_stats(
+    de: &walkdir::DirEntry,
+    links: Option<&LinkTracker>,
+    skipped: &SkipLog,
+) -> EntryStats {
+    match de.metadata() {
+        Ok(m) => metadata_stats(&m, links),
+        Err(err) => {
+            skipped.record_walkdir(err, de.path(), SkipAction::Access);
+            EntryStats::default()
+        }
+    }
+}
+
+/// Returns sizes, owner and timestamps from already-read metadata.
+///
+/// Repeated hard links count as size 0 (but keep their owner and times).
+fn metadata_stats(metadata: &fs::Metadata, links: Option<&LinkTracker>) -> EntryStats {
+    let stats = match links {
+        Some(tracker) if tracker.every_inode => followed_stats(metadata),
+        _ => raw_stats(metadata),
+    };
+    counted(stats, links)
+}
+
+/// Returns sizes, owner and timestamps from metadata, without deduplication.
+fn raw_stats(metadata: &fs::Metadata) -> EntryStats {
+    EntryStats {
+        size: metadata.len(),
+        allocated: allocated_size(metadata),
+        owner: owner(metadata),
+        times: timestamps(metadata),
+        link: hard_link(metadata),
+    }
+}
+
+/// Returns raw stats identified by `(device, inode)` whatever the link
+/// count, for walks that follow every symlink.
+fn followed_stats(metadata: &fs::Metadata) -> EntryStats {
+    EntryStats {
+        link: file_id(metadata),
+        ..raw_stats(metadata)
+    }
+}
+
+/// Returns the user and group owning an entry.
+#[cfg(unix)]
+fn owner(metadata: &fs::Metadata) -> Option<Owner> {
+    use std::os::unix::fs::MetadataExt;
+
+    Some(Owner {
+        uid: metadata.uid(),
+        gid: metadata.gid(),
+    })
+}
+
+/// Returns the user and group owning an entry.
+///
+/// Non-Unix fallback: numeric owners aren't available.
+#[cfg(not(unix))]
+fn owner(_metadata: &fs::Metadata) -> Option<Owner> {
+    None
+}
+
+/// Returns the modification, access and change times of an entry.
+#[cfg(unix)]
+fn timestamps(metadata: &fs::Metadata) -> Option<Timestamps> {
+    use std::os::unix::fs::MetadataExt;
+
+    Some(Timestamps {
+        modified: metadata.mtime(),
+        accessed: metadata.atime(),
+        changed: metadata.ctime(),
+    })
+}
+
+/// Returns the modification, access and change times of an entry.
+///
+/// Non-Unix fallback: there is no `st_ctime`, so no timestamps are recorded.
+#[cfg(not(unix))]
+fn timestamps(_metadata: &fs::Metadata) -> Option<Timestamps> {
+    None
+}
+
+/// Returns the device id of an entry's filesystem.
+///
+/// Returns `None` on platforms without device ids, which disables
+/// one-file-system pruning there.
+#[cfg(unix)]
+pub(crate) fn device_id(metadata: &fs::Metadata) -> Option<u64> {
+    use std::os::unix::fs::MetadataExt;
+
+    Some(metadata.dev())
+}
+
+/// Returns the device id of an entry's filesystem.
+///
+/// Non-Unix fallback: device ids aren't available.
+#[cfg(not(unix))]
+pub(crate) fn device_id(_metadata: &fs::Metadata) -> Option<u64> {
+    None
+}
+
+/// Reads the sizes, owner and timestamps a walked entry contributes.
+///
+/// Mount points that were not crossed report a size of 0: their contents
+/// (and the directory inode itself) belong to another filesystem.
+///
+/// # Arguments
+///
+/// * `de` - Directory entry from walkdir
+/// * `entry_type` - Type assigned by [`walk`]
+/// * `links` - Hard-link tracker, or `None` when counting every link
+/// * `skipped` - Collects entries whose metadata can't be read
+fn walked_stats(
+    de: &walkdir::DirEntry,
+    entry_type: &EntryType,
+    links: Option<&LinkTracker>,
+    skipped: &SkipLog,
+) -> EntryStats {
+    if *entry_type == EntryType::MountPoint {
+        EntryStats::default()
+    } else {
+        // Get sizes, owner and times, defaults (and a diagnostic) on error
+        entry_stats(de, links, skipped)
+    }
+}
+
+/// Converts a walked entry into a [`FlatEntry`], reading its metadata.
+///
+/// # Arguments
+///
+/// * `de` - Directory entry from walkdir
+/// * `entry_type` - Type assigned by [`walk`]
+/// * `links` - Hard-link tracker, or `None` when counting every link
+/// * `skipped` - Collects entries whose metadata can't be read
+fn flat_entry(
+    de: &walkdir::DirEntry,
+    entry_type: EntryType,
+    links: Option<&LinkTracker>,
+    skipped: &SkipLog,
+) -> FlatEntry {
+    FlatEntry {
+        path: de.path().to_path_buf(),
+        stats: walked_stats(de, &entry_type, links, skipped),
+        target: link_target(de.path(), &entry_type),
+        entry_type,
+        depth: de.depth(),
+    }
+}
+
+/// Walks the tree under `root`, passing each entry and its type to `visit`.
+///
+/// This is the enumeration layer shared by [`traverse`] and
+/// [`traverse_parallel`]: options that decide *which* entries are visited
+/// are applied here, so both traversals see exactly the same entries.
+///
+/// # Pruning
+///
+/// - Paths matched by the [`PathFilter`] (excludes, `.gitignore`) are
+///   skipped before being stat'ed; excluded directories are not read.
+/// - With `options.one_file_system`, each directory's device id is compared
+///   with the root's. A directory on another device is visited once as
+///   [`EntryType::MountPoint`] and its contents are skipped.
+/// - With [`Dereference::Always`], symlinks are followed and visited with
+///   the type of their target. walkdir leaves out directories that lead
+///   back to an ancestor; they are recorded as loops in `skipped`. Unless
+///   `options.count_links` is set, a directory already visited through
+///   another path is visited again but its contents are skipped.
+///
+/// # Arguments
+///
+/// * `root` - Resolved root path (see [`resolve_root`])
+/// * `options` - Traversal options
+/// * `skipped` - Collects directories and entries that can't be read
+/// * `visit` - Called for each entry in walk order (parents before children)
+///
+/// # Returns
+///
+/// `None` after a complete walk. If `options.cancel` stops the walk, the
+/// directory whose listing was left unfinished: the walk is pre-order, so
+/// it and its ancestors are the only incomplete directories. The root is
+/// always visited.
+///
+/// # Errors
+///
+/// Returns an error if the exclusion filter can't be built (see
+/// [`PathFilter::new`]). Unreadable entries are skipped and recorded in
+/// `skipped`, not returned.
+fn walk(
+    root: &Path,
+    options: &TraversalOptions,
+    skipped: &SkipLog,
+    mut visit: impl FnMut(walkdir::DirEntry, EntryType),
+) -> Result<Option<PathBuf>, DuskError> {
+    let filter = PathFilter::new(root, options)?;
+
+    // Device of the starting directory (only needed for -x)
+    let root_dev = if options.one_file_system {
+        fs::symlink_metadata(root).ok().and_then(|m| device_id(&m))
+    } else {
+        None
+    };
+
+    // Directories read so far (only needed for -L)
+    let once = options.dereference == Dereference::Always && !options.count_links;
+    let mut read_dirs = HashSet::new();
+
+    // The root was resolved already: a symlink root is listed as a link.
+    // filter_entry prunes excluded subtrees without descending into them
+    let mut entries = WalkDir::new(root)
+        .follow_links(options.dereference == Dereference::Always)
+        .follow_root_links(false)
+        .into_iter()
+        .filter_entry(|e| {
+            filter.is_empty() || !filter.is_excluded(e.path(), e.file_type().is_dir())
+        });
+
+    while let Some(result) = entries.next() {
+        // Error reading a directory or entry (permission denied, etc.)
+        // Record it and continue with the rest of the tree
+        let dir_entry = match result {
+            Ok(dir_entry) => dir_entry,
+            Err(err) => {
+                let path = err.path().unwrap_or(root).to_path_buf();
+                // walkdir reports failed listings at the directory's path
+                let action = if fs::metadata(&path).is_ok_and(|m| m.is_dir()) {
+                    SkipAction::ReadDirectory
+                } else {
+                    SkipAction::Access
+                };
+                skipped.record_walkdir(err, &path, action);
+                continue;
+            }
+        };
+
+        // Stop before this entry; its directory is the one left unfinished
+        if dir_entry.depth() > 0 && cancel::is_cancelled(options.cancel.as_ref()) {
+            return Ok(dir_entry.path().parent().map(Path::to_path_buf));
+        }
+
+        let mut entry_type = dir_entry_to_entry_type(&dir_entry);
+
+        // A directory on another device is a mount point: show it, skip its contents
+        if let Some(root_dev) = root_dev
+            && entry_type == EntryType::Directory
+            && dir_entry.depth() > 0
+            && dir_entry.metadata().ok().and_then(|m| device_id(&m)) != Some(root_dev)
+        {
+            entry_type = EntryType::MountPoint;
+            entries.skip_current_dir();
+        }
+
+        // Reached again through another link: listed, but read only once
+        if once
+            && entry_type == EntryType::Directory
+            && let Some(id) = dir_entry.metadata().ok().as_ref().and_then(file_id)
+            && !read_dirs.insert(id)
+        {
+            entries.skip_current_dir();
+        }
+
+        visit(dir_entry, entry_type);
+    }
+
+    Ok(None)
+}
+
+/// Flags the directories left unfinished by a cancelled scan.
+///
+/// Sets [`DiskEntry::incomplete`] on every entry of `tree` listed in
+/// `unfinished` and on all their ancestors, so the root of a partial tree
+/// is always flagged. Call it before collapsing the tree, so collapsed
+/// directories keep the flag of their unfinished descendants.
+fn mark_incomplete(tree: &mut DiskEntry, unfinished: &[PathBuf]) {
+    if unfinished.is_empty() {
+        return;
+    }
+
+    // Every unfinished directory and its ancestors, up to the root
+    let mut flagged: HashSet<&Path> = HashSet::new();
+    for dir in unfinished {
+        for ancestor in dir.ancestors() {
+            if !ancestor.starts_with(&tree.path) || !flagged.insert(ancestor) {
+                break;
+            }
+        }
+    }
+
+    fn flag(entry: &mut DiskEntry, flagged: &HashSet<&Path>) {
+        if flagged.contains(entry.path.as_path()) {
+            entry.incomplete = true;
+            for child in &mut entry.children {
+                flag(child, flagged);
+            }
+        }
+    }
+    flag(tree, &flagged);
+}
+
+/// Work-stealing directory walker behind [`traverse_parallel_with_options`].
+///
+/// Every directory is a task on the rayon pool: the task reads the
+/// directory, stats its entries and spawns one task per subdirectory. Idle
+/// workers steal pending directories, so slow `readdir` calls (NFS,
+/// spinning disks) overlap instead of queuing behind a single thread.
+///
+/// Entries are selected with the same rules as [`walk`] (exclusions, mount
+/// points, symlinks and loops), so the collected [`FlatEntry`] list
+/// builds the same tree as the sequential traversal. Only the order in
+/// which directories complete varies, and [`build_tree`] keeps each
+/// directory's children in `readdir` order regardless.
+///
+/// With a [`ScanCache`], a directory whose inode and mtime match its cached
+/// listing isn't read: the cached entries are reused and only its
+/// subdirectories are stat'ed, so they can be checked in turn.
+///
+/// Following every symlink without `count_links`, each directory is read
+/// once, by the first task to claim its `(device, inode)`. Other paths to it
+/// are recorded without contents, and [`resolve_walk_order`] later moves
+/// the listing to the path that comes first in walk order.
+struct ParallelWalk<'a> {
+    /// Resolved root path.
+    root: &'a Path,
+    /// Exclusion filter; shared by all tasks.
+    filter: PathFilter,
+    /// Device of the root when `one_file_system` is set.
+    root_dev: Option<u64>,
+    /// Follow every symlink ([`Dereference::Always`]).
+    follow: bool,
+    /// Path each directory was read at, by `(device, inode)`; only kept
+    /// when following every symlink and counting each inode once.
+    claims: Option<Mutex<HashMap<(u64, u64), PathBuf>>>,
+    /// Directory listings from earlier runs, if caching.
+    cache: Option<&'a ScanCache>,
+    /// Directories and entries that couldn't be read.
+    skipped: &'a SkipLog,
+    /// Counts recorded entries for progress reports.
+    progress: &'a ProgressTracker,
+    /// Stops the walk early, if set.
+    cancel: Option<&'a CancelToken>,
+    /// Entries collected so far, appended one directory at a time.
+    entries: Mutex<Vec<FlatEntry>>,
+    /// Directories not read, or read only in part, because the walk was
+    /// cancelled.
+    unfinished: Mutex<Vec<PathBuf>>,
+}
+
+/// What a [`ParallelWalk`] collected.
+struct WalkedEntries {
+    /// Every visited entry, each directory's entries contiguous and in
+    /// listing order.
+    entries: Vec<FlatEntry>,
+    /// Directories left unfinished by a cancelled walk.
+    unfinished: Vec<PathBuf>,
+    /// Path each directory was read at, when directories were claimed.
+    readers: HashMap<(u64, u64), PathBuf>,
+}
+
+/// A directory being read and its ancestors, up to the root.
+///
+/// Only tracked when following symlinks and counting every link: a
+/// subdirectory with the same `(device, inode)` as one of them would lead
+/// into an endless loop. Otherwise claimed directories are never read
+/// twice, and loops are found by [`resolve_walk_order`].
+struct Ancestor {
+    id: Option<(u64, u64)>,
+    path: PathBuf,
+    parent: Option<Arc<Ancestor>>,
+}
+
+impl Ancestor {
+    /// Returns the directory itself or the ancestor with identity `id`.
+    fn find(&self, id: (u64, u64)) -> Option<&Ancestor> {
+        let mut ancestor = Some(self);
+        while let Some(dir) = ancestor {
+            if dir.id == Some(id) {
+                return Some(dir);
+            }
+            ancestor = dir.parent.as_deref();
+        }
+        None
+    }
+}
+
+/// An entry of the directory being read, before mount points and hard
+/// links are resolved.
+struct Child {
+    path: PathBuf,
+    /// Type as reported by `readdir`, or of the target of a followed link.
+    entry_type: EntryType,
+    /// Stats as read, or `None` if the entry's metadata couldn't be read.
+    stats: Option<EntryStats>,
+    /// Metadata of a subdirectory, for the mount point check and its cache key.
+    dir_metadata: Option<fs::Metadata>,
+}
+
+impl<'a> ParallelWalk<'a> {
+    /// Prepares a walk of the tree under `root`.
+    ///
+    /// Exclusions and mount points are resolved relative to `scan_root`,
+    /// which is `root` itself except when a subtree of an earlier scan is
+    /// read again (see [`traverse_subtree`]).
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the exclusion filter can't be built (see
+    /// [`PathFilter::new`]).
+    fn new(
+        root: &'a Path,
+        scan_root: &Path,
+        options: &'a TraversalOptions,
+        skipped: &'a SkipLog,
+        progress: &'a ProgressTracker,
+    ) -> Result<Self, DuskError> {
+        let filter = PathFilter::new(scan_root, options)?;
+
+        // Device of the starting directory (only needed for -x)
+        let root_dev = if options.one_file_system {
+            fs::symlink_metadata(scan_root)
+                .ok()
+                .and_then(|m| device_id(&m))
+        } else {
+            None
+        };
+
+        // Cached listings hold links as links, not as what they point to
+        let follow = options.dereference == Dereference::Always;
+
+        Ok(ParallelWalk {
+            root,
+            filter,
+            root_dev,
+            follow,
+            claims: (follow && !options.count_links).then(Mutex::default),
+            cache: options.cache.as_deref().filter(|_| !follow),
+            skipped,
+            progress,
+            cancel: options.cancel.as_ref(),
+            entries: Mutex::new(Vec::new()),
+            unfinished: Mutex::new(Vec::new()),
+        })
+    }
+
+    /// Walks the whole tree and returns every visited entry, the
+    /// directories left unfinished if the walk was cancelled, and where
+    /// claimed directories were read.
+    ///
+    /// Runs on the current rayon pool, so call it inside `pool.install`.
+    fn run(self) -> WalkedEntries {
+        // The root is never excluded; like walkdir, read it with lstat
+        let Ok(metadata) = fs::symlink_metadata(self.root) else {
+            return WalkedEntries {
+                entries: Vec::new(),
+                unfinished: Vec::new(),
+                readers: HashMap::new(),
+            };
+        };
+        let entry_type = file_type_to_entry_type(metadata.file_type());
+        let stats = self.stats(&metadata);
+        let is_dir = entry_type == EntryType::Directory;
+
+        self.record(
+            self.root,
+            vec![FlatEntry {
+                path: self.root.to_path_buf(),
+                stats,
+                target: link_target(self.root, &entry_type),
+                entry_type,
+                depth: 0,
+            }],
+        );
+
+        if is_dir {
+            if let Some(cache) = self.cache {
+                cache.add_root(self.root);
+            }
+            let key = self.cache_key(&metadata);
+            self.claim(file_id(&metadata), self.root);
+            let ancestors = (self.follow && self.claims.is_none()).then(|| {
+                Arc::new(Ancestor {
+                    id: file_id(&metadata),
+                    path: self.root.to_path_buf(),
+                    parent: None,
+                })
+            });
+            rayon::scope(|scope| {
+                self.read_dir(scope, self.root.to_path_buf(), key, 1, ancestors);
+            });
+        }
+
+        WalkedEntries {
+            entries: self
+                .entries
+                .into_inner()
+                .unwrap_or_else(PoisonError::into_inner),
+            unfinished: self
+                .unfinished
+                .into_inner()
+                .unwrap_or_else(PoisonError::into_inner),
+            readers: self
+                .claims
+                .map(|claims| claims.into_inner().unwrap_or_else(PoisonError::into_inner))
+                .unwrap_or_default(),
+        }
+    }
+
+    /// Claims the directory with identity `id` for reading at `dir`.
+    ///
+    /// Returns `false` if another path to the same directory claimed it
+    /// first. Without claims (or inode numbers) every directory is read.
+    fn claim(&self, id: Option<(u64, u64)>, dir: &Path) -> bool {
+        let (Some(claims), Some(id)) = (&self.claims, id) else {
+            return true;
+        };
+        let mut claims = claims.lock().unwrap_or_else(PoisonError::into_inner);
+        match claims.entry(id) {
+            Entry::Occupied(_) => false,
+            Entry::Vacant(slot) => {
+                slot.insert(dir.to_path_buf());
+                true
+            }
+        }
+    }
+
+    /// Returns `true` if the walk was cancelled.
+    fn cancelled(&self) -> bool {
+        cancel::is_cancelled(self.cancel)
+    }
+
+    /// Remembers a directory whose contents are missing from the tree
+    /// because the walk was cancelled.
+    fn unfinished(&self, dir: &Path) {
+        self.unfinished
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .push(dir.to_path_buf());
+    }
+
+    /// Returns the cache key of a directory, or `None` when not caching.
+    fn cache_key(&self, metadata: &fs::Metadata) -> Option<DirKey> {
+        self.cache.and_then(|_| DirKey::from_metadata(metadata))
+    }
+
+    /// Reads one directory (or its cached listing) and records its entries.
+    ///
+    /// `depth` is the depth of the entries inside `dir`, and `key` the
+    /// directory's cache key. `ancestors` holds `dir` and its ancestors
+    /// when following symlinks without claims. An unreadable directory contributes no
+    /// entries, and unreadable entries are skipped; both are recorded, as
+    /// in [`walk`]. Once the walk is cancelled, pending directories are
+    /// only recorded as unfinished.
+    fn read_dir<'s>(
+        &'s self,
+        scope: &rayon::Scope<'s>,
+        dir: PathBuf,
+        key: Option<DirKey>,
+        depth: usize,
+        ancestors: Option<Arc<Ancestor>>,
+    ) {
+        if self.cancelled() {
+            self.unfinished(&dir);
+            return;
+        }
+
+        let children = match key.and_then(|key| self.cached_children(&dir, key)) {
+            Some(children) => children,
+            None => match self.read_children(&dir, key) {
+                Some(children) => children,
+                None => return,
+            },
+        };
+
+        // A huge directory is spread over the pool as well
+        let flat_entries = children
+            .into_par_iter()
+            .filter_map(|child| self.visit(scope, child, depth, ancestors.as_ref()))
+            .collect();
+
+        self.record(&dir, flat_entries);
+    }
+
+    /// Returns the entries of `dir` from its cached listing.
+    ///
+    /// `None` if the listing is missing or stale, or can't be used as is:
+    /// a subdirectory that is no longer a directory, or an entry that
+    /// wasn't stat'ed when the listing was cached. The directory is then
+    /// read from disk.
+    fn cached_children(&self, dir: &Path, key: DirKey) -> Option<Vec<Child>> {
+        let cache = self.cache?;
+        let cached = cache.lookup(dir, key)?;
+
+        let children = cached
+            .entries
+            .par_iter()
+            .filter_map(|entry| {
+                let path = dir.join(&entry.name);
+                let is_dir = entry.entry_type == EntryType::Directory;
+                if !self.filter.is_empty() && self.filter.is_excluded(&path, is_dir) {
+                    return None;
+                }
+                Some(self.cached_child(path, entry))
+            })
+            .collect::<Option<Vec<Child>>>();
+
+        match children {
+            Some(_) => cache.reuse(dir),
+            None => cache.reject(),
+        }
+        children
+    }
+
+    /// Turns a cached entry into a child, or `None` if it can't be reused.
+    ///
+    /// Subdirectories are stat'ed again: their own metadata may have
+    /// changed, and their key decides whether their listing is reused.
+    fn cached_child(&self, path: PathBuf, entry: &CachedEntry) -> Option<Child> {
+        if entry.entry_type != EntryType::Directory {
+            return Some(Child {
+                path,
+                entry_type: entry.entry_type.clone(),
+                stats: Some(entry.stats?),
+                dir_metadata: None,
+            });
+        }
+
+        let metadata = fs::symlink_metadata(&path)
+            .ok()
+            .filter(fs::Metadata::is_dir)?;
+        Some(Child {
+            path,
+            entry_type: EntryType::Directory,
+            stats: Some(raw_stats(&metadata)),
+            dir_metadata: Some(metadata),
+        })
+    }
+
+    /// Reads and stats the entries of `dir` from disk.
+    ///
+    /// Returns `None` if the directory can't be listed. With a `key`, the
+    /// complete listing (excluded entries included) is stored in the cache.
+    /// If the walk is cancelled while reading, the entries read so far are
+    /// returned and the directory is recorded as unfinished.
+    fn read_children(&self, dir: &Path, key: Option<DirKey>) -> Option<Vec<Child>> {
+        let listing = match fs::read_dir(dir) {
+            Ok(listing) => listing,
+            Err(err) => {
+                self.skipped.record(dir, SkipAction::ReadDirectory, err);
+                return None;
+            }
+        };
+
+        // readdir pass: exclusions only need the name and file type. A
+        // listing with errors is used, but not cached
+        let mut complete = true;
+        let mut listed: Vec<(fs::DirEntry, EntryType, bool)> = Vec::new();
+        let mut cut = false;
+        for result in listing {
+            if self.cancelled() {
+                cut = true;
+                break;
+            }
+            let de = match result {
+                Ok(de) => de,
+                Err(err) => {
+                    complete = false;
+                    self.skipped.record(dir, SkipAction::ReadDirectory, err);
+                    continue;
+                }
+            };
+            match de.file_type() {
+                Ok(ft) => {
+                    let excluded =
+                        !self.filter.is_empty() && self.filter.is_excluded(&de.path(), ft.is_dir());
+                    listed.push((de, file_type_to_entry_type(ft), excluded));
+                }
+                Err(err) => {
+                    complete = false;
+                    self.skipped.record(&de.path(), SkipAction::Access, err);
+                }
+            }
+        }
+
+        // stat pass: a huge directory is spread over the pool as well
+        let stat_cut = AtomicBool::new(false);
+        let children: Vec<Option<Child>> = listed
+            .par_iter()
+            .map(|(de, entry_type, excluded)| {
+                if *excluded {
+                    None
+                } else if self.cancelled() {
+                    stat_cut.store(true, Ordering::Relaxed);
+                    None
+                } else {
+                    self.stat(de, entry_type)
+                }
+            })
+            .collect();
+
+        // A partial listing is kept in the tree, but never cached
+        if cut || stat_cut.into_inner() {
+            complete = false;
+            self.unfinished(dir);
+        }
+
+        if let (Some(cache), Some(key), true) = (self.cache, key, complete) {
+            let entries = listed
+                .iter()
+                .zip(&children)
+                .map(|((de, entry_type, _), child)| {
+                    Some(CachedEntry {
+                        name: de.file_name().into_string().ok()?,
+                        entry_type: entry_type.clone(),
+                        stats: child
+                            .as_ref()
+                            .filter(|_| *entry_type != EntryType::Directory)
+                            .and_then(|child| child.stats),
+                    })
+                })
+                .collect::<Option<Vec<_>>>();
+            if let Some(entries) = entries {
+                cache.store(dir, key, entries);
+            }
+        }
+
+        Some(children.into_iter().flatten().collect())
+    }
+
+    /// Stats one listed entry.
+    ///
+    /// When following symlinks, a link is stat'ed through and takes its
+    /// target's type. Like walkdir, a link that points nowhere is recorded
+    /// and left out (`None`), and so is a link to an excluded directory.
+    fn stat(&self, de: &fs::DirEntry, entry_type: &EntryType) -> Option<Child> {
+        let path = de.path();
+        if self.follow && *entry_type == EntryType::Symlink {
+            return match fs::metadata(&path) {
+                Ok(metadata) if metadata.is_dir() => {
+                    if !self.filter.is_empty() && self.filter.is_excluded(&path, true) {
+                        return None;
+                    }
+                    Some(Child {
+                        path,
+                        entry_type: EntryType::Directory,
+                        stats: Some(followed_stats(&metadata)),
+                        dir_metadata: Some(metadata),
+                    })
+                }
+                Ok(metadata) => Some(Child {
+                    path,
+                    entry_type: file_type_to_entry_type(metadata.file_type()),
+                    stats: Some(followed_stats(&metadata)),
+                    dir_metadata: None,
+                }),
+                Err(err) => {
+                    self.skipped.record(&path, SkipAction::Access, err);
+                    None
+                }
+            };
+        }
+
+        let (stats, dir_metadata) = match de.metadata() {
+            Ok(metadata) => {
+                let stats = self.stats(&metadata);
+                (Some(stats), metadata.is_dir().then_some(metadata))
+            }
+            Err(err) => {
+                self.skipped.record(&path, SkipAction::Access, err);
+                (None, None)
+            }
+        };
+
+        Some(Child {
+            path,
+            entry_type: entry_type.clone(),
+            stats,
+            dir_metadata,
+        })
+    }
+
+    /// Resolves one entry, spawning a task to read it if it is a directory.
+    ///
+    /// Directories on another device are turned into
+    /// [`EntryType::MountPoint`] entries of size 0 when `one_file_system`
+    /// is set, and not read. When following symlinks with an ancestor
+    /// chain, a directory that is one of its own ancestors is recorded as a
+    /// loop and left out (`None`); with claims, a directory already claimed
+    /// through another path is kept but not read. Stats are kept as read;
+    /// hard links are deduplicated once the walk is complete (see
+    /// [`resolve_walk_order`]).
+    fn visit<'s>(
+        &'s self,
+        scope: &rayon::Scope<'s>,
+        child: Child,
+        depth: usize,
+        ancestors: Option<&Arc<Ancestor>>,
+    ) -> Option<FlatEntry> {
+        let mut entry_type = child.entry_type;
+
+        if entry_type == EntryType::Directory {
+            // Following links, a directory may lead back to an ancestor
+            let id = child.dir_metadata.as_ref().and_then(file_id);
+            if let (Some(ancestors), Some(id)) = (ancestors, id)
+                && let Some(ancestor) = ancestors.find(id)
+            {
+                self.skipped.record_loop(&child.path, &ancestor.path);
+                return None;
+            }
+
+            if let Some(root_dev) = self.root_dev
+                && child.dir_metadata.as_ref().and_then(device_id) != Some(root_dev)
+            {
+                entry_type = EntryType::MountPoint;
+            } else if self.claim(id, &child.path) {
+                let key = child
+                    .dir_metadata
+                    .as_ref()
+                    .and_then(|metadata| self.cache_key(metadata));
+                let dir = child.path.clone();
+                let ancestors = ancestors.map(|parent| {
+                    Arc::new(Ancestor {
+                        id,
+                        path: dir.clone(),
+                        parent: Some(Arc::clone(parent)),
+                    })
+                });
+                scope.spawn(move |scope| self.read_dir(scope, dir, key, depth + 1, ancestors));
+            }
+        }
+
+        let stats = match child.stats {
+            _ if entry_type == EntryType::MountPoint => EntryStats::default(),
+            Some(stats) => stats,
+            None => EntryStats::default(),
+        };
+
+        Some(FlatEntry {
+            target: link_target(&child.path, &entry_type),
+            path: child.path,
+            stats,
+            entry_type,
+            depth,
+        })
+    }
+
+    /// Returns an entry's stats as read, identified by inode when following
+    /// every symlink (see [`followed_stats`]).
+    fn stats(&self, metadata: &fs::Metadata) -> EntryStats {
+        if self.follow {
+            followed_stats(metadata)
+        } else {
+            raw_stats(metadata)
+        }
+    }
+
+    /// Appends the entries read in `dir` to the shared list.
+    ///
+    /// Progress reports count every hard link in full: which link keeps an
+    /// inode's size is only known at the end of the walk.
+    fn record(&self, dir: &Path, mut flat_entries: Vec<FlatEntry>) {
+        let (apparent, allocated) = flat_entries.iter().fold((0, 0), |(a, b), e| {
+            (a + e.stats.size, b + e.stats.allocated)
+        });
+        self.progress
+            .add(dir, flat_entries.len() as u64, apparent, allocated);
+
+        self.entries
+            .lock()
+            // A panicking worker can't leave the list half-updated
+            .unwrap_or_else(PoisonError::into_inner)
+            .append(&mut flat_entries);
+    }
+}
+
+/// A directory open on the path [`resolve_walk_order`] is replaying.
+struct OpenDir {
+    /// `(device, inode)`, if known.
+    id: Option<(u64, u64)>,
+    path: PathBuf,
+    depth: usize,
+}
+
+/// Deduplicates a parallel walk's entries in walk order.
+///
+/// Workers reach the links of an inode in no particular order, so sizes
+/// are assigned once every entry is in, by replaying the walk: pre-order,
+/// with each directory's entries in the order they were listed. That is
+/// the order [`walk`] (and `du`) visits them in, so the first link keeps an
+/// inode's size and the others report 0, exactly as in [`traverse`], and
+/// the result is the same from one run to the next.
+///
+/// When following every symlink, directories are identified too and were
+/// read only once, at the path in `walked.readers`. The replay gives that
+/// listing to the first path to the directory in walk order, moving its
+/// entries, diagnostics and unfinished mark there if it was read through
+/// another path. Later paths keep an entry at 0 bytes without contents. A
+/// directory that is one of its own ancestors is left out and reported as
+/// a loop, as walkdir does.
+///
+/// Relies on each directory's entries being contiguous and in listing
+/// order, as [`ParallelWalk::record`] appends them. Exclusions were
+/// applied at the path a directory was read at.
+fn resolve_walk_order(
+    walked: WalkedEntries,
+    skipped: Vec<SkippedEntry>,
+) -> (Vec<FlatEntry>, Vec<SkippedEntry>, Vec<PathBuf>) {
+    let WalkedEntries {
+        entries,
+        unfinished,
+        readers,
+    } = walked;
+
+    // Entries of each directory, as listed, by the path it was read at
+    let mut root = None;
+    let mut listings: HashMap<PathBuf, Vec<FlatEntry>> = HashMap::new();
+    let mut run: Option<(PathBuf, Vec<FlatEntry>)> = None;
+    for entry in entries {
+        let Some(parent) = entry.path.parent().filter(|_| entry.depth > 0) else {
+            root = Some(entry);
+            continue;
+        };
+        match &mut run {
+            Some((dir, listing)) if dir == parent => listing.push(entry),
+            _ => {
+                let next = (parent.to_path_buf(), vec![entry]);
+                if let Some((dir, listing)) = run.replace(next) {
+                    listings.entry(dir).or_default().extend(listing);
+                }
+            }
+        }
+    }
+    if let Some((dir, listing)) = run {
+        listings.entry(dir).or_default().extend(listing);
+    }
+
+    // Diagnostics belong to the listing of the directory they came from
+    let mut diagnostics: HashMap<PathBuf, Vec<SkippedEntry>> = HashMap::new();
+    for entry in skipped {
+        let dir = match entry.action {
+            SkipAction::ReadDirectory => Some(entry.path.as_path()),
+            _ => entry.path.parent(),
+        };
+        let dir = dir.unwrap_or(&entry.path).to_path_buf();
+        diagnostics.entry(dir).or_default().push(entry);
+    }
+    let unfinished: HashSet<PathBuf> = unfinished.into_iter().collect();
+
+    let tracker = LinkTracker::default();
+    let mut kept = Vec::new();
+    let mut kept_skipped = Vec::new();
+    let mut kept_unfinished = Vec::new();
+    let mut open: Vec<OpenDir> = Vec::new();
+    // Entries still to replay, with the path each was listed at
+    let mut pending: Vec<(FlatEntry, PathBuf)> = root
+        .map(|root| {
+            let listed_at = root.path.clone();
+            (root, listed_at)
+        })
+        .into_iter()
+        .collect();
+    while let Some((mut entry, listed_at)) = pending.pop() {
+        while open.last().is_some_and(|dir| dir.depth >= entry.depth) {
+            open.pop();
+        }
+        let is_dir = entry.entry_type == EntryType::Directory;
+        let id = entry.stats.link;
+
+        // Following links, a directory may lead back to an ancestor
+        if is_dir
+            && let Some(id) = id
+            && let Some(ancestor) = open.iter().find(|dir| dir.id == Some(id))
+        {
+            kept_skipped.push(SkippedEntry {
+                path: entry.path,
+                action: SkipAction::Follow,
+                error: DuskError::SymlinkLoop(ancestor.path.clone()),
+            });
+            continue;
+        }
+
+        if id.is_some_and(|(dev, ino)| !tracker.first_sighting(dev, ino)) {
+            entry.stats.size = 0;
+            entry.stats.allocated = 0;
+        } else if is_dir {
+            // Read here, or through another path to the same directory
+            let source = id
+                .and_then(|id| readers.get(&id))
+                .unwrap_or(&listed_at)
+                .clone();
+            let rebase = |path: &Path| match path.strip_prefix(&source) {
+                Ok(rest) if !rest.as_os_str().is_empty() => entry.path.join(rest),
+                _ => entry.path.clone(),
+            };
+
+            for mut child in listings
+                .remove(&source)
+                .unwrap_or_default()
+                .into_iter()
+                .rev()
+            {
+                let listed_at = std::mem::replace(&mut child.path, PathBuf::new());
+                child.path = rebase(&listed_at);
+                child.depth = entry.depth + 1;
+                pending.push((child, listed_at));
+            }
+            for mut diagnostic in diagnostics.remove(&source).unwrap_or_default() {
+                diagnostic.path = rebase(&diagnostic.path);
+                kept_skipped.push(diagnostic);
+            }
+            if unfinished.contains(&source) {
+                kept_unfinished.push(entry.path.clone());
+            }
+            open.push(OpenDir {
+                id,
+                path: entry.path.clone(),
+                depth: entry.depth,
+            });
+        }
+        kept.push(entry);
+    }
+
+    kept_skipped.sort_by(|a, b| a.path.cmp(&b.path));
+    (kept, kept_skipped, kept_unfinished)
+}
+
 /// Builds a tree from a flat list of entries.
 ///
 /// This is a key function that converts the flat list collected during
@@ -181,7 +1725,7 @@ fn build_tree(flat_entries: Vec<FlatEntry>) -> Result<DiskEntry, DuskError> {
     // Sort by depth descending so children are processed before parents
     // This is critical for the bottom-up tree construction
     let mut sorted = flat_entries;
-    sorted.sort_by(|a, b| b.depth.cmp(&a.depth));
+    sorted.sort_by_key(|e| Reverse(e.depth));
 
     // Map: parent path → list of children
     // As we process deep entries, we add them to their parent's list
@@ -198,10 +1742,14 @@ fn build_tree(flat_entries: Vec<FlatEntry>) -> Result<DiskEntry, DuskError> {
         // Create the DiskEntry with its children
         let mut disk_entry = DiskEntry::new(
             entry.path.clone(),
-            entry.size,
+            entry.stats.size,
             entry.entry_type,
             entry.depth,
         );
+        disk_entry.allocated_bytes = entry.stats.allocated;
+        disk_entry.owner = entry.stats.owner;
+        disk_entry.times = entry.stats.times;
+        disk_entry.link_target = entry.target;
         disk_entry.children = children;
 
         if entry.depth == 0 {
@@ -220,6 +1768,22 @@ fn build_tree(flat_entries: Vec<FlatEntry>) -> Result<DiskEntry, DuskError> {
     root.ok_or_else(|| DuskError::TraversalError("no root entry found".to_string()))
 }
 
+/// Builds a childless entry from an entry's `lstat` metadata.
+///
+/// Hard links are not deduplicated: the entry is looked at on its own, not
+/// as part of a walk.
+pub(crate) fn stat_entry(path: PathBuf, metadata: &fs::Metadata, depth: usize) -> DiskEntry {
+    let stats = raw_stats(metadata);
+    let entry_type = file_type_to_entry_type(metadata.file_type());
+
+    let mut entry = DiskEntry::new(path, stats.size, entry_type, depth);
+    entry.allocated_bytes = stats.allocated;
+    entry.owner = stats.owner;
+    entry.times = stats.times;
+    entry.link_target = link_target(&entry.path, &entry.entry_type);
+    entry
+}
+
 /// Traverses a filesystem path sequentially.
 ///
 /// This is the simpler, single-threaded version. Good for small directories
@@ -227,7 +1791,7 @@ fn build_tree(flat_entries: Vec<FlatEntry>) -> Result<DiskEntry, DuskError> {
 ///
 /// # Workflow
 ///
-/// 1. Canonicalize path (resolve symlinks, make absolute)
+/// 1. Resolve path (make absolute, see [`Dereference`] for symlinks)
 /// 2. Walk directory tree with `walkdir` (single-threaded)
 /// 3. Collect metadata for each entry
 /// 4. Build tree structure
@@ -267,79 +1831,114 @@ fn build_tree(flat_entries: Vec<FlatEntry>) -> Result<DiskEntry, DuskError> {
 /// # Errors
 ///
 /// - `PathNotFound` if path doesn't exist
-/// - `TraversalError` on permission errors (skips and continues)
+/// - `InvalidPattern` if an exclude pattern doesn't compile
+///
+/// Unreadable directories and entries below the root are skipped; use
+/// [`traverse_report`] to find out which.
 pub fn traverse(path: &Path, max_depth: Option<usize>) -> Result<DiskEntry, DuskError> {
-    // Canonicalize: convert to absolute path and resolve symlinks
-    // This ensures we're working with a real, absolute path
-    let root = path
-        .canonicalize()
-        .map_err(|_| DuskError::PathNotFound(path.to_path_buf()))?;
+    let options = TraversalOptions {
+        max_depth,
+        ..TraversalOptions::default()
+    };
+    traverse_with_options(path, &options)
+}
 
-    // Create walkdir iterator
-    // follow_links(false) prevents infinite loops from symlink cycles
-    let walker = WalkDir::new(&root).follow_links(false);
+/// Traverses a filesystem path sequentially with explicit options.
+///
+/// Same as [`traverse`], but takes a full [`TraversalOptions`]
+/// (`threads` is ignored).
+///
+/// # Examples
+///
+/// ```no_run
+/// use disk_usage_clone::traversal::{traverse_with_options, TraversalOptions};
+/// use std::path::Path;
+///
+/// let options = TraversalOptions {
+///     count_links: true,
+///     ..TraversalOptions::default()
+/// };
+/// let tree = traverse_with_options(Path::new("/backups"), &options).unwrap();
+/// ```
+///
+/// # Errors
+///
+/// Same as [`traverse`].
+pub fn traverse_with_options(
+    path: &Path,
+    options: &TraversalOptions,
+) -> Result<DiskEntry, DuskError> {
+    traverse_report(path, options).map(|report| report.tree)
+}
 
-    // Walk the tree and collect entries
-    // filter_map: keep only successful entries, skip errors
-    let flat_entries: Vec<FlatEntry> = walker
-        .into_iter()
-        .filter_map(|result| match result {
-            Ok(dir_entry) => {
-                // Successfully read this entry
-                let entry_type = dir_entry_to_entry_type(&dir_entry);
-
-                // Get file size (0 for directories, actual size for files)
-                // Unwrap metadata, default to 0 on error
-                let size = dir_entry.metadata().map(|m| m.len()).unwrap_or(0);
-
-                let depth = dir_entry.depth();
-
-                Some(FlatEntry {
-                    path: dir_entry.into_path(),
-                    size,
-                    entry_type,
-                    depth,
-                })
-            }
-            Err(_) => {
-                // Error reading this entry (permission denied, etc.)
-                // Skip it and continue
-                None
-            }
-        })
-        .collect();
+/// Traverses a filesystem path sequentially, reporting skipped entries.
+///
+/// Same as [`traverse_with_options`], but also returns every directory
+/// and entry that couldn't be read (see [`TraversalReport`]).
+///
+/// # Errors
+///
+/// Same as [`traverse`]. Unreadable paths below the root are not errors.
+pub fn traverse_report(
+    path: &Path,
+    options: &TraversalOptions,
+) -> Result<TraversalReport, DuskError> {
+    // Resolve: convert to an absolute path, following symlinks as asked
+    // This ensures we're working with a real, absolute path
+    let root = resolve_root(path, options)?;
+
+    // Hard-link bookkeeping (None = count every link)
+    let tracker = LinkTracker::for_options(options);
+    let links = tracker.as_ref();
+
+    // Walk the tree and collect entries, reading metadata as we go
+    let skipped = SkipLog::default();
+    let progress = ProgressTracker::new(options.progress.as_ref());
+    let mut flat_entries = Vec::new();
+    let unfinished = walk(&root, options, &skipped, |dir_entry, entry_type| {
+        let entry = flat_entry(&dir_entry, entry_type, links, &skipped);
+        progress.add(
+            entry.path.parent().unwrap_or(&entry.path),
+            1,
+            entry.stats.size,
+            entry.stats.allocated,
+        );
+        flat_entries.push(entry);
+    })?;
 
     // Build the tree from flat entries
+    progress.phase(ScanPhase::BuildingTree);
     let mut tree = build_tree(flat_entries)?;
+    mark_incomplete(&mut tree, unfinished.as_slice());
 
     // Apply depth limit if specified
-    if let Some(depth) = max_depth {
+    if let Some(depth) = options.max_depth {
         tree.collapse_to_depth(depth);
     }
+    progress.phase(ScanPhase::Done);
 
-    Ok(tree)
+    Ok(TraversalReport {
+        tree,
+        skipped: skipped.into_entries(),
+    })
 }
 
-/// Traverses a filesystem path with parallel metadata collection.
-///
-/// This is the high-performance version that uses rayon to parallelize
-/// metadata collection. Recommended for large directory trees.
+/// Traverses a filesystem path with a parallel, work-stealing walker.
 ///
-/// # Two-Phase Approach
+/// This is the high-performance version that reads directories and
+/// collects metadata on a rayon pool. Recommended for large directory
+/// trees.
 ///
-/// **Phase 1 (Sequential):**
-/// - Use `walkdir` to enumerate all entries
-/// - This is fast (just reading directory structures)
-/// - Collect all `DirEntry` objects
+/// # Directory-Level Tasks
 ///
-/// **Phase 2 (Parallel):**
-/// - Use `rayon` to call `.metadata()` on all entries in parallel
-/// - This is slow (many stat syscalls) but parallelizes well
-/// - Convert to `FlatEntry` with sizes
+/// - Each directory is read by its own task on the pool
+/// - The task stats the directory's entries (in parallel for large
+///   directories) and spawns a task per subdirectory
+/// - Idle threads steal pending directories, so work stays balanced on
+///   both wide and deep trees
 ///
-/// This approach gives the best of both worlds:
-/// - Fast enumeration (walkdir is optimized for this)
-/// - Parallel I/O (metadata collection benefits from concurrency)
+/// Entries are then assembled into a tree exactly as in [`traverse`]; the
+/// two functions return the same tree for the same options.
 ///
 /// # Arguments
 ///
@@ -372,8 +1971,9 @@ pub fn traverse(path: &Path, max_depth: Option<usize>) -> Result<DiskEntry, Dusk
 ///
 /// Significantly faster than [`traverse`] for:
 /// - Large directory trees (1000+ files)
-/// - Network filesystems (high latency)
-/// - Slow storage (spinning disks)
+/// - Network filesystems (high `readdir` and `stat` latency)
+/// - Slow storage (spinning disks), where concurrent requests let the
+///   device reorder seeks
 ///
 /// May be slower for:
 /// - Small directories (<100 files) due to thread overhead
@@ -392,22 +1992,87 @@ pub fn traverse_parallel(
     max_depth: Option<usize>,
     num_threads: Option<usize>,
 ) -> Result<DiskEntry, DuskError> {
-    // Canonicalize path
-    let root = path
-        .canonicalize()
-        .map_err(|_| DuskError::PathNotFound(path.to_path_buf()))?;
+    let options = TraversalOptions {
+        max_depth,
+        threads: num_threads,
+        ..TraversalOptions::default()
+    };
+    traverse_parallel_with_options(path, &options)
+}
+
+/// Traverses a filesystem path in parallel with explicit options.
+///
+/// Same as [`traverse_parallel`], but takes a full [`TraversalOptions`].
+///
+/// # Hard Links
+///
+/// Unless `options.count_links` is set, each inode is counted once, at the
+/// link that comes first in walk order, exactly as in [`traverse`]. This is
+/// resolved after the walk, so scheduling doesn't change which directory
+/// the size is charged to.
+///
+/// # Errors
+///
+/// Same as [`traverse`].
+pub fn traverse_parallel_with_options(
+    path: &Path,
+    options: &TraversalOptions,
+) -> Result<DiskEntry, DuskError> {
+    traverse_parallel_report(path, options).map(|report| report.tree)
+}
 
-    // Create walkdir iterator
-    let walker = WalkDir::new(&root).follow_links(false);
+/// Traverses a filesystem path in parallel, reporting skipped entries.
+///
+/// Same as [`traverse_parallel_with_options`], but also returns every
+/// directory and entry that couldn't be read (see [`TraversalReport`]).
+///
+/// # Errors
+///
+/// Same as [`traverse`]. Unreadable paths below the root are not errors.
+pub fn traverse_parallel_report(
+    path: &Path,
+    options: &TraversalOptions,
+) -> Result<TraversalReport, DuskError> {
+    // Resolve path
+    let root = resolve_root(path, options)?;
 
-    // Phase 1: Collect DirEntry objects single-threaded (fast readdir)
-    // We keep the DirEntry objects (not consuming them yet) so we can
-    // parallelize the metadata collection in phase 2
-    let dir_entries: Vec<walkdir::DirEntry> = walker.into_iter().filter_map(|r| r.ok()).collect();
+    parallel_report(&root, &root, options)
+}
+
+/// Reads a directory of an earlier scan again, as that scan would have.
+///
+/// `scan_root` is the canonicalized root of the earlier scan and `dir` a
+/// directory below it, which must not be a symlink. Exclusion patterns stay
+/// anchored at `scan_root` and mount points are judged against its device,
+/// so the subtree matches what a full scan would contain. The returned
+/// tree starts at depth 0; `options.max_depth` and `options.cache` are
+/// ignored.
+///
+/// # Errors
+///
+/// Same as [`traverse_parallel_report`].
+pub(crate) fn traverse_subtree(
+    scan_root: &Path,
+    dir: &Path,
+    options: &TraversalOptions,
+) -> Result<TraversalReport, DuskError> {
+    let options = TraversalOptions {
+        max_depth: None,
+        cache: None,
+        progress: None,
+        ..options.clone()
+    };
+    parallel_report(dir, scan_root, &options)
+}
 
-    // Phase 2: Parallel metadata collection using rayon
+/// Shared body of [`traverse_parallel_report`] and [`traverse_subtree`].
+fn parallel_report(
+    root: &Path,
+    scan_root: &Path,
+    options: &TraversalOptions,
+) -> Result<TraversalReport, DuskError> {
     // Build thread pool with specified size (or default to CPU count)
-    let pool = match num_threads {
+    let pool = match options.threads {
         Some(n) => rayon::ThreadPoolBuilder::new()
             .num_threads(n)
             .build()
@@ -417,45 +2082,187 @@ pub fn traverse_parallel(
             .map_err(|e| DuskError::TraversalError(e.to_string()))?,
     };
 
-    // Use the thread pool to process entries in parallel
-    let flat_entries: Vec<FlatEntry> = pool.install(|| {
-        dir_entries
-            .par_iter() // Rayon parallel iterator
-            .map(|dir_entry| {
-                // Each thread processes a subset of entries
-                let entry_type = dir_entry_to_entry_type(dir_entry);
-
-                // The expensive part: stat syscall to get metadata
-                // This happens in parallel across all threads
-                let size = dir_entry.metadata().map(|m| m.len()).unwrap_or(0);
-
-                let depth = dir_entry.depth();
-
-                FlatEntry {
-                    path: dir_entry.path().to_path_buf(),
-                    size,
-                    entry_type,
-                    depth,
-                }
-            })
-            .collect() // Rayon collects in parallel
-    });
+    // Read directories and stat entries on the pool
+    let skipped = SkipLog::default();
+    let progress = ProgressTracker::new(options.progress.as_ref());
+    let walker = ParallelWalk::new(root, scan_root, options, &skipped, &progress)?;
+    let walked = pool.install(|| walker.run());
+
+    // Hard links go to the first path in walk order, not the first reached
+    let (flat_entries, skipped, unfinished) = if options.count_links {
+        (walked.entries, skipped.into_entries(), walked.unfinished)
+    } else {
+        resolve_walk_order(walked, skipped.into_entries())
+    };
 
     // Build the tree from flat entries (single-threaded, fast)
+    progress.phase(ScanPhase::BuildingTree);
     let mut tree = build_tree(flat_entries)?;
+    mark_incomplete(&mut tree, &unfinished);
 
     // Apply depth limit if specified
-    if let Some(depth) = max_depth {
+    if let Some(depth) = options.max_depth {
         tree.collapse_to_depth(depth);
     }
-
-    Ok(tree)
+    progress.phase(ScanPhase::Done);
+
+    Ok(TraversalReport { tree, skipped })
+}
+
+/// Traverses a filesystem path, aggregating sizes as the walk goes.
+///
+/// Produces the same tree as [`traverse_with_options`], but never holds a
+/// record per file. The walk is pre-order, so an entry's ancestors are
+/// always the open entries on a stack:
+///
+/// - An entry within `options.max_depth` closes every open entry at its
+///   depth or deeper (attaching each to its parent), then is opened itself.
+/// - An entry below `options.max_depth` only adds its sizes to the deepest
+///   open entry, its ancestor at the display depth, and is then dropped.
+///
+/// Without `max_depth` the whole tree is kept, but the flat entry list and
+/// the depth sort of the other traversals are still avoided.
+///
+/// # Memory
+///
+/// Proportional to the kept tree (depth × fan-out up to `max_depth`) plus
+/// walkdir's per-directory buffers along the current path, and the set of
+/// multiply-linked inodes seen unless `count_links` is set.
+///
+/// # Arguments
+///
+/// * `path` - Path to analyze (file or directory)
+/// * `options` - Traversal options (`threads` is ignored)
+///
+/// # Examples
+///
+/// ```no_run
+/// use disk_usage_clone::traversal::{traverse_streaming, TraversalOptions};
+/// use std::path::Path;
+///
+/// // Only the first two levels are ever kept in memory
+/// let options = TraversalOptions {
+///     max_depth: Some(2),
+///     ..TraversalOptions::default()
+/// };
+/// let tree = traverse_streaming(Path::new("/srv"), &options).unwrap();
+/// println!("Total: {} bytes", tree.total_size());
+/// ```
+///
+/// # Errors
+///
+/// Same as [`traverse`].
+pub fn traverse_streaming(path: &Path, options: &TraversalOptions) -> Result<DiskEntry, DuskError> {
+    traverse_streaming_report(path, options).map(|report| report.tree)
+}
+
+/// Streaming traversal that also reports skipped entries.
+///
+/// Same as [`traverse_streaming`], but also returns every directory and
+/// entry that couldn't be read (see [`TraversalReport`]). The list of
+/// skipped paths is the only part of the result that grows with the tree.
+///
+/// # Errors
+///
+/// Same as [`traverse`]. Unreadable paths below the root are not errors.
+pub fn traverse_streaming_report(
+    path: &Path,
+    options: &TraversalOptions,
+) -> Result<TraversalReport, DuskError> {
+    let root_path = resolve_root(path, options)?;
+
+    // Hard-link bookkeeping (None = count every link)
+    let tracker = LinkTracker::for_options(options);
+    let links = tracker.as_ref();
+
+    // Deepest level kept as separate entries
+    let keep_depth = options.max_depth.unwrap_or(usize::MAX);
+
+    // Open entries, root first; each is an ancestor of the next
+    let mut open: Vec<DiskEntry> = Vec::new();
+    let mut root = None;
+
+    let skipped = SkipLog::default();
+    let progress = ProgressTracker::new(options.progress.as_ref());
+    let unfinished = walk(&root_path, options, &skipped, |dir_entry, entry_type| {
+        let depth = dir_entry.depth();
+        let stats = walked_stats(&dir_entry, &entry_type, links, &skipped);
+        let path = dir_entry.path();
+        progress.add(
+            path.parent().unwrap_or(path),
+            1,
+            stats.size,
+            stats.allocated,
+        );
+
+        // Below the display depth only the size, latest times and the
+        // number of folded children survive
+        if depth > keep_depth {
+            if let Some(ancestor) = open.last_mut() {
+                ancestor.size_bytes += stats.size;
+                ancestor.allocated_bytes += stats.allocated;
+                if depth == keep_depth + 1 {
+                    ancestor.folded_children += 1;
+                }
+                if let Some(times) = stats.times {
+                    ancestor.times = Some(ancestor.times.map_or(times, |t| t.latest(times)));
+                }
+            }
+            return;
+        }
+
+        // Siblings and their subtrees are complete once we move past them
+        while open.last().is_some_and(|e| e.depth >= depth) {
+            close_deepest(&mut open, &mut root);
+        }
+
+        let target = link_target(path, &entry_type);
+        let mut entry = DiskEntry::new(dir_entry.into_path(), stats.size, entry_type, depth);
+        entry.allocated_bytes = stats.allocated;
+        entry.owner = stats.owner;
+        entry.times = stats.times;
+        entry.link_target = target;
+        open.push(entry);
+    })?;
+
+    // Open entries are the unfinished directory's kept ancestors (or itself)
+    if let Some(dir) = &unfinished {
+        for entry in open.iter_mut().filter(|e| dir.starts_with(&e.path)) {
+            entry.incomplete = true;
+        }
+    }
+
+    while !open.is_empty() {
+        close_deepest(&mut open, &mut root);
+    }
+
+    let tree = root.ok_or_else(|| {
+        DuskError::TraversalError("no entries found during traversal".to_string())
+    })?;
+    progress.phase(ScanPhase::Done);
+
+    Ok(TraversalReport {
+        tree,
+        skipped: skipped.into_entries(),
+    })
+}
+
+/// Pops the deepest open entry and attaches it to its parent.
+///
+/// The last entry to be closed is the root.
+fn close_deepest(open: &mut Vec<DiskEntry>, root: &mut Option<DiskEntry>) {
+    if let Some(entry) = open.pop() {
+        match open.last_mut() {
+            Some(parent) => parent.children.push(entry),
+            None => *root = Some(entry),
+        }
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use std::fs;
+    use crate::entry::SizeMode;
     use tempfile::TempDir;
 
     fn create_test_tree() -> TempDir {
@@ -648,6 +2455,210 @@ mod tests {
         assert!(max_depth(&root) <= 1);
     }
 
+    #[test]
+    fn test_traversals_report_progress() {
+        let tmp = create_test_tree();
+        type Traverse = fn(&Path, &TraversalOptions) -> Result<DiskEntry, DuskError>;
+        let traversals: [Traverse; 3] = [
+            traverse_with_options,
+            traverse_parallel_with_options,
+            traverse_streaming,
+        ];
+
+        for traverse in traversals {
+            let (handler, updates) = ProgressHandler::channel();
+            let options = TraversalOptions {
+                max_depth: Some(1),
+                progress: Some(handler),
+                ..TraversalOptions::default()
+            };
+            let tree = traverse(tmp.path(), &options).unwrap();
+            drop(options);
+            let reports: Vec<_> = updates.iter().collect();
+
+            // The last report is Done and counts every entry, below the
+            // display depth too
+            let done = reports.last().unwrap();
+            assert_eq!(done.phase, ScanPhase::Done);
+            assert_eq!(done.entries, 6);
+            assert_eq!(done.apparent_bytes, tree.total_size_for(SizeMode::Apparent));
+            assert_eq!(
+                done.allocated_bytes,
+                tree.total_size_for(SizeMode::Allocated)
+            );
+            assert!(reports.iter().all(|r| r.entries <= 6));
+        }
+    }
+
+    #[test]
+    fn test_cancelled_traversals_return_flagged_root() {
+        let tmp = create_test_tree();
+        type Traverse = fn(&Path, &TraversalOptions) -> Result<DiskEntry, DuskError>;
+        let traversals: [Traverse; 3] = [
+            traverse_with_options,
+            traverse_parallel_with_options,
+            traverse_streaming,
+        ];
+
+        let token = CancelToken::new();
+        token.cancel();
+        let options = TraversalOptions {
+            cancel: Some(token),
+            ..TraversalOptions::default()
+        };
+        for traverse in traversals {
+            let tree = traverse(tmp.path(), &options).unwrap();
+            assert!(tree.incomplete);
+            assert!(tree.children.is_empty());
+        }
+
+        // An unused token changes nothing
+        let options = TraversalOptions {
+            cancel: Some(CancelToken::new()),
+            ..TraversalOptions::default()
+        };
+        for traverse in traversals {
+            let tree = traverse(tmp.path(), &options).unwrap();
+            assert!(!tree.incomplete);
+            assert_eq!(
+                tree.total_size(),
+                traverse_with_options(tmp.path(), &TraversalOptions::default())
+                    .unwrap()
+                    .total_size()
+            );
+        }
+    }
+
+    #[test]
+    fn test_mark_incomplete_flags_ancestors_only() {
+        let tmp = create_test_tree();
+        let root = tmp.path().canonicalize().unwrap();
+        let mut tree = traverse(&root, None).unwrap();
+        mark_incomplete(&mut tree, &[root.join("subdir/nested")]);
+
+        fn flagged(entry: &DiskEntry, out: &mut Vec<PathBuf>) {
+            if entry.incomplete {
+                out.push(entry.path.clone());
+            }
+            for child in &entry.children {
+                flagged(child, out);
+            }
+        }
+        let mut paths = Vec::new();
+        flagged(&tree, &mut paths);
+        paths.sort();
+        assert_eq!(
+            paths,
+            vec![
+                root.clone(),
+                root.join("subdir"),
+                root.join("subdir/nested")
+            ]
+        );
+
+        // The flag survives collapsing
+        tree.collapse_to_depth(1);
+        assert!(tree.incomplete);
+        let subdir = tree
+            .children
+            .iter()
+            .find(|c| c.path.ends_with("subdir"))
+            .unwrap();
+        assert!(subdir.incomplete);
+    }
+
+    #[test]
+    fn test_walk_reports_unfinished_directory() {
+        let tmp = create_test_tree();
+        let root = tmp.path().canonicalize().unwrap();
+        let token = CancelToken::new();
+        let options = TraversalOptions {
+            cancel: Some(token.clone()),
+            ..TraversalOptions::default()
+        };
+
+        // Stop right after entering subdir: its first entry is never visited
+        let mut visited = Vec::new();
+        let unfinished = walk(&root, &options, &SkipLog::default(), |de, _| {
+            if de.path().ends_with("subdir") {
+                token.cancel();
+            }
+            visited.push(de.into_path());
+        })
+        .unwrap();
+
+        assert_eq!(unfinished, Some(root.join("subdir")));
+        assert_eq!(visited.last(), Some(&root.join("subdir")));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_traversals_record_owner() {
+        use std::os::unix::fs::MetadataExt;
+
+        let tmp = create_test_tree();
+        let metadata = fs::metadata(tmp.path().join("file_a.txt")).unwrap();
+        let expected = Some(Owner {
+            uid: metadata.uid(),
+            gid: metadata.gid(),
+        });
+
+        let trees = [
+            traverse(tmp.path(), None).unwrap(),
+            traverse_parallel(tmp.path(), None, None).unwrap(),
+            traverse_streaming(tmp.path(), &TraversalOptions::default()).unwrap(),
+        ];
+        for tree in &trees {
+            let mut pending = vec![tree];
+            while let Some(entry) = pending.pop() {
+                assert_eq!(entry.owner, expected, "{}", entry.path.display());
+                pending.extend(&entry.children);
+            }
+        }
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_traversals_record_timestamps() {
+        use std::time::{Duration, UNIX_EPOCH};
+
+        let tmp = create_test_tree();
+        let file_c = tmp.path().join("subdir/nested/file_c.txt");
+        let future = UNIX_EPOCH + Duration::from_secs(4_000_000_000);
+        fs::File::options()
+            .write(true)
+            .open(&file_c)
+            .unwrap()
+            .set_modified(future)
+            .unwrap();
+
+        let trees = [
+            traverse(tmp.path(), None).unwrap(),
+            traverse_parallel(tmp.path(), None, None).unwrap(),
+            traverse_streaming(tmp.path(), &TraversalOptions::default()).unwrap(),
+        ];
+        for tree in &trees {
+            let mut pending = vec![tree];
+            while let Some(entry) = pending.pop() {
+                let times = entry.times.expect("timestamps recorded");
+                if entry.path == file_c {
+                    assert_eq!(times.modified, 4_000_000_000);
+                }
+                pending.extend(&entry.children);
+            }
+            assert_eq!(tree.latest_times().unwrap().modified, 4_000_000_000);
+        }
+
+        // Entries folded below --max-depth still date their ancestor
+        let options = TraversalOptions {
+            max_depth: Some(0),
+            ..Default::default()
+        };
+        let root = traverse_streaming(tmp.path(), &options).unwrap();
+        assert!(root.children.is_empty());
+        assert_eq!(root.times.unwrap().modified, 4_000_000_000);
+    }
+
     #[test]
     fn test_traverse_empty_directory() {
         let tmp = TempDir::new().unwrap();
@@ -656,10 +2667,900 @@ mod tests {
         assert!(result.children.is_empty());
     }
 
+    #[cfg(unix)]
+    #[test]
+    fn test_traverse_records_allocated_size_for_sparse_file() {
+        let tmp = TempDir::new().unwrap();
+        let file_path = tmp.path().join("sparse.img");
+        let file = fs::File::create(&file_path).unwrap();
+        // Extending with set_len leaves a hole: no blocks are allocated
+        file.set_len(10 * 1024 * 1024).unwrap();
+
+        let seq = traverse(&file_path, None).unwrap();
+        assert_eq!(seq.size(SizeMode::Apparent), 10 * 1024 * 1024);
+        assert!(seq.size(SizeMode::Allocated) < seq.size(SizeMode::Apparent));
+
+        let par = traverse_parallel(&file_path, None, Some(2)).unwrap();
+        assert_eq!(par.allocated_bytes, seq.allocated_bytes);
+    }
+
+    #[test]
+    fn test_traverse_parallel_allocated_matches_sequential() {
+        let tmp = create_test_tree();
+        let seq = traverse(tmp.path(), None).unwrap();
+        let par = traverse_parallel(tmp.path(), None, Some(4)).unwrap();
+
+        assert_eq!(
+            seq.total_size_for(SizeMode::Allocated),
+            par.total_size_for(SizeMode::Allocated)
+        );
+    }
+
+    #[cfg(unix)]
+    fn create_hard_link_tree() -> TempDir {
+        let tmp = TempDir::new().unwrap();
+        let root = tmp.path();
+
+        // root/
+        //   original.bin (1000 bytes)
+        //   a/link.bin  -> same inode
+        //   b/link.bin  -> same inode
+        fs::write(root.join("original.bin"), vec![0u8; 1000]).unwrap();
+        fs::create_dir(root.join("a")).unwrap();
+        fs::create_dir(root.join("b")).unwrap();
+        fs::hard_link(root.join("original.bin"), root.join("a/link.bin")).unwrap();
+        fs::hard_link(root.join("original.bin"), root.join("b/link.bin")).unwrap();
+
+        tmp
+    }
+
+    #[cfg(unix)]
+    fn file_sizes(entry: &DiskEntry) -> u64 {
+        let own = if entry.entry_type == EntryType::File {
+            entry.size_bytes
+        } else {
+            0
+        };
+        own + entry.children.iter().map(file_sizes).sum::<u64>()
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_traverse_counts_hard_links_once() {
+        let tmp = create_hard_link_tree();
+        let root = traverse(tmp.path(), None).unwrap();
+        assert_eq!(file_sizes(&root), 1000);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_traverse_parallel_counts_hard_links_once() {
+        let tmp = create_hard_link_tree();
+        for _ in 0..10 {
+            let root = traverse_parallel(tmp.path(), None, Some(4)).unwrap();
+            assert_eq!(file_sizes(&root), 1000);
+        }
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_traverse_count_links_option() {
+        let tmp = create_hard_link_tree();
+        let options = TraversalOptions {
+            count_links: true,
+            ..TraversalOptions::default()
+        };
+
+        let seq = traverse_with_options(tmp.path(), &options).unwrap();
+        assert_eq!(file_sizes(&seq), 3000);
+
+        let par = traverse_parallel_with_options(tmp.path(), &options).unwrap();
+        assert_eq!(file_sizes(&par), 3000);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_traverse_one_file_system_marks_mount_points() {
+        use std::os::unix::fs::MetadataExt;
+
+        // /dev usually has other filesystems (devpts, tmpfs) mounted below it
+        let dev = Path::new("/dev");
+        let Ok(dev_meta) = fs::metadata(dev) else {
+            return;
+        };
+        let Some(mount) = fs::read_dir(dev).unwrap().flatten().find(|e| {
+            e.file_type().is_ok_and(|t| t.is_dir())
+                && e.metadata().is_ok_and(|m| m.dev() != dev_meta.dev())
+        }) else {
+            // No nested mount in this environment
+            return;
+        };
+
+        let options = TraversalOptions {
+            one_file_system: true,
+            ..TraversalOptions::default()
+        };
+        for root in [
+            traverse_with_options(dev, &options).unwrap(),
+            traverse_parallel_with_options(dev, &options).unwrap(),
+        ] {
+            let entry = root
+                .children
+                .iter()
+                .find(|c| c.path == mount.path())
+                .unwrap();
+            assert_eq!(entry.entry_type, EntryType::MountPoint);
+            assert!(entry.children.is_empty());
+            assert_eq!(entry.total_size(), 0);
+        }
+    }
+
+    #[test]
+    fn test_traverse_one_file_system_same_device() {
+        // Nothing to prune inside a single temp directory
+        let tmp = create_test_tree();
+        let options = TraversalOptions {
+            one_file_system: true,
+            ..TraversalOptions::default()
+        };
+        let pruned = traverse_with_options(tmp.path(), &options).unwrap();
+        let full = traverse(tmp.path(), None).unwrap();
+        assert_eq!(pruned.total_size(), full.total_size());
+    }
+
+    /// Creates the test tree with a link to `subdir/nested` next to it and
+    /// a link from `subdir/nested` back up to the root.
+    #[cfg(unix)]
+    fn create_symlink_tree() -> TempDir {
+        let tmp = create_test_tree();
+        std::os::unix::fs::symlink("nested", tmp.path().join("subdir/link")).unwrap();
+        std::os::unix::fs::symlink("../..", tmp.path().join("subdir/nested/up")).unwrap();
+        tmp
+    }
+
+    /// Finds the entry at `path` in a tree.
+    fn find_entry<'a>(entry: &'a DiskEntry, path: &Path) -> Option<&'a DiskEntry> {
+        if entry.path == path {
+            return Some(entry);
+        }
+        entry
+            .children
+            .iter()
+            .find_map(|child| find_entry(child, path))
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_traversals_list_symlinks_with_targets() {
+        let tmp = create_symlink_tree();
+        let root = tmp.path().canonicalize().unwrap();
+        let options = TraversalOptions::default();
+
+        for report in [
+            traverse_report(&root, &options).unwrap(),
+            traverse_parallel_report(&root, &options).unwrap(),
+            traverse_streaming_report(&root, &options).unwrap(),
+        ] {
+            assert!(report.skipped.is_empty());
+            let link = find_entry(&report.tree, &root.join("subdir/link")).unwrap();
+            assert_eq!(link.entry_type, EntryType::Symlink);
+            assert_eq!(link.link_target, Some(PathBuf::from("nested")));
+            assert!(link.children.is_empty());
+        }
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_traversals_follow_symlinks_and_report_loops() {
+        let tmp = create_symlink_tree();
+        let root = tmp.path().canonicalize().unwrap();
+        let options = TraversalOptions {
+            dereference: Dereference::Always,
+            ..TraversalOptions::default()
+        };
+
+        let mut trees = Vec::new();
+        for report in [
+            traverse_report(&root, &options).unwrap(),
+            traverse_parallel_report(&root, &options).unwrap(),
+            traverse_streaming_report(&root, &options).unwrap(),
+        ] {
+            // `link` and `nested` are one directory: whichever comes first
+            // in walk order is read, the other is listed without contents
+            let link = find_entry(&report.tree, &root.join("subdir/link")).unwrap();
+            let nested = find_entry(&report.tree, &root.join("subdir/nested")).unwrap();
+            assert_eq!(link.entry_type, EntryType::Directory);
+            assert_eq!(link.link_target, None);
+            let (read, repeated) = if link.children.is_empty() {
+                (nested, link)
+            } else {
+                (link, nested)
+            };
+            assert!(find_entry(read, &read.path.join("file_c.txt")).is_some());
+            assert!(repeated.children.is_empty());
+            assert_eq!(repeated.size_bytes, 0);
+
+            // The way into `up` leads back to an ancestor
+            let looped: Vec<_> = report.skipped.iter().map(|s| &s.path).collect();
+            assert_eq!(looped, [&read.path.join("up")]);
+            assert!(
+                report
+                    .skipped
+                    .iter()
+                    .all(|s| s.action == SkipAction::Follow)
+            );
+            assert!(find_entry(&report.tree, &root.join("subdir/nested/up")).is_none());
+
+            let mut flat = Vec::new();
+            flatten(&report.tree, &mut flat);
+            trees.push(flat);
+        }
+        assert_eq!(trees[0], trees[1]);
+        assert_eq!(trees[0], trees[2]);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_dereference_counts_each_inode_once() {
+        let tmp = create_test_tree();
+        let root = tmp.path().canonicalize().unwrap();
+        std::os::unix::fs::symlink("subdir", root.join("linked_dir")).unwrap();
+        std::os::unix::fs::symlink("file_a.txt", root.join("linked_file")).unwrap();
+
+        // What the links point to is counted through the real paths already
+        let plain = traverse(&root, None).unwrap();
+        let link_sizes: u64 = plain
+            .children
+            .iter()
+            .filter(|child| child.entry_type == EntryType::Symlink)
+            .map(|child| child.size_bytes)
+            .sum();
+        let once = plain.total_size() - link_sizes;
+        let subdir = find_entry(&plain, &root.join("subdir")).unwrap();
+        let every_path = once + subdir.total_size() + 5;
+
+        let options = TraversalOptions {
+            dereference: Dereference::Always,
+            ..TraversalOptions::default()
+        };
+        let count_links = TraversalOptions {
+            count_links: true,
+            ..options.clone()
+        };
+        for (traversal, expected) in [(&options, once), (&count_links, every_path)] {
+            for tree in [
+                traverse_with_options(&root, traversal).unwrap(),
+                traverse_parallel_with_options(&root, traversal).unwrap(),
+                traverse_streaming(&root, traversal).unwrap(),
+            ] {
+                assert_eq!(tree.total_size(), expected, "{traversal:?}");
+            }
+        }
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_dereference_reads_each_directory_once() {
+        let tmp = TempDir::new().unwrap();
+        let root = tmp.path().canonicalize().unwrap();
+        fs::create_dir(root.join("big")).unwrap();
+        for i in 0..200 {
+            fs::write(root.join(format!("big/f{i}.dat")), vec![b'x'; 10]).unwrap();
+        }
+        std::os::unix::fs::symlink("big", root.join("a")).unwrap();
+        std::os::unix::fs::symlink("big", root.join("b")).unwrap();
+
+        type Traverse = fn(&Path, &TraversalOptions) -> Result<DiskEntry, DuskError>;
+        let traversals: [Traverse; 2] = [traverse_with_options, traverse_parallel_with_options];
+        let mut trees = Vec::new();
+        for traverse in traversals {
+            let (handler, updates) = ProgressHandler::channel();
+            let options = TraversalOptions {
+                dereference: Dereference::Always,
+                progress: Some(handler),
+                ..TraversalOptions::default()
+            };
+            let tree = traverse(&root, &options).unwrap();
+            drop(options);
+
+            // The root, its three entries, and the 200 files read through
+            // whichever of them got there first
+            let done = updates.iter().last().unwrap();
+            assert_eq!(done.entries, 204);
+            let mut flat = Vec::new();
+            flatten(&tree, &mut flat);
+            trees.push(flat);
+        }
+
+        // Contents still go to the first path in walk order, as listed
+        assert_eq!(trees[0], trees[1]);
+        let child_counts: Vec<usize> = ["a", "b", "big"]
+            .iter()
+            .map(|name| {
+                let row = trees[0].iter().find(|row| row.0 == root.join(name));
+                row.unwrap().5
+            })
+            .collect();
+        assert_eq!(child_counts.iter().sum::<usize>(), 200);
+        assert!(child_counts.contains(&200));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_dereference_args_follows_root_only() {
+        let tmp = create_symlink_tree();
+        let root = tmp.path().canonicalize().unwrap();
+        let subdir_link = root.join("subdir/link");
+
+        // Not followed, the argument is the link itself
+        let tree = traverse_with_options(&subdir_link, &TraversalOptions::default()).unwrap();
+        assert_eq!(tree.path, subdir_link);
+        assert_eq!(tree.entry_type, EntryType::Symlink);
+        assert!(tree.children.is_empty());
+
+        let options = TraversalOptions {
+            dereference: Dereference::Args,
+            ..TraversalOptions::default()
+        };
+        for tree in [
+            traverse_with_options(&subdir_link, &options).unwrap(),
+            traverse_parallel_with_options(&subdir_link, &options).unwrap(),
+        ] {
+            assert_eq!(tree.path, root.join("subdir/nested"));
+            assert_eq!(tree.entry_type, EntryType::Directory);
+            // Links below the argument stay links
+            let up = find_entry(&tree, &root.join("subdir/nested/up")).unwrap();
+            assert_eq!(up.entry_type, EntryType::Symlink);
+        }
+    }
+
+    fn collect_names(entry: &DiskEntry, names: &mut Vec<String>) {
+        names.push(
+            entry
+                .path
+                .file_name()
+                .unwrap()
+                .to_string_lossy()
+                .into_owned(),
+        );
+        for child in &entry.children {
+            collect_names(child, names);
+        }
+    }
+
+    #[test]
+    fn test_traverse_exclude_prunes_subtree() {
+        let tmp = create_test_tree();
+        let options = TraversalOptions {
+            exclude: vec!["subdir/".to_string()],
+            ..TraversalOptions::default()
+        };
+
+        for root in [
+            traverse_with_options(tmp.path(), &options).unwrap(),
+            traverse_parallel_with_options(tmp.path(), &options).unwrap(),
+        ] {
+            let mut names = Vec::new();
+            collect_names(&root, &mut names);
+            assert!(names.contains(&"file_a.txt".to_string()));
+            assert!(
+                !names
+                    .iter()
+                    .any(|n| n == "subdir" || n == "file_b.txt" || n == "file_c.txt")
+            );
+        }
+    }
+
+    #[test]
+    fn test_traverse_exclude_files_by_glob() {
+        let tmp = create_test_tree();
+        let options = TraversalOptions {
+            exclude: vec!["file_[bc].txt".to_string()],
+            ..TraversalOptions::default()
+        };
+        let root = traverse_parallel_with_options(tmp.path(), &options).unwrap();
+        let mut names = Vec::new();
+        collect_names(&root, &mut names);
+        assert!(names.contains(&"nested".to_string()));
+        assert!(names.contains(&"file_a.txt".to_string()));
+        assert!(!names.contains(&"file_b.txt".to_string()));
+        assert!(!names.contains(&"file_c.txt".to_string()));
+    }
+
+    #[test]
+    fn test_traverse_respect_gitignore() {
+        let tmp = create_test_tree();
+        fs::write(tmp.path().join(".gitignore"), "nested/\n").unwrap();
+
+        let options = TraversalOptions {
+            respect_gitignore: true,
+            ..TraversalOptions::default()
+        };
+        let root = traverse_with_options(tmp.path(), &options).unwrap();
+        let mut names = Vec::new();
+        collect_names(&root, &mut names);
+        assert!(names.contains(&"file_b.txt".to_string()));
+        assert!(!names.contains(&"nested".to_string()));
+        assert!(!names.contains(&"file_c.txt".to_string()));
+
+        // Without the option, .gitignore has no effect
+        let root = traverse(tmp.path(), None).unwrap();
+        let mut names = Vec::new();
+        collect_names(&root, &mut names);
+        assert!(names.contains(&"file_c.txt".to_string()));
+    }
+
+    #[test]
+    fn test_traverse_invalid_exclude_pattern() {
+        let tmp = create_test_tree();
+        let options = TraversalOptions {
+            exclude: vec!["{a,b".to_string()],
+            ..TraversalOptions::default()
+        };
+        let result = traverse_with_options(tmp.path(), &options);
+        assert!(matches!(result, Err(DuskError::InvalidPattern(_))));
+    }
+
     #[test]
     fn test_traverse_parallel_nonexistent_path() {
         let result =
             traverse_parallel(Path::new("/nonexistent/path/does/not/exist"), None, Some(2));
         assert!(result.is_err());
     }
+
+    /// Flattens a tree into comparable `(path, type, depth, size, allocated)` tuples.
+    fn flatten(entry: &DiskEntry, out: &mut Vec<(PathBuf, EntryType, usize, u64, u64, usize)>) {
+        out.push((
+            entry.path.clone(),
+            entry.entry_type.clone(),
+            entry.depth,
+            entry.size_bytes,
+            entry.allocated_bytes,
+            entry.child_count(),
+        ));
+        for child in &entry.children {
+            flatten(child, out);
+        }
+    }
+
+    #[test]
+    fn test_traverse_streaming_matches_traverse() {
+        let tmp = create_test_tree();
+        for max_depth in [None, Some(0), Some(1), Some(2), Some(5)] {
+            let options = TraversalOptions {
+                max_depth,
+                ..TraversalOptions::default()
+            };
+            let (mut expected, mut streamed) = (Vec::new(), Vec::new());
+            flatten(
+                &traverse_with_options(tmp.path(), &options).unwrap(),
+                &mut expected,
+            );
+            flatten(
+                &traverse_streaming(tmp.path(), &options).unwrap(),
+                &mut streamed,
+            );
+            assert_eq!(expected, streamed, "max_depth {max_depth:?}");
+        }
+    }
+
+    #[test]
+    fn test_traverse_streaming_keeps_only_display_depth() {
+        let tmp = create_test_tree();
+        let options = TraversalOptions {
+            max_depth: Some(1),
+            ..TraversalOptions::default()
+        };
+        let root = traverse_streaming(tmp.path(), &options).unwrap();
+
+        let subdir = root
+            .children
+            .iter()
+            .find(|c| c.path.ends_with("subdir"))
+            .unwrap();
+        assert!(subdir.children.is_empty());
+        // file_b (10) + file_c (20) rolled into subdir, plus the directories
+        assert!(subdir.size(SizeMode::Apparent) >= 30);
+        assert_eq!(
+            root.total_size_for(SizeMode::Apparent),
+            traverse(tmp.path(), None)
+                .unwrap()
+                .total_size_for(SizeMode::Apparent)
+        );
+    }
+
+    #[test]
+    fn test_traverse_streaming_single_file() {
+        let tmp = TempDir::new().unwrap();
+        let file_path = tmp.path().join("single.txt");
+        fs::write(&file_path, "data").unwrap();
+
+        let root = traverse_streaming(&file_path, &TraversalOptions::default()).unwrap();
+        assert_eq!(root.entry_type, EntryType::File);
+        assert_eq!(root.size_bytes, 4);
+    }
+
+    #[test]
+    fn test_traverse_streaming_nonexistent_path() {
+        let result = traverse_streaming(
+            Path::new("/nonexistent/path/does/not/exist"),
+            &TraversalOptions::default(),
+        );
+        assert!(matches!(result, Err(DuskError::PathNotFound(_))));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_traverse_streaming_counts_hard_links_once() {
+        let tmp = create_hard_link_tree();
+        let root = traverse_streaming(tmp.path(), &TraversalOptions::default()).unwrap();
+        assert_eq!(file_sizes(&root), 1000);
+
+        // Links below the display depth are deduplicated too
+        let options = TraversalOptions {
+            max_depth: Some(0),
+            ..TraversalOptions::default()
+        };
+        let collapsed = traverse_streaming(tmp.path(), &options).unwrap();
+        assert_eq!(
+            collapsed.size_bytes,
+            traverse(tmp.path(), None).unwrap().total_size()
+        );
+    }
+
+    /// Fills `dir` with a pseudo-random tree derived from `seed`.
+    ///
+    /// Uses a small linear congruential generator so every run builds the
+    /// same tree without a randomness dependency.
+    fn generate_tree(dir: &Path, seed: u64, levels: usize) {
+        fn next(state: &mut u64) -> u64 {
+            *state = state
+                .wrapping_mul(6364136223846793005)
+                .wrapping_add(1442695040888963407);
+            *state >> 33
+        }
+
+        let mut state = seed;
+        for i in 0..next(&mut state) % 6 {
+            let size = (next(&mut state) % 5000) as usize;
+            fs::write(dir.join(format!("f{i}.dat")), vec![b'x'; size]).unwrap();
+        }
+        if levels > 0 {
+            for i in 0..next(&mut state) % 4 + 1 {
+                let sub = dir.join(format!("d{i}"));
+                fs::create_dir(&sub).unwrap();
+                generate_tree(&sub, next(&mut state), levels - 1);
+            }
+        }
+    }
+
+    /// Hard-links every third file of the tree into the next directory
+    /// (in walk order), so inodes are shared across directories.
+    fn link_across_directories(dir: &Path) {
+        let entries: Vec<_> = WalkDir::new(dir)
+            .sort_by_file_name()
+            .into_iter()
+            .map(|entry| entry.unwrap())
+            .collect();
+        let dirs: Vec<&Path> = entries
+            .iter()
+            .filter(|e| e.file_type().is_dir())
+            .map(|e| e.path())
+            .collect();
+        let files = entries.iter().filter(|e| e.file_type().is_file());
+        for (i, file) in files.enumerate().step_by(3) {
+            let dir = dirs[(i / 3 + 1) % dirs.len()];
+            fs::hard_link(file.path(), dir.join(format!("hl{i}.dat"))).unwrap();
+        }
+    }
+
+    #[test]
+    fn test_traverse_parallel_equals_traverse_on_generated_trees() {
+        for seed in 1..=8 {
+            let tmp = TempDir::new().unwrap();
+            generate_tree(tmp.path(), seed, 4);
+            link_across_directories(tmp.path());
+
+            let mut expected = Vec::new();
+            flatten(&traverse(tmp.path(), None).unwrap(), &mut expected);
+
+            for threads in [1, 2, 8] {
+                let mut parallel = Vec::new();
+                flatten(
+                    &traverse_parallel(tmp.path(), None, Some(threads)).unwrap(),
+                    &mut parallel,
+                );
+                assert_eq!(expected, parallel, "seed {seed}, {threads} threads");
+            }
+        }
+    }
+
+    #[test]
+    fn test_traverse_parallel_equals_traverse_with_options() {
+        let tmp = TempDir::new().unwrap();
+        generate_tree(tmp.path(), 42, 4);
+        fs::write(tmp.path().join(".gitignore"), "d1/\n").unwrap();
+
+        let option_sets = [
+            TraversalOptions {
+                max_depth: Some(2),
+                ..TraversalOptions::default()
+            },
+            TraversalOptions {
+                exclude: vec!["f0.dat".to_string(), "/d0/d1".to_string()],
+                ..TraversalOptions::default()
+            },
+            TraversalOptions {
+                respect_gitignore: true,
+                one_file_system: true,
+                ..TraversalOptions::default()
+            },
+        ];
+
+        for options in option_sets {
+            let (mut expected, mut parallel) = (Vec::new(), Vec::new());
+            flatten(
+                &traverse_with_options(tmp.path(), &options).unwrap(),
+                &mut expected,
+            );
+            flatten(
+                &traverse_parallel_with_options(tmp.path(), &options).unwrap(),
+                &mut parallel,
+            );
+            assert_eq!(expected, parallel, "{options:?}");
+        }
+    }
+
+    /// Sets the mtime of every directory under `dir` (included) to a day
+    /// ago, so the scan cache accepts their listings.
+    #[cfg(unix)]
+    fn backdate_dirs(dir: &Path) {
+        let day_ago = std::time::SystemTime::now() - std::time::Duration::from_secs(86_400);
+        for entry in WalkDir::new(dir) {
+            let entry = entry.unwrap();
+            if entry.file_type().is_dir() {
+                fs::File::open(entry.path())
+                    .unwrap()
+                    .set_modified(day_ago)
+                    .unwrap();
+            }
+        }
+    }
+
+    /// One entry as flattened by [`flatten`].
+    #[cfg(unix)]
+    type FlatRow = (PathBuf, EntryType, usize, u64, u64, usize);
+
+    /// Scans `root` with the cache stored in `file`, then saves the cache.
+    ///
+    /// Returns the flattened tree and the cache's hit and miss counts.
+    #[cfg(unix)]
+    fn cached_scan(
+        root: &Path,
+        file: &Path,
+        options: &TraversalOptions,
+    ) -> (Vec<FlatRow>, usize, usize) {
+        let cache = Arc::new(ScanCache::load(file));
+        let options = TraversalOptions {
+            cache: Some(Arc::clone(&cache)),
+            ..options.clone()
+        };
+        let mut flat = Vec::new();
+        flatten(
+            &traverse_parallel_with_options(root, &options).unwrap(),
+            &mut flat,
+        );
+        cache.save(file).unwrap();
+        (flat, cache.hits(), cache.misses())
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_cached_scan_matches_fresh_scan() {
+        let tmp = TempDir::new().unwrap();
+        let root = tmp.path().join("root");
+        let file = tmp.path().join("cache.gz");
+        fs::create_dir(&root).unwrap();
+        generate_tree(&root, 7, 3);
+        backdate_dirs(&root);
+
+        let options = TraversalOptions::default();
+        let fresh = |flat: &mut Vec<_>| {
+            flatten(&traverse_with_options(&root, &options).unwrap(), flat);
+        };
+        let mut expected = Vec::new();
+        fresh(&mut expected);
+        let dirs = expected
+            .iter()
+            .filter(|entry| entry.1 == EntryType::Directory)
+            .count();
+
+        // First run fills the cache, second run reuses every directory
+        let (first, hits, misses) = cached_scan(&root, &file, &options);
+        assert_eq!(first, expected);
+        assert_eq!((hits, misses), (0, dirs));
+        let (second, hits, misses) = cached_scan(&root, &file, &options);
+        assert_eq!(second, expected);
+        assert_eq!((hits, misses), (dirs, 0));
+
+        // Changed directories are read again, the rest still comes from the cache
+        fs::write(root.join("d0/new.dat"), vec![b'n'; 3000]).unwrap();
+        fs::remove_file(root.join("f0.dat")).ok();
+        fs::rename(root.join("d1"), root.join("d0/moved")).ok();
+        fs::create_dir(root.join("d0/empty")).unwrap();
+        let mut expected = Vec::new();
+        fresh(&mut expected);
+        let (third, hits, misses) = cached_scan(&root, &file, &options);
+        assert_eq!(third, expected);
+        assert!(hits > 0 && misses > 0, "{hits} hits, {misses} misses");
+
+        // Recently modified directories aren't cached, so they miss again
+        let (fourth, _, misses) = cached_scan(&root, &file, &options);
+        assert_eq!(fourth, expected);
+        assert!(misses > 0);
+        backdate_dirs(&root);
+        cached_scan(&root, &file, &options);
+        let (fifth, _, misses) = cached_scan(&root, &file, &options);
+        assert_eq!(fifth, expected);
+        assert_eq!(misses, 0);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_cached_scan_matches_fresh_scan_with_options() {
+        let tmp = TempDir::new().unwrap();
+        let root = tmp.path().join("root");
+        let file = tmp.path().join("cache.gz");
+        fs::create_dir(&root).unwrap();
+        generate_tree(&root, 42, 3);
+        fs::hard_link(root.join("d0/f0.dat"), root.join("link.dat")).ok();
+        backdate_dirs(&root);
+
+        let plain = TraversalOptions::default();
+        let excluding = TraversalOptions {
+            exclude: vec!["f1.dat".to_string(), "/d0/d0".to_string()],
+            ..TraversalOptions::default()
+        };
+        let counting = TraversalOptions {
+            count_links: true,
+            ..TraversalOptions::default()
+        };
+
+        // Each option set is checked against a cache written by another
+        for (writer, reader) in [
+            (&plain, &excluding),
+            (&excluding, &plain),
+            (&plain, &counting),
+            (&counting, &plain),
+        ] {
+            fs::remove_file(&file).ok();
+            cached_scan(&root, &file, writer);
+
+            let mut expected = Vec::new();
+            flatten(
+                &traverse_with_options(&root, reader).unwrap(),
+                &mut expected,
+            );
+            let (cached, _, _) = cached_scan(&root, &file, reader);
+            assert_eq!(cached, expected, "{reader:?} after {writer:?}");
+        }
+    }
+
+    #[test]
+    fn test_traverse_parallel_deep_tree() {
+        let tmp = TempDir::new().unwrap();
+        let mut dir = tmp.path().to_path_buf();
+        for _ in 0..200 {
+            dir.push("d");
+            fs::create_dir(&dir).unwrap();
+        }
+        fs::write(dir.join("leaf.txt"), "leaf").unwrap();
+
+        let seq = traverse(tmp.path(), None).unwrap();
+        let par = traverse_parallel(tmp.path(), None, Some(4)).unwrap();
+        assert_eq!(seq.total_size(), par.total_size());
+
+        let (mut expected, mut parallel) = (Vec::new(), Vec::new());
+        flatten(&seq, &mut expected);
+        flatten(&par, &mut parallel);
+        assert_eq!(expected.len(), 202);
+        assert_eq!(expected, parallel);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_traverse_parallel_unreadable_directory() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let tmp = create_test_tree();
+        let locked = tmp.path().join("subdir/nested");
+        fs::set_permissions(&locked, fs::Permissions::from_mode(0o000)).unwrap();
+
+        // Root can read anything; the comparison still has to hold
+        let (mut expected, mut parallel) = (Vec::new(), Vec::new());
+        flatten(&traverse(tmp.path(), None).unwrap(), &mut expected);
+        flatten(
+            &traverse_parallel(tmp.path(), None, Some(2)).unwrap(),
+            &mut parallel,
+        );
+
+        fs::set_permissions(&locked, fs::Permissions::from_mode(0o755)).unwrap();
+        assert_eq!(expected, parallel);
+    }
+
+    #[test]
+    fn test_skipped_entry_display() {
+        let denied = SkippedEntry::new(
+            Path::new("/srv/x"),
+            SkipAction::ReadDirectory,
+            io::Error::from_raw_os_error(13),
+        );
+        assert!(matches!(denied.error, DuskError::PermissionDenied(_)));
+        assert_eq!(
+            denied.to_string(),
+            "cannot read directory '/srv/x': Permission denied"
+        );
+
+        let failed = SkippedEntry::new(
+            Path::new("/srv/y"),
+            SkipAction::Access,
+            io::Error::other("stale file handle"),
+        );
+        assert!(matches!(failed.error, DuskError::IoError(_)));
+        assert_eq!(
+            failed.to_string(),
+            "cannot access '/srv/y': stale file handle"
+        );
+    }
+
+    #[test]
+    fn test_traverse_report_complete() {
+        let tmp = create_test_tree();
+        let options = TraversalOptions::default();
+        for report in [
+            traverse_report(tmp.path(), &options).unwrap(),
+            traverse_parallel_report(tmp.path(), &options).unwrap(),
+            traverse_streaming_report(tmp.path(), &options).unwrap(),
+        ] {
+            assert!(report.is_complete());
+        }
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_traverse_reports_unreadable_directory() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let tmp = create_test_tree();
+        let locked = tmp.path().join("subdir/nested");
+        fs::set_permissions(&locked, fs::Permissions::from_mode(0o000)).unwrap();
+
+        // Privileged users can read it anyway; nothing to report then
+        if fs::read_dir(&locked).is_ok() {
+            fs::set_permissions(&locked, fs::Permissions::from_mode(0o755)).unwrap();
+            return;
+        }
+
+        let options = TraversalOptions::default();
+        let reports = [
+            traverse_report(tmp.path(), &options).unwrap(),
+            traverse_parallel_report(tmp.path(), &options).unwrap(),
+            traverse_streaming_report(tmp.path(), &options).unwrap(),
+        ];
+        fs::set_permissions(&locked, fs::Permissions::from_mode(0o755)).unwrap();
+
+        let locked = locked.canonicalize().unwrap();
+        for report in reports {
+            assert_eq!(report.skipped.len(), 1);
+            let skipped = &report.skipped[0];
+            assert_eq!(skipped.path, locked);
+            assert_eq!(skipped.action, SkipAction::ReadDirectory);
+            assert!(matches!(skipped.error, DuskError::PermissionDenied(_)));
+            // The directory is still listed, without its contents
+            let mut names = Vec::new();
+            collect_names(&report.tree, &mut names);
+            assert!(names.contains(&"nested".to_string()));
+            assert!(!names.contains(&"file_c.txt".to_string()));
+        }
+    }
 }
@@ -0,0 +1,404 @@
+//! Graphical tree output (`--tree`).
+//!
+//! Renders a tree top-down with box-drawing connectors, the share of each
+//! entry in its parent and a proportional bar, like `tree` combined with
+//! `ncdu`'s graph column:
+//!
+//! ```text
+//!   1.5G  100.0%  ████████████████████  /var
+//!   1.2G   80.0%  ████████████████      ├── lib
+//!   1.0G   83.3%  ████████████████▋     │   ├── docker
+//! 200.0M   16.7%  ███▍                  │   └── apt
+//! 300.0M   20.0%  ████                  └── log
+//! ```
+//!
+//! - Entries are listed in pre-order (parents before children), in the
+//!   tree's current order; `--tree` sorts by size unless `--sort` is given
+//! - The root shows its full path, every other entry only its name
+//! - The percentage and bar are relative to the parent's total, so each
+//!   level's bars add up to the parent's share of what is listed
+//! - The bar width follows the terminal width (see [`bar_width`]), and
+//!   eighth-block characters give it sub-character resolution
+//!
+//! Visibility follows the flat text output: directories, mount points and
+//! `--top` remainders are always listed, files only with `--all`.
+//!
+//! # Examples
+//!
+//! ```
+//! use disk_usage_clone::entry::{DiskEntry, EntryType, SizeMode};
+//! use disk_usage_clone::tree_view::render_tree_view;
+//! use std::path::PathBuf;
+//!
+//! let mut dir = DiskEntry::new(PathBuf::from("/tmp"), 0, EntryType::Directory, 0);
+//! dir.children.push(DiskEntry::new(
+//!     PathBuf::from("/tmp/a.txt"),
+//!     75,
+//!     EntryType::File,
+//!     1,
+//! ));
+//! dir.children.push(DiskEntry::new(
+//!     PathBuf::from("/tmp/b.txt"),
+//!     25,
+//!     EntryType::File,
+//!     1,
+//! ));
+//!
+//! let output = render_tree_view(&dir, false, true, false, false, SizeMode::Apparent, 40);
+//! let lines: Vec<&str> = output.lines().collect();
+//! assert_eq!(lines[0], "100  100.0%  ██████████  /tmp");
+//! assert_eq!(lines[1], " 75   75.0%  ███████▌    ├── a.txt");
+//! assert_eq!(lines[2], " 25   25.0%  ██▌         └── b.txt");
+//! ```
+
+use crate::entry::{DiskEntry, EntryType, SizeMode};
+use crate::formatter::format_size;
+use crate::output::{
+    INCOMPLETE_MARKER, MOUNT_POINT_MARKER, colorize_path, colorize_size, link_suffix,
+};
+
+use colored::Colorize;
+
+/// Narrowest bar, used on very narrow terminals.
+const MIN_BAR_WIDTH: usize = 10;
+
+/// Widest bar, so wide terminals keep room for long names.
+const MAX_BAR_WIDTH: usize = 40;
+
+/// Partial blocks, indexed by eighths of a character (index 0 is unused).
+const PARTIAL_BLOCKS: [char; 8] = [' ', '▏', '▎', '▍', '▌', '▋', '▊', '▉'];
+
+/// One listed entry, collected before the size column width is known.
+struct Row {
+    size: u64,
+    percent: f64,
+    prefix: String,
+    name: String,
+    target: String,
+    entry_type: EntryType,
+    incomplete: bool,
+}
+
+/// Returns the bar width for a terminal of `terminal_width` columns.
+///
+/// A quarter of the terminal, between 10 and 40 characters.
+///
+/// # Examples
+///
+/// ```
+/// use disk_usage_clone::tree_view::bar_width;
+///
+/// assert_eq!(bar_width(80), 20);
+/// assert_eq!(bar_width(20), 10);
+/// assert_eq!(bar_width(300), 40);
+/// ```
+pub fn bar_width(terminal_width: usize) -> usize {
+    (terminal_width / 4).clamp(MIN_BAR_WIDTH, MAX_BAR_WIDTH)
+}
+
+/// Draws a bar filled to `fraction` (0.0 to 1.0) of `width` characters.
+///
+/// The result is always `width` characters wide, padded with spaces.
+///
+/// # Examples
+///
+/// ```
+/// use disk_usage_clone::tree_view::render_bar;
+///
+/// assert_eq!(render_bar(1.0, 4), "████");
+/// assert_eq!(render_bar(0.5, 4), "██  ");
+/// assert_eq!(render_bar(0.3, 4), "█▎  ");
+/// assert_eq!(render_bar(0.0, 4), "    ");
+/// ```
+pub fn render_bar(fraction: f64, width: usize) -> String {
+    let eighths = (fraction.clamp(0.0, 1.0) * (width * 8) as f64).round() as usize;
+    let (full, partial) = (eighths / 8, eighths % 8);
+
+    let mut bar = "█".repeat(full);
+    let mut drawn = full;
+    if partial > 0 {
+        bar.push(PARTIAL_BLOCKS[partial]);
+        drawn += 1;
+    }
+    bar.push_str(&" ".repeat(width - drawn));
+    bar
+}
+
+/// Renders a tree as an indented graph with percentages and bars.
+///
+/// # Arguments
+///
+/// * `entry` - Root of tree to render
+/// * `human_readable` - Format sizes as K/M/G?
+/// * `show_all` - Show files, or directories only?
+/// * `summarize` - Show only the root?
+/// * `use_color` - Apply colorization?
+/// * `size_mode` - Show apparent or allocated sizes?
+/// * `terminal_width` - Columns available, used to size the bars
+///
+/// # Returns
+///
+/// Newline-joined lines, root first. The size column is right-aligned to
+/// the widest size in the tree.
+pub fn render_tree_view(
+    entry: &DiskEntry,
+    human_readable: bool,
+    show_all: bool,
+    summarize: bool,
+    use_color: bool,
+    size_mode: SizeMode,
+    terminal_width: usize,
+) -> String {
+    let mut rows = vec![Row {
+        size: entry.total_size_for(size_mode),
+        percent: 100.0,
+        prefix: String::new(),
+        name: entry.path.display().to_string(),
+        target: link_suffix(entry),
+        entry_type: entry.entry_type.clone(),
+        incomplete: entry.incomplete,
+    }];
+    if !summarize {
+        collect_rows(entry, show_all, size_mode, "", &mut rows);
+    }
+
+    let sizes: Vec<String> = rows
+        .iter()
+        .map(|row| format_size(row.size, human_readable))
+        .collect();
+    let size_width = sizes.iter().map(String::len).max().unwrap_or(0);
+    let bar_width = bar_width(terminal_width);
+
+    rows.iter()
+        .zip(&sizes)
+        .map(|(row, size_str)| {
+            let padded = format!("{size_str:>size_width$}");
+            let percent = format!("{:>5.1}%", row.percent);
+            let bar = render_bar(row.percent / 100.0, bar_width);
+            let marker = if row.entry_type == EntryType::MountPoint {
+                MOUNT_POINT_MARKER
+            } else {
+                ""
+            };
+            let incomplete = if row.incomplete {
+                INCOMPLETE_MARKER
+            } else {
+                ""
+            };
+
+            if use_color {
+                format!(
+                    "{}  {percent}  {bar}  {}{}{}{}{}",
+                    colorize_size(&padded, row.size),
+                    row.prefix.dimmed(),
+                    colorize_path(&row.name, &row.entry_type),
+                    row.target,
+                    marker.dimmed(),
+                    incomplete.yellow()
+                )
+            } else {
+                format!(
+                    "{padded}  {percent}  {bar}  {}{}{}{marker}{incomplete}",
+                    row.prefix, row.name, row.target
+                )
+            }
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Recursively collects the listed children of `entry` in pre-order.
+///
+/// `indent` holds the connector columns of the ancestors: `│   ` where an
+/// ancestor has later siblings, four spaces where it was the last one.
+fn collect_rows(
+    entry: &DiskEntry,
+    show_all: bool,
+    size_mode: SizeMode,
+    indent: &str,
+    rows: &mut Vec<Row>,
+) {
+    let parent_size = entry.total_size_for(size_mode);
+    let listed: Vec<&DiskEntry> = entry
+        .children
+        .iter()
+        .filter(|child| match child.entry_type {
+            EntryType::Directory | EntryType::MountPoint | EntryType::Aggregate => true,
+            _ => show_all,
+        })
+        .collect();
+
+    for (index, child) in listed.iter().enumerate() {
+        let last = index + 1 == listed.len();
+        let size = child.total_size_for(size_mode);
+        let percent = if parent_size == 0 {
+            0.0
+        } else {
+            size as f64 * 100.0 / parent_size as f64
+        };
+        let name = child
+            .path
+            .file_name()
+            .map(|name| name.to_string_lossy().into_owned())
+            .unwrap_or_else(|| child.path.display().to_string());
+
+        rows.push(Row {
+            size,
+            percent,
+            prefix: format!("{indent}{}", if last { "└── " } else { "├── " }),
+            name,
+            target: link_suffix(child),
+            entry_type: child.entry_type.clone(),
+            incomplete: child.incomplete,
+        });
+
+        let child_indent = format!("{indent}{}", if last { "    " } else { "│   " });
+        collect_rows(child, show_all, size_mode, &child_indent, rows);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+
+    fn file(path: &str, size: u64, depth: usize) -> DiskEntry {
+        DiskEntry::new(PathBuf::from(path), size, EntryType::File, depth)
+    }
+
+    fn dir(path: &str, depth: usize, children: Vec<DiskEntry>) -> DiskEntry {
+        let mut entry = DiskEntry::new(PathBuf::from(path), 0, EntryType::Directory, depth);
+        entry.children = children;
+        entry
+    }
+
+    fn sample() -> DiskEntry {
+        dir(
+            "/r",
+            0,
+            vec![
+                dir(
+                    "/r/a",
+                    1,
+                    vec![file("/r/a/x", 60, 2), file("/r/a/y", 20, 2)],
+                ),
+                dir(
+                    "/r/b",
+                    1,
+                    vec![dir("/r/b/c", 2, vec![file("/r/b/c/z", 20, 3)])],
+                ),
+            ],
+        )
+    }
+
+    /// Strips the size, percentage and bar columns (3-digit sizes, 10-character bar).
+    fn names(output: &str) -> Vec<String> {
+        output
+            .lines()
+            .map(|line| line.chars().skip(25).collect())
+            .collect()
+    }
+
+    #[test]
+    fn test_connectors_and_order() {
+        let output = render_tree_view(&sample(), false, true, false, false, SizeMode::Apparent, 40);
+        assert_eq!(
+            names(&output),
+            vec![
+                "/r",
+                "├── a",
+                "│   ├── x",
+                "│   └── y",
+                "└── b",
+                "    └── c",
+                "        └── z",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_percent_of_parent() {
+        let output = render_tree_view(&sample(), false, true, false, false, SizeMode::Apparent, 40);
+        let lines: Vec<&str> = output.lines().collect();
+        assert!(lines[1].starts_with(" 80   80.0%"));
+        assert!(lines[2].starts_with(" 60   75.0%"));
+        assert!(lines[6].starts_with(" 20  100.0%"));
+    }
+
+    #[test]
+    fn test_files_hidden_without_all() {
+        let output = render_tree_view(
+            &sample(),
+            false,
+            false,
+            false,
+            false,
+            SizeMode::Apparent,
+            40,
+        );
+        assert_eq!(names(&output), vec!["/r", "├── a", "└── b", "    └── c"]);
+    }
+
+    #[test]
+    fn test_summarize_shows_root_only() {
+        let output = render_tree_view(&sample(), true, true, true, false, SizeMode::Apparent, 40);
+        assert_eq!(output, "100B  100.0%  ██████████  /r");
+    }
+
+    #[test]
+    fn test_empty_parent_has_zero_percent() {
+        let tree = dir("/r", 0, vec![dir("/r/empty", 1, vec![])]);
+        let output = render_tree_view(&tree, false, false, false, false, SizeMode::Apparent, 40);
+        let expected = format!("0    0.0%{}└── empty", " ".repeat(14));
+        assert_eq!(output.lines().nth(1).unwrap(), expected);
+    }
+
+    #[test]
+    fn test_bar_scales_with_terminal() {
+        let narrow = render_tree_view(&sample(), false, false, true, false, SizeMode::Apparent, 40);
+        let wide = render_tree_view(
+            &sample(),
+            false,
+            false,
+            true,
+            false,
+            SizeMode::Apparent,
+            160,
+        );
+        assert_eq!(narrow.matches('█').count(), 10);
+        assert_eq!(wide.matches('█').count(), 40);
+    }
+
+    #[test]
+    fn test_render_bar_partial_blocks() {
+        assert_eq!(render_bar(0.5, 1), "▌");
+        assert_eq!(render_bar(0.99, 2), "██");
+        assert_eq!(render_bar(2.0, 3), "███");
+        assert_eq!(render_bar(-1.0, 3), "   ");
+    }
+
+    #[test]
+    fn test_mount_point_marker() {
+        let mut tree = dir("/r", 0, vec![]);
+        tree.children.push(DiskEntry::new(
+            PathBuf::from("/r/proc"),
+            0,
+            EntryType::MountPoint,
+            1,
+        ));
+        let output = render_tree_view(&tree, false, false, false, false, SizeMode::Apparent, 40);
+        assert!(output.ends_with("└── proc [mount point]"));
+    }
+
+    #[test]
+    fn test_incomplete_marker() {
+        let mut sub = dir("/r/big", 1, vec![]);
+        sub.incomplete = true;
+        let mut tree = dir("/r", 0, vec![sub]);
+        tree.incomplete = true;
+        let output = render_tree_view(&tree, false, false, false, false, SizeMode::Apparent, 40);
+        let lines: Vec<&str> = output.lines().collect();
+        assert!(lines[0].ends_with("/r [incomplete]"));
+        assert!(lines[1].ends_with("└── big [incomplete]"));
+    }
+}
@@ -0,0 +1,665 @@
+//! Live totals for changing trees (`--watch`).
+//!
+//! `dusk --watch PATH` scans once, then keeps the tree up to date from
+//! filesystem change notifications (inotify on Linux, FSEvents on macOS,
+//! `ReadDirectoryChangesW` on Windows, via the `notify` crate) and
+//! re-renders it at a fixed interval, like `watch dusk PATH` without the
+//! repeated full scans.
+//!
+//! # Incremental Updates
+//!
+//! Notifications only name the paths that changed. Each interval, every
+//! changed path is looked at again with `lstat` and the tree is patched
+//! (see [`LiveTree::apply`]):
+//!
+//! - A file that changed gets its new size, owner and timestamps
+//! - A removed file or directory is dropped with its subtree
+//! - A new directory (created, or moved in from outside the tree) is
+//!   scanned in full, with the exclusions of the original scan
+//! - The parent of a changed entry is re-stat'ed as well, since its own
+//!   size and mtime change with its listing
+//!
+//! Ancestors need no update of their own: totals are always summed from the
+//! children, so patching a node updates every total above it.
+//!
+//! Hard links created while watching are counted in full; a rescan (or the
+//! next run) counts each inode once again.
+//!
+//! # Fallback
+//!
+//! Recursive watches need one kernel watch per directory, and inotify
+//! limits them (`fs.inotify.max_user_watches`). When the limit is reached,
+//! or notifications stop or overflow, the session falls back to a full
+//! rescan every interval (see [`WatchMode::Polling`]). An overflowed event
+//! queue on its own only triggers a single rescan.
+//!
+//! # Examples
+//!
+//! ```no_run
+//! use disk_usage_clone::traversal::TraversalOptions;
+//! use disk_usage_clone::watch::{LiveTree, WatchSession};
+//! use std::path::Path;
+//! use std::time::Duration;
+//!
+//! let tree = LiveTree::scan(Path::new("target"), &TraversalOptions::default()).unwrap();
+//! let mut session = WatchSession::new(vec![tree], Duration::from_secs(2));
+//! loop {
+//!     if session.next_update() {
+//!         println!("{} bytes", session.trees()[0].tree().total_size());
+//!     }
+//! }
+//! ```
+
+use std::collections::BTreeSet;
+use std::fs;
+use std::path::Path;
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use notify::{ErrorKind, Event, EventKind, RecommendedWatcher, RecursiveMode, Watcher};
+
+use crate::entry::{DiskEntry, EntryType};
+use crate::error::DuskError;
+use crate::filter::PathFilter;
+use crate::traversal::{
+    TraversalOptions, device_id, stat_entry, traverse_parallel_report, traverse_subtree,
+};
+
+/// A scanned tree that can be patched as its paths change.
+pub struct LiveTree {
+    /// Current state of the tree, always scanned to full depth.
+    tree: DiskEntry,
+    /// Settings of the original scan, reused for rescans.
+    options: TraversalOptions,
+    /// Exclusions of the original scan, anchored at its root.
+    filter: PathFilter,
+    /// Device of the root when `one_file_system` is set.
+    root_dev: Option<u64>,
+}
+
+impl LiveTree {
+    /// Scans `path` in full (`options.max_depth` is ignored).
+    ///
+    /// `options.progress` receives reports from this scan only; rescans
+    /// and updates don't report progress.
+    ///
+    /// # Errors
+    ///
+    /// Same as [`traverse_parallel_report`]. Unreadable paths below the
+    /// root are left out without an error.
+    pub fn scan(path: &Path, options: &TraversalOptions) -> Result<LiveTree, DuskError> {
+        let options = TraversalOptions {
+            max_depth: None,
+            ..options.clone()
+        };
+        let tree = traverse_parallel_report(path, &options)?.tree;
+        let filter = PathFilter::new(&tree.path, &options)?;
+        let root_dev = if options.one_file_system {
+            fs::symlink_metadata(&tree.path)
+                .ok()
+                .and_then(|m| device_id(&m))
+        } else {
+            None
+        };
+
+        Ok(LiveTree {
+            tree,
+            options: TraversalOptions {
+                progress: None,
+                ..options
+            },
+            filter,
+            root_dev,
+        })
+    }
+
+    /// Returns the current tree.
+    pub fn tree(&self) -> &DiskEntry {
+        &self.tree
+    }
+
+    /// Returns the canonicalized root path.
+    pub fn root(&self) -> &Path {
+        &self.tree.path
+    }
+
+    /// Replaces the tree with a fresh full scan.
+    ///
+    /// # Errors
+    ///
+    /// Same as [`LiveTree::scan`], e.g. if the root was removed. The old
+    /// tree is kept in that case.
+    pub fn rescan(&mut self) -> Result<(), DuskError> {
+        self.tree = traverse_parallel_report(&self.tree.path, &self.options)?.tree;
+        Ok(())
+    }
+
+    /// Brings the tree up to date with the current state of `path`.
+    ///
+    /// `path` is a path reported as changed: it may have been created,
+    /// modified or removed, and may be anywhere below the root. Paths
+    /// outside the tree, below excluded directories or below mount points
+    /// (with `one_file_system`) are ignored.
+    ///
+    /// Returns `true` if the tree changed.
+    pub fn apply(&mut self, path: &Path) -> bool {
+        if path == self.tree.path {
+            return refresh_own_stats(&mut self.tree);
+        }
+        if !path.starts_with(&self.tree.path) {
+            return false;
+        }
+
+        let LiveTree {
+            tree,
+            options,
+            filter,
+            root_dev,
+        } = self;
+        let scan_root = tree.path.clone();
+
+        // The deepest directory above `path` that the tree already holds;
+        // whatever changed is its child on the way down to `path`
+        let parent = deepest_directory_above(tree, path);
+        let Some(child_path) = path
+            .strip_prefix(&parent.path)
+            .ok()
+            .and_then(|rest| rest.components().next())
+            .map(|name| parent.path.join(name))
+        else {
+            return false;
+        };
+
+        let depth = parent.depth + 1;
+        let position = parent
+            .children
+            .iter()
+            .position(|child| child.path == child_path);
+        let existing = position.map(|index| &parent.children[index]);
+
+        let fresh = match fresh_state(&child_path, depth, filter, *root_dev) {
+            // A directory the tree already holds keeps its subtree
+            Some(Fresh::Directory)
+                if existing.is_some_and(|e| e.entry_type == EntryType::Directory) =>
+            {
+                Some(Fresh::Directory)
+            }
+            // Anything else that is now a directory is read in full
+            Some(Fresh::Directory) => {
+                scan_directory(&scan_root, &child_path, depth, options).map(Fresh::Entry)
+            }
+            fresh => fresh,
+        };
+
+        let mut changed = refresh_own_stats(parent);
+        changed |= match (position, fresh) {
+            (None, None) => false,
+            (Some(index), None) => {
+                parent.children.remove(index);
+                true
+            }
+            (None, Some(Fresh::Entry(entry))) => {
+                parent.children.push(entry);
+                true
+            }
+            (Some(index), Some(fresh)) => update_child(&mut parent.children[index], fresh),
+            (None, Some(Fresh::Directory)) => false,
+        };
+        changed
+    }
+}
+
+/// The current state of a changed path, as far as the tree is concerned.
+enum Fresh {
+    /// A complete entry: a file, a mount point, or a newly read subtree.
+    Entry(DiskEntry),
+    /// A directory, whose contents are either kept or read separately.
+    Directory,
+}
+
+/// Reads the current state of `path`, or `None` if it is gone or excluded.
+///
+/// Mount points (with `one_file_system`) become childless
+/// [`EntryType::MountPoint`] entries.
+fn fresh_state(
+    path: &Path,
+    depth: usize,
+    filter: &PathFilter,
+    root_dev: Option<u64>,
+) -> Option<Fresh> {
+    let metadata = fs::symlink_metadata(path).ok()?;
+    let is_dir = metadata.is_dir();
+    if !filter.is_empty() && filter.is_excluded(path, is_dir) {
+        return None;
+    }
+
+    if !is_dir {
+        return Some(Fresh::Entry(stat_entry(
+            path.to_path_buf(),
+            &metadata,
+            depth,
+        )));
+    }
+    if let Some(root_dev) = root_dev
+        && device_id(&metadata) != Some(root_dev)
+    {
+        return Some(Fresh::Entry(DiskEntry::new(
+            path.to_path_buf(),
+            0,
+            EntryType::MountPoint,
+            depth,
+        )));
+    }
+    Some(Fresh::Directory)
+}
+
+/// Reads a new directory of the tree rooted at `scan_root` in full.
+///
+/// Returns `None` if it vanished before it could be read.
+fn scan_directory(
+    scan_root: &Path,
+    dir: &Path,
+    depth: usize,
+    options: &TraversalOptions,
+) -> Option<DiskEntry> {
+    let mut subtree = traverse_subtree(scan_root, dir, options).ok()?.tree;
+    set_depth(&mut subtree, depth);
+    Some(subtree)
+}
+
+/// Updates an entry of the tree from its fresh state.
+///
+/// Returns `true` if anything changed.
+fn update_child(entry: &mut DiskEntry, fresh: Fresh) -> bool {
+    match fresh {
+        Fresh::Directory if entry.entry_type == EntryType::Directory => refresh_own_stats(entry),
+        Fresh::Directory => false,
+        Fresh::Entry(fresh) => {
+            let changed = fresh.entry_type != entry.entry_type
+                || fresh.size_bytes != entry.size_bytes
+                || fresh.allocated_bytes != entry.allocated_bytes
+                || fresh.owner != entry.owner
+                || fresh.times != entry.times
+                || !entry.children.is_empty();
+            *entry = fresh;
+            changed
+        }
+    }
+}
+
+/// Re-reads the size, owner and timestamps of an entry, keeping its children.
+///
+/// Returns `true` if any of them changed. An entry that can't be read
+/// anymore is left alone; its removal is handled from its parent.
+fn refresh_own_stats(entry: &mut DiskEntry) -> bool {
+    if entry.entry_type == EntryType::MountPoint {
+        return false;
+    }
+    let Ok(metadata) = fs::symlink_metadata(&entry.path) else {
+        return false;
+    };
+
+    let fresh = stat_entry(entry.path.clone(), &metadata, entry.depth);
+    let changed = fresh.size_bytes != entry.size_bytes
+        || fresh.allocated_bytes != entry.allocated_bytes
+        || fresh.owner != entry.owner
+        || fresh.times != entry.times;
+    entry.size_bytes = fresh.size_bytes;
+    entry.allocated_bytes = fresh.allocated_bytes;
+    entry.owner = fresh.owner;
+    entry.times = fresh.times;
+    changed
+}
+
+/// Finds the deepest directory of the tree that is a strict ancestor of `path`.
+fn deepest_directory_above<'a>(entry: &'a mut DiskEntry, path: &Path) -> &'a mut DiskEntry {
+    let next = entry.children.iter().position(|child| {
+        child.entry_type == EntryType::Directory
+            && child.path != path
+            && path.starts_with(&child.path)
+    });
+    match next {
+        Some(index) => deepest_directory_above(&mut entry.children[index], path),
+        None => entry,
+    }
+}
+
+/// Sets the depth of every entry of a subtree, starting at `depth`.
+fn set_depth(entry: &mut DiskEntry, depth: usize) {
+    entry.depth = depth;
+    for child in &mut entry.children {
+        set_depth(child, depth + 1);
+    }
+}
+
+/// How a [`WatchSession`] notices changes.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum WatchMode {
+    /// Change notifications; only changed paths are read again.
+    Events,
+    /// Full rescans every interval, because notifications are unavailable.
+    Polling {
+        /// Why notifications can't be used, for the user.
+        reason: String,
+    },
+}
+
+/// Live trees, kept up to date from change notifications.
+pub struct WatchSession {
+    trees: Vec<LiveTree>,
+    interval: Duration,
+    mode: WatchMode,
+    /// Keeps the notifications flowing; `None` when polling.
+    watcher: Option<RecommendedWatcher>,
+    /// Notifications from the watcher thread.
+    events: Receiver<notify::Result<Event>>,
+}
+
+impl WatchSession {
+    /// Starts watching the roots of `trees`.
+    ///
+    /// Falls back to [`WatchMode::Polling`] if a watcher can't be created
+    /// or a root can't be watched (e.g. the inotify watch limit is reached).
+    pub fn new(trees: Vec<LiveTree>, interval: Duration) -> WatchSession {
+        let (sender, events) = mpsc::channel();
+        let mut session = WatchSession {
+            trees,
+            interval,
+            mode: WatchMode::Events,
+            watcher: None,
+            events,
+        };
+
+        match session.start_watcher(sender) {
+            Ok(watcher) => session.watcher = Some(watcher),
+            Err(err) => session.fall_back(&err),
+        }
+        session
+    }
+
+    /// Creates a session that rescans every interval, without notifications.
+    pub fn polling(trees: Vec<LiveTree>, interval: Duration, reason: &str) -> WatchSession {
+        let (_, events) = mpsc::channel();
+        WatchSession {
+            trees,
+            interval,
+            mode: WatchMode::Polling {
+                reason: reason.to_string(),
+            },
+            watcher: None,
+            events,
+        }
+    }
+
+    /// Creates a watcher and subscribes to every root, recursively.
+    fn start_watcher(
+        &self,
+        sender: mpsc::Sender<notify::Result<Event>>,
+    ) -> notify::Result<RecommendedWatcher> {
+        let mut watcher = notify::recommended_watcher(sender)?;
+        for tree in &self.trees {
+            watcher.watch(tree.root(), RecursiveMode::Recursive)?;
+        }
+        Ok(watcher)
+    }
+
+    /// Stops listening for notifications and switches to polling.
+    fn fall_back(&mut self, err: &notify::Error) {
+        self.watcher = None;
+        let reason = match err.kind {
+            ErrorKind::MaxFilesWatch => {
+                "the limit of watched directories was reached (fs.inotify.max_user_watches)"
+                    .to_string()
+            }
+            _ => format!("change notifications are unavailable ({err})"),
+        };
+        self.mode = WatchMode::Polling { reason };
+    }
+
+    /// Returns how changes are noticed.
+    pub fn mode(&self) -> &WatchMode {
+        &self.mode
+    }
+
+    /// Returns the live trees, in the order they were given.
+    pub fn trees(&self) -> &[LiveTree] {
+        &self.trees
+    }
+
+    /// Waits one interval, then applies the changes seen meanwhile.
+    ///
+    /// Returns `true` if any tree changed (always after a rescan), so the
+    /// caller only needs to re-render then.
+    pub fn next_update(&mut self) -> bool {
+        if self.watcher.is_none() {
+            thread::sleep(self.interval);
+            self.rescan_all();
+            return true;
+        }
+
+        let deadline = Instant::now() + self.interval;
+        let mut changed_paths = BTreeSet::new();
+        let mut rescan = false;
+
+        loop {
+            let remaining = deadline.saturating_duration_since(Instant::now());
+            match self.events.recv_timeout(remaining) {
+                Ok(Ok(event)) => {
+                    rescan |= event.need_rescan();
+                    // Reading a file doesn't change its size
+                    if !matches!(event.kind, EventKind::Access(_)) {
+                        changed_paths.extend(event.paths);
+                    }
+                }
+                Ok(Err(err)) => {
+                    if matches!(err.kind, ErrorKind::MaxFilesWatch) {
+                        self.fall_back(&err);
+                    }
+                    rescan = true;
+                }
+                Err(RecvTimeoutError::Timeout) => break,
+                Err(RecvTimeoutError::Disconnected) => {
+                    self.fall_back(&notify::Error::generic("the watcher stopped"));
+                    rescan = true;
+                    break;
+                }
+            }
+        }
+
+        if rescan {
+            self.rescan_all();
+            return true;
+        }
+
+        // Sorted, so parents are patched before their children
+        let mut changed = false;
+        for path in &changed_paths {
+            for tree in &mut self.trees {
+                changed |= tree.apply(path);
+            }
+        }
+        changed
+    }
+
+    /// Rescans every tree, keeping the old state of those that fail.
+    fn rescan_all(&mut self) {
+        for tree in &mut self.trees {
+            // A vanished root keeps its last state until it comes back
+            let _ = tree.rescan();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::traversal::traverse;
+    use std::path::PathBuf;
+    use tempfile::TempDir;
+
+    /// Paths, types, depths and own sizes of a tree, in path order.
+    fn flatten(entry: &DiskEntry) -> Vec<(PathBuf, EntryType, usize, u64)> {
+        let mut out = vec![(
+            entry.path.clone(),
+            entry.entry_type.clone(),
+            entry.depth,
+            entry.size_bytes,
+        )];
+        for child in &entry.children {
+            out.extend(flatten(child));
+        }
+        out.sort_by(|a, b| a.0.cmp(&b.0));
+        out
+    }
+
+    fn create_tree() -> TempDir {
+        let tmp = TempDir::new().unwrap();
+        fs::write(tmp.path().join("a.txt"), "hello").unwrap();
+        fs::create_dir_all(tmp.path().join("sub/nested")).unwrap();
+        fs::write(tmp.path().join("sub/b.txt"), "0123456789").unwrap();
+        fs::write(tmp.path().join("sub/nested/c.txt"), "x".repeat(20)).unwrap();
+        tmp
+    }
+
+    fn assert_matches_fresh_scan(live: &LiveTree) {
+        let fresh = traverse(live.root(), None).unwrap();
+        assert_eq!(flatten(live.tree()), flatten(&fresh));
+    }
+
+    #[test]
+    fn test_apply_file_changes() {
+        let tmp = create_tree();
+        let mut live = LiveTree::scan(tmp.path(), &TraversalOptions::default()).unwrap();
+        let root = live.root().to_path_buf();
+
+        fs::write(root.join("sub/b.txt"), "much longer than before").unwrap();
+        fs::write(root.join("sub/nested/new.txt"), "new").unwrap();
+        fs::remove_file(root.join("a.txt")).unwrap();
+        for path in ["sub/b.txt", "sub/nested/new.txt", "a.txt"] {
+            assert!(live.apply(&root.join(path)), "{path}");
+        }
+        assert_matches_fresh_scan(&live);
+
+        // Nothing changed since
+        assert!(!live.apply(&root.join("sub/b.txt")));
+    }
+
+    #[test]
+    fn test_apply_directory_creation_and_removal() {
+        let tmp = create_tree();
+        let mut live = LiveTree::scan(tmp.path(), &TraversalOptions::default()).unwrap();
+        let root = live.root().to_path_buf();
+
+        // Only the deepest path is reported for the new directories
+        fs::create_dir_all(root.join("new/deep")).unwrap();
+        fs::write(root.join("new/deep/d.txt"), "dddd").unwrap();
+        assert!(live.apply(&root.join("new/deep/d.txt")));
+        assert_matches_fresh_scan(&live);
+
+        fs::remove_dir_all(root.join("sub")).unwrap();
+        assert!(live.apply(&root.join("sub/nested/c.txt")));
+        assert!(live.apply(&root.join("sub")));
+        assert_matches_fresh_scan(&live);
+    }
+
+    #[test]
+    fn test_apply_rename_and_type_change() {
+        let tmp = create_tree();
+        let mut live = LiveTree::scan(tmp.path(), &TraversalOptions::default()).unwrap();
+        let root = live.root().to_path_buf();
+
+        fs::rename(root.join("sub"), root.join("moved")).unwrap();
+        live.apply(&root.join("sub"));
+        live.apply(&root.join("moved"));
+        assert_matches_fresh_scan(&live);
+
+        fs::remove_file(root.join("a.txt")).unwrap();
+        fs::create_dir(root.join("a.txt")).unwrap();
+        fs::write(root.join("a.txt/inside"), "i").unwrap();
+        assert!(live.apply(&root.join("a.txt")));
+        assert_matches_fresh_scan(&live);
+    }
+
+    #[test]
+    fn test_apply_keeps_exclusions() {
+        let tmp = create_tree();
+        let options = TraversalOptions {
+            exclude: vec!["*.log".to_string(), "/cache".to_string()],
+            ..TraversalOptions::default()
+        };
+        let mut live = LiveTree::scan(tmp.path(), &options).unwrap();
+        let root = live.root().to_path_buf();
+        let before = flatten(live.tree());
+
+        fs::write(root.join("sub/build.log"), "log").unwrap();
+        fs::create_dir(root.join("cache")).unwrap();
+        fs::write(root.join("cache/blob"), "blob").unwrap();
+        live.apply(&root.join("sub/build.log"));
+        live.apply(&root.join("cache/blob"));
+
+        let paths = |flat: &[(PathBuf, EntryType, usize, u64)]| -> Vec<PathBuf> {
+            flat.iter().map(|entry| entry.0.clone()).collect()
+        };
+        assert_eq!(paths(&flatten(live.tree())), paths(&before));
+    }
+
+    #[test]
+    fn test_apply_ignores_outside_paths() {
+        let tmp = create_tree();
+        let other = TempDir::new().unwrap();
+        let mut live = LiveTree::scan(tmp.path(), &TraversalOptions::default()).unwrap();
+        assert!(!live.apply(other.path()));
+        assert!(!live.apply(&live.root().join("missing/file")));
+    }
+
+    #[test]
+    fn test_polling_session_rescans() {
+        let tmp = create_tree();
+        let live = LiveTree::scan(tmp.path(), &TraversalOptions::default()).unwrap();
+        let mut session = WatchSession::polling(vec![live], Duration::from_millis(10), "test");
+        assert!(matches!(session.mode(), WatchMode::Polling { .. }));
+
+        fs::write(tmp.path().join("sub/new.txt"), "new").unwrap();
+        assert!(session.next_update());
+        assert_matches_fresh_scan(&session.trees()[0]);
+    }
+
+    #[test]
+    fn test_session_follows_notifications() {
+        let tmp = create_tree();
+        let live = LiveTree::scan(tmp.path(), &TraversalOptions::default()).unwrap();
+        let mut session = WatchSession::new(vec![live], Duration::from_millis(200));
+        if *session.mode() != WatchMode::Events {
+            // No notification support in this environment; polling is tested above
+            return;
+        }
+
+        fs::create_dir(tmp.path().join("fresh")).unwrap();
+        fs::write(tmp.path().join("fresh/f.txt"), "fresh data").unwrap();
+        fs::remove_file(tmp.path().join("sub/b.txt")).unwrap();
+
+        // Events may straddle an interval boundary
+        for _ in 0..10 {
+            session.next_update();
+            let live = &session.trees()[0];
+            if flatten(live.tree()) == flatten(&traverse(live.root(), None).unwrap()) {
+                return;
+            }
+        }
+        assert_matches_fresh_scan(&session.trees()[0]);
+    }
+
+    #[test]
+    fn test_set_depth() {
+        let mut entry = DiskEntry::new(PathBuf::from("/r/a"), 0, EntryType::Directory, 0);
+        entry.children.push(DiskEntry::new(
+            PathBuf::from("/r/a/b"),
+            1,
+            EntryType::File,
+            1,
+        ));
+        set_depth(&mut entry, 3);
+        assert_eq!((entry.depth, entry.children[0].depth), (3, 4));
+    }
+}
@@ -207,3 +207,772 @@ fn test_multiple_paths() {
         .assert()
         .success();
 }
+
+#[test]
+fn test_apparent_size_flag() {
+    let tmp = create_test_tree();
+    cmd()
+        .args(["-a", "--apparent-size", "--no-color"])
+        .arg(tmp.path().to_str().unwrap())
+        .assert()
+        .success()
+        // file_a.txt contains "hello" (5 bytes)
+        .stdout(predicate::str::contains("5\t").and(predicate::str::contains("file_a.txt")));
+}
+
+#[cfg(unix)]
+#[test]
+fn test_count_links_flag() {
+    let tmp = TempDir::new().unwrap();
+    let root = tmp.path();
+    fs::write(root.join("original.bin"), vec![0u8; 1000]).unwrap();
+    fs::hard_link(root.join("original.bin"), root.join("link.bin")).unwrap();
+
+    let summarize = |extra: &[&str]| -> u64 {
+        let output = cmd()
+            .args(["-s", "--apparent-size", "--no-color"])
+            .args(extra)
+            .arg(root.to_str().unwrap())
+            .output()
+            .unwrap();
+        let stdout = String::from_utf8(output.stdout).unwrap();
+        stdout.split('\t').next().unwrap().trim().parse().unwrap()
+    };
+
+    // The second link adds exactly one more copy of the file
+    assert_eq!(summarize(&["--count-links"]) - summarize(&[]), 1000);
+}
+
+#[test]
+fn test_one_file_system_flag() {
+    let tmp = create_test_tree();
+    cmd()
+        .args(["-x", "--no-color"])
+        .arg(tmp.path().to_str().unwrap())
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("subdir"))
+        .stdout(predicate::str::contains("[mount point]").not());
+}
+
+#[test]
+fn test_exclude_flag() {
+    let tmp = create_test_tree();
+    cmd()
+        .args(["-a", "--no-color", "--exclude", "nested/"])
+        .arg(tmp.path().to_str().unwrap())
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("file_b.txt"))
+        .stdout(predicate::str::contains("nested").not())
+        .stdout(predicate::str::contains("file_c.txt").not());
+}
+
+#[test]
+fn test_exclude_from_flag() {
+    let tmp = create_test_tree();
+    let list = TempDir::new().unwrap();
+    let list_path = list.path().join("excludes");
+    fs::write(&list_path, "# skip text files\n*.txt\n").unwrap();
+
+    cmd()
+        .args(["-a", "--no-color", "--exclude-from"])
+        .arg(list_path.to_str().unwrap())
+        .arg(tmp.path().to_str().unwrap())
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("nested"))
+        .stdout(predicate::str::contains(".txt").not());
+}
+
+#[test]
+fn test_respect_gitignore_flag() {
+    let tmp = create_test_tree();
+    fs::write(tmp.path().join(".gitignore"), "file_a.txt\n").unwrap();
+
+    cmd()
+        .args(["-a", "--no-color", "--respect-gitignore"])
+        .arg(tmp.path().to_str().unwrap())
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("file_b.txt"))
+        .stdout(predicate::str::contains("file_a.txt").not());
+}
+
+#[test]
+fn test_format_json() {
+    let tmp = create_test_tree();
+    let output = cmd()
+        .args(["--format", "json", "--apparent-size", "-a"])
+        .arg(tmp.path().to_str().unwrap())
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+
+    let parsed: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    let root = &parsed[0];
+    assert_eq!(root["depth"], 0);
+    // file_a (5) + file_b (10) + file_c (20) plus directory entries
+    assert!(root["apparent_size"].as_u64().unwrap() >= 35);
+    assert_eq!(root["child_count"], 2);
+}
+
+#[test]
+fn test_format_json_child_count_ignores_collapsing() {
+    let tmp = create_test_tree();
+    let json = |flag: &str| -> serde_json::Value {
+        let output = cmd()
+            .args(["--format", "json", flag])
+            .arg(tmp.path())
+            .output()
+            .unwrap();
+        assert!(output.status.success());
+        serde_json::from_slice(&output.stdout).unwrap()
+    };
+
+    // The root holds file_a.txt and subdir however much is listed
+    for flag in ["-d0", "-s", "-a"] {
+        let root = &json(flag)[0];
+        assert_eq!(root["child_count"], 2, "{flag}");
+    }
+    let parsed = json("-d1");
+    let subdir = &parsed[0]["children"][0];
+    assert!(subdir["children"].as_array().unwrap().is_empty());
+    assert_eq!(subdir["child_count"], 2);
+}
+
+#[test]
+fn test_format_ndjson() {
+    let tmp = create_test_tree();
+    let output = cmd()
+        .args(["--format", "ndjson", "-a"])
+        .arg(tmp.path().to_str().unwrap())
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    let objects: Vec<serde_json::Value> = stdout
+        .lines()
+        .map(|line| serde_json::from_str(line).unwrap())
+        .collect();
+    // 3 files, 2 subdirectories and the root
+    assert_eq!(objects.len(), 6);
+    assert!(objects.iter().any(|o| o["type"] == "file"));
+    // Never colored, even without --no-color
+    assert!(!stdout.contains('\x1b'));
+}
+
+#[test]
+fn test_format_rejects_unknown() {
+    cmd().args(["--format", "yaml", "."]).assert().failure();
+}
+
+#[test]
+fn test_format_csv() {
+    let tmp = create_test_tree();
+    fs::write(tmp.path().join("a, \"b\".txt"), "x").unwrap();
+
+    let output = cmd()
+        .args(["--format", "csv", "-a", "--apparent-size"])
+        .arg(tmp.path().to_str().unwrap())
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    let lines: Vec<&str> = stdout.lines().collect();
+    assert_eq!(
+        lines[0],
+        "path,size_bytes,size_human,type,depth,parent,incomplete"
+    );
+    // Header, 4 files, 2 subdirectories and the root
+    assert_eq!(lines.len(), 8);
+    assert!(stdout.contains("a, \"\"b\"\".txt\",1,1B,file,1,"));
+    // Never colored, even without --no-color
+    assert!(!stdout.contains('\x1b'));
+}
+
+#[test]
+fn test_interactive_needs_terminal() {
+    let tmp = create_test_tree();
+    // stdout is captured by the test harness, so it is never a terminal
+    cmd()
+        .arg("--interactive")
+        .arg(tmp.path().to_str().unwrap())
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("needs a terminal"));
+}
+
+#[test]
+fn test_interactive_single_path_only() {
+    let tmp = create_test_tree();
+    let path = tmp.path().to_str().unwrap();
+    cmd()
+        .args(["--interactive", path, path])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("single path"));
+}
+
+#[test]
+fn test_save_and_diff_snapshots() {
+    let tmp = create_test_tree();
+    let snapshots = TempDir::new().unwrap();
+    let old = snapshots.path().join("old.dusk");
+    let new = snapshots.path().join("new.dusk");
+    let root = tmp.path().to_str().unwrap();
+
+    cmd()
+        .args(["--no-color", "--save", old.to_str().unwrap(), root])
+        .assert()
+        .success();
+
+    fs::write(
+        tmp.path().join("subdir/file_b.txt"),
+        "0123456789".repeat(10),
+    )
+    .unwrap();
+    fs::remove_file(tmp.path().join("file_a.txt")).unwrap();
+    fs::write(tmp.path().join("added.bin"), "new").unwrap();
+
+    cmd()
+        .args(["--no-color", "--save", new.to_str().unwrap(), root])
+        .assert()
+        .success();
+
+    // The scanned tree can disappear; diff only reads the snapshots
+    drop(tmp);
+
+    let output = cmd()
+        .args(["diff", "--apparent-size"])
+        .arg(&old)
+        .arg(&new)
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    let first = stdout.lines().next().unwrap();
+    // Largest absolute change first: file_b and subdir grew by 90 bytes
+    assert!(first.starts_with("+90\tgrown\t"), "{stdout}");
+    assert!(stdout.contains("+90\tgrown\t") && stdout.contains("file_b.txt"));
+    assert!(stdout.contains("-5\tremoved\t"));
+    assert!(stdout.contains("+3\tadded\t"));
+}
+
+#[test]
+fn test_diff_invalid_snapshot() {
+    let tmp = TempDir::new().unwrap();
+    let bogus = tmp.path().join("bogus.dusk");
+    fs::write(&bogus, "plain text").unwrap();
+
+    cmd()
+        .arg("diff")
+        .arg(&bogus)
+        .arg(&bogus)
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("invalid snapshot"));
+}
+
+#[test]
+fn test_stream_flag() {
+    let tmp = create_test_tree();
+    let root = tmp.path().to_str().unwrap();
+    let default = cmd()
+        .args(["--no-color", "--apparent-size", "-d", "1", root])
+        .output()
+        .unwrap();
+    let stream = cmd()
+        .args(["--no-color", "--apparent-size", "--stream", "-d", "1", root])
+        .output()
+        .unwrap();
+
+    assert!(stream.status.success());
+    assert_eq!(default.stdout, stream.stdout);
+}
+
+#[test]
+fn test_threshold_flag() {
+    let tmp = create_test_tree();
+    let root = tmp.path().to_str().unwrap();
+
+    cmd()
+        .args(["--no-color", "--apparent-size", "-a", "-t", "15", root])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("file_c.txt"))
+        .stdout(predicate::str::contains("file_a.txt").not())
+        .stdout(predicate::str::contains("file_b.txt").not());
+
+    cmd()
+        .args(["--no-color", "--apparent-size", "-a", "-t=-15", root])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("file_a.txt"))
+        .stdout(predicate::str::contains("file_b.txt"))
+        .stdout(predicate::str::contains("file_c.txt").not());
+}
+
+#[test]
+fn test_top_flag_keeps_totals() {
+    let tmp = create_test_tree();
+    let root = tmp.path().to_str().unwrap();
+    let last_line = |stdout: &[u8]| {
+        String::from_utf8_lossy(stdout)
+            .lines()
+            .last()
+            .unwrap()
+            .to_string()
+    };
+
+    let full = cmd().args(["--no-color", "-a", root]).output().unwrap();
+    let top = cmd()
+        .args(["--no-color", "-a", "--top", "1", root])
+        .output()
+        .unwrap();
+
+    assert!(top.status.success());
+    let stdout = String::from_utf8_lossy(&top.stdout);
+    assert!(stdout.contains("<1 other entry>"));
+    assert!(!stdout.contains("file_a.txt"));
+    // The root total is unchanged
+    assert_eq!(last_line(&full.stdout), last_line(&top.stdout));
+}
+
+#[test]
+fn test_tree_flag() {
+    let tmp = create_test_tree();
+    let root = tmp.path().to_str().unwrap();
+
+    let output = cmd()
+        .args(["--no-color", "--tree", "-a", root])
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let lines: Vec<&str> = stdout.lines().collect();
+    // Pre-order: the root comes first, with the full path
+    assert!(lines[0].contains("100.0%"));
+    assert!(lines[0].ends_with(root));
+    // nested (20 bytes) is sorted before file_b.txt (10 bytes) below subdir
+    assert!(stdout.contains("├── nested"));
+    assert!(stdout.contains("└── file_b.txt"));
+    assert!(stdout.contains("█"));
+}
+
+#[test]
+fn test_by_extension_flag() {
+    let tmp = create_test_tree();
+    fs::write(tmp.path().join("subdir/image.png"), "png").unwrap();
+    let root = tmp.path().to_str().unwrap();
+
+    cmd()
+        .args(["--no-color", "--apparent-size", "--by-extension", root])
+        .assert()
+        .success()
+        .stdout("35\t3\t.txt\n3\t1\t.png\n");
+
+    cmd()
+        .args(["--apparent-size", "--by-type", "--format", "csv", root])
+        .assert()
+        .success()
+        .stdout("group,files,size_bytes,size_human\nother,3,35,35B\nimages,1,3,3B\n");
+}
+
+#[cfg(unix)]
+#[test]
+fn test_by_user_flag() {
+    let tmp = create_test_tree();
+    let root = tmp.path().to_str().unwrap();
+
+    let output = cmd()
+        .args(["--no-color", "--apparent-size", "--by-user", root])
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+
+    // Everything belongs to the current user: one total line (root, two
+    // directories, three files) and one line per top-level directory. The
+    // root's line holds its own inode and file_a.txt
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let lines: Vec<&str> = stdout.lines().collect();
+    assert_eq!(lines.len(), 3);
+    assert_eq!(lines[0].split('\t').nth(1), Some("6"));
+    assert!(
+        lines
+            .iter()
+            .any(|l| l.split('\t').nth(1) == Some("4") && l.ends_with(&format!("\t{root}/subdir")))
+    );
+    assert!(
+        lines
+            .iter()
+            .any(|l| l.split('\t').nth(1) == Some("2") && l.ends_with(&format!("\t{root}")))
+    );
+    assert!(!stdout.contains("file_a.txt"), "{stdout}");
+}
+
+/// Sets a file's modification time to `days` days ago.
+fn backdate(path: &std::path::Path, days: u64) {
+    let time = std::time::SystemTime::now() - std::time::Duration::from_secs(days * 86_400);
+    fs::File::options()
+        .write(true)
+        .open(path)
+        .unwrap()
+        .set_modified(time)
+        .unwrap();
+}
+
+#[cfg(unix)]
+#[test]
+fn test_older_than_flag() {
+    let tmp = create_test_tree();
+    let root = tmp.path().to_str().unwrap();
+    backdate(&tmp.path().join("subdir/nested/file_c.txt"), 400);
+
+    let output = cmd()
+        .args([
+            "--no-color",
+            "--apparent-size",
+            "-a",
+            "--older-than",
+            "180d",
+            root,
+        ])
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+
+    // Only the stale file and the directories leading to it remain
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let paths: Vec<&str> = stdout
+        .lines()
+        .map(|l| l.split('\t').nth(1).unwrap())
+        .collect();
+    assert_eq!(
+        paths,
+        vec![
+            format!("{root}/subdir/nested/file_c.txt"),
+            format!("{root}/subdir/nested"),
+            format!("{root}/subdir"),
+            root.to_string(),
+        ]
+    );
+
+    let output = cmd()
+        .args(["--no-color", "-a", "--newer-than", "1d", "-d", "1", root])
+        .output()
+        .unwrap();
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("file_a.txt"));
+    assert!(!stdout.contains("nested"));
+}
+
+#[cfg(unix)]
+#[test]
+fn test_time_column() {
+    let tmp = create_test_tree();
+    let root = tmp.path().to_str().unwrap();
+
+    let output = cmd()
+        .args(["--no-color", "-s", "--time", root])
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+
+    // size <TAB> YYYY-MM-DD HH:MM <TAB> path
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let fields: Vec<&str> = stdout.trim_end().split('\t').collect();
+    assert_eq!(fields.len(), 3);
+    assert_eq!(fields[1].len(), 16);
+    assert_eq!(fields[2], root);
+}
+
+#[cfg(unix)]
+#[test]
+fn test_age_histogram_flag() {
+    let tmp = create_test_tree();
+    let root = tmp.path().to_str().unwrap();
+    backdate(&tmp.path().join("subdir/file_b.txt"), 1000);
+
+    let output = cmd()
+        .args([
+            "--apparent-size",
+            "--age-histogram",
+            "--format",
+            "csv",
+            root,
+        ])
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let lines: Vec<&str> = stdout.lines().collect();
+    assert_eq!(
+        lines,
+        vec![
+            "bucket,max_age_seconds,files,size_bytes,size_human",
+            "< 1 day,86400,2,25,25B",
+            "> 2 years,,1,10,10B",
+        ]
+    );
+}
+
+#[cfg(unix)]
+#[test]
+fn test_dupes_subcommand() {
+    let tmp = create_test_tree();
+    let root = tmp.path();
+    fs::write(root.join("copy_of_b.txt"), "0123456789").unwrap();
+    fs::hard_link(root.join("file_a.txt"), root.join("link_to_a.txt")).unwrap();
+
+    let output = cmd()
+        .args(["dupes", root.to_str().unwrap()])
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+
+    // The copy is reported, the hard link is not
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let root = root.display();
+    assert_eq!(
+        stdout.trim_end(),
+        format!(
+            "10\t2 copies of 10\n\t{root}/copy_of_b.txt\n\t{root}/subdir/file_b.txt\n\n\
+             10\treclaimable in 1 group"
+        )
+    );
+}
+
+#[test]
+fn test_timeout_not_reached_leaves_output_unchanged() {
+    let tmp = create_test_tree();
+    let root = tmp.path().to_str().unwrap();
+
+    let fresh = cmd().args(["--no-color", "-a", root]).output().unwrap();
+    cmd()
+        .args(["--no-color", "-a", "--timeout", "600", root])
+        .assert()
+        .success()
+        .stdout(predicate::eq(fresh.stdout.as_slice()));
+
+    cmd()
+        .args(["--timeout", "0", root])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("--timeout"));
+}
+
+#[test]
+fn test_cache_file_reused_between_runs() {
+    let tmp = create_test_tree();
+    let root = tmp.path().to_str().unwrap();
+    let cache_dir = TempDir::new().unwrap();
+    let cache = cache_dir.path().join("dusk/scan-cache.gz");
+    let cache_arg = cache.to_str().unwrap();
+
+    let fresh = cmd().args(["--no-color", "-a", root]).output().unwrap();
+    assert!(fresh.status.success());
+
+    // --no-cache neither reads nor writes the cache
+    cmd()
+        .args([
+            "--no-color",
+            "-a",
+            "--cache-file",
+            cache_arg,
+            "--no-cache",
+            root,
+        ])
+        .assert()
+        .success()
+        .stdout(predicate::eq(fresh.stdout.as_slice()));
+    assert!(!cache.exists());
+
+    for _ in 0..2 {
+        cmd()
+            .args(["--no-color", "-a", "--cache-file", cache_arg, root])
+            .assert()
+            .success()
+            .stdout(predicate::eq(fresh.stdout.as_slice()));
+    }
+    assert!(cache.exists());
+}
+
+#[test]
+fn test_watch_redraws_after_changes() {
+    let tmp = create_test_tree();
+    let mut child = std::process::Command::new(env!("CARGO_BIN_EXE_disk-usage-clone"))
+        .args(["--watch", "--interval", "1", "--no-color"])
+        .arg(tmp.path())
+        .stdout(std::process::Stdio::piped())
+        .spawn()
+        .unwrap();
+
+    std::thread::sleep(std::time::Duration::from_millis(1500));
+    fs::create_dir(tmp.path().join("appeared")).unwrap();
+    fs::write(tmp.path().join("appeared/data.bin"), vec![0u8; 5000]).unwrap();
+    std::thread::sleep(std::time::Duration::from_millis(3000));
+
+    child.kill().unwrap();
+    let output = child.wait_with_output().unwrap();
+    let stdout = String::from_utf8(output.stdout).unwrap();
+
+    // The first frame predates the new directory, a later one shows it
+    let frames: Vec<&str> = stdout.split("\n\n").filter(|f| !f.is_empty()).collect();
+    assert!(frames.len() >= 2, "{stdout}");
+    assert!(!frames[0].contains("appeared"));
+    assert!(frames.last().unwrap().contains("appeared"), "{stdout}");
+}
+
+#[cfg(unix)]
+#[test]
+fn test_symlinks_listed_with_targets() {
+    let tmp = create_test_tree();
+    std::os::unix::fs::symlink("nested", tmp.path().join("subdir/link")).unwrap();
+
+    cmd()
+        .args(["--no-color", "-a"])
+        .arg(tmp.path())
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("link -> nested\n"))
+        .stdout(predicate::str::contains("subdir/link/file_c.txt").not());
+}
+
+#[cfg(unix)]
+#[test]
+fn test_dereference_reports_symlink_loops() {
+    let tmp = create_test_tree();
+    std::os::unix::fs::symlink("nested", tmp.path().join("subdir/link")).unwrap();
+    std::os::unix::fs::symlink("../..", tmp.path().join("subdir/nested/up")).unwrap();
+
+    let output = cmd()
+        .args(["--no-color", "-a", "-L"])
+        .arg(tmp.path())
+        .output()
+        .unwrap();
+
+    // The linked directory is read once, through one of its two paths;
+    // the loop inside it is reported and skipped
+    assert_eq!(output.status.code(), Some(1));
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert_eq!(stdout.matches("/file_c.txt").count(), 1, "{stdout}");
+    assert!(!stdout.contains("up/"), "{stdout}");
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert!(stderr.contains("dusk: cannot follow '"), "{stderr}");
+    assert!(stderr.contains("symlink loop back to"), "{stderr}");
+    assert!(
+        stderr.contains("dusk: 1 path could not be read"),
+        "{stderr}"
+    );
+}
+
+#[cfg(unix)]
+#[test]
+fn test_dereference_args_follows_argument() {
+    let tmp = create_test_tree();
+    let link = tmp.path().join("link");
+    std::os::unix::fs::symlink("subdir", &link).unwrap();
+
+    // Like du, the link itself is tiny unless it is followed
+    let plain = cmd()
+        .args(["-s", "--apparent-size", "--no-color"])
+        .arg(&link)
+        .output()
+        .unwrap();
+    let followed = cmd()
+        .args(["-s", "--apparent-size", "--no-color", "-D"])
+        .arg(&link)
+        .output()
+        .unwrap();
+    let size = |stdout: &[u8]| -> u64 {
+        let stdout = String::from_utf8_lossy(stdout);
+        stdout.split('\t').next().unwrap().parse().unwrap()
+    };
+    assert!(size(&plain.stdout) < size(&followed.stdout));
+    assert!(
+        String::from_utf8_lossy(&followed.stdout)
+            .trim_end()
+            .ends_with("subdir")
+    );
+}
+
+#[test]
+fn test_total_counts_nested_paths_once() {
+    let tmp = create_test_tree();
+    let root = tmp.path();
+
+    let output = cmd()
+        .args(["-s", "-c", "--apparent-size", "--no-color"])
+        .arg(root)
+        .arg(root.join("subdir"))
+        .arg(root)
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    let lines: Vec<&str> = stdout.lines().collect();
+    assert_eq!(lines.len(), 4, "{stdout}");
+
+    // The total is the root's alone
+    let root_size = lines[0].split('\t').next().unwrap();
+    assert_eq!(lines[3], format!("{root_size}\ttotal"));
+}
+
+#[test]
+fn test_compare_paths() {
+    let tmp = create_test_tree();
+    let root = tmp.path();
+
+    cmd()
+        .args(["--compare", "--no-color"])
+        .arg(root.join("subdir"))
+        .arg(root.join("file_a.txt"))
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("%"))
+        .stdout(predicate::str::ends_with("total\n"));
+
+    let output = cmd()
+        .args(["--compare", "--format", "json", "--apparent-size"])
+        .arg(root)
+        .arg(root.join("subdir"))
+        .output()
+        .unwrap();
+    let value: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(value["paths"][0]["percent"], 100.0);
+    assert_eq!(value["paths"][1]["within"], value["paths"][0]["path"]);
+    assert_eq!(value["total"]["size"], value["paths"][0]["size"]);
+}
+
+#[cfg(unix)]
+#[test]
+fn test_unreadable_directory_reported() {
+    use std::os::unix::fs::PermissionsExt;
+
+    let tmp = create_test_tree();
+    let locked = tmp.path().join("subdir/nested");
+    fs::set_permissions(&locked, fs::Permissions::from_mode(0o000)).unwrap();
+
+    // Privileged users can read it anyway
+    if fs::read_dir(&locked).is_ok() {
+        fs::set_permissions(&locked, fs::Permissions::from_mode(0o755)).unwrap();
+        return;
+    }
+
+    let output = cmd().arg("--no-color").arg(tmp.path()).output().unwrap();
+    fs::set_permissions(&locked, fs::Permissions::from_mode(0o755)).unwrap();
+
+    // Output is still printed, but the run fails like `du`
+    assert_eq!(output.status.code(), Some(1));
+    assert!(String::from_utf8_lossy(&output.stdout).contains("subdir"));
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert!(stderr.contains("dusk: cannot read directory '"), "{stderr}");
+    assert!(stderr.contains("nested': Permission denied"));
+    assert!(stderr.contains("dusk: 1 path could not be read; totals are incomplete"));
+}
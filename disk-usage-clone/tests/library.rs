@@ -0,0 +1,593 @@
+//! The scenarios of `integration.rs`, run through the library API
+//! (`ScanOptions` and the renderers) instead of the binary.
+
+use std::fs;
+use std::sync::Arc;
+
+use disk_usage_clone::age::{AgeFilter, AgeLimit};
+use disk_usage_clone::cache::ScanCache;
+use disk_usage_clone::cancel::CancelToken;
+use disk_usage_clone::compare::{combined_total, compare_paths};
+use disk_usage_clone::entry::{SizeMode, SortOrder, TimeKind};
+use disk_usage_clone::error::DuskError;
+use disk_usage_clone::prune::{Threshold, apply_threshold, keep_top};
+use disk_usage_clone::render::{
+    CsvRenderer, JsonRenderer, NdjsonRenderer, Render, TextRenderer, TreeViewRenderer,
+};
+use disk_usage_clone::scan::ScanOptions;
+use disk_usage_clone::traversal::Dereference;
+use tempfile::TempDir;
+
+fn create_test_tree() -> TempDir {
+    let tmp = TempDir::new().unwrap();
+    let root = tmp.path();
+
+    fs::write(root.join("file_a.txt"), "hello").unwrap();
+    fs::create_dir(root.join("subdir")).unwrap();
+    fs::write(root.join("subdir/file_b.txt"), "0123456789").unwrap();
+    fs::create_dir(root.join("subdir/nested")).unwrap();
+    fs::write(
+        root.join("subdir/nested/file_c.txt"),
+        "01234567890123456789",
+    )
+    .unwrap();
+
+    tmp
+}
+
+/// Scans `options` and renders every entry as text.
+fn render_all(options: ScanOptions, size_mode: SizeMode) -> String {
+    let result = options.scan().unwrap();
+    TextRenderer::new()
+        .show_all(true)
+        .size_mode(size_mode)
+        .render(&result.trees)
+}
+
+#[test]
+fn test_scan_temp_dir() {
+    let tmp = create_test_tree();
+    let result = ScanOptions::new().path(tmp.path()).scan().unwrap();
+
+    assert_eq!(result.trees.len(), 1);
+    assert!(result.skipped.is_empty());
+    assert!(!TextRenderer::new().render(&result.trees).is_empty());
+}
+
+#[test]
+fn test_human_readable() {
+    let tmp = create_test_tree();
+    let result = ScanOptions::new().path(tmp.path()).scan().unwrap();
+    let output = TextRenderer::new()
+        .human_readable(true)
+        .render(&result.trees);
+    assert!(output.contains('B') || output.contains('K'));
+}
+
+#[test]
+fn test_summarize() {
+    let tmp = create_test_tree();
+    let result = ScanOptions::new().path(tmp.path()).scan().unwrap();
+    let output = TextRenderer::new().summarize(true).render(&result.trees);
+    assert_eq!(output.lines().count(), 1);
+}
+
+#[test]
+fn test_show_all() {
+    let tmp = create_test_tree();
+    let output = render_all(ScanOptions::new().path(tmp.path()), SizeMode::Allocated);
+    assert!(output.contains("file_a.txt"));
+    assert!(output.contains("file_b.txt"));
+    assert!(output.contains("file_c.txt"));
+}
+
+#[test]
+fn test_max_depth() {
+    let tmp = create_test_tree();
+    let result = ScanOptions::new()
+        .path(tmp.path())
+        .max_depth(0)
+        .scan()
+        .unwrap();
+    assert!(result.trees[0].children.is_empty());
+    assert_eq!(TextRenderer::new().render(&result.trees).lines().count(), 1);
+}
+
+#[test]
+fn test_sort_orders() {
+    let tmp = create_test_tree();
+    let names = |sort: SortOrder| {
+        let result = ScanOptions::new()
+            .path(tmp.path())
+            .size_mode(SizeMode::Apparent)
+            .sort(sort)
+            .scan()
+            .unwrap();
+        result.trees[0]
+            .children
+            .iter()
+            .map(|child| child.path.file_name().unwrap().to_owned())
+            .collect::<Vec<_>>()
+    };
+
+    assert_eq!(names(SortOrder::Name), ["file_a.txt", "subdir"]);
+    assert_eq!(names(SortOrder::SizeDescending), ["subdir", "file_a.txt"]);
+    assert_eq!(names(SortOrder::SizeAscending), ["file_a.txt", "subdir"]);
+}
+
+#[test]
+fn test_threads() {
+    let tmp = create_test_tree();
+    let single = render_all(ScanOptions::new().path(tmp.path()), SizeMode::Apparent);
+    let two = render_all(
+        ScanOptions::new().path(tmp.path()).threads(2),
+        SizeMode::Apparent,
+    );
+    // Unsorted, so compare the lines as sets
+    let mut single: Vec<&str> = single.lines().collect();
+    let mut two: Vec<&str> = two.lines().collect();
+    single.sort_unstable();
+    two.sort_unstable();
+    assert_eq!(single, two);
+}
+
+#[test]
+fn test_nonexistent_path_fails() {
+    let result = ScanOptions::new()
+        .path("/nonexistent/path/that/does/not/exist")
+        .scan();
+    assert!(matches!(result, Err(DuskError::PathNotFound(_))));
+}
+
+#[test]
+fn test_multiple_paths() {
+    let tmp1 = create_test_tree();
+    let tmp2 = create_test_tree();
+    let result = ScanOptions::new()
+        .paths([tmp1.path(), tmp2.path()])
+        .scan()
+        .unwrap();
+
+    assert_eq!(result.trees.len(), 2);
+    let output = TextRenderer::new().summarize(true).render(&result.trees);
+    assert_eq!(output.lines().count(), 2);
+}
+
+#[test]
+fn test_apparent_size() {
+    let tmp = create_test_tree();
+    let output = render_all(ScanOptions::new().path(tmp.path()), SizeMode::Apparent);
+    // file_a.txt contains "hello" (5 bytes)
+    assert!(
+        output
+            .lines()
+            .any(|line| line.starts_with("5\t") && line.ends_with("file_a.txt"))
+    );
+}
+
+#[cfg(unix)]
+#[test]
+fn test_count_links() {
+    let tmp = TempDir::new().unwrap();
+    let root = tmp.path();
+    fs::write(root.join("original.bin"), vec![0u8; 1000]).unwrap();
+    fs::hard_link(root.join("original.bin"), root.join("link.bin")).unwrap();
+
+    let total = |count_links: bool| {
+        let result = ScanOptions::new()
+            .path(root)
+            .count_links(count_links)
+            .scan()
+            .unwrap();
+        result.trees[0].total_size_for(SizeMode::Apparent)
+    };
+
+    // The second link adds exactly one more copy of the file
+    assert_eq!(total(true) - total(false), 1000);
+}
+
+#[test]
+fn test_one_file_system() {
+    let tmp = create_test_tree();
+    let result = ScanOptions::new()
+        .path(tmp.path())
+        .one_file_system(true)
+        .scan()
+        .unwrap();
+    let output = TextRenderer::new().render(&result.trees);
+    assert!(output.contains("subdir"));
+    assert!(!output.contains("[mount point]"));
+}
+
+#[test]
+fn test_exclude() {
+    let tmp = create_test_tree();
+    let output = render_all(
+        ScanOptions::new().path(tmp.path()).exclude("nested/"),
+        SizeMode::Allocated,
+    );
+    assert!(output.contains("file_b.txt"));
+    assert!(!output.contains("nested"));
+    assert!(!output.contains("file_c.txt"));
+}
+
+#[test]
+fn test_exclude_from() {
+    let tmp = create_test_tree();
+    let list = TempDir::new().unwrap();
+    let list_path = list.path().join("excludes");
+    fs::write(&list_path, "# skip text files\n*.txt\n").unwrap();
+
+    let output = render_all(
+        ScanOptions::new().path(tmp.path()).exclude_from(&list_path),
+        SizeMode::Allocated,
+    );
+    assert!(output.contains("nested"));
+    assert!(!output.contains(".txt"));
+}
+
+#[test]
+fn test_respect_gitignore() {
+    let tmp = create_test_tree();
+    fs::write(tmp.path().join(".gitignore"), "file_a.txt\n").unwrap();
+
+    let output = render_all(
+        ScanOptions::new().path(tmp.path()).respect_gitignore(true),
+        SizeMode::Allocated,
+    );
+    assert!(output.contains("file_b.txt"));
+    assert!(!output.contains("file_a.txt"));
+}
+
+#[test]
+fn test_json_renderer() {
+    let tmp = create_test_tree();
+    let result = ScanOptions::new().path(tmp.path()).scan().unwrap();
+    let output = JsonRenderer::new()
+        .show_all(true)
+        .size_mode(SizeMode::Apparent)
+        .render(&result.trees);
+
+    let parsed: serde_json::Value = serde_json::from_str(&output).unwrap();
+    let root = &parsed[0];
+    assert_eq!(root["depth"], 0);
+    // file_a (5) + file_b (10) + file_c (20) plus directory entries
+    assert!(root["apparent_size"].as_u64().unwrap() >= 35);
+    assert_eq!(root["child_count"], 2);
+}
+
+#[test]
+fn test_ndjson_renderer() {
+    let tmp = create_test_tree();
+    let result = ScanOptions::new().path(tmp.path()).scan().unwrap();
+    let output = NdjsonRenderer::new().show_all(true).render(&result.trees);
+
+    let objects: Vec<serde_json::Value> = output
+        .lines()
+        .map(|line| serde_json::from_str(line).unwrap())
+        .collect();
+    // 3 files, 2 subdirectories and the root
+    assert_eq!(objects.len(), 6);
+    assert!(objects.iter().any(|o| o["type"] == "file"));
+}
+
+#[test]
+fn test_csv_renderer() {
+    let tmp = create_test_tree();
+    fs::write(tmp.path().join("a, \"b\".txt"), "x").unwrap();
+
+    let result = ScanOptions::new().path(tmp.path()).scan().unwrap();
+    let output = CsvRenderer::new()
+        .show_all(true)
+        .size_mode(SizeMode::Apparent)
+        .render(&result.trees);
+
+    let lines: Vec<&str> = output.lines().collect();
+    assert_eq!(
+        lines[0],
+        "path,size_bytes,size_human,type,depth,parent,incomplete"
+    );
+    // Header, 4 files, 2 subdirectories and the root
+    assert_eq!(lines.len(), 8);
+    assert!(output.contains("a, \"\"b\"\".txt\",1,1B,file,1,"));
+}
+
+#[test]
+fn test_stream_matches_default() {
+    let tmp = create_test_tree();
+    let options = ScanOptions::new()
+        .path(tmp.path())
+        .max_depth(1)
+        .sort(SortOrder::Name);
+
+    let default = render_all(options.clone(), SizeMode::Apparent);
+    let stream = render_all(options.stream(true), SizeMode::Apparent);
+    assert_eq!(default, stream);
+}
+
+#[test]
+fn test_threshold() {
+    let tmp = create_test_tree();
+    let render = |threshold: Threshold| {
+        let mut result = ScanOptions::new().path(tmp.path()).scan().unwrap();
+        for tree in &mut result.trees {
+            apply_threshold(tree, threshold, SizeMode::Apparent);
+        }
+        TextRenderer::new()
+            .show_all(true)
+            .size_mode(SizeMode::Apparent)
+            .render(&result.trees)
+    };
+
+    let large = render(Threshold::AtLeast(15));
+    assert!(large.contains("file_c.txt"));
+    assert!(!large.contains("file_a.txt"));
+    assert!(!large.contains("file_b.txt"));
+
+    let small = render(Threshold::AtMost(15));
+    assert!(small.contains("file_a.txt"));
+    assert!(small.contains("file_b.txt"));
+    assert!(!small.contains("file_c.txt"));
+}
+
+#[test]
+fn test_top_keeps_totals() {
+    let tmp = create_test_tree();
+    let result = ScanOptions::new().path(tmp.path()).scan().unwrap();
+    let mut tree = result.trees[0].clone();
+    let total = tree.total_size();
+
+    keep_top(&mut tree, 1, true, SizeMode::Allocated);
+    let output = TextRenderer::new()
+        .show_all(true)
+        .render(std::slice::from_ref(&tree));
+    assert!(output.contains("<1 other entry>"));
+    assert!(!output.contains("file_a.txt"));
+    // The root total is unchanged
+    assert_eq!(tree.total_size(), total);
+}
+
+#[test]
+fn test_tree_view_renderer() {
+    let tmp = create_test_tree();
+    let root = tmp.path().to_str().unwrap();
+    let result = ScanOptions::new()
+        .path(tmp.path())
+        .size_mode(SizeMode::Apparent)
+        .sort(SortOrder::SizeDescending)
+        .scan()
+        .unwrap();
+    let output = TreeViewRenderer::new()
+        .show_all(true)
+        .size_mode(SizeMode::Apparent)
+        .render(&result.trees);
+
+    let lines: Vec<&str> = output.lines().collect();
+    // Pre-order: the root comes first, with the full path
+    assert!(lines[0].contains("100.0%"));
+    assert!(lines[0].ends_with(root));
+    // nested (20 bytes) is sorted before file_b.txt (10 bytes) below subdir
+    assert!(output.contains("├── nested"));
+    assert!(output.contains("└── file_b.txt"));
+    assert!(output.contains("█"));
+}
+
+#[cfg(unix)]
+fn backdate(path: &std::path::Path, days: u64) {
+    let time = std::time::SystemTime::now() - std::time::Duration::from_secs(days * 86_400);
+    fs::File::options()
+        .write(true)
+        .open(path)
+        .unwrap()
+        .set_modified(time)
+        .unwrap();
+}
+
+#[cfg(unix)]
+#[test]
+fn test_age_filter() {
+    let tmp = create_test_tree();
+    let root = tmp.path().to_str().unwrap();
+    backdate(&tmp.path().join("subdir/nested/file_c.txt"), 400);
+
+    let now = disk_usage_clone::age::now();
+    let older = AgeFilter::new(AgeLimit::parse("180d"), None, TimeKind::Modified, now);
+    let output = render_all(
+        ScanOptions::new().path(tmp.path()).age_filter(older),
+        SizeMode::Apparent,
+    );
+
+    // Only the stale file and the directories leading to it remain
+    let paths: Vec<&str> = output
+        .lines()
+        .map(|l| l.split('\t').nth(1).unwrap())
+        .collect();
+    assert_eq!(
+        paths,
+        vec![
+            format!("{root}/subdir/nested/file_c.txt"),
+            format!("{root}/subdir/nested"),
+            format!("{root}/subdir"),
+            root.to_string(),
+        ]
+    );
+
+    let newer = AgeFilter::new(None, AgeLimit::parse("1d"), TimeKind::Modified, now);
+    let output = render_all(
+        ScanOptions::new()
+            .path(tmp.path())
+            .age_filter(newer)
+            .max_depth(1),
+        SizeMode::Apparent,
+    );
+    assert!(output.contains("file_a.txt"));
+    assert!(!output.contains("nested"));
+}
+
+#[cfg(unix)]
+#[test]
+fn test_time_column() {
+    let tmp = create_test_tree();
+    let root = tmp.path().to_str().unwrap();
+    let result = ScanOptions::new().path(tmp.path()).scan().unwrap();
+    let output = TextRenderer::new()
+        .summarize(true)
+        .time_column(TimeKind::Modified)
+        .render(&result.trees);
+
+    // size <TAB> YYYY-MM-DD HH:MM <TAB> path
+    let fields: Vec<&str> = output.split('\t').collect();
+    assert_eq!(fields.len(), 3);
+    assert_eq!(fields[1].len(), 16);
+    assert_eq!(fields[2], root);
+}
+
+#[test]
+fn test_timeout_not_reached_leaves_result_unchanged() {
+    let tmp = create_test_tree();
+    let options = ScanOptions::new().path(tmp.path()).sort(SortOrder::Name);
+
+    let fresh = options.scan().unwrap();
+    let timed = options
+        .clone()
+        .cancel(CancelToken::with_timeout(std::time::Duration::from_secs(
+            600,
+        )))
+        .scan()
+        .unwrap();
+    assert!(!timed.is_incomplete());
+    assert_eq!(
+        TextRenderer::new().show_all(true).render(&fresh.trees),
+        TextRenderer::new().show_all(true).render(&timed.trees)
+    );
+
+    let cancelled = CancelToken::new();
+    cancelled.cancel();
+    let partial = options.cancel(cancelled).scan().unwrap();
+    assert!(partial.is_incomplete());
+    assert!(
+        TextRenderer::new()
+            .render(&partial.trees)
+            .contains("[incomplete]")
+    );
+}
+
+#[test]
+fn test_cache_reused_between_scans() {
+    let tmp = create_test_tree();
+    let cache_dir = TempDir::new().unwrap();
+    let cache_file = cache_dir.path().join("scan-cache.gz");
+    let options = ScanOptions::new().path(tmp.path()).sort(SortOrder::Name);
+    let fresh = render_all(options.clone(), SizeMode::Apparent);
+
+    let scan_with_cache = || {
+        let cache = Arc::new(ScanCache::load(&cache_file));
+        let output = render_all(
+            options.clone().cache(Arc::clone(&cache)),
+            SizeMode::Apparent,
+        );
+        cache.save(&cache_file).unwrap();
+        output
+    };
+    assert_eq!(scan_with_cache(), fresh);
+    assert!(cache_file.exists());
+    // The second scan reads the listings back from the file
+    assert_eq!(scan_with_cache(), fresh);
+}
+
+#[cfg(unix)]
+#[test]
+fn test_symlinks_listed_with_targets() {
+    let tmp = create_test_tree();
+    std::os::unix::fs::symlink("nested", tmp.path().join("subdir/link")).unwrap();
+
+    let output = render_all(ScanOptions::new().path(tmp.path()), SizeMode::Apparent);
+    assert!(output.contains("link -> nested\n"), "{output}");
+    assert!(!output.contains("subdir/link/file_c.txt"));
+}
+
+#[cfg(unix)]
+#[test]
+fn test_dereference_reports_symlink_loops() {
+    let tmp = create_test_tree();
+    std::os::unix::fs::symlink("nested", tmp.path().join("subdir/link")).unwrap();
+    std::os::unix::fs::symlink("../..", tmp.path().join("subdir/nested/up")).unwrap();
+
+    let result = ScanOptions::new()
+        .path(tmp.path())
+        .follow_links(Dereference::Always)
+        .scan()
+        .unwrap();
+    // `link` and `nested` are one directory, read once
+    let output = TextRenderer::new().show_all(true).render(&result.trees);
+    assert_eq!(output.matches("/file_c.txt").count(), 1, "{output}");
+    assert_eq!(result.skipped.len(), 1);
+    assert!(
+        result
+            .skipped
+            .iter()
+            .all(|entry| matches!(entry.error, DuskError::SymlinkLoop(_)))
+    );
+}
+
+#[test]
+fn test_total_counts_nested_paths_once() {
+    let tmp = create_test_tree();
+    let result = ScanOptions::new()
+        .path(tmp.path())
+        .path(tmp.path().join("subdir"))
+        .scan()
+        .unwrap();
+    let root_total = result.trees[0].total_size_for(SizeMode::Apparent);
+    assert_eq!(
+        combined_total(&result.trees, SizeMode::Apparent),
+        root_total
+    );
+
+    let output = TextRenderer::new()
+        .summarize(true)
+        .size_mode(SizeMode::Apparent)
+        .total(true)
+        .render(&result.trees);
+    assert!(
+        output.ends_with(&format!("\n{root_total}\ttotal")),
+        "{output}"
+    );
+}
+
+#[test]
+fn test_compare_paths() {
+    let tmp = create_test_tree();
+    let result = ScanOptions::new()
+        .path(tmp.path().join("subdir"))
+        .path(tmp.path().join("file_a.txt"))
+        .scan()
+        .unwrap();
+    let comparison = compare_paths(&result.trees, SizeMode::Apparent, &SortOrder::Name);
+    assert!(comparison.paths[0].path.ends_with("file_a.txt"));
+    let shares: f64 = comparison
+        .paths
+        .iter()
+        .map(|path| comparison.percent(path, SizeMode::Apparent))
+        .sum();
+    assert!((shares - 100.0).abs() < 1e-9);
+}
+
+#[test]
+fn test_renderers_behind_trait_objects() {
+    let tmp = create_test_tree();
+    let result = ScanOptions::new().path(tmp.path()).scan().unwrap();
+    let renderers: Vec<Box<dyn Render>> = vec![
+        Box::new(TextRenderer::new()),
+        Box::new(TreeViewRenderer::new()),
+        Box::new(JsonRenderer::new()),
+        Box::new(NdjsonRenderer::new()),
+        Box::new(CsvRenderer::new()),
+    ];
+    for renderer in &renderers {
+        let output = renderer.render(&result.trees);
+        assert!(!output.is_empty());
+        // Color is opt-in for library use
+        assert!(!output.contains('\x1b'));
+    }
+}